@@ -0,0 +1,141 @@
+use std::process::ExitCode;
+use std::time::Instant;
+
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use weavetree_core::SearchConfig;
+use weavetree_mdp::MdpSpec;
+use weavetree_mdp::benchmarks::{FROZEN_LAKE_8X8, chain, frozen_lake, inventory, river_swim, taxi};
+
+use crate::error::CliError;
+use crate::heap;
+use crate::run::search;
+
+/// Built-in benchmark model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BenchModel {
+    /// `chain(10)`.
+    Chain,
+    /// `river_swim(6)`.
+    RiverSwim,
+    /// Slippery `frozen_lake` on the 8x8 map.
+    FrozenLake,
+    /// The 500-state `taxi` domain.
+    Taxi,
+    /// `inventory(20)`, with 21 outcomes per action.
+    Inventory,
+}
+
+impl BenchModel {
+    fn spec(self) -> MdpSpec {
+        match self {
+            Self::Chain => chain(10),
+            Self::RiverSwim => river_swim(6),
+            Self::FrozenLake => frozen_lake(&FROZEN_LAKE_8X8, true),
+            Self::Taxi => taxi(),
+            Self::Inventory => inventory(20),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Chain => "chain",
+            Self::RiverSwim => "river-swim",
+            Self::FrozenLake => "frozen-lake",
+            Self::Taxi => "taxi",
+            Self::Inventory => "inventory",
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    /// MCTS iterations per model.
+    #[arg(long, default_value_t = 10_000)]
+    pub iterations: usize,
+    /// Models to run; all of them by default. Repeat to pick several.
+    #[arg(long = "model", value_enum)]
+    pub models: Vec<BenchModel>,
+    /// Seed of every search.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Print one JSON object per model instead of a table.
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Measurements of one benchmark search.
+#[derive(Debug, Serialize)]
+struct BenchResult {
+    model: &'static str,
+    version: &'static str,
+    states: usize,
+    iterations: usize,
+    nodes: usize,
+    seconds: f64,
+    iterations_per_second: f64,
+    nodes_per_second: f64,
+    /// Most heap bytes the search held on top of what was allocated before it started;
+    /// `None` unless built with the `heap-stats` feature.
+    peak_heap_bytes: Option<usize>,
+}
+
+/// Time one search per model with the default config and the requested iteration count.
+pub fn run(args: &BenchArgs) -> Result<ExitCode, CliError> {
+    let models = if args.models.is_empty() {
+        BenchModel::value_variants().to_vec()
+    } else {
+        args.models.clone()
+    };
+    let config = SearchConfig {
+        iterations: args.iterations,
+        ..SearchConfig::default()
+    };
+
+    if !args.json {
+        println!(
+            "{:<12} {:>7} {:>10} {:>8} {:>9} {:>12} {:>12} {:>10}",
+            "model", "states", "iterations", "nodes", "time (s)", "iter/s", "nodes/s", "peak MiB"
+        );
+    }
+    for model in models {
+        let compiled = model.spec().compile()?;
+        let baseline = heap::ENABLED.then(heap::reset_peak);
+        let started = Instant::now();
+        let (tree, metrics) = search(&compiled, &config, args.seed)?;
+        let seconds = started.elapsed().as_secs_f64();
+        let result = BenchResult {
+            model: model.name(),
+            version: env!("CARGO_PKG_VERSION"),
+            states: compiled.state_count(),
+            iterations: metrics.iterations_completed,
+            nodes: tree.node_count(),
+            seconds,
+            iterations_per_second: metrics.iterations_completed as f64 / seconds,
+            nodes_per_second: tree.node_count() as f64 / seconds,
+            peak_heap_bytes: baseline.map(|baseline| heap::peak().saturating_sub(baseline)),
+        };
+        drop(tree);
+
+        if args.json {
+            println!("{}", serde_json::to_string(&result)?);
+        } else {
+            let peak_mib = result.peak_heap_bytes.map_or_else(
+                || "-".to_string(),
+                |bytes| format!("{:.2}", bytes as f64 / (1024.0 * 1024.0)),
+            );
+            println!(
+                "{:<12} {:>7} {:>10} {:>8} {:>9.3} {:>12.0} {:>12.0} {:>10}",
+                result.model,
+                result.states,
+                result.iterations,
+                result.nodes,
+                result.seconds,
+                result.iterations_per_second,
+                result.nodes_per_second,
+                peak_mib
+            );
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+use weavetree_mdp::save_binary;
+
+use crate::error::CliError;
+use crate::model::load_spec;
+
+#[derive(Debug, Args)]
+pub struct CompileArgs {
+    /// Model file: .yaml, .yml, .json, .toml, or .bin.
+    pub model: PathBuf,
+    /// Output file; defaults to the model path with a .bin extension.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Compile the model to validate it, then write its spec, includes merged, as MessagePack.
+pub fn run(args: &CompileArgs) -> Result<ExitCode, CliError> {
+    let spec = load_spec(&args.model)?;
+    let compiled = spec.compile()?;
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| args.model.with_extension("bin"));
+    save_binary(&output, &spec)?;
+    println!(
+        "{}: wrote {} states to {}",
+        args.model.display(),
+        compiled.state_count(),
+        output.display()
+    );
+    Ok(ExitCode::SUCCESS)
+}
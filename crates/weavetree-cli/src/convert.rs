@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+
+use crate::error::CliError;
+use crate::model::{load_spec, save_spec};
+
+#[derive(Debug, Args)]
+pub struct ConvertArgs {
+    /// Model file to read: .yaml, .yml, .json, .toml, or .bin.
+    pub input: PathBuf,
+    /// Model file to write, in the format its extension names.
+    pub output: PathBuf,
+    /// Resolve params and `rest` probabilities, and rescale actions whose probabilities sum
+    /// to within `--tolerance` of 1.0 to sum to exactly 1.0.
+    #[arg(long)]
+    pub normalize: bool,
+    /// Largest distance from 1.0 that `--normalize` rescales.
+    #[arg(long, default_value_t = 1e-6, requires = "normalize")]
+    pub tolerance: f64,
+}
+
+/// Rewrite a model in another format, optionally normalizing its probabilities. The model
+/// is not validated, so broken files can be converted for editing.
+pub fn run(args: &ConvertArgs) -> Result<ExitCode, CliError> {
+    let spec = load_spec(&args.input)?;
+    let spec = if args.normalize {
+        spec.resolve_params()?.normalized(args.tolerance)
+    } else {
+        spec
+    };
+    save_spec(&args.output, &spec)?;
+    println!(
+        "{}: wrote {} states to {}",
+        args.input.display(),
+        spec.states.len(),
+        args.output.display()
+    );
+    Ok(ExitCode::SUCCESS)
+}
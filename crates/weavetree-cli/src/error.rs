@@ -0,0 +1,32 @@
+use thiserror::Error;
+use weavetree_core::{SearchConfigError, TreeError};
+use weavetree_mdp::MdpError;
+
+#[derive(Debug, Error)]
+/// Error type for CLI commands.
+pub enum CliError {
+    #[error(transparent)]
+    Mdp(#[from] MdpError),
+
+    #[error(transparent)]
+    Config(#[from] SearchConfigError),
+
+    #[error(transparent)]
+    Tree(#[from] TreeError),
+
+    #[error("failed to write JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("failed to write '{path}': {source}")]
+    Write {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[cfg(feature = "tui")]
+    #[error("terminal error: {0}")]
+    Terminal(std::io::Error),
+
+    #[error("cannot tell the model format of '{path}'; use .yaml, .yml, .json, .toml, or .bin")]
+    UnknownFormat { path: String },
+}
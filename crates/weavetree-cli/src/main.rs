@@ -0,0 +1,67 @@
+//! `weavetree` command-line tool for checking, converting, compiling, searching, and solving
+//! MDP models without writing code.
+
+mod bench;
+mod compile;
+mod convert;
+mod error;
+mod heap;
+mod model;
+mod run;
+mod solve;
+#[cfg(feature = "tui")]
+mod tui;
+mod validate;
+
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+use crate::error::CliError;
+
+#[cfg(feature = "heap-stats")]
+#[global_allocator]
+static ALLOCATOR: heap::CountingAllocator = heap::CountingAllocator;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "weavetree",
+    version,
+    about = "Check, convert, compile, search, and solve weavetree MDP models"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Validate a model and print lint warnings.
+    Validate(validate::ValidateArgs),
+    /// Validate a model and write it as MessagePack.
+    Compile(compile::CompileArgs),
+    /// Rewrite a model in another file format.
+    Convert(convert::ConvertArgs),
+    /// Run MCTS from the start state and print the recommended action.
+    Run(run::RunArgs),
+    /// Solve a model exactly with value iteration.
+    Solve(solve::SolveArgs),
+    /// Time searches on built-in benchmark models.
+    Bench(bench::BenchArgs),
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result: Result<ExitCode, CliError> = match &cli.command {
+        Command::Validate(args) => validate::run(args),
+        Command::Compile(args) => compile::run(args),
+        Command::Convert(args) => convert::run(args),
+        Command::Run(args) => run::run(args),
+        Command::Solve(args) => solve::run(args),
+        Command::Bench(args) => bench::run(args),
+    };
+    result.unwrap_or_else(|err| {
+        eprintln!("error: {err}");
+        ExitCode::FAILURE
+    })
+}
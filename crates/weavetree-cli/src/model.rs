@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use weavetree_mdp::{
+    MdpSpec, load_binary, load_json, load_toml, load_yaml, save_binary, save_json, save_toml,
+    save_yaml,
+};
+
+use crate::error::CliError;
+
+/// File format of a model, chosen by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Yaml,
+    Json,
+    Toml,
+    /// MessagePack, as written by `weavetree compile`.
+    Binary,
+}
+
+impl Format {
+    /// Pick the format from the extension of `path`.
+    pub fn from_path(path: &Path) -> Result<Self, CliError> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            Some("json") => Ok(Self::Json),
+            Some("toml") => Ok(Self::Toml),
+            Some("bin" | "msgpack") => Ok(Self::Binary),
+            _ => Err(CliError::UnknownFormat {
+                path: path.display().to_string(),
+            }),
+        }
+    }
+}
+
+/// Load a model spec in the format its extension names, following includes.
+pub fn load_spec(path: &Path) -> Result<MdpSpec, CliError> {
+    let spec = match Format::from_path(path)? {
+        Format::Yaml => load_yaml(path)?,
+        Format::Json => load_json(path)?,
+        Format::Toml => load_toml(path)?,
+        Format::Binary => load_binary(path)?,
+    };
+    Ok(spec)
+}
+
+/// Write a model spec in the format the extension of `path` names.
+pub fn save_spec(path: &Path, spec: &MdpSpec) -> Result<(), CliError> {
+    match Format::from_path(path)? {
+        Format::Yaml => save_yaml(path, spec)?,
+        Format::Json => save_json(path, spec)?,
+        Format::Toml => save_toml(path, spec)?,
+        Format::Binary => save_binary(path, spec)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Args;
+use rand::{Rng, SeedableRng};
+use weavetree_core::{
+    ActionId, RunMetrics, SearchConfig, SeedSequence, StateKey as CoreStateKey, Tree, TreeSnapshot,
+};
+use weavetree_mdp::{CompiledMdp, DefaultRng, EpisodeSimulator, MdpSimulator, StateKey};
+
+use crate::error::CliError;
+use crate::model::load_spec;
+
+/// `z` of the confidence intervals printed for root actions, for 95% coverage.
+const CONFIDENCE_Z: f64 = 1.96;
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Model file: .yaml, .yml, .json, .toml, or .bin.
+    pub model: PathBuf,
+    /// Search config YAML; unknown fields are rejected. Defaults to `SearchConfig::default()`.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// Seed of the simulator; rollouts draw from a stream derived from it.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Print the tree snapshot as JSON after the results.
+    #[arg(long)]
+    pub dump_tree: bool,
+    /// Write the tree as a Graphviz DOT graph.
+    #[arg(long, value_name = "PATH")]
+    pub export_dot: Option<PathBuf>,
+    /// Keep only nodes up to this many levels below the root in the DOT graph.
+    #[arg(long, requires = "export_dot")]
+    pub max_depth: Option<u64>,
+    /// Write the tree snapshot as pretty JSON.
+    #[arg(long, value_name = "PATH")]
+    pub export_json: Option<PathBuf>,
+    /// Write the tree as node-link JSON for NetworkX or d3.
+    #[arg(long, value_name = "PATH")]
+    pub export_node_link: Option<PathBuf>,
+    /// Watch the search in a terminal dashboard; press `q` to stop it early.
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    pub tui: bool,
+}
+
+/// Search from the model's start state and print the recommended root action.
+pub fn run(args: &RunArgs) -> Result<ExitCode, CliError> {
+    let compiled = load_spec(&args.model)?.compile()?;
+    let config = match &args.config {
+        Some(path) => SearchConfig::from_yaml_path_strict(path)?,
+        None => SearchConfig::default(),
+    };
+    #[cfg(feature = "tui")]
+    let (tree, metrics) = if args.tui {
+        crate::tui::search(&compiled, &config, args.seed, &args.model)?
+    } else {
+        search(&compiled, &config, args.seed)?
+    };
+    #[cfg(not(feature = "tui"))]
+    let (tree, metrics) = search(&compiled, &config, args.seed)?;
+
+    let name = |action: Option<ActionId>| match action {
+        Some(action) => format!(
+            "{} ({})",
+            compiled
+                .action_name(compiled.start(), action.index())
+                .unwrap_or("?"),
+            action.index()
+        ),
+        None => "none".to_string(),
+    };
+    println!(
+        "best action by value:  {}",
+        name(tree.best_root_action_by_value()?)
+    );
+    println!(
+        "best action by visits: {}",
+        name(tree.best_root_action_by_visits()?)
+    );
+
+    println!("root actions:");
+    for stat in tree.root_action_stats(CONFIDENCE_Z)? {
+        let (low, high) = stat.confidence_interval;
+        println!(
+            "  {:<24} visits {:>8}  q {:>10.4}  95% CI [{:.4}, {:.4}]",
+            name(Some(stat.action_id)),
+            stat.visits,
+            stat.q,
+            low,
+            high
+        );
+    }
+
+    println!(
+        "iterations:         {} of {}",
+        metrics.iterations_completed, metrics.iterations_requested
+    );
+    println!("average return:     {:.4}", metrics.average_total_return);
+    println!("nodes:              {}", tree.node_count());
+    println!("rollout cache hits: {}", metrics.rollout_cache_hits);
+    println!("nodes pruned:       {}", metrics.nodes_pruned);
+    println!("selection caps:     {}", metrics.selection_caps);
+    println!(
+        "simulator steps:    {} selection, {} rollout",
+        metrics.selection_step_calls, metrics.rollout_step_calls
+    );
+
+    let snapshot = labelled_snapshot(&tree, &compiled);
+    if let Some(path) = &args.export_dot {
+        write_file(path, &snapshot.to_dot(args.max_depth))?;
+    }
+    if let Some(path) = &args.export_json {
+        write_file(path, &serde_json::to_string_pretty(&snapshot)?)?;
+    }
+    if let Some(path) = &args.export_node_link {
+        write_file(path, &snapshot.to_node_link_json()?)?;
+    }
+    if args.dump_tree {
+        println!("{}", serde_json::to_string_pretty(&snapshot)?);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Snapshot the tree with the model's state and action ids as labels.
+fn labelled_snapshot(tree: &Tree, compiled: &CompiledMdp) -> TreeSnapshot {
+    tree.snapshot()
+        .with_state_labels(|key| compiled.state_id(StateKey::from(key)).map(str::to_string))
+        .with_action_labels(|key, action| {
+            compiled
+                .action_name(StateKey::from(key), action.index())
+                .map(str::to_string)
+        })
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), CliError> {
+    std::fs::write(path, contents).map_err(|source| CliError::Write {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+/// Run one search on a fresh tree, with a seeded uniform rollout policy for configs that
+/// leave rollouts to the caller.
+pub fn search(
+    compiled: &CompiledMdp,
+    config: &SearchConfig,
+    seed: u64,
+) -> Result<(Tree, RunMetrics), CliError> {
+    search_in_chunks(
+        compiled,
+        config,
+        seed,
+        config.iterations,
+        |_tree, _metrics| Ok(true),
+    )
+}
+
+/// Run `search` in chunks of `chunk` iterations, calling `on_chunk` with the tree and the
+/// metrics so far after each. The search stops early once `on_chunk` returns `false`.
+pub fn search_in_chunks(
+    compiled: &CompiledMdp,
+    config: &SearchConfig,
+    seed: u64,
+    chunk: usize,
+    mut on_chunk: impl FnMut(&Tree, &RunMetrics) -> Result<bool, CliError>,
+) -> Result<(Tree, RunMetrics), CliError> {
+    let simulator = RefCell::new(MdpSimulator::new(compiled.clone(), seed));
+    let mut rollout_rng = DefaultRng::seed_from_u64(SeedSequence::new(seed).child_seed(0));
+    let start = simulator.borrow().start_key();
+    let mut tree = Tree::new(start, simulator.borrow().is_terminal_key(start));
+
+    let mut metrics = RunMetrics::new(config.iterations);
+    let mut chunk_config = config.clone();
+    while metrics.iterations_completed < config.iterations {
+        chunk_config.iterations = chunk
+            .max(1)
+            .min(config.iterations - metrics.iterations_completed);
+        let part = tree.run(
+            &chunk_config,
+            |state: CoreStateKey| simulator.borrow().num_actions_key(state),
+            |state, action| simulator.borrow_mut().step_key(state, action),
+            |_state, num_actions| ActionId::from(rollout_rng.gen_range(0..num_actions.max(1))),
+        )?;
+        metrics.absorb(&part);
+        if !on_chunk(&tree, &metrics)? {
+            break;
+        }
+    }
+    Ok((tree, metrics))
+}
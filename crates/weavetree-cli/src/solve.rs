@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+use weavetree_mdp::{StateKey, value_iteration};
+
+use crate::error::CliError;
+use crate::model::load_spec;
+
+#[derive(Debug, Args)]
+pub struct SolveArgs {
+    /// Model file: .yaml, .yml, .json, .toml, or .bin.
+    pub model: PathBuf,
+    /// Discount factor.
+    #[arg(long, default_value_t = 0.95)]
+    pub gamma: f64,
+    /// Stop once no state value changes by more than this in a sweep.
+    #[arg(long, default_value_t = 1e-9)]
+    pub tolerance: f64,
+    /// Stop after this many sweeps even if values still change.
+    #[arg(long, default_value_t = 100_000)]
+    pub max_iterations: usize,
+    /// Print the policy as `state,action,value` CSV rows and nothing else.
+    #[arg(long)]
+    pub csv: bool,
+}
+
+/// Solve the model exactly with value iteration and print the start value and greedy policy.
+pub fn run(args: &SolveArgs) -> Result<ExitCode, CliError> {
+    let compiled = load_spec(&args.model)?.compile()?;
+    let values = value_iteration(&compiled, args.gamma, args.tolerance, args.max_iterations);
+    if values.residual > args.tolerance {
+        eprintln!(
+            "warning: stopped after {} iterations with residual {:e}",
+            values.iterations, values.residual
+        );
+    }
+
+    let rows: Vec<(&str, &str, f64)> = (0..compiled.state_count())
+        .map(StateKey::from)
+        .map(|state| {
+            let action = values
+                .best_action(state)
+                .and_then(|action| compiled.action_name(state, action))
+                .unwrap_or("");
+            let id = compiled.state_id(state).unwrap_or("");
+            (id, action, values.value(state))
+        })
+        .collect();
+
+    if args.csv {
+        println!("state,action,value");
+        for (state, action, value) in rows {
+            println!("{},{},{value}", csv_field(state), csv_field(action));
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let start = compiled.start();
+    println!(
+        "start state {}: value {:.6}",
+        compiled.state_id(start).unwrap_or(""),
+        values.value(start)
+    );
+    println!(
+        "iterations: {}, residual {:e}",
+        values.iterations, values.residual
+    );
+    println!("policy:");
+    for (state, action, value) in rows {
+        let action = if action.is_empty() { "-" } else { action };
+        println!("  {state:<24} {action:<16} {value:.6}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Quote a CSV field if it contains a separator, quote, or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
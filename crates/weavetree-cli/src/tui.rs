@@ -0,0 +1,188 @@
+//! `weavetree run --tui`: a terminal dashboard of the running search.
+
+use std::cmp::Reverse;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, Gauge, Paragraph};
+use weavetree_core::{RootActionStat, RunMetrics, SearchConfig, Tree};
+use weavetree_mdp::CompiledMdp;
+
+use crate::error::CliError;
+use crate::run::search_in_chunks;
+
+/// Iterations between checks for a redraw or a key press.
+const CHUNK: usize = 256;
+/// Minimum time between redraws.
+const REFRESH: Duration = Duration::from_millis(100);
+/// Root actions shown in the visits chart.
+const TOP_ACTIONS: usize = 10;
+
+/// Run `run::search` while drawing progress, throughput, tree size, average return, and
+/// the most visited root actions. `q`, `Esc`, or `Ctrl-C` stops the search early; the
+/// dashboard closes when the search ends, leaving the usual summary on stdout.
+pub fn search(
+    compiled: &CompiledMdp,
+    config: &SearchConfig,
+    seed: u64,
+    model: &Path,
+) -> Result<(Tree, RunMetrics), CliError> {
+    if !io::stdout().is_terminal() {
+        return Err(CliError::Terminal(io::Error::other(
+            "--tui needs stdout to be an interactive terminal",
+        )));
+    }
+    let mut terminal = ratatui::try_init().map_err(CliError::Terminal)?;
+    let title = format!(" weavetree run {} ", model.display());
+    let started = Instant::now();
+    let mut last_draw: Option<Instant> = None;
+
+    let result = search_in_chunks(compiled, config, seed, CHUNK, |tree, metrics| {
+        let finished = metrics.iterations_completed == metrics.iterations_requested;
+        if finished || last_draw.is_none_or(|drawn| drawn.elapsed() >= REFRESH) {
+            let dashboard = Dashboard {
+                title: &title,
+                metrics,
+                node_count: tree.node_count(),
+                elapsed: started.elapsed(),
+                actions: top_actions(tree, compiled)?,
+            };
+            terminal
+                .draw(|frame| dashboard.render(frame))
+                .map_err(CliError::Terminal)?;
+            last_draw = Some(Instant::now());
+        }
+        Ok(!stop_requested().map_err(CliError::Terminal)?)
+    });
+
+    ratatui::try_restore().map_err(CliError::Terminal)?;
+    result
+}
+
+/// One frame of the dashboard.
+struct Dashboard<'a> {
+    title: &'a str,
+    metrics: &'a RunMetrics,
+    node_count: usize,
+    elapsed: Duration,
+    actions: Vec<(String, RootActionStat)>,
+}
+
+impl Dashboard<'_> {
+    fn render(&self, frame: &mut Frame) {
+        let [progress, stats, chart, help] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(6),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ])
+            .areas(frame.area());
+
+        let completed = self.metrics.iterations_completed;
+        let requested = self.metrics.iterations_requested;
+        let ratio = if requested == 0 {
+            1.0
+        } else {
+            completed as f64 / requested as f64
+        };
+        frame.render_widget(
+            Gauge::default()
+                .block(Block::bordered().title(self.title))
+                .gauge_style(Style::default().fg(Color::Cyan))
+                .ratio(ratio)
+                .label(format!("{completed} of {requested} iterations")),
+            progress,
+        );
+
+        let seconds = self.elapsed.as_secs_f64();
+        let rate = if seconds > 0.0 {
+            completed as f64 / seconds
+        } else {
+            0.0
+        };
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(format!("iterations/sec:  {rate:.0}")),
+                Line::from(format!("nodes:           {}", self.node_count)),
+                Line::from(format!(
+                    "average return:  {:.4}",
+                    self.metrics.average_total_return
+                )),
+                Line::from(format!("elapsed:         {seconds:.1}s")),
+            ])
+            .block(Block::bordered().title(" search ")),
+            stats,
+        );
+
+        let bars: Vec<Bar> = self
+            .actions
+            .iter()
+            .map(|(name, stat)| {
+                Bar::default()
+                    .label(Line::from(name.as_str()))
+                    .value(stat.visits)
+                    .text_value(format!("{}  q {:.4}", stat.visits, stat.q))
+            })
+            .collect();
+        frame.render_widget(
+            BarChart::default()
+                .block(Block::bordered().title(" root actions by visits "))
+                .direction(Direction::Horizontal)
+                .bar_width(1)
+                .bar_gap(0)
+                .bar_style(Style::default().fg(Color::Green))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Green))
+                .data(BarGroup::default().bars(&bars)),
+            chart,
+        );
+
+        frame.render_widget(Line::from(" q: stop the search early"), help);
+    }
+}
+
+/// The most visited legal root actions, named by the model.
+fn top_actions(
+    tree: &Tree,
+    compiled: &CompiledMdp,
+) -> Result<Vec<(String, RootActionStat)>, CliError> {
+    let mut stats: Vec<RootActionStat> = tree
+        .root_action_stats(0.0)?
+        .into_iter()
+        .filter(|stat| stat.legal)
+        .collect();
+    stats.sort_by_key(|stat| Reverse(stat.visits));
+    stats.truncate(TOP_ACTIONS);
+    Ok(stats
+        .into_iter()
+        .map(|stat| {
+            let index = stat.action_id.index();
+            let name = compiled
+                .action_name(compiled.start(), index)
+                .map_or_else(|| index.to_string(), str::to_string);
+            (name, stat)
+        })
+        .collect())
+}
+
+/// Drain pending terminal events and report whether one asked to stop.
+fn stop_requested() -> io::Result<bool> {
+    let mut stop = false;
+    while event::poll(Duration::ZERO)? {
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            stop |= matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+                || (key.code == KeyCode::Char('c')
+                    && key.modifiers.contains(KeyModifiers::CONTROL));
+        }
+    }
+    Ok(stop)
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+
+use crate::error::CliError;
+use crate::model::load_spec;
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    /// Model file: .yaml, .yml, .json, .toml, or .bin.
+    pub model: PathBuf,
+    /// Exit with an error status if there are lint warnings.
+    #[arg(long)]
+    pub deny_warnings: bool,
+}
+
+/// Compile the model to surface validation errors, then print one line per lint.
+pub fn run(args: &ValidateArgs) -> Result<ExitCode, CliError> {
+    let spec = load_spec(&args.model)?;
+    let lints = spec.lint()?;
+    for lint in &lints {
+        println!("warning: {lint}");
+    }
+    println!(
+        "{}: valid, {} states, {} warning{}",
+        args.model.display(),
+        spec.states.len(),
+        lints.len(),
+        if lints.len() == 1 { "" } else { "s" }
+    );
+    if args.deny_warnings && !lints.is_empty() {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
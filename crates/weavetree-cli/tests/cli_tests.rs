@@ -0,0 +1,379 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use weavetree_mdp::benchmarks::chain;
+use weavetree_mdp::{load_binary, load_json, load_yaml, save_yaml, value_iteration};
+
+const MODEL_YAML: &str = r#"
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: go
+        outcomes:
+          - next: goal
+            prob: 0.8
+            reward: 1.0
+          - next: s0
+            prob: rest
+            reward: 0.0
+  - id: goal
+    terminal: true
+  - id: orphan
+    terminal: true
+"#;
+
+/// Run the `weavetree` binary with `args`.
+fn weavetree(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_weavetree"))
+        .args(args)
+        .output()
+        .expect("weavetree binary should run")
+}
+
+/// Return a fresh temp directory for one test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("weavetree-cli-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir should be creatable");
+    dir
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn validate_prints_lint_warnings_and_errors() {
+    let dir = temp_dir("validate");
+    let model = dir.join("model.yaml");
+    std::fs::write(&model, MODEL_YAML).expect("model should be writable");
+    let model = model.to_str().expect("utf-8 path");
+
+    let output = weavetree(&["validate", model]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "warning: state 'orphan' is unreachable from the start state\n\
+             {model}: valid, 3 states, 1 warning\n"
+        )
+    );
+
+    let output = weavetree(&["validate", "--deny-warnings", model]);
+    assert!(!output.status.success());
+
+    let broken = dir.join("broken.yaml");
+    std::fs::write(&broken, MODEL_YAML.replace("prob: 0.8", "prob: 1.8"))
+        .expect("model should be writable");
+    let output = weavetree(&["validate", broken.to_str().expect("utf-8 path")]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).starts_with("error: "),
+        "{}",
+        stderr(&output)
+    );
+
+    let output = weavetree(&["validate", "model.txt"]);
+    assert!(stderr(&output).contains("cannot tell the model format of 'model.txt'"));
+
+    std::fs::remove_dir_all(&dir).expect("temp dir should be removable");
+}
+
+#[test]
+fn compile_writes_a_loadable_binary_model() {
+    let dir = temp_dir("compile");
+    let model = dir.join("model.yaml");
+    std::fs::write(&model, MODEL_YAML).expect("model should be writable");
+
+    let output = weavetree(&["compile", model.to_str().expect("utf-8 path")]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let default_output = dir.join("model.bin");
+    let compiled = load_binary(&default_output)
+        .expect("binary should load")
+        .compile()
+        .expect("binary should compile");
+    assert_eq!(compiled.state_count(), 3);
+    assert_eq!(compiled.state_id(compiled.start()), Some("s0"));
+
+    let custom = dir.join("custom.bin");
+    let output = weavetree(&[
+        "compile",
+        default_output.to_str().expect("utf-8 path"),
+        "-o",
+        custom.to_str().expect("utf-8 path"),
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).ends_with(&format!("wrote 3 states to {}\n", custom.display())));
+    assert!(custom.exists());
+
+    std::fs::remove_dir_all(&dir).expect("temp dir should be removable");
+}
+
+#[test]
+fn run_searches_and_prints_the_best_root_action() {
+    let dir = temp_dir("run");
+    let model = dir.join("model.yaml");
+    std::fs::write(
+        &model,
+        r#"
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: stay
+        outcomes:
+          - next: done
+            prob: 1.0
+            reward: 0.1
+      - id: gamble
+        outcomes:
+          - next: done
+            prob: 0.5
+            reward: 1.0
+          - next: done
+            prob: rest
+            reward: 0.0
+  - id: done
+    terminal: true
+"#,
+    )
+    .expect("model should be writable");
+    let config = dir.join("search.yaml");
+    std::fs::write(&config, "iterations: 200\nc: 1.0\n").expect("config should be writable");
+    let model = model.to_str().expect("utf-8 path");
+    let config = config.to_str().expect("utf-8 path");
+
+    let output = weavetree(&["run", model, "--config", config, "--seed", "7"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.contains("best action by value:  gamble (1)"), "{text}");
+    assert!(text.contains("best action by visits: gamble (1)"), "{text}");
+    assert!(text.contains("iterations:         200 of 200"), "{text}");
+    assert!(text.contains("nodes:              3"), "{text}");
+    assert!(
+        text.contains("simulator steps:    200 selection, 0 rollout"),
+        "{text}"
+    );
+
+    let again = weavetree(&["run", model, "--config", config, "--seed", "7"]);
+    assert_eq!(stdout(&again), text);
+
+    let output = weavetree(&["run", model, "--config", config, "--dump-tree"]);
+    let text = stdout(&output);
+    let snapshot = &text[text.find('{').expect("snapshot json")..];
+    let snapshot: serde_json::Value = serde_json::from_str(snapshot).expect("snapshot parses");
+    assert!(snapshot.get("nodes").is_some(), "{snapshot}");
+
+    std::fs::write(config, "iteration: 200\n").expect("config should be writable");
+    let output = weavetree(&["run", model, "--config", config]);
+    assert!(stderr(&output).contains("unknown field `iteration`"));
+
+    std::fs::remove_dir_all(&dir).expect("temp dir should be removable");
+}
+
+#[test]
+fn bench_reports_throughput_and_memory_per_model() {
+    let output = weavetree(&[
+        "bench",
+        "--iterations",
+        "50",
+        "--model",
+        "chain",
+        "--model",
+        "taxi",
+        "--json",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let results: Vec<serde_json::Value> = stdout(&output)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("one JSON object per line"))
+        .collect();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["model"], "chain");
+    assert_eq!(results[1]["model"], "taxi");
+    assert_eq!(results[1]["states"], 500);
+    for result in &results {
+        assert_eq!(result["iterations"], 50);
+        assert_eq!(result["nodes"], 51);
+        assert!(result["iterations_per_second"].as_f64().expect("number") > 0.0);
+        assert!(result["nodes_per_second"].as_f64().expect("number") > 0.0);
+        if cfg!(feature = "heap-stats") {
+            assert!(result["peak_heap_bytes"].as_u64().expect("number") > 0);
+        } else {
+            assert!(result["peak_heap_bytes"].is_null());
+        }
+    }
+}
+
+#[test]
+fn convert_moves_models_between_formats() {
+    let dir = temp_dir("convert");
+    let yaml = dir.join("model.yaml");
+    std::fs::write(&yaml, MODEL_YAML).expect("model should be writable");
+    let original = load_yaml(&yaml).expect("yaml should load");
+
+    let mut previous = yaml;
+    for name in ["model.json", "model.toml", "model.bin", "round-trip.yml"] {
+        let next = dir.join(name);
+        let output = weavetree(&[
+            "convert",
+            previous.to_str().expect("utf-8 path"),
+            next.to_str().expect("utf-8 path"),
+        ]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        previous = next;
+    }
+    let round_trip = load_yaml(&previous).expect("yaml should load");
+    assert_eq!(
+        serde_json::to_value(&round_trip).expect("spec should serialize"),
+        serde_json::to_value(&original).expect("spec should serialize")
+    );
+
+    let rough = dir.join("rough.yaml");
+    std::fs::write(
+        &rough,
+        MODEL_YAML
+            .replace("prob: 0.8", "prob: 0.5")
+            .replace("prob: rest", "prob: 0.4999999"),
+    )
+    .expect("model should be writable");
+    let normalized = dir.join("normalized.json");
+    let output = weavetree(&[
+        "convert",
+        rough.to_str().expect("utf-8 path"),
+        normalized.to_str().expect("utf-8 path"),
+        "--normalize",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let spec = load_json(&normalized).expect("json should load");
+    let probs = spec.states[0].actions.as_ref().expect("actions")[0].resolved_probabilities();
+    assert_eq!(probs.iter().sum::<f64>(), 1.0);
+    assert!(spec.compile().is_ok());
+
+    std::fs::remove_dir_all(&dir).expect("temp dir should be removable");
+}
+
+#[test]
+fn solve_prints_the_optimal_start_value_and_policy() {
+    let dir = temp_dir("solve");
+    let model = dir.join("chain.yaml");
+    let spec = chain(5);
+    save_yaml(&model, &spec).expect("model should be writable");
+    let model = model.to_str().expect("utf-8 path");
+    let compiled = spec.compile().expect("chain compiles");
+    let exact = value_iteration(&compiled, 0.9, 1e-9, 100_000);
+
+    let output = weavetree(&["solve", model, "--gamma", "0.9"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(
+        text.starts_with(&format!(
+            "start state s0: value {:.6}\n",
+            exact.value(compiled.start())
+        )),
+        "{text}"
+    );
+    assert!(
+        text.contains("  s4                       forward"),
+        "{text}"
+    );
+
+    let output = weavetree(&["solve", model, "--gamma", "0.9", "--csv"]);
+    let text = stdout(&output);
+    let rows: Vec<&str> = text.lines().collect();
+    assert_eq!(rows.len(), 6);
+    assert_eq!(rows[0], "state,action,value");
+    assert_eq!(
+        rows[1],
+        format!("s0,forward,{}", exact.value(compiled.start()))
+    );
+
+    let output = weavetree(&["solve", model, "--gamma", "0.9", "--max-iterations", "1"]);
+    assert!(output.status.success());
+    assert!(stderr(&output).starts_with("warning: stopped after 1 iterations"));
+
+    std::fs::remove_dir_all(&dir).expect("temp dir should be removable");
+}
+
+#[test]
+fn run_exports_the_tree_as_dot_and_json() {
+    let dir = temp_dir("export");
+    let model = dir.join("chain.yaml");
+    save_yaml(&model, &chain(5)).expect("model should be writable");
+    let dot = dir.join("tree.dot");
+    let json = dir.join("tree.json");
+    let node_link = dir.join("tree.node-link.json");
+
+    let output = weavetree(&[
+        "run",
+        model.to_str().expect("utf-8 path"),
+        "--export-dot",
+        dot.to_str().expect("utf-8 path"),
+        "--max-depth",
+        "1",
+        "--export-json",
+        json.to_str().expect("utf-8 path"),
+        "--export-node-link",
+        node_link.to_str().expect("utf-8 path"),
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let dot = std::fs::read_to_string(&dot).expect("dot file written");
+    assert!(dot.starts_with("digraph tree {\n"), "{dot}");
+    assert!(
+        dot.contains("n0 [label=\"s0\\nvisits 256, depth 0\"];"),
+        "{dot}"
+    );
+    assert!(dot.contains("[label=\"forward\\nn="), "{dot}");
+    assert!(!dot.contains("depth 2"), "{dot}");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json).expect("json file written"))
+            .expect("snapshot parses");
+    let nodes = json["nodes"].as_array().expect("nodes");
+    assert!(nodes.iter().any(|node| node["depth"] == 2));
+    assert_eq!(nodes[0]["label"], "s0");
+    assert_eq!(nodes[0]["edges"][1]["label"], "back");
+
+    let node_link: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&node_link).expect("node-link file written"))
+            .expect("node-link json parses");
+    assert_eq!(node_link["nodes"][0]["id"], 0);
+    assert_eq!(node_link["nodes"][0]["label"], "s0");
+    assert_eq!(node_link["nodes"][0]["visits"], 256);
+    let links = node_link["links"].as_array().expect("links");
+    assert!(links.iter().any(|link| link["label"] == "forward"));
+
+    let output = weavetree(&[
+        "run",
+        model.to_str().expect("utf-8 path"),
+        "--max-depth",
+        "1",
+    ]);
+    assert!(!output.status.success());
+
+    std::fs::remove_dir_all(&dir).expect("temp dir should be removable");
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn run_tui_needs_a_terminal() {
+    let dir = temp_dir("tui");
+    let model = dir.join("chain.yaml");
+    save_yaml(&model, &chain(3)).expect("model should be writable");
+
+    // Test output is captured, so stdout is never a terminal here.
+    let output = weavetree(&["run", model.to_str().expect("utf-8 path"), "--tui"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        stderr(&output),
+        "error: terminal error: --tui needs stdout to be an interactive terminal\n"
+    );
+
+    std::fs::remove_dir_all(&dir).expect("temp dir should be removable");
+}
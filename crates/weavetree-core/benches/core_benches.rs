@@ -0,0 +1,177 @@
+//! Criterion benchmarks of the search hot paths on synthetic workloads.
+//!
+//! Run with `cargo bench -p weavetree-core`.
+
+use std::hint::black_box;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use weavetree_core::{ActionId, SearchConfig, StateKey, Tree};
+
+/// Synthetic MDP over hashed state keys: every state offers `branching` actions, each with two
+/// equally likely outcomes, and roughly one state in `1 << terminal_bits` is terminal.
+#[derive(Debug, Clone, Copy)]
+struct SyntheticEnv {
+    branching: usize,
+    terminal_bits: u32,
+}
+
+impl SyntheticEnv {
+    fn num_actions(&self, state: StateKey) -> usize {
+        if self.is_terminal(state) {
+            0
+        } else {
+            self.branching
+        }
+    }
+
+    fn is_terminal(&self, state: StateKey) -> bool {
+        state.value() != 0 && state.value().trailing_zeros() >= self.terminal_bits
+    }
+
+    /// Sample a transition, drawing the outcome from `rng`.
+    fn step(&self, rng: &mut u64, state: StateKey, action: ActionId) -> (StateKey, f64, bool) {
+        let outcome = splitmix(rng) & 1;
+        let next = mix(state.value() ^ ((action.index() as u64) << 1 | outcome));
+        let next = StateKey::from(next);
+        let reward = (next.value() % 7) as f64 / 7.0;
+        (next, reward, self.is_terminal(next))
+    }
+}
+
+/// SplitMix64 step, a tiny deterministic RNG so the benchmarks need no RNG crate.
+fn splitmix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    mix(*state)
+}
+
+/// Draw one of `n` actions uniformly for rollouts.
+fn uniform(rng: &mut u64, n: usize) -> ActionId {
+    ActionId::from(splitmix(rng) as usize % n.max(1))
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Grow a tree over `env` with `iterations` search iterations and uniform rollouts.
+fn grown_tree(env: SyntheticEnv, iterations: usize) -> Tree {
+    let config = SearchConfig {
+        iterations,
+        max_steps: 16,
+        ..SearchConfig::default()
+    };
+    let mut rng = 1;
+    let mut rollout_rng = 2;
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run(
+        &config,
+        |state| env.num_actions(state),
+        |state, action| env.step(&mut rng, state, action),
+        |_state, n| uniform(&mut rollout_rng, n),
+    )
+    .expect("synthetic search succeeds");
+    tree
+}
+
+fn tree_policy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tree_policy");
+    for branching in [2, 8, 32] {
+        let env = SyntheticEnv {
+            branching,
+            terminal_bits: 12,
+        };
+        let tree = grown_tree(env, 5_000);
+        group.bench_with_input(BenchmarkId::from_parameter(branching), &tree, |b, tree| {
+            let mut rng = 3;
+            b.iter_batched(
+                || tree.clone(),
+                |mut tree| {
+                    black_box(
+                        tree.tree_policy(
+                            1.4,
+                            |state| env.num_actions(state),
+                            |state, action| env.step(&mut rng, state, action),
+                        )
+                        .expect("selection succeeds"),
+                    )
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn backpropagate(c: &mut Criterion) {
+    let env = SyntheticEnv {
+        branching: 8,
+        terminal_bits: 12,
+    };
+    let mut tree = grown_tree(env, 5_000);
+    let mut rng = 3;
+    let path = tree
+        .tree_policy(
+            1.4,
+            |state| env.num_actions(state),
+            |state, action| env.step(&mut rng, state, action),
+        )
+        .expect("selection succeeds")
+        .path;
+
+    c.bench_function("backpropagate", |b| {
+        b.iter(|| {
+            tree.backpropagate(black_box(&path), black_box(0.5))
+                .expect("backpropagation succeeds")
+        });
+    });
+}
+
+fn rollout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rollout");
+    for max_steps in [16, 128, 1024] {
+        // Terminal states are too rare for rollouts to end before `max_steps`.
+        let env = SyntheticEnv {
+            branching: 4,
+            terminal_bits: 40,
+        };
+        let config = SearchConfig {
+            iterations: 64,
+            max_steps,
+            ..SearchConfig::default()
+        };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(max_steps),
+            &config,
+            |b, config| {
+                let mut rng = 1;
+                let mut rollout_rng = 2;
+                b.iter(|| {
+                    let mut tree = Tree::new(StateKey::from(0), false);
+                    tree.run(
+                        config,
+                        |state| env.num_actions(state),
+                        |state, action| env.step(&mut rng, state, action),
+                        |_state, n| uniform(&mut rollout_rng, n),
+                    )
+                    .expect("synthetic search succeeds")
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn search(c: &mut Criterion) {
+    let env = SyntheticEnv {
+        branching: 8,
+        terminal_bits: 8,
+    };
+    c.bench_function("search_1000_iterations", |b| {
+        b.iter(|| grown_tree(env, 1_000));
+    });
+}
+
+criterion_group!(benches, tree_policy, backpropagate, rollout, search);
+criterion_main!(benches);
@@ -1,10 +1,32 @@
+#[cfg(feature = "testing")]
+pub mod testing;
 mod tree;
 
+#[cfg(feature = "parquet")]
+pub use tree::columnar::{
+    ParquetRunLogger, run_log_record_batch, run_log_schema, training_record_batch, training_schema,
+};
+pub use tree::compare::RunComparison;
+pub use tree::config_builder::SearchConfigBuilder;
+pub use tree::ensemble::{EnsembleActionStat, EnsembleReport};
 pub use tree::error::TreeError;
-pub use tree::ids::{ActionId, NodeId, StateKey};
+pub use tree::extensions::{SearchExtensions, StepEvent, StepPhase};
+#[cfg(feature = "http")]
+pub use tree::http::HttpInspector;
+pub use tree::ids::{ActionId, NodeId, StateKey, StateKeyValue};
 pub use tree::mcts::{
-    IterationMetrics, RunError, RunLogEvent, RunMetrics, SearchConfig, SearchConfigError,
+    IterationMetrics, PlayerMode, RunError, RunLogEvent, RunMetrics, SearchConfig,
+    SearchConfigError, ValueNormalization,
 };
-pub use tree::rollout::ReturnType;
-pub use tree::search_tree::{Tree, TreePolicyResult};
+pub use tree::policy::GreedyPolicy;
+pub use tree::rollout::{ReturnType, RolloutPolicyKind};
+pub use tree::run_log::{LogFormat, RunLogError, RunLogger};
+pub use tree::search_tree::{RootActionStat, Tree, TreePolicyResult};
+pub use tree::seed::SeedSequence;
 pub use tree::snapshot::{ActionEdgeSnapshot, NodeSnapshot, OutcomeSnapshot, TreeSnapshot};
+#[cfg(feature = "sqlite")]
+pub use tree::sqlite::SqliteRunSink;
+#[cfg(feature = "tensorboard")]
+pub use tree::tensorboard::TensorBoardWriter;
+pub use tree::trace::{SearchTrace, TraceEvent};
+pub use tree::training::TrainingSample;
@@ -0,0 +1,226 @@
+//! Proptest strategies for property-testing code that embeds weavetree: valid search
+//! configs, small random environments, and trees grown on them.
+//!
+//! Enabled by the `testing` feature.
+
+use std::cell::Cell;
+
+use proptest::option;
+use proptest::prelude::*;
+
+use crate::{
+    ActionId, PlayerMode, ReturnType, RolloutPolicyKind, RunMetrics, SearchConfig,
+    SearchExtensions, StateKey, Tree, TreeError, ValueNormalization,
+};
+
+/// Small seeded MDP over hashed state keys, for driving searches in tests.
+///
+/// Every non-terminal state offers the same number of actions. Each action leads to one of
+/// a fixed number of successors, drawn from the environment's own random stream, with a
+/// reward in `[-1, 1]` that depends only on the successor. Any state but the root is
+/// terminal with the given probability. The same arguments always describe the same
+/// dynamics, and cloning the environment clones its stream.
+#[derive(Debug, Clone)]
+pub struct RandomEnv {
+    seed: u64,
+    num_actions: usize,
+    num_outcomes: usize,
+    terminal_probability: f64,
+    stream: Cell<u64>,
+}
+
+impl RandomEnv {
+    /// Create an environment; `num_actions` and `num_outcomes` are raised to at least one.
+    pub fn new(
+        seed: u64,
+        num_actions: usize,
+        num_outcomes: usize,
+        terminal_probability: f64,
+    ) -> Self {
+        Self {
+            seed,
+            num_actions: num_actions.max(1),
+            num_outcomes: num_outcomes.max(1),
+            terminal_probability,
+            stream: Cell::new(seed),
+        }
+    }
+
+    /// Return the root state, which is never terminal.
+    pub fn root(&self) -> StateKey {
+        StateKey::from(mix(self.seed))
+    }
+
+    pub fn is_terminal(&self, state: StateKey) -> bool {
+        state != self.root()
+            && unit(mix(state.raw() as u64 ^ self.seed)) < self.terminal_probability
+    }
+
+    /// Return the number of actions of `state`, `0` for terminal states.
+    pub fn num_actions(&self, state: StateKey) -> usize {
+        if self.is_terminal(state) {
+            0
+        } else {
+            self.num_actions
+        }
+    }
+
+    /// Sample a transition as `(next, reward, next_is_terminal)`.
+    pub fn step(&self, state: StateKey, action: ActionId) -> (StateKey, f64, bool) {
+        let outcome = self.draw() as usize % self.num_outcomes;
+        let branch = (action.index() * self.num_outcomes + outcome + 1) as u64;
+        let next = StateKey::from(mix(
+            mix(state.raw() as u64) ^ branch.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ));
+        let reward = 2.0 * unit(mix((next.raw() as u64).rotate_left(17) ^ self.seed)) - 1.0;
+        (next, reward, self.is_terminal(next))
+    }
+
+    /// Return a fixed value estimate of `state` in `[-1, 1]`, for n-step bootstrapping.
+    pub fn value_estimate(&self, state: StateKey) -> f64 {
+        2.0 * unit(mix((state.raw() as u64).rotate_left(31) ^ self.seed)) - 1.0
+    }
+
+    /// Search from the root on a fresh tree with `config`, choosing rollout actions
+    /// uniformly from the environment's stream when the config leaves them to the caller
+    /// and bootstrapping from `value_estimate`.
+    pub fn grow_tree(&self, config: &SearchConfig) -> Result<(Tree, RunMetrics), TreeError> {
+        let mut tree = Tree::new(self.root(), false);
+        let metrics = tree.run_with_extensions(
+            config,
+            |state| self.num_actions(state),
+            |state, action| self.step(state, action),
+            |_state, num_actions| ActionId::from(self.draw() as usize % num_actions.max(1)),
+            SearchExtensions::new().with_value_bootstrap(|state| self.value_estimate(state)),
+        )?;
+        Ok((tree, metrics))
+    }
+
+    fn draw(&self) -> u64 {
+        let state = self.stream.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.stream.set(state);
+        mix(state)
+    }
+}
+
+/// SplitMix64 finalizer.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Map a hash to `[0, 1)`.
+fn unit(hash: u64) -> f64 {
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Generate valid `SearchConfig`s with budgets small enough for many cases per test,
+/// covering every return type, value normalization, player mode, and rollout policy, with
+/// and without depth, node, and cache limits.
+pub fn search_config() -> impl Strategy<Value = SearchConfig> {
+    let budget = (1..=64usize, 1..=32usize, 1..=32usize, 0..=8usize);
+    let selection = (0.0..4.0f64, 0.0..1.0f64, 0.0..=1.0f64);
+    let limits = (
+        option::of(1..=8u64),
+        option::of(1..=8usize),
+        option::of(2..=256usize),
+    );
+    let modes = (
+        return_type(),
+        value_normalization(),
+        player_mode(),
+        rollout_policy(),
+    );
+    let flags = (
+        any::<bool>(),
+        any::<bool>(),
+        0..=16usize,
+        1..=4usize,
+        any::<bool>(),
+    );
+    (budget, selection, limits, modes, flags).prop_map(
+        |(
+            (iterations, max_steps, fixed_horizon_steps, bootstrap_steps),
+            (c, novelty_bonus, gamma),
+            (max_tree_depth, max_selection_depth, max_nodes),
+            (return_type, value_normalization, player_mode, rollout),
+            (
+                solver,
+                strict_rewards,
+                rollout_cache_capacity,
+                rollout_cache_min_samples,
+                phase_timing,
+            ),
+        )| SearchConfig {
+            iterations,
+            c,
+            novelty_bonus,
+            gamma,
+            max_steps,
+            return_type,
+            fixed_horizon_steps,
+            bootstrap_steps,
+            max_tree_depth,
+            max_selection_depth,
+            max_nodes,
+            value_normalization,
+            player_mode,
+            solver,
+            rollout_cache_capacity,
+            rollout_cache_min_samples,
+            strict_rewards,
+            phase_timing,
+            rollout,
+        },
+    )
+}
+
+pub fn return_type() -> impl Strategy<Value = ReturnType> {
+    prop_oneof![
+        Just(ReturnType::EpisodicUndiscounted),
+        Just(ReturnType::Discounted),
+        Just(ReturnType::FixedHorizon),
+        Just(ReturnType::NStepBootstrap),
+    ]
+}
+
+pub fn value_normalization() -> impl Strategy<Value = ValueNormalization> {
+    prop_oneof![
+        Just(ValueNormalization::None),
+        Just(ValueNormalization::MinMax),
+    ]
+}
+
+pub fn player_mode() -> impl Strategy<Value = PlayerMode> {
+    prop_oneof![Just(PlayerMode::SingleAgent), Just(PlayerMode::Negamax)]
+}
+
+pub fn rollout_policy() -> impl Strategy<Value = RolloutPolicyKind> {
+    prop_oneof![
+        Just(RolloutPolicyKind::Custom),
+        Just(RolloutPolicyKind::FirstAction),
+        any::<u64>().prop_map(|seed| RolloutPolicyKind::UniformRandom { seed }),
+        (0.0..=1.0f64, any::<u64>())
+            .prop_map(|(eps, seed)| RolloutPolicyKind::EpsilonGreedy { eps, seed }),
+    ]
+}
+
+/// Generate `RandomEnv`s with one to four actions, one to three outcomes per action, and
+/// a terminal probability of at most one half.
+pub fn random_env() -> impl Strategy<Value = RandomEnv> {
+    (any::<u64>(), 1..=4usize, 1..=3usize, 0.0..=0.5f64).prop_map(
+        |(seed, num_actions, num_outcomes, terminal_probability)| {
+            RandomEnv::new(seed, num_actions, num_outcomes, terminal_probability)
+        },
+    )
+}
+
+/// Generate trees grown by searching a `random_env` with a `search_config`.
+pub fn tree() -> impl Strategy<Value = Tree> {
+    (random_env(), search_config()).prop_map(|(env, config)| {
+        env.grow_tree(&config)
+            .expect("searching a random environment with a valid config succeeds")
+            .0
+    })
+}
@@ -1,14 +1,15 @@
-use std::{
-    slice::{Iter, IterMut},
-    vec::IntoIter,
-};
+use std::{iter::Flatten, slice, vec};
 
 use crate::tree::ids::NodeId;
 
 /// Holds all items and allows for fast allocation and is cache friendly.
+/// Released slots are tombstoned and recycled by later allocations, so ids stay stable
+/// while memory is reused.
 #[derive(Debug, Clone)]
 pub struct Arena<T> {
-    storage: Vec<T>,
+    storage: Vec<Option<T>>,
+    free: Vec<usize>,
+    live: usize,
 }
 
 impl<T> Arena<T> {
@@ -16,49 +17,108 @@ impl<T> Arena<T> {
     pub fn new() -> Self {
         Arena {
             storage: Vec::new(),
+            free: Vec::new(),
+            live: 0,
         }
     }
 
     /// Allocate a new item to the storage and return the associated NodeId
+    /// Released slots are reused before the storage grows.
     pub fn allocate(&mut self, item: T) -> NodeId {
-        let id = NodeId::from(self.storage.len());
-        self.storage.push(item);
-        id
+        self.live += 1;
+        match self.free.pop() {
+            Some(index) => {
+                self.storage[index] = Some(item);
+                NodeId::from(index)
+            }
+            None => {
+                let id = NodeId::from(self.storage.len());
+                self.storage.push(Some(item));
+                id
+            }
+        }
+    }
+
+    /// Reserve room for at least `additional` more items beyond the live ones.
+    /// Released slots count towards the reservation since they are reused first.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = additional.saturating_sub(self.free.len());
+        self.storage.reserve(needed);
+    }
+
+    /// Return how many more items fit before the storage has to grow.
+    pub fn capacity(&self) -> usize {
+        self.storage.capacity() - self.storage.len() + self.free.len()
+    }
+
+    /// Release an item, tombstoning its slot for reuse.
+    /// Returns the item if the slot was occupied.
+    pub fn release(&mut self, node_id: NodeId) -> Option<T> {
+        let item = self.storage.get_mut(node_id.index())?.take()?;
+        self.free.push(node_id.index());
+        self.live -= 1;
+        Some(item)
     }
 
     /// Retrieve an associated item from the Area
     pub fn get(&self, node_id: NodeId) -> Option<&T> {
-        self.storage.get(node_id.index())
+        self.storage.get(node_id.index())?.as_ref()
     }
 
     /// Retrieve an associated item from the Arena as a mutable borrow
     pub fn get_mut(&mut self, node_id: NodeId) -> Option<&mut T> {
-        self.storage.get_mut(node_id.index())
+        self.storage.get_mut(node_id.index())?.as_mut()
     }
 
-    /// Check the length of the Arena
+    /// Check the amount of live items in the Arena
     pub fn len(&self) -> usize {
+        self.live
+    }
+
+    /// Return the amount of slots, live or released.
+    /// Every id handed out so far is below this bound.
+    pub fn slot_count(&self) -> usize {
         self.storage.len()
     }
 
+    /// Return the amount of released slots waiting for reuse.
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+
     /// Clear the internal storage for full reset
     pub fn clear(&mut self) {
         self.storage.clear();
+        self.free.clear();
+        self.live = 0;
     }
 
     /// Check if the Arena is empty
     pub fn is_empty(&self) -> bool {
-        self.storage.is_empty()
+        self.live == 0
+    }
+
+    /// Iteration helper for the Arena, skipping released slots
+    pub fn iter(&self) -> Flatten<slice::Iter<'_, Option<T>>> {
+        self.storage.iter().flatten()
+    }
+
+    /// Mutable iteration helper for the Arena, skipping released slots
+    pub fn iter_mut(&mut self) -> Flatten<slice::IterMut<'_, Option<T>>> {
+        self.storage.iter_mut().flatten()
     }
 
-    /// Iteration helper for the Arena
-    pub fn iter(&self) -> Iter<'_, T> {
-        self.storage.iter()
+    /// Iterate live items together with their ids.
+    pub fn iter_with_ids(&self) -> impl Iterator<Item = (NodeId, &T)> + '_ {
+        self.storage
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.as_ref().map(|item| (NodeId::from(index), item)))
     }
 
-    /// Mutable iteration helper for the Arena
-    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
-        self.storage.iter_mut()
+    /// Consume the Arena into its raw slots, indexed by id.
+    pub fn into_slots(self) -> Vec<Option<T>> {
+        self.storage
     }
 }
 
@@ -66,10 +126,10 @@ impl<T> Arena<T> {
 /// - `for x in arena` (moves items out)
 impl<T> IntoIterator for Arena<T> {
     type Item = T;
-    type IntoIter = IntoIter<T>;
+    type IntoIter = Flatten<vec::IntoIter<Option<T>>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.storage.into_iter()
+        self.storage.into_iter().flatten()
     }
 }
 
@@ -77,10 +137,10 @@ impl<T> IntoIterator for Arena<T> {
 /// - `for x in &arena` (borrows items)
 impl<'a, T> IntoIterator for &'a Arena<T> {
     type Item = &'a T;
-    type IntoIter = Iter<'a, T>;
+    type IntoIter = Flatten<slice::Iter<'a, Option<T>>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.storage.iter()
+        self.storage.iter().flatten()
     }
 }
 
@@ -88,9 +148,9 @@ impl<'a, T> IntoIterator for &'a Arena<T> {
 /// - `for x in &mut arena` (mutably borrows items)
 impl<'a, T> IntoIterator for &'a mut Arena<T> {
     type Item = &'a mut T;
-    type IntoIter = IterMut<'a, T>;
+    type IntoIter = Flatten<slice::IterMut<'a, Option<T>>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.storage.iter_mut()
+        self.storage.iter_mut().flatten()
     }
 }
@@ -0,0 +1,381 @@
+//! Arrow record batches and Parquet files for run logs and training samples.
+//!
+//! Enabled by the `parquet` feature. Columns carry the same names as the JSONL fields, so
+//! analysis code can read either export.
+
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, UInt64Array,
+    builder::{Float64Builder, ListBuilder},
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+
+use crate::tree::{
+    ids::{ActionId, StateKey},
+    mcts::{RunLogEvent, RunMetrics, SearchConfig},
+    run_log::RunLogError,
+    search_tree::Tree,
+    training::TrainingSample,
+};
+
+/// Events `ParquetRunLogger` buffers before writing them as one record batch.
+const BATCH_ROWS: usize = 65_536;
+
+/// Schema of `run_log_record_batch`: the `event` tag, then the fields of every event kind,
+/// null in rows of events without that field.
+pub fn run_log_schema() -> SchemaRef {
+    let count = |name| Field::new(name, DataType::UInt64, true);
+    let real = |name| Field::new(name, DataType::Float64, true);
+    Arc::new(Schema::new(vec![
+        Field::new("event", DataType::Utf8, false),
+        count("iterations_requested"),
+        real("c"),
+        real("gamma"),
+        count("max_steps"),
+        Field::new("return_type", DataType::Utf8, true),
+        count("fixed_horizon_steps"),
+        count("iteration"),
+        count("leaf_node_id"),
+        Field::new("leaf_is_new", DataType::Boolean, true),
+        count("path_len"),
+        real("reward_prefix"),
+        real("rollout_return"),
+        real("total_return"),
+        count("node_count"),
+        count("iterations_completed"),
+        real("total_return_sum"),
+        real("average_total_return"),
+        real("total_seconds"),
+        real("selection_seconds"),
+        real("rollout_seconds"),
+        real("backprop_seconds"),
+        real("simulator_seconds"),
+    ]))
+}
+
+/// Convert events to one record batch of `run_log_schema`, one row per event.
+pub fn run_log_record_batch(events: &[RunLogEvent]) -> Result<RecordBatch, ArrowError> {
+    let rows: Vec<RunLogRow> = events.iter().map(RunLogRow::from).collect();
+    let count = |field: fn(&RunLogRow) -> Option<u64>| {
+        Arc::new(rows.iter().map(field).collect::<UInt64Array>()) as ArrayRef
+    };
+    let real = |field: fn(&RunLogRow) -> Option<f64>| {
+        Arc::new(rows.iter().map(field).collect::<Float64Array>()) as ArrayRef
+    };
+    RecordBatch::try_new(
+        run_log_schema(),
+        vec![
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| row.event),
+            )),
+            count(|row| row.iterations_requested),
+            real(|row| row.c),
+            real(|row| row.gamma),
+            count(|row| row.max_steps),
+            Arc::new(
+                rows.iter()
+                    .map(|row| row.return_type)
+                    .collect::<StringArray>(),
+            ),
+            count(|row| row.fixed_horizon_steps),
+            count(|row| row.iteration),
+            count(|row| row.leaf_node_id),
+            Arc::new(
+                rows.iter()
+                    .map(|row| row.leaf_is_new)
+                    .collect::<BooleanArray>(),
+            ),
+            count(|row| row.path_len),
+            real(|row| row.reward_prefix),
+            real(|row| row.rollout_return),
+            real(|row| row.total_return),
+            count(|row| row.node_count),
+            count(|row| row.iterations_completed),
+            real(|row| row.total_return_sum),
+            real(|row| row.average_total_return),
+            real(|row| row.total_seconds),
+            real(|row| row.selection_seconds),
+            real(|row| row.rollout_seconds),
+            real(|row| row.backprop_seconds),
+            real(|row| row.simulator_seconds),
+        ],
+    )
+}
+
+/// Schema of `training_record_batch`. State keys are `UInt64`, or decimal strings with
+/// `wide-state-keys`.
+pub fn training_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("state_key", state_key_type(), false),
+        Field::new("player", DataType::UInt64, false),
+        Field::new("visits", DataType::UInt64, false),
+        Field::new_list(
+            "policy",
+            Field::new_list_field(DataType::Float64, true),
+            false,
+        ),
+        Field::new("value", DataType::Float64, false),
+    ]))
+}
+
+/// Convert samples to one record batch of `training_schema`, one row per sample.
+pub fn training_record_batch(samples: &[TrainingSample]) -> Result<RecordBatch, ArrowError> {
+    let mut policy = ListBuilder::new(Float64Builder::new());
+    for sample in samples {
+        policy.values().append_slice(&sample.policy);
+        policy.append(true);
+    }
+    let columns: Vec<ArrayRef> = vec![
+        state_key_column(samples.iter().map(|sample| sample.state_key)),
+        Arc::new(UInt64Array::from_iter_values(
+            samples.iter().map(|sample| sample.player as u64),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            samples.iter().map(|sample| sample.visits),
+        )),
+        Arc::new(policy.finish()),
+        Arc::new(Float64Array::from_iter_values(
+            samples.iter().map(|sample| sample.value),
+        )),
+    ];
+    RecordBatch::try_new(training_schema(), columns)
+}
+
+/// Writes `RunLogEvent`s to a Snappy-compressed Parquet file of `run_log_schema`.
+/// Events are buffered and written in batches, so memory stays bounded on long runs.
+/// Call `close` to write the file footer; a logger dropped without it leaves an
+/// unreadable file.
+pub struct ParquetRunLogger<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    buffer: Vec<RunLogEvent>,
+}
+
+impl<W: Write + Send> ParquetRunLogger<W> {
+    /// Create a logger writing into `sink`.
+    pub fn new(sink: W) -> io::Result<Self> {
+        let writer = ArrowWriter::try_new(sink, run_log_schema(), Some(writer_properties()))
+            .map_err(io::Error::other)?;
+        Ok(ParquetRunLogger {
+            writer,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Buffer one event, writing the buffer out once it is full.
+    pub fn log(&mut self, event: &RunLogEvent) -> io::Result<()> {
+        self.buffer.push(event.clone());
+        if self.buffer.len() >= BATCH_ROWS {
+            self.write_buffer()?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered events and the file footer, and return the sink.
+    pub fn close(mut self) -> io::Result<W> {
+        self.write_buffer()?;
+        self.writer.into_inner().map_err(io::Error::other)
+    }
+
+    fn write_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let batch = run_log_record_batch(&self.buffer).map_err(io::Error::other)?;
+        self.writer.write(&batch).map_err(io::Error::other)?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl<P: Default> Tree<P> {
+    /// Run MCTS like `run_with_logger`, writing the events to `sink` as a Parquet file
+    /// through `ParquetRunLogger`.
+    pub fn run_with_parquet_logger<FNum, FStep, FPolicy, W>(
+        &mut self,
+        config: &SearchConfig,
+        num_actions: FNum,
+        step: FStep,
+        rollout_policy: FPolicy,
+        sink: W,
+    ) -> Result<RunMetrics, RunLogError>
+    where
+        FNum: FnMut(StateKey) -> usize,
+        FStep: FnMut(StateKey, ActionId) -> (StateKey, f64, bool),
+        FPolicy: FnMut(StateKey, usize) -> ActionId,
+        W: Write + Send,
+    {
+        let mut logger = ParquetRunLogger::new(sink)?;
+        logger.log(&RunLogEvent::run_started(config))?;
+
+        let mut write_result = Ok(());
+        let mut iteration = 0;
+        let metrics = self.run_with_hook(
+            config,
+            num_actions,
+            step,
+            rollout_policy,
+            |iteration_metrics| {
+                if write_result.is_ok() {
+                    write_result = logger.log(&RunLogEvent::iteration_completed(
+                        iteration,
+                        iteration_metrics,
+                    ));
+                }
+                iteration += 1;
+            },
+        )?;
+        write_result?;
+
+        logger.log(&RunLogEvent::run_completed(&metrics))?;
+        logger.close()?;
+        Ok(metrics)
+    }
+}
+
+impl<P> Tree<P> {
+    /// Write `training_samples(min_visits)` to `writer` as a Snappy-compressed Parquet file
+    /// of `training_schema`. Returns how many samples were written.
+    pub fn write_training_parquet<W: Write + Send>(
+        &self,
+        min_visits: u64,
+        writer: W,
+    ) -> io::Result<usize> {
+        let samples = self.training_samples(min_visits);
+        let batch = training_record_batch(&samples).map_err(io::Error::other)?;
+        let mut writer = ArrowWriter::try_new(writer, batch.schema(), Some(writer_properties()))
+            .map_err(io::Error::other)?;
+        writer.write(&batch).map_err(io::Error::other)?;
+        writer.close().map_err(io::Error::other)?;
+        Ok(samples.len())
+    }
+}
+
+fn writer_properties() -> WriterProperties {
+    WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build()
+}
+
+/// One run log row, with the fields its event lacks left as `None`.
+#[derive(Default)]
+struct RunLogRow {
+    event: &'static str,
+    iterations_requested: Option<u64>,
+    c: Option<f64>,
+    gamma: Option<f64>,
+    max_steps: Option<u64>,
+    return_type: Option<&'static str>,
+    fixed_horizon_steps: Option<u64>,
+    iteration: Option<u64>,
+    leaf_node_id: Option<u64>,
+    leaf_is_new: Option<bool>,
+    path_len: Option<u64>,
+    reward_prefix: Option<f64>,
+    rollout_return: Option<f64>,
+    total_return: Option<f64>,
+    node_count: Option<u64>,
+    iterations_completed: Option<u64>,
+    total_return_sum: Option<f64>,
+    average_total_return: Option<f64>,
+    total_seconds: Option<f64>,
+    selection_seconds: Option<f64>,
+    rollout_seconds: Option<f64>,
+    backprop_seconds: Option<f64>,
+    simulator_seconds: Option<f64>,
+}
+
+impl From<&RunLogEvent> for RunLogRow {
+    fn from(event: &RunLogEvent) -> Self {
+        let mut row = RunLogRow::default();
+        match *event {
+            RunLogEvent::RunStarted {
+                iterations_requested,
+                c,
+                gamma,
+                max_steps,
+                return_type,
+                fixed_horizon_steps,
+            } => {
+                row.event = "run_started";
+                row.iterations_requested = Some(iterations_requested as u64);
+                row.c = Some(c);
+                row.gamma = Some(gamma);
+                row.max_steps = Some(max_steps as u64);
+                row.return_type = Some(RunLogEvent::return_type_name(return_type));
+                row.fixed_horizon_steps = Some(fixed_horizon_steps as u64);
+            }
+            RunLogEvent::IterationCompleted {
+                iteration,
+                leaf_node_id,
+                leaf_is_new,
+                path_len,
+                reward_prefix,
+                rollout_return,
+                total_return,
+                node_count,
+            } => {
+                row.event = "iteration_completed";
+                row.iteration = Some(iteration as u64);
+                row.leaf_node_id = Some(leaf_node_id as u64);
+                row.leaf_is_new = Some(leaf_is_new);
+                row.path_len = Some(path_len as u64);
+                row.reward_prefix = Some(reward_prefix);
+                row.rollout_return = Some(rollout_return);
+                row.total_return = Some(total_return);
+                row.node_count = Some(node_count as u64);
+            }
+            RunLogEvent::RunCompleted {
+                iterations_requested,
+                iterations_completed,
+                total_return_sum,
+                average_total_return,
+                total_seconds,
+                selection_seconds,
+                rollout_seconds,
+                backprop_seconds,
+                simulator_seconds,
+            } => {
+                row.event = "run_completed";
+                row.iterations_requested = Some(iterations_requested as u64);
+                row.iterations_completed = Some(iterations_completed as u64);
+                row.total_return_sum = Some(total_return_sum);
+                row.average_total_return = Some(average_total_return);
+                row.total_seconds = Some(total_seconds);
+                row.selection_seconds = Some(selection_seconds);
+                row.rollout_seconds = Some(rollout_seconds);
+                row.backprop_seconds = Some(backprop_seconds);
+                row.simulator_seconds = Some(simulator_seconds);
+            }
+        }
+        row
+    }
+}
+
+#[cfg(not(feature = "wide-state-keys"))]
+fn state_key_type() -> DataType {
+    DataType::UInt64
+}
+
+#[cfg(not(feature = "wide-state-keys"))]
+fn state_key_column(keys: impl Iterator<Item = StateKey>) -> ArrayRef {
+    Arc::new(UInt64Array::from_iter_values(keys.map(|key| key.raw())))
+}
+
+/// Arrow has no 128-bit unsigned integer, so wide keys are written as decimal strings,
+/// like trace events do.
+#[cfg(feature = "wide-state-keys")]
+fn state_key_type() -> DataType {
+    DataType::Utf8
+}
+
+#[cfg(feature = "wide-state-keys")]
+fn state_key_column(keys: impl Iterator<Item = StateKey>) -> ArrayRef {
+    Arc::new(StringArray::from_iter_values(
+        keys.map(|key| key.raw().to_string()),
+    ))
+}
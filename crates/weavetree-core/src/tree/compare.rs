@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tree::{
+    ensemble::EnsembleReport,
+    error::TreeError,
+    ids::{ActionId, StateKey},
+    mcts::SearchConfig,
+    search_tree::Tree,
+};
+
+/// Two-sided 95% critical values of Student's t distribution for 1 to 30 degrees of freedom.
+const T_CRITICAL_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+/// Critical value used past the table, where the t distribution is close to normal.
+const Z_CRITICAL_95: f64 = 1.96;
+
+/// A/B comparison of two search configs run on the same seeds, see `Tree::compare_runs`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunComparison {
+    pub a: EnsembleReport,
+    pub b: EnsembleReport,
+    /// Mean over seeds of each run's `average_total_return` under config A.
+    pub mean_return_a: f64,
+    /// Mean over seeds of each run's `average_total_return` under config B.
+    pub mean_return_b: f64,
+    /// Share of seeds on which both configs picked the same most visited root action.
+    pub best_action_agreement: f64,
+    /// Paired t statistic of the per-seed return differences `b - a`.
+    /// `0.0` with fewer than two seeds or when every difference is identical.
+    pub t_statistic: f64,
+    /// Whether the return difference is significant at the 95% level (two-sided paired t-test).
+    /// Identical nonzero differences on every seed count as significant.
+    pub significant: bool,
+}
+
+impl Tree {
+    /// Run `config_a` and `config_b` on every seed and compare them.
+    /// Both configs see the same seeds, so per-seed returns are compared pairwise.
+    /// `simulator(seed)` is called once per config and seed, see `Tree::run_ensemble`.
+    pub fn compare_runs<S, FNum, FStep, FPolicy>(
+        root_state_key: StateKey,
+        config_a: &SearchConfig,
+        config_b: &SearchConfig,
+        seeds: &[u64],
+        mut simulator: S,
+    ) -> Result<RunComparison, TreeError>
+    where
+        S: FnMut(u64) -> (FNum, FStep, FPolicy),
+        FNum: FnMut(StateKey) -> usize,
+        FStep: FnMut(StateKey, ActionId) -> (StateKey, f64, bool),
+        FPolicy: FnMut(StateKey, usize) -> ActionId,
+    {
+        let a = Tree::run_ensemble(root_state_key, config_a, seeds, &mut simulator)?;
+        let b = Tree::run_ensemble(root_state_key, config_b, seeds, &mut simulator)?;
+
+        let returns_a: Vec<f64> = a.runs.iter().map(|run| run.average_total_return).collect();
+        let returns_b: Vec<f64> = b.runs.iter().map(|run| run.average_total_return).collect();
+        let differences: Vec<f64> = returns_a
+            .iter()
+            .zip(&returns_b)
+            .map(|(a, b)| b - a)
+            .collect();
+        let t = paired_t_statistic(&differences);
+        let significant = match t {
+            Some(t) => t.abs() > t_critical_95(differences.len() - 1),
+            None => differences.len() >= 2 && mean(&differences) != 0.0,
+        };
+
+        let best_action_agreement = if seeds.is_empty() {
+            0.0
+        } else {
+            let agreeing = a
+                .best_actions
+                .iter()
+                .zip(&b.best_actions)
+                .filter(|(a, b)| a == b)
+                .count();
+            agreeing as f64 / seeds.len() as f64
+        };
+
+        Ok(RunComparison {
+            mean_return_a: mean(&returns_a),
+            mean_return_b: mean(&returns_b),
+            best_action_agreement,
+            t_statistic: t.unwrap_or(0.0),
+            significant,
+            a,
+            b,
+        })
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+/// Mean difference over its standard error; `None` with fewer than two values or zero spread.
+fn paired_t_statistic(differences: &[f64]) -> Option<f64> {
+    let n = differences.len();
+    if n < 2 {
+        return None;
+    }
+    let mean_difference = mean(differences);
+    let variance = differences
+        .iter()
+        .map(|d| (d - mean_difference).powi(2))
+        .sum::<f64>()
+        / (n - 1) as f64;
+    let stderr = (variance / n as f64).sqrt();
+    (stderr > 0.0).then(|| mean_difference / stderr)
+}
+
+fn t_critical_95(degrees_of_freedom: usize) -> f64 {
+    T_CRITICAL_95
+        .get(degrees_of_freedom.wrapping_sub(1))
+        .copied()
+        .unwrap_or(Z_CRITICAL_95)
+}
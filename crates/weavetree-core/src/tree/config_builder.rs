@@ -0,0 +1,162 @@
+use crate::tree::{
+    mcts::{PlayerMode, SearchConfig, SearchConfigError, ValueNormalization},
+    rollout::{ReturnType, RolloutPolicyKind},
+};
+
+/// Named presets bundled with this crate, as `(name, yaml)`.
+const PRESETS: &[(&str, &str)] = &[
+    ("fast", include_str!("../../config/presets/fast.yaml")),
+    (
+        "balanced",
+        include_str!("../../config/presets/balanced.yaml"),
+    ),
+    (
+        "thorough",
+        include_str!("../../config/presets/thorough.yaml"),
+    ),
+];
+
+impl SearchConfig {
+    /// Start building a config from the defaults.
+    pub fn builder() -> SearchConfigBuilder {
+        SearchConfigBuilder::new()
+    }
+
+    /// Return the names accepted by `SearchConfig::preset`.
+    pub fn preset_names() -> impl Iterator<Item = &'static str> {
+        PRESETS.iter().map(|(name, _)| *name)
+    }
+
+    /// Load a named preset: `fast`, `balanced` (the defaults), or `thorough`.
+    pub fn preset(name: &str) -> Result<Self, SearchConfigError> {
+        let (_, yaml) = PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .ok_or_else(|| {
+                SearchConfigError::Invalid(format!(
+                    "unknown preset '{name}', expected one of: {}",
+                    Self::preset_names().collect::<Vec<_>>().join(", ")
+                ))
+            })?;
+        Self::from_yaml_str(yaml)
+    }
+}
+
+/// Builder for `SearchConfig`; every field starts at its default and is validated by `build`.
+#[derive(Debug, Clone, Default)]
+pub struct SearchConfigBuilder {
+    config: SearchConfig,
+}
+
+impl SearchConfigBuilder {
+    /// Create a builder starting from `SearchConfig::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a builder starting from a named preset.
+    pub fn from_preset(name: &str) -> Result<Self, SearchConfigError> {
+        Ok(SearchConfigBuilder {
+            config: SearchConfig::preset(name)?,
+        })
+    }
+
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.config.iterations = iterations;
+        self
+    }
+
+    pub fn c(mut self, c: f64) -> Self {
+        self.config.c = c;
+        self
+    }
+
+    pub fn novelty_bonus(mut self, novelty_bonus: f64) -> Self {
+        self.config.novelty_bonus = novelty_bonus;
+        self
+    }
+
+    pub fn gamma(mut self, gamma: f64) -> Self {
+        self.config.gamma = gamma;
+        self
+    }
+
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.config.max_steps = max_steps;
+        self
+    }
+
+    pub fn return_type(mut self, return_type: ReturnType) -> Self {
+        self.config.return_type = return_type;
+        self
+    }
+
+    pub fn fixed_horizon_steps(mut self, fixed_horizon_steps: usize) -> Self {
+        self.config.fixed_horizon_steps = fixed_horizon_steps;
+        self
+    }
+
+    pub fn bootstrap_steps(mut self, bootstrap_steps: usize) -> Self {
+        self.config.bootstrap_steps = bootstrap_steps;
+        self
+    }
+
+    pub fn max_tree_depth(mut self, max_tree_depth: u64) -> Self {
+        self.config.max_tree_depth = Some(max_tree_depth);
+        self
+    }
+
+    pub fn max_selection_depth(mut self, max_selection_depth: usize) -> Self {
+        self.config.max_selection_depth = Some(max_selection_depth);
+        self
+    }
+
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.config.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub fn value_normalization(mut self, value_normalization: ValueNormalization) -> Self {
+        self.config.value_normalization = value_normalization;
+        self
+    }
+
+    pub fn player_mode(mut self, player_mode: PlayerMode) -> Self {
+        self.config.player_mode = player_mode;
+        self
+    }
+
+    pub fn solver(mut self, solver: bool) -> Self {
+        self.config.solver = solver;
+        self
+    }
+
+    /// Enable the rollout cache with room for `capacity` states, trusting a mean after `min_samples` rollouts.
+    pub fn rollout_cache(mut self, capacity: usize, min_samples: usize) -> Self {
+        self.config.rollout_cache_capacity = capacity;
+        self.config.rollout_cache_min_samples = min_samples;
+        self
+    }
+
+    pub fn strict_rewards(mut self, strict_rewards: bool) -> Self {
+        self.config.strict_rewards = strict_rewards;
+        self
+    }
+
+    /// Measure phase and simulator callback times into the metrics.
+    pub fn phase_timing(mut self, phase_timing: bool) -> Self {
+        self.config.phase_timing = phase_timing;
+        self
+    }
+
+    pub fn rollout(mut self, rollout: RolloutPolicyKind) -> Self {
+        self.config.rollout = rollout;
+        self
+    }
+
+    /// Validate and return the config, with the same rules as YAML loading.
+    pub fn build(self) -> Result<SearchConfig, SearchConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
@@ -1,29 +1,36 @@
 #![allow(dead_code)]
 
 use core::f64;
+use std::ops::{Deref, DerefMut};
 
 use crate::tree::{
     ids::{ActionId, NodeId, StateKey},
     outcomes::OutcomeSet,
-    stats::EdgeStats,
+    stats::{EdgeStats, ValueBounds},
 };
 
 #[derive(Debug, Clone)]
 /// represents “taking a particular action from this node.”
-/// Allows holding the stats of the edge and the outcomes associated with it
+/// Holds the cold data of an edge: the outcomes associated with it and its flags.
+/// The hot visit/value statistics live in the node's contiguous `EdgeStats` array,
+/// `EdgeRef`/`EdgeMut` join both halves back together.
 pub struct ActionEdge {
     action: ActionId,
-    edge_stats: EdgeStats,
     outcomes: OutcomeSet,
+    legal: bool,
+    player_value_sums: Vec<f64>,
+    proven_value: Option<f64>,
 }
 
 impl ActionEdge {
     /// Create a new action edge
     pub fn new(action: ActionId) -> Self {
         ActionEdge {
-            edge_stats: EdgeStats::new(),
             outcomes: OutcomeSet::new(),
             action,
+            legal: true,
+            player_value_sums: Vec::new(),
+            proven_value: None,
         }
     }
 
@@ -32,19 +39,34 @@ impl ActionEdge {
         self.action
     }
 
-    /// Function to be used for backpropagation.
-    /// Immediately records the rollout return and increments the visits.
-    pub fn record(&mut self, rollout_return: f64) {
-        self.edge_stats.record(rollout_return);
+    /// Check whether this action may be selected by the tree policy.
+    pub fn is_legal(&self) -> bool {
+        self.legal
     }
 
-    /// Calculate UCB score for this given edge
-    pub fn ucb_score(&self, n_parent: u64, c: f64) -> f64 {
-        if self.edge_stats.is_unvisited() {
-            f64::INFINITY
-        } else {
-            self.edge_stats.q()
-                + c * f64::sqrt(f64::ln(n_parent as f64) / self.edge_stats.visits() as f64)
+    /// Mark this action as legal or illegal without changing its index.
+    pub fn set_legal(&mut self, legal: bool) {
+        self.legal = legal
+    }
+
+    /// Return the exact value of this action for the node's player, once the solver proved it.
+    pub fn proven_value(&self) -> Option<f64> {
+        self.proven_value
+    }
+
+    /// Mark this action as proven with an exact value for the node's player.
+    pub fn set_proven_value(&mut self, value: f64) {
+        self.proven_value = Some(value)
+    }
+
+    /// Accumulate a per-player return vector (MaxN backpropagation).
+    /// The visit itself is counted by `EdgeMut::record`.
+    pub fn record_player_values(&mut self, returns: &[f64]) {
+        if self.player_value_sums.len() < returns.len() {
+            self.player_value_sums.resize(returns.len(), 0.0);
+        }
+        for (sum, value) in self.player_value_sums.iter_mut().zip(returns.iter()) {
+            *sum += value;
         }
     }
 
@@ -61,39 +83,177 @@ impl ActionEdge {
         self.outcomes.insert_outcome(next_state_key, child_id)
     }
 
+    /// Rewrite outcome child ids, dropping outcomes for which `remap` returns `None`.
+    pub fn remap_children<F>(&mut self, remap: F)
+    where
+        F: FnMut(NodeId) -> Option<NodeId>,
+    {
+        self.outcomes.remap_children(remap)
+    }
+
     /// Icrement the count on a single occurence
     /// Returns Option<NodeId>, with Some(child_id) in case the incrementing worked
     pub fn increment_outcome(&mut self, next_state_key: StateKey) -> Option<NodeId> {
         self.outcomes.increment_outcome(next_state_key)
     }
 
+    /// Return the amount of distinct outcomes observed under this edge.
+    pub fn outcomes_len(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    /// Return the count for a given observed next state key.
+    pub fn outcome_count_for(&self, next_state_key: StateKey) -> Option<u64> {
+        self.outcomes.count_for(next_state_key)
+    }
+
+    /// Estimate how likely the next sample is an outcome not observed yet under this edge.
+    pub fn unseen_outcome_mass(&self) -> f64 {
+        self.outcomes.unseen_mass()
+    }
+
+    /// Iterate outcomes as `(next_state_key, child_node_id, count)`.
+    pub fn outcomes_iter(&self) -> impl Iterator<Item = (StateKey, NodeId, u64)> + '_ {
+        self.outcomes.iter()
+    }
+}
+
+/// Read-only view joining an edge's cold data with its hot statistics.
+/// Dereferences to `ActionEdge` for everything that isn't a statistic.
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeRef<'a> {
+    edge: &'a ActionEdge,
+    stats: &'a EdgeStats,
+}
+
+impl<'a> EdgeRef<'a> {
+    pub(crate) fn new(edge: &'a ActionEdge, stats: &'a EdgeStats) -> Self {
+        EdgeRef { edge, stats }
+    }
+
     /// Return the amount of times this edge has been visited
     pub fn visits(&self) -> u64 {
-        self.edge_stats.visits()
+        self.stats.visits()
     }
 
     /// Return the mean value estimate for this edge.
     pub fn q(&self) -> f64 {
-        self.edge_stats.q()
+        self.stats.q()
     }
 
     /// Return the raw accumulated return for this edge.
     pub fn value_sum(&self) -> f64 {
-        self.edge_stats.value_sum()
+        self.stats.value_sum()
     }
 
-    /// Return the amount of distinct outcomes observed under this edge.
-    pub fn outcomes_len(&self) -> usize {
-        self.outcomes.len()
+    /// Return the sample variance of the returns recorded on this edge.
+    pub fn variance(&self) -> f64 {
+        self.stats.variance()
     }
 
-    /// Return the count for a given observed next state key.
-    pub fn outcome_count_for(&self, next_state_key: StateKey) -> Option<u64> {
-        self.outcomes.count_for(next_state_key)
+    /// Return the standard error of this edge's Q value.
+    pub fn stderr(&self) -> f64 {
+        self.stats.stderr()
     }
 
-    /// Iterate outcomes as `(next_state_key, child_node_id, count)`.
-    pub fn outcomes_iter(&self) -> impl Iterator<Item = (StateKey, NodeId, u64)> + '_ {
-        self.outcomes.iter()
+    /// Return the `q ± z * stderr` confidence interval for this edge.
+    pub fn confidence_interval(&self, z: f64) -> (f64, f64) {
+        self.stats.confidence_interval(z)
+    }
+
+    /// Return the mean return per player, empty unless a MaxN search visited this edge.
+    pub fn player_q(&self) -> Vec<f64> {
+        let visits = self.visits();
+        if visits == 0 {
+            return vec![0.0; self.edge.player_value_sums.len()];
+        }
+        self.edge
+            .player_value_sums
+            .iter()
+            .map(|sum| sum / visits as f64)
+            .collect()
+    }
+
+    /// Calculate UCB score for this given edge
+    /// When `bounds` is given the exploitation term is rescaled into `[0, 1]` first.
+    /// A solver-proven value replaces the running mean as the exploitation term.
+    pub fn ucb_score(&self, n_parent: u64, c: f64, bounds: Option<&ValueBounds>) -> f64 {
+        ucb_score(self.stats, self.edge.proven_value, n_parent, c, bounds)
+    }
+}
+
+impl Deref for EdgeRef<'_> {
+    type Target = ActionEdge;
+
+    fn deref(&self) -> &ActionEdge {
+        self.edge
+    }
+}
+
+/// Mutable view joining an edge's cold data with its hot statistics.
+#[derive(Debug)]
+pub struct EdgeMut<'a> {
+    edge: &'a mut ActionEdge,
+    stats: &'a mut EdgeStats,
+}
+
+impl<'a> EdgeMut<'a> {
+    pub(crate) fn new(edge: &'a mut ActionEdge, stats: &'a mut EdgeStats) -> Self {
+        EdgeMut { edge, stats }
+    }
+
+    /// Reborrow as a read-only view.
+    pub fn as_ref(&self) -> EdgeRef<'_> {
+        EdgeRef::new(self.edge, self.stats)
+    }
+
+    /// Function to be used for backpropagation.
+    /// Immediately records the rollout return and increments the visits.
+    pub fn record(&mut self, rollout_return: f64) {
+        self.stats.record(rollout_return);
+    }
+
+    /// Return the mean value estimate for this edge.
+    pub fn q(&self) -> f64 {
+        self.stats.q()
+    }
+
+    /// Return the amount of times this edge has been visited
+    pub fn visits(&self) -> u64 {
+        self.stats.visits()
+    }
+}
+
+impl Deref for EdgeMut<'_> {
+    type Target = ActionEdge;
+
+    fn deref(&self) -> &ActionEdge {
+        self.edge
+    }
+}
+
+impl DerefMut for EdgeMut<'_> {
+    fn deref_mut(&mut self) -> &mut ActionEdge {
+        self.edge
+    }
+}
+
+/// UCB1 score from the hot statistics, shared by views and the selection loop.
+pub(crate) fn ucb_score(
+    stats: &EdgeStats,
+    proven_value: Option<f64>,
+    n_parent: u64,
+    c: f64,
+    bounds: Option<&ValueBounds>,
+) -> f64 {
+    if stats.is_unvisited() {
+        f64::INFINITY
+    } else {
+        let q = proven_value.unwrap_or(stats.q());
+        let q = match bounds {
+            Some(bounds) => bounds.normalize(q),
+            None => q,
+        };
+        q + c * f64::sqrt(f64::ln(n_parent as f64) / stats.visits() as f64)
     }
 }
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tree::{
+    error::TreeError,
+    ids::{ActionId, StateKey},
+    mcts::{RunMetrics, SearchConfig},
+    search_tree::Tree,
+    seed::SeedSequence,
+};
+
+/// Root statistics of one action aggregated over every seed of an ensemble.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleActionStat {
+    pub action_id: ActionId,
+    /// Visits summed over all seeds.
+    pub visits: u64,
+    /// Visit-weighted mean value over all seeds.
+    pub q: f64,
+    /// Number of seeds whose most visited action this was.
+    pub votes: usize,
+}
+
+/// Outcome of `Tree::run_ensemble`: one search per seed plus the aggregated root decision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleReport {
+    pub seeds: Vec<u64>,
+    /// Run metrics of each seed's search, in seed order.
+    pub runs: Vec<RunMetrics>,
+    /// Most visited root action of each seed's search, in seed order.
+    pub best_actions: Vec<Option<ActionId>>,
+    /// Aggregated statistics per root action index.
+    pub actions: Vec<EnsembleActionStat>,
+    /// Action picked by most seeds; ties go to the action with more total visits.
+    pub majority_action: Option<ActionId>,
+    /// Action with the most visits summed over all seeds.
+    pub most_visited_action: Option<ActionId>,
+    /// Share of seeds whose best action differs from `majority_action`.
+    pub disagreement: f64,
+}
+
+impl Tree {
+    /// Run the same search once per seed on fresh trees and aggregate the root statistics.
+    /// `simulator(seed)` returns the `(num_actions, step, rollout_policy)` callbacks for one
+    /// search, e.g. a simulator seeded with `seed`. Seeded rollout policies in
+    /// `config.rollout` are reseeded with `seed` as well.
+    pub fn run_ensemble<S, FNum, FStep, FPolicy>(
+        root_state_key: StateKey,
+        config: &SearchConfig,
+        seeds: &[u64],
+        mut simulator: S,
+    ) -> Result<EnsembleReport, TreeError>
+    where
+        S: FnMut(u64) -> (FNum, FStep, FPolicy),
+        FNum: FnMut(StateKey) -> usize,
+        FStep: FnMut(StateKey, ActionId) -> (StateKey, f64, bool),
+        FPolicy: FnMut(StateKey, usize) -> ActionId,
+    {
+        let mut runs = Vec::with_capacity(seeds.len());
+        let mut best_actions = Vec::with_capacity(seeds.len());
+        let mut actions: Vec<EnsembleActionStat> = Vec::new();
+
+        for &seed in seeds {
+            let config = SearchConfig {
+                rollout: config.rollout.with_seed(seed),
+                ..config.clone()
+            };
+            let (num_actions, step, rollout_policy) = simulator(seed);
+            let mut tree = Tree::new(root_state_key, false);
+            runs.push(tree.run(&config, num_actions, step, rollout_policy)?);
+
+            let best = tree.best_root_action_by_visits()?;
+            best_actions.push(best);
+            for stat in tree.root_action_stats(0.0)? {
+                let index = stat.action_id.index();
+                if actions.len() <= index {
+                    actions.extend((actions.len()..=index).map(|i| EnsembleActionStat {
+                        action_id: ActionId::from(i),
+                        visits: 0,
+                        q: 0.0,
+                        votes: 0,
+                    }));
+                }
+                let aggregate = &mut actions[index];
+                let visits = aggregate.visits + stat.visits;
+                if visits > 0 {
+                    aggregate.q += (stat.q - aggregate.q) * stat.visits as f64 / visits as f64;
+                }
+                aggregate.visits = visits;
+            }
+            if let Some(best) = best {
+                actions[best.index()].votes += 1;
+            }
+        }
+
+        let majority_action = actions
+            .iter()
+            .filter(|stat| stat.votes > 0)
+            .max_by(|a, b| {
+                (a.votes, a.visits)
+                    .cmp(&(b.votes, b.visits))
+                    .then(b.action_id.index().cmp(&a.action_id.index()))
+            })
+            .map(|stat| stat.action_id);
+        let most_visited_action = actions
+            .iter()
+            .filter(|stat| stat.visits > 0)
+            .max_by(|a, b| {
+                a.visits
+                    .cmp(&b.visits)
+                    .then(b.action_id.index().cmp(&a.action_id.index()))
+            })
+            .map(|stat| stat.action_id);
+        let disagreement = if seeds.is_empty() {
+            0.0
+        } else {
+            let disagreeing = best_actions
+                .iter()
+                .filter(|best| **best != majority_action)
+                .count();
+            disagreeing as f64 / seeds.len() as f64
+        };
+
+        Ok(EnsembleReport {
+            seeds: seeds.to_vec(),
+            runs,
+            best_actions,
+            actions,
+            majority_action,
+            most_visited_action,
+            disagreement,
+        })
+    }
+
+    /// Run `runs` independent searches like `run_ensemble`, with seeds split from `master_seed`
+    /// by `SeedSequence`. The report lists the derived seeds, so any single run can be repeated.
+    pub fn run_ensemble_from_master_seed<S, FNum, FStep, FPolicy>(
+        root_state_key: StateKey,
+        config: &SearchConfig,
+        master_seed: u64,
+        runs: usize,
+        simulator: S,
+    ) -> Result<EnsembleReport, TreeError>
+    where
+        S: FnMut(u64) -> (FNum, FStep, FPolicy),
+        FNum: FnMut(StateKey) -> usize,
+        FStep: FnMut(StateKey, ActionId) -> (StateKey, f64, bool),
+        FPolicy: FnMut(StateKey, usize) -> ActionId,
+    {
+        let seeds = SeedSequence::new(master_seed).seeds(runs);
+        Tree::run_ensemble(root_state_key, config, &seeds, simulator)
+    }
+}
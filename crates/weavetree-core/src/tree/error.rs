@@ -1,9 +1,15 @@
 use std::fmt;
 
+use serde::Serialize;
+
 use crate::tree::ids::{ActionId, NodeId, StateKey};
 
 /// Error type for MCTS tree construction and search operations.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Serializes as an object with the stable `code` of `TreeError::code` and the variant's
+/// fields, e.g. `{"code":"missing_edge","node_id":3,"action_id":1}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
 pub enum TreeError {
     /// Attempted to access a node id that does not exist in the arena.
     MissingNode { node_id: NodeId },
@@ -19,12 +25,74 @@ pub enum TreeError {
         node_id: NodeId,
         action_id: ActionId,
     },
-    /// Rollout policy returned an action outside `[0, num_actions)`.
+    /// Rollout policy returned an action outside `[0, num_actions)` or one the action mask
+    /// marks illegal.
     InvalidRolloutAction {
         state_key: StateKey,
         action_id: ActionId,
         num_actions: usize,
     },
+    /// Action mask callback returned a mask whose length differs from the action count.
+    InvalidActionMask {
+        node_id: NodeId,
+        expected: usize,
+        actual: usize,
+    },
+    /// Action mask callback returned a mask whose length differs from a rollout state's
+    /// action count.
+    InvalidRolloutActionMask {
+        state_key: StateKey,
+        expected: usize,
+        actual: usize,
+    },
+    /// Edge prior callback returned a different number of priors than expanded actions.
+    InvalidEdgePriors {
+        node_id: NodeId,
+        expected: usize,
+        actual: usize,
+    },
+    /// An n-step bootstrapped rollout was truncated but no value callback is configured.
+    MissingValueBootstrap { state_key: StateKey },
+    /// The root can't be pruned; reset the tree instead.
+    CannotPruneRoot,
+    /// With `strict_rewards`, a step callback returned a NaN or infinite reward.
+    NonFiniteReward {
+        state_key: StateKey,
+        action_id: ActionId,
+    },
+    /// With `strict_rewards`, a rollout from this leaf state accumulated a NaN or infinite return.
+    NonFiniteReturn { state_key: StateKey },
+    /// The configured rollout policy needs a `rollout_policy` closure but the run has none.
+    MissingRolloutPolicy,
+    /// A replayed search requested a callback the trace doesn't record at `position`.
+    TraceDivergence { position: usize },
+    /// MaxN searches can't use `ReturnType::NStepBootstrap`: the value callback returns one
+    /// scalar, not one value per player.
+    MaxNBootstrapUnsupported,
+}
+
+impl TreeError {
+    /// Return the stable, machine-readable code of this error: the variant name in
+    /// snake case, as in the serialized `code` field.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TreeError::MissingNode { .. } => "missing_node",
+            TreeError::MissingEdge { .. } => "missing_edge",
+            TreeError::ActionSelectionFailed { .. } => "action_selection_failed",
+            TreeError::OutcomeInsertFailed { .. } => "outcome_insert_failed",
+            TreeError::InvalidRolloutAction { .. } => "invalid_rollout_action",
+            TreeError::InvalidActionMask { .. } => "invalid_action_mask",
+            TreeError::InvalidRolloutActionMask { .. } => "invalid_rollout_action_mask",
+            TreeError::InvalidEdgePriors { .. } => "invalid_edge_priors",
+            TreeError::MissingValueBootstrap { .. } => "missing_value_bootstrap",
+            TreeError::CannotPruneRoot => "cannot_prune_root",
+            TreeError::NonFiniteReward { .. } => "non_finite_reward",
+            TreeError::NonFiniteReturn { .. } => "non_finite_return",
+            TreeError::MissingRolloutPolicy => "missing_rollout_policy",
+            TreeError::TraceDivergence { .. } => "trace_divergence",
+            TreeError::MaxNBootstrapUnsupported => "max_n_bootstrap_unsupported",
+        }
+    }
 }
 
 impl fmt::Display for TreeError {
@@ -56,9 +124,76 @@ impl fmt::Display for TreeError {
                 f,
                 "rollout policy selected invalid action {} for state {} with {} actions",
                 action_id.index(),
-                state_key.value(),
+                state_key.raw(),
                 num_actions
             ),
+            TreeError::InvalidActionMask {
+                node_id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "action mask for node {} has length {} but {} actions were expanded",
+                node_id.index(),
+                actual,
+                expected
+            ),
+            TreeError::InvalidRolloutActionMask {
+                state_key,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "action mask for rollout state {} has length {} but it has {} actions",
+                state_key.raw(),
+                actual,
+                expected
+            ),
+            TreeError::InvalidEdgePriors {
+                node_id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "edge priors for node {} have length {} but {} actions were expanded",
+                node_id.index(),
+                actual,
+                expected
+            ),
+            TreeError::MissingValueBootstrap { state_key } => write!(
+                f,
+                "n-step bootstrap rollout stopped at state {} but no value callback is configured",
+                state_key.raw()
+            ),
+            TreeError::CannotPruneRoot => write!(f, "cannot prune the root node"),
+            TreeError::NonFiniteReward {
+                state_key,
+                action_id,
+            } => write!(
+                f,
+                "step from state {} with action {} returned a non-finite reward",
+                state_key.raw(),
+                action_id.index()
+            ),
+            TreeError::NonFiniteReturn { state_key } => write!(
+                f,
+                "rollout from state {} accumulated a non-finite return",
+                state_key.raw()
+            ),
+            TreeError::MissingRolloutPolicy => write!(
+                f,
+                "the configured rollout policy needs a rollout_policy closure"
+            ),
+            TreeError::TraceDivergence { position } => {
+                write!(
+                    f,
+                    "replayed search diverged from the trace at event {position}"
+                )
+            }
+            TreeError::MaxNBootstrapUnsupported => write!(
+                f,
+                "MaxN searches do not support the n_step_bootstrap return type"
+            ),
         }
     }
 }
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use crate::tree::ids::{ActionId, StateKey};
+
+type ActionMaskFn<'a> = dyn FnMut(StateKey) -> Vec<bool> + 'a;
+type EdgePriorsFn<'a> = dyn FnMut(StateKey, usize) -> Vec<(f64, u64)> + 'a;
+type PlayerToMoveFn<'a> = dyn FnMut(StateKey) -> usize + 'a;
+type ValueBootstrapFn<'a> = dyn FnMut(StateKey) -> f64 + 'a;
+type StepHookFn<'a> = dyn FnMut(&StepEvent<'_>) + 'a;
+
+/// Which part of an iteration issued a simulator call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepPhase {
+    /// The tree policy descending (and expanding) the tree.
+    Selection,
+    /// The default policy simulating from the leaf.
+    Rollout,
+}
+
+/// One simulator `step` call observed by `SearchExtensions::with_step_hook`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepEvent<'r> {
+    pub state_key: StateKey,
+    pub action_id: ActionId,
+    pub next_state_key: StateKey,
+    /// The step reward as returned by the simulator: one value, or one per player in MaxN searches.
+    pub rewards: &'r [f64],
+    pub is_terminal: bool,
+    pub phase: StepPhase,
+}
+
+impl StepEvent<'_> {
+    /// Return the scalar step reward (the first component for MaxN searches).
+    pub fn reward(&self) -> f64 {
+        self.rewards.first().copied().unwrap_or(0.0)
+    }
+}
+
+/// Optional callbacks that customize how the search loop expands and evaluates nodes.
+/// Every extension is disabled by default, which reproduces plain UCT behaviour.
+#[derive(Default)]
+pub struct SearchExtensions<'a> {
+    action_mask: Option<Box<ActionMaskFn<'a>>>,
+    edge_priors: Option<Box<EdgePriorsFn<'a>>>,
+    player_to_move: Option<Box<PlayerToMoveFn<'a>>>,
+    value_bootstrap: Option<Box<ValueBootstrapFn<'a>>>,
+    step_hook: Option<Box<StepHookFn<'a>>>,
+}
+
+impl<'a> SearchExtensions<'a> {
+    /// Create an empty set of extensions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark action indices as illegal right after a node is expanded and in every rollout state.
+    /// The callback must return one flag per action, `false` meaning illegal.
+    /// Masked actions keep their index, so the dense action space is never renumbered.
+    /// Built-in rollout policies only pick legal actions, and a `rollout_policy` closure that
+    /// returns a masked action fails the run with `TreeError::InvalidRolloutAction`.
+    /// A state whose actions are all masked is treated as terminal: it is not rolled out.
+    pub fn with_action_mask<F>(mut self, action_mask: F) -> Self
+    where
+        F: FnMut(StateKey) -> Vec<bool> + 'a,
+    {
+        self.action_mask = Some(Box::new(action_mask));
+        self
+    }
+
+    /// Warm-start new edges with `(q, visits)` pseudo-counts.
+    /// The callback receives the state and its action count and must return one prior per action.
+    /// Pseudo-counts take part in UCB and root recommendations exactly like real visits.
+    pub fn with_edge_priors<F>(mut self, edge_priors: F) -> Self
+    where
+        F: FnMut(StateKey, usize) -> Vec<(f64, u64)> + 'a,
+    {
+        self.edge_priors = Some(Box::new(edge_priors));
+        self
+    }
+
+    /// Resolve the player to move for every node from its state.
+    /// Overrides the depth-based turn order of `PlayerMode` and is required for MaxN searches.
+    pub fn with_player_to_move<F>(mut self, player_to_move: F) -> Self
+    where
+        F: FnMut(StateKey) -> usize + 'a,
+    {
+        self.player_to_move = Some(Box::new(player_to_move));
+        self
+    }
+
+    /// Estimate the value of the state where an n-step bootstrapped rollout stops.
+    /// The value is scored for the player to move in that state.
+    /// Required by `ReturnType::NStepBootstrap`.
+    pub fn with_value_bootstrap<F>(mut self, value_bootstrap: F) -> Self
+    where
+        F: FnMut(StateKey) -> f64 + 'a,
+    {
+        self.value_bootstrap = Some(Box::new(value_bootstrap));
+        self
+    }
+
+    /// Observe every simulator `step` call, tagged with the phase that issued it.
+    /// Useful for counting simulator calls or tracking down reward anomalies
+    /// without wrapping the search closures.
+    pub fn with_step_hook<F>(mut self, step_hook: F) -> Self
+    where
+        F: FnMut(&StepEvent<'_>) + 'a,
+    {
+        self.step_hook = Some(Box::new(step_hook));
+        self
+    }
+
+    /// Evaluate the action mask for a state, if one is configured.
+    pub(crate) fn action_mask(&mut self, state_key: StateKey) -> Option<Vec<bool>> {
+        self.action_mask.as_mut().map(|mask| mask(state_key))
+    }
+
+    /// Evaluate the edge priors for a freshly expanded state, if configured.
+    pub(crate) fn edge_priors(
+        &mut self,
+        state_key: StateKey,
+        num_actions: usize,
+    ) -> Option<Vec<(f64, u64)>> {
+        self.edge_priors
+            .as_mut()
+            .map(|priors| priors(state_key, num_actions))
+    }
+
+    /// Resolve the player to move for a state, if configured.
+    pub(crate) fn player_to_move(&mut self, state_key: StateKey) -> Option<usize> {
+        self.player_to_move
+            .as_mut()
+            .map(|player_to_move| player_to_move(state_key))
+    }
+
+    /// Estimate a state's value for bootstrapping, if configured.
+    pub(crate) fn value_bootstrap(&mut self, state_key: StateKey) -> Option<f64> {
+        self.value_bootstrap.as_mut().map(|value| value(state_key))
+    }
+
+    /// Report a simulator call to the step hook, if configured.
+    pub(crate) fn observe_step(&mut self, event: &StepEvent<'_>) {
+        if let Some(step_hook) = self.step_hook.as_mut() {
+            step_hook(event);
+        }
+    }
+}
+
+impl fmt::Debug for SearchExtensions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchExtensions")
+            .field("action_mask", &self.action_mask.is_some())
+            .field("edge_priors", &self.edge_priors.is_some())
+            .field("player_to_move", &self.player_to_move.is_some())
+            .field("value_bootstrap", &self.value_bootstrap.is_some())
+            .field("step_hook", &self.step_hook.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,237 @@
+//! Localhost HTTP endpoint serving the tree snapshot and run metrics of a running search.
+//!
+//! Enabled by the `http` feature. The server is a single background thread on
+//! `std::net`, answering `GET` requests with JSON that the search thread publishes; it is
+//! meant for watching a long search from a browser or `curl`, not for serving traffic.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::tree::{
+    extensions::SearchExtensions,
+    ids::{ActionId, StateKey},
+    mcts::{RunError, RunMetrics, SearchConfig},
+    search_tree::Tree,
+    snapshot::TreeSnapshot,
+    timing::Stopwatch,
+};
+
+/// How long the server waits for a client to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Most bytes of request line and headers read from one client; the routes need far fewer.
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// JSON bodies last published by the search thread.
+#[derive(Default)]
+struct Published {
+    snapshot: Option<String>,
+    metrics: Option<String>,
+}
+
+/// Serves the last published snapshot and metrics over HTTP until dropped.
+///
+/// Routes: `GET /snapshot` returns the `TreeSnapshot` JSON, `GET /metrics` the
+/// `RunMetrics` JSON. Both answer `503` until something is published. No CORS headers
+/// are sent, so other web pages open in the browser can't read the search.
+pub struct HttpInspector {
+    local_addr: SocketAddr,
+    published: Arc<Mutex<Published>>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl HttpInspector {
+    /// Bind `addr`, e.g. `"127.0.0.1:8080"` (port `0` picks a free one), and start serving.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let published = Arc::new(Mutex::new(Published::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let published = Arc::clone(&published);
+            let shutdown = Arc::clone(&shutdown);
+            thread::Builder::new()
+                .name("weavetree-http".to_string())
+                .spawn(move || serve(listener, &published, &shutdown))?
+        };
+        Ok(HttpInspector {
+            local_addr,
+            published,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Return the address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Replace the served snapshot and metrics.
+    pub fn publish(
+        &self,
+        snapshot: &TreeSnapshot,
+        metrics: &RunMetrics,
+    ) -> Result<(), serde_json::Error> {
+        let snapshot = serde_json::to_string(snapshot)?;
+        let metrics = serde_json::to_string(metrics)?;
+        let mut published = self
+            .published
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        published.snapshot = Some(snapshot);
+        published.metrics = Some(metrics);
+        Ok(())
+    }
+}
+
+impl HttpInspector {
+    /// Like `publish`, but serves a serialization error as the JSON body of both routes.
+    fn publish_or_error(&self, snapshot: &TreeSnapshot, metrics: &RunMetrics) {
+        if let Err(err) = self.publish(snapshot, metrics) {
+            let body = error_body(&err.to_string());
+            let mut published = self
+                .published
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            published.snapshot = Some(body.clone());
+            published.metrics = Some(body);
+        }
+    }
+}
+
+impl Drop for HttpInspector {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the blocking `accept` so the thread sees the flag. A wildcard address isn't
+        // connectable everywhere, so aim at loopback on the bound port.
+        let _ = TcpStream::connect_timeout(&wake_addr(self.local_addr), READ_TIMEOUT);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl<P: Default> Tree<P> {
+    /// Run MCTS like `run_with_extensions_fallible`, publishing the snapshot and running
+    /// metrics to `inspector` from the iteration hook after every `every`-th iteration (and
+    /// after the last one). `every` is raised to at least one; each publish serializes the
+    /// whole tree, so keep it well above one for large searches. A publish that fails to
+    /// serialize is served as a JSON error body instead.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_with_http_inspector<FNum, FStep, FPolicy, E>(
+        &mut self,
+        config: &SearchConfig,
+        num_actions: FNum,
+        step: FStep,
+        rollout_policy: FPolicy,
+        extensions: SearchExtensions<'_>,
+        inspector: &HttpInspector,
+        every: usize,
+    ) -> Result<RunMetrics, RunError<E>>
+    where
+        FNum: FnMut(StateKey) -> Result<usize, E>,
+        FStep: FnMut(StateKey, ActionId) -> Result<(StateKey, f64, bool), E>,
+        FPolicy: FnMut(StateKey, usize) -> Result<ActionId, E>,
+    {
+        let every = every.max(1);
+        let started = Stopwatch::start();
+        self.run_observed(
+            config,
+            num_actions,
+            step,
+            rollout_policy,
+            extensions,
+            |tree, _iteration_metrics, metrics| {
+                let completed = metrics.iterations_completed;
+                if completed.is_multiple_of(every) || completed == config.iterations {
+                    let mut metrics = metrics.clone();
+                    metrics.total_time = started.elapsed();
+                    inspector.publish_or_error(&tree.snapshot(), &metrics);
+                }
+            },
+        )
+    }
+}
+
+/// Return an address that reaches a listener bound to `addr`.
+fn wake_addr(mut addr: SocketAddr) -> SocketAddr {
+    if addr.ip().is_unspecified() {
+        addr.set_ip(match addr {
+            SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+            SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+        });
+    }
+    addr
+}
+
+fn serve(listener: TcpListener, published: &Mutex<Published>, shutdown: &AtomicBool) {
+    for stream in listener.incoming() {
+        if shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+        // A misbehaving client only loses its own response.
+        if let Ok(stream) = stream {
+            let _ = respond(stream, published);
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, published: &Mutex<Published>) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    // Past the limit `read_line` sees end of input, so a client can't grow the buffers.
+    let mut reader = BufReader::new((&stream).take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers; closing with unread input would reset the connection.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+
+    let body = {
+        let published = published
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match (method, path) {
+            ("GET", "/snapshot") => Ok(published.snapshot.clone()),
+            ("GET", "/metrics") => Ok(published.metrics.clone()),
+            ("GET", _) => Err("404 Not Found"),
+            _ => Err("405 Method Not Allowed"),
+        }
+    };
+    let (status, body) = match body {
+        Ok(Some(json)) => ("200 OK", json),
+        Ok(None) => (
+            "503 Service Unavailable",
+            error_body("nothing published yet"),
+        ),
+        Err(status) => (status, error_body(&status[4..])),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message.to_ascii_lowercase() }).to_string()
+}
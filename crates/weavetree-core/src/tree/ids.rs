@@ -1,5 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 /// A wraper for an integer index used to index nodes
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct NodeId(usize);
 
 impl NodeId {
@@ -16,27 +19,75 @@ impl From<usize> for NodeId {
     }
 }
 
+/// Full-width integer behind a `StateKey`: `u64`, or `u128` with the `wide-state-keys` feature.
+#[cfg(not(feature = "wide-state-keys"))]
+pub type StateKeyValue = u64;
+/// Full-width integer behind a `StateKey`: `u64`, or `u128` with the `wide-state-keys` feature.
+#[cfg(feature = "wide-state-keys")]
+pub type StateKeyValue = u128;
+
+/// Serde adapter that writes wide keys as decimal strings. Trace events need it because
+/// internally tagged enums buffer their fields and serde's buffer has no `u128`, and
+/// snapshots use it so JSON readers that parse numbers as doubles keep every digit.
+#[cfg(feature = "wide-state-keys")]
+pub(crate) mod wide_key {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
 /// Representation of the state to avoid storing the full state and heavy cloning.
 /// This needs to be deterministic, collision-resistant, and must not depend on rollout/search metadata.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub struct StateKey(u64);
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StateKey(StateKeyValue);
 
 impl StateKey {
     /// Return the internal numeric representation of this key.
+    /// With `wide-state-keys` this panics for keys above `u64::MAX`; code that may see hashed
+    /// keys should read them with `raw` instead.
+    #[cfg(not(feature = "wide-state-keys"))]
     pub fn value(&self) -> u64 {
         self.0
     }
+
+    /// Return the internal numeric representation of this key.
+    /// With `wide-state-keys` this panics for keys above `u64::MAX`; code that may see hashed
+    /// keys should read them with `raw` instead.
+    #[cfg(feature = "wide-state-keys")]
+    pub fn value(&self) -> u64 {
+        u64::try_from(self.0).expect("state key does not fit in 64 bits, use StateKey::raw")
+    }
+
+    /// Return the key at full width.
+    pub fn raw(&self) -> StateKeyValue {
+        self.0
+    }
+
+    /// Build a key from its full-width value, e.g. a 128-bit state hash with `wide-state-keys`.
+    pub const fn from_raw(value: StateKeyValue) -> Self {
+        StateKey(value)
+    }
 }
 
 impl From<u64> for StateKey {
     /// Allow for explicit conversion from u64 to StateKey.
     fn from(value: u64) -> Self {
-        StateKey(value)
+        StateKey(value as StateKeyValue)
     }
 }
 
 /// A wraper for an integer index used to determine the node's action list
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct ActionId(usize);
 
 impl ActionId {
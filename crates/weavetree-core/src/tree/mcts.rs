@@ -1,27 +1,91 @@
-use std::{fmt, fs, path::Path};
+use std::{
+    cell::{Cell, RefCell},
+    fmt, fs,
+    path::Path,
+    time::Duration,
+};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
 
-use crate::tree::rollout::rollout_fallible;
+use crate::tree::rollout::rollout_with;
 use crate::tree::{
     error::TreeError,
+    extensions::{SearchExtensions, StepEvent, StepPhase},
     ids::{ActionId, NodeId},
-    rollout::{ReturnType, RolloutParams},
+    reward::Reward,
+    rollout::{ReturnType, RolloutParams, RolloutPolicyKind},
     search_tree::Tree,
+    timing::{CallbackClock, Stopwatch},
 };
 
 const DEFAULT_SEARCH_CONFIG_YAML: &str = include_str!("../../config/search.default.yaml");
 
 /// Search configuration for MCTS iterations.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct SearchConfig {
     pub iterations: usize,
     pub c: f64,
+    pub novelty_bonus: f64,
     pub gamma: f64,
     pub max_steps: usize,
     pub return_type: ReturnType,
     pub fixed_horizon_steps: usize,
+    pub bootstrap_steps: usize,
+    pub max_tree_depth: Option<u64>,
+    pub max_selection_depth: Option<usize>,
+    pub max_nodes: Option<usize>,
+    pub value_normalization: ValueNormalization,
+    pub player_mode: PlayerMode,
+    pub solver: bool,
+    pub rollout_cache_capacity: usize,
+    pub rollout_cache_min_samples: usize,
+    pub strict_rewards: bool,
+    pub phase_timing: bool,
+    pub rollout: RolloutPolicyKind,
+}
+
+/// Controls how edge Q values are scaled before the UCB exploration term is added.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueNormalization {
+    /// Use raw Q values; `c` must be tuned to the domain's reward scale.
+    #[default]
+    None,
+    /// Rescale Q into `[0, 1]` using the running min/max of backed-up Q values.
+    MinMax,
+}
+
+/// Controls how returns are credited to the players acting in the tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerMode {
+    /// One agent acts everywhere; every edge maximizes the same return.
+    #[default]
+    SingleAgent,
+    /// Two players alternate turns in a zero-sum game.
+    /// Step rewards are credited to the player who moved and are sign-flipped per ply,
+    /// so every edge maximizes the return of the player to move at its node.
+    Negamax,
+}
+
+impl PlayerMode {
+    /// Return the player to move at a given tree depth.
+    pub fn player_at_depth(self, depth: u64) -> usize {
+        match self {
+            PlayerMode::SingleAgent => 0,
+            PlayerMode::Negamax => (depth % 2) as usize,
+        }
+    }
+
+    /// Convert a value seen from the root player's perspective into `player`'s perspective.
+    /// This is symmetric, so it also converts `player`'s values back to the root's perspective.
+    pub fn value_for_player(self, player: usize, value: f64) -> f64 {
+        match self {
+            PlayerMode::Negamax if player % 2 == 1 => -value,
+            PlayerMode::SingleAgent | PlayerMode::Negamax => value,
+        }
+    }
 }
 
 impl Default for SearchConfig {
@@ -29,28 +93,83 @@ impl Default for SearchConfig {
         SearchConfig {
             iterations: 256,
             c: 1.4,
+            novelty_bonus: 0.0,
             gamma: 1.0,
             max_steps: 128,
             return_type: ReturnType::Discounted,
             fixed_horizon_steps: 32,
+            bootstrap_steps: 8,
+            max_tree_depth: None,
+            max_selection_depth: None,
+            max_nodes: None,
+            value_normalization: ValueNormalization::None,
+            player_mode: PlayerMode::SingleAgent,
+            solver: false,
+            rollout_cache_capacity: 0,
+            rollout_cache_min_samples: 1,
+            strict_rewards: false,
+            phase_timing: false,
+            rollout: RolloutPolicyKind::Custom,
         }
     }
 }
 
 impl SearchConfig {
     /// Parse a search config from YAML text.
+    /// Unknown fields are ignored; use `from_yaml_str_strict` to reject them.
     pub fn from_yaml_str(yaml: &str) -> Result<Self, SearchConfigError> {
         let config: SearchConfig = serde_yaml::from_str(yaml).map_err(SearchConfigError::Yaml)?;
         config.validate()?;
         Ok(config)
     }
 
+    /// Parse a search config from YAML text, rejecting fields `SearchConfig` doesn't know.
+    /// Catches typos such as `iteration: 512` that lenient parsing silently drops.
+    pub fn from_yaml_str_strict(yaml: &str) -> Result<Self, SearchConfigError> {
+        let value: serde_yaml::Value =
+            serde_yaml::from_str(yaml).map_err(SearchConfigError::Yaml)?;
+        if let serde_yaml::Value::Mapping(fields) = &value {
+            let known =
+                serde_yaml::to_value(SearchConfig::default()).map_err(SearchConfigError::Yaml)?;
+            for key in fields.keys() {
+                if known.get(key).is_none() {
+                    let name = key
+                        .as_str()
+                        .map_or_else(|| format!("{key:?}"), str::to_string);
+                    return Err(SearchConfigError::Invalid(format!(
+                        "unknown field `{name}`"
+                    )));
+                }
+            }
+        }
+        let config: SearchConfig =
+            serde_yaml::from_value(value).map_err(SearchConfigError::Yaml)?;
+        config.validate()?;
+        Ok(config)
+    }
+
     /// Parse a search config from a YAML file path.
     pub fn from_yaml_path(path: impl AsRef<Path>) -> Result<Self, SearchConfigError> {
         let yaml = fs::read_to_string(path).map_err(SearchConfigError::Io)?;
         Self::from_yaml_str(&yaml)
     }
 
+    /// Parse a search config from a YAML file path, rejecting unknown fields.
+    pub fn from_yaml_path_strict(path: impl AsRef<Path>) -> Result<Self, SearchConfigError> {
+        let yaml = fs::read_to_string(path).map_err(SearchConfigError::Io)?;
+        Self::from_yaml_str_strict(&yaml)
+    }
+
+    /// Serialize every field of this config as YAML, in the layout of `search.default.yaml`.
+    pub fn to_yaml_string(&self) -> Result<String, SearchConfigError> {
+        serde_yaml::to_string(self).map_err(SearchConfigError::Yaml)
+    }
+
+    /// Write this config to a YAML file that `from_yaml_path` reads back unchanged.
+    pub fn save_yaml(&self, path: impl AsRef<Path>) -> Result<(), SearchConfigError> {
+        fs::write(path, self.to_yaml_string()?).map_err(SearchConfigError::Io)
+    }
+
     /// Return the default YAML config included with this crate.
     pub fn default_yaml() -> &'static str {
         DEFAULT_SEARCH_CONFIG_YAML
@@ -61,7 +180,7 @@ impl SearchConfig {
         Self::from_yaml_str(Self::default_yaml())
     }
 
-    fn validate(&self) -> Result<(), SearchConfigError> {
+    pub(crate) fn validate(&self) -> Result<(), SearchConfigError> {
         if self.iterations == 0 {
             return Err(SearchConfigError::Invalid(
                 "iterations must be greater than 0".to_string(),
@@ -72,6 +191,11 @@ impl SearchConfig {
                 "c must be finite and >= 0".to_string(),
             ));
         }
+        if !self.novelty_bonus.is_finite() || self.novelty_bonus < 0.0 {
+            return Err(SearchConfigError::Invalid(
+                "novelty_bonus must be finite and >= 0".to_string(),
+            ));
+        }
         if !self.gamma.is_finite() || self.gamma < 0.0 {
             return Err(SearchConfigError::Invalid(
                 "gamma must be finite and >= 0".to_string(),
@@ -87,6 +211,33 @@ impl SearchConfig {
                 "fixed_horizon_steps must be greater than 0".to_string(),
             ));
         }
+        if self.max_tree_depth == Some(0) {
+            return Err(SearchConfigError::Invalid(
+                "max_tree_depth must be greater than 0 when set".to_string(),
+            ));
+        }
+        if self.max_selection_depth == Some(0) {
+            return Err(SearchConfigError::Invalid(
+                "max_selection_depth must be greater than 0 when set".to_string(),
+            ));
+        }
+        if self.max_nodes.is_some_and(|max_nodes| max_nodes < 2) {
+            return Err(SearchConfigError::Invalid(
+                "max_nodes must be at least 2 when set".to_string(),
+            ));
+        }
+        if let RolloutPolicyKind::EpsilonGreedy { eps, .. } = self.rollout
+            && !(0.0..=1.0).contains(&eps)
+        {
+            return Err(SearchConfigError::Invalid(
+                "rollout eps must be within [0, 1]".to_string(),
+            ));
+        }
+        if self.rollout_cache_min_samples == 0 {
+            return Err(SearchConfigError::Invalid(
+                "rollout_cache_min_samples must be greater than 0".to_string(),
+            ));
+        }
         Ok(())
     }
 
@@ -96,6 +247,8 @@ impl SearchConfig {
             gamma: self.gamma,
             max_steps: self.max_steps,
             fixed_horizon_steps: self.fixed_horizon_steps,
+            bootstrap_steps: self.bootstrap_steps,
+            alternate_sign: self.player_mode == PlayerMode::Negamax,
         }
     }
 }
@@ -141,6 +294,37 @@ where
 
 impl<E> std::error::Error for RunError<E> where E: std::error::Error + 'static {}
 
+impl<E> RunError<E> {
+    /// Return the stable, machine-readable code of this error: `TreeError::code` for tree
+    /// errors and `"callback_failed"` for callback errors.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RunError::Tree(err) => err.code(),
+            RunError::Callback(_) => "callback_failed",
+        }
+    }
+}
+
+/// Tree errors serialize as the `TreeError` itself. Callback errors have no structure the
+/// search knows about, so they serialize as `{"code":"callback_failed","message":...}` with
+/// the error's `Display` text.
+impl<E> Serialize for RunError<E>
+where
+    E: fmt::Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RunError::Tree(err) => err.serialize(serializer),
+            RunError::Callback(err) => {
+                let mut state = serializer.serialize_struct("RunError", 2)?;
+                state.serialize_field("code", self.code())?;
+                state.serialize_field("message", &err.to_string())?;
+                state.end()
+            }
+        }
+    }
+}
+
 impl<E> From<TreeError> for RunError<E> {
     fn from(value: TreeError) -> Self {
         RunError::Tree(value)
@@ -148,7 +332,14 @@ impl<E> From<TreeError> for RunError<E> {
 }
 
 /// Per-iteration metrics emitted by MCTS.
-#[derive(Debug, Clone, Copy)]
+///
+/// The `*_time` fields are measured only with `SearchConfig::phase_timing` and are zero
+/// otherwise. `simulator_time` is the part of `selection_time` and `rollout_time` spent
+/// inside the `num_actions` and `step` callbacks.
+///
+/// The `*_calls` fields count those callbacks, split by whether tree policy or the
+/// rollout made them; they are always counted.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct IterationMetrics {
     pub leaf: NodeId,
     pub leaf_is_new: bool,
@@ -157,15 +348,44 @@ pub struct IterationMetrics {
     pub rollout_return: f64,
     pub total_return: f64,
     pub node_count: usize,
+    pub rollout_cache_hit: bool,
+    pub nodes_pruned: usize,
+    pub selection_capped: bool,
+    pub selection_time: Duration,
+    pub rollout_time: Duration,
+    pub backprop_time: Duration,
+    pub simulator_time: Duration,
+    pub selection_num_actions_calls: usize,
+    pub selection_step_calls: usize,
+    pub rollout_num_actions_calls: usize,
+    pub rollout_step_calls: usize,
 }
 
 /// Aggregate metrics for a complete search run.
-#[derive(Debug, Clone)]
+///
+/// `total_time` is the wall-clock time of the whole run. The phase times are the sums of
+/// the `IterationMetrics` phase times, so they stay zero without
+/// `SearchConfig::phase_timing`. The `*_calls` fields sum the simulator callback counts
+/// of all iterations, a better measure of search cost than iterations when rollout
+/// lengths vary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RunMetrics {
     pub iterations_requested: usize,
     pub iterations_completed: usize,
     pub total_return_sum: f64,
     pub average_total_return: f64,
+    pub rollout_cache_hits: usize,
+    pub nodes_pruned: usize,
+    pub selection_caps: usize,
+    pub total_time: Duration,
+    pub selection_time: Duration,
+    pub rollout_time: Duration,
+    pub backprop_time: Duration,
+    pub simulator_time: Duration,
+    pub selection_num_actions_calls: usize,
+    pub selection_step_calls: usize,
+    pub rollout_num_actions_calls: usize,
+    pub rollout_step_calls: usize,
 }
 
 /// Standardized event model for detailed run logging.
@@ -195,15 +415,21 @@ pub enum RunLogEvent {
         iterations_completed: usize,
         total_return_sum: f64,
         average_total_return: f64,
+        total_seconds: f64,
+        selection_seconds: f64,
+        rollout_seconds: f64,
+        backprop_seconds: f64,
+        simulator_seconds: f64,
     },
 }
 
 impl RunLogEvent {
-    fn return_type_name(return_type: ReturnType) -> &'static str {
+    pub(crate) fn return_type_name(return_type: ReturnType) -> &'static str {
         match return_type {
             ReturnType::Discounted => "discounted",
             ReturnType::EpisodicUndiscounted => "episodic_undiscounted",
             ReturnType::FixedHorizon => "fixed_horizon",
+            ReturnType::NStepBootstrap => "n_step_bootstrap",
         }
     }
 
@@ -237,6 +463,11 @@ impl RunLogEvent {
             iterations_completed: metrics.iterations_completed,
             total_return_sum: metrics.total_return_sum,
             average_total_return: metrics.average_total_return,
+            total_seconds: metrics.total_time.as_secs_f64(),
+            selection_seconds: metrics.selection_time.as_secs_f64(),
+            rollout_seconds: metrics.rollout_time.as_secs_f64(),
+            backprop_seconds: metrics.backprop_time.as_secs_f64(),
+            simulator_seconds: metrics.simulator_time.as_secs_f64(),
         }
     }
 
@@ -283,9 +514,22 @@ impl RunLogEvent {
                 iterations_completed,
                 total_return_sum,
                 average_total_return,
+                total_seconds,
+                selection_seconds,
+                rollout_seconds,
+                backprop_seconds,
+                simulator_seconds,
             } => format!(
-                "run_completed iterations_requested={} iterations_completed={} total_return_sum={:.6} average_total_return={:.6}",
-                iterations_requested, iterations_completed, total_return_sum, average_total_return
+                "run_completed iterations_requested={} iterations_completed={} total_return_sum={:.6} average_total_return={:.6} total_seconds={:.6} selection_seconds={:.6} rollout_seconds={:.6} backprop_seconds={:.6} simulator_seconds={:.6}",
+                iterations_requested,
+                iterations_completed,
+                total_return_sum,
+                average_total_return,
+                total_seconds,
+                selection_seconds,
+                rollout_seconds,
+                backprop_seconds,
+                simulator_seconds
             ),
         }
     }
@@ -296,36 +540,97 @@ impl RunLogEvent {
 }
 
 impl RunMetrics {
-    fn new(iterations_requested: usize) -> Self {
+    /// Return empty metrics for a run of `iterations_requested` iterations.
+    pub fn new(iterations_requested: usize) -> Self {
         RunMetrics {
             iterations_requested,
             iterations_completed: 0,
             total_return_sum: 0.0,
             average_total_return: 0.0,
+            rollout_cache_hits: 0,
+            nodes_pruned: 0,
+            selection_caps: 0,
+            total_time: Duration::ZERO,
+            selection_time: Duration::ZERO,
+            rollout_time: Duration::ZERO,
+            backprop_time: Duration::ZERO,
+            simulator_time: Duration::ZERO,
+            selection_num_actions_calls: 0,
+            selection_step_calls: 0,
+            rollout_num_actions_calls: 0,
+            rollout_step_calls: 0,
         }
     }
 
-    fn record(&mut self, metrics: IterationMetrics) {
+    pub(crate) fn record(&mut self, metrics: IterationMetrics) {
         self.iterations_completed += 1;
+        self.rollout_cache_hits += usize::from(metrics.rollout_cache_hit);
+        self.nodes_pruned += metrics.nodes_pruned;
+        self.selection_caps += usize::from(metrics.selection_capped);
+        self.selection_time += metrics.selection_time;
+        self.rollout_time += metrics.rollout_time;
+        self.backprop_time += metrics.backprop_time;
+        self.simulator_time += metrics.simulator_time;
+        self.selection_num_actions_calls += metrics.selection_num_actions_calls;
+        self.selection_step_calls += metrics.selection_step_calls;
+        self.rollout_num_actions_calls += metrics.rollout_num_actions_calls;
+        self.rollout_step_calls += metrics.rollout_step_calls;
         self.total_return_sum += metrics.total_return;
         self.average_total_return = self.total_return_sum / self.iterations_completed as f64;
     }
+
+    /// Add the counts and times of `other`, e.g. one chunk of a search run in several calls.
+    /// `iterations_requested` is kept and the average return is recomputed over both.
+    pub fn absorb(&mut self, other: &RunMetrics) {
+        self.iterations_completed += other.iterations_completed;
+        self.total_return_sum += other.total_return_sum;
+        self.average_total_return = if self.iterations_completed == 0 {
+            0.0
+        } else {
+            self.total_return_sum / self.iterations_completed as f64
+        };
+        self.rollout_cache_hits += other.rollout_cache_hits;
+        self.nodes_pruned += other.nodes_pruned;
+        self.selection_caps += other.selection_caps;
+        self.total_time += other.total_time;
+        self.selection_time += other.selection_time;
+        self.rollout_time += other.rollout_time;
+        self.backprop_time += other.backprop_time;
+        self.simulator_time += other.simulator_time;
+        self.selection_num_actions_calls += other.selection_num_actions_calls;
+        self.selection_step_calls += other.selection_step_calls;
+        self.rollout_num_actions_calls += other.rollout_num_actions_calls;
+        self.rollout_step_calls += other.rollout_step_calls;
+    }
 }
 
-impl Tree {
+impl<P: Default> Tree<P> {
     /// Backpropagate one return across all edges traversed by tree policy.
     pub fn backpropagate(
         &mut self,
         path: &[(NodeId, ActionId)],
         total_return: f64,
+    ) -> Result<(), TreeError> {
+        self.backpropagate_for(path, &total_return, PlayerMode::SingleAgent)
+    }
+
+    /// Backpropagate a root-perspective return, crediting each edge from its node's player view.
+    pub(crate) fn backpropagate_for<R: Reward>(
+        &mut self,
+        path: &[(NodeId, ActionId)],
+        total_return: &R,
+        player_mode: PlayerMode,
     ) -> Result<(), TreeError> {
         for (node_id, action_id) in path {
             let node = self.node_mut(*node_id)?;
-            let edge = node.edge_mut(*action_id).ok_or(TreeError::MissingEdge {
+            let player = node.player();
+            let mut edge = node.edge_mut(*action_id).ok_or(TreeError::MissingEdge {
                 node_id: *node_id,
                 action_id: *action_id,
             })?;
-            edge.record(total_return);
+            total_return.record_on(&mut edge, player_mode, player);
+            let q = edge.q();
+            self.observe_value(q);
         }
         Ok(())
     }
@@ -374,36 +679,209 @@ impl Tree {
         ) -> Result<(crate::tree::ids::StateKey, f64, bool), E>,
         FPolicy: FnMut(crate::tree::ids::StateKey, usize) -> Result<ActionId, E>,
     {
-        let policy_result = self.tree_policy_fallible(
-            config.c,
-            |s| num_actions(s).map_err(RunError::Callback),
-            |s, a| step(s, a).map_err(RunError::Callback),
+        self.iterate_with_extensions_fallible(
+            config,
+            num_actions,
+            step,
+            rollout_policy,
+            &mut SearchExtensions::default(),
+        )
+    }
+
+    /// Execute one complete MCTS iteration with fallible callbacks and search extensions.
+    pub fn iterate_with_extensions_fallible<FNum, FStep, FPolicy, E>(
+        &mut self,
+        config: &SearchConfig,
+        num_actions: &mut FNum,
+        step: &mut FStep,
+        rollout_policy: &mut FPolicy,
+        extensions: &mut SearchExtensions<'_>,
+    ) -> Result<IterationMetrics, RunError<E>>
+    where
+        FNum: FnMut(crate::tree::ids::StateKey) -> Result<usize, E>,
+        FStep: FnMut(
+            crate::tree::ids::StateKey,
+            ActionId,
+        ) -> Result<(crate::tree::ids::StateKey, f64, bool), E>,
+        FPolicy: FnMut(crate::tree::ids::StateKey, usize) -> Result<ActionId, E>,
+    {
+        self.iterate_with(config, num_actions, step, rollout_policy, extensions)
+    }
+
+    /// Shared iteration body for scalar and per-player reward signals.
+    fn iterate_with<FNum, FStep, FPolicy, E, R>(
+        &mut self,
+        config: &SearchConfig,
+        num_actions: &mut FNum,
+        step: &mut FStep,
+        rollout_policy: &mut FPolicy,
+        extensions: &mut SearchExtensions<'_>,
+    ) -> Result<IterationMetrics, RunError<E>>
+    where
+        FNum: FnMut(crate::tree::ids::StateKey) -> Result<usize, E>,
+        FStep: FnMut(
+            crate::tree::ids::StateKey,
+            ActionId,
+        ) -> Result<(crate::tree::ids::StateKey, R, bool), E>,
+        FPolicy: FnMut(crate::tree::ids::StateKey, usize) -> Result<ActionId, E>,
+        R: Reward,
+    {
+        #[cfg(feature = "tracing")]
+        let _iteration_span = tracing::debug_span!("weavetree.iteration").entered();
+
+        // Prune before selecting, so this iteration's new node still fits the budget
+        // and the leaf id reported in the metrics stays valid.
+        let mut nodes_pruned = 0;
+        if let Some(max_nodes) = config.max_nodes
+            && self.node_count() >= max_nodes
+        {
+            let keep = (max_nodes - max_nodes / 4).min(max_nodes - 1);
+            nodes_pruned = self.prune_to(keep)?;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(nodes_pruned, node_count = self.node_count(), "pruned tree");
+        }
+
+        let simulator = CallbackClock::new(config.phase_timing);
+        let num_actions_calls = Cell::new(0);
+        let step_calls = Cell::new(0);
+        let phase = Stopwatch::start_if(config.phase_timing);
+        let policy_result = self.tree_policy_with_extensions(
+            config,
+            |s| {
+                num_actions_calls.set(num_actions_calls.get() + 1);
+                simulator
+                    .time(|| num_actions(s))
+                    .map_err(RunError::Callback)
+            },
+            |s, a| {
+                step_calls.set(step_calls.get() + 1);
+                let outcome = simulator.time(|| step(s, a)).map_err(RunError::Callback)?;
+                check_step_reward(config, s, a, &outcome.1)?;
+                Ok(outcome)
+            },
+            extensions,
         )?;
+        let selection_time = phase.elapsed();
+        let selection_num_actions_calls = num_actions_calls.replace(0);
+        let selection_step_calls = step_calls.replace(0);
+        let phase = Stopwatch::start_if(config.phase_timing);
         let leaf = self.node(policy_result.leaf)?;
         let leaf_state_key = leaf.state_key();
-        let rollout_return = if leaf.is_terminal() {
-            0.0
+        let leaf_player = leaf.player();
+        // A node whose actions are all masked has nothing legal to roll out.
+        let leaf_is_terminal =
+            leaf.is_terminal() || (leaf.is_expanded() && !leaf.has_legal_edges());
+        let use_cache = config.rollout_cache_capacity > 0;
+        let cached = if use_cache && !leaf_is_terminal {
+            R::rollout_cache(self).lookup(leaf_state_key, config.rollout_cache_min_samples)
         } else {
-            rollout_fallible(
+            None
+        };
+        let rollout_cache_hit = cached.is_some();
+        let rollout_return = if leaf_is_terminal {
+            R::zero()
+        } else if let Some(cached) = cached {
+            cached
+        } else {
+            // Both the step hook and the bootstrap callback live in `extensions`.
+            let extensions = RefCell::new(&mut *extensions);
+            let rollout_kind = config.rollout;
+            let rng = self.rollout_rng_mut(rollout_kind.seed().unwrap_or_default());
+            let rollout_return = rollout_with(
                 leaf_state_key,
-                |s| num_actions(s).map_err(RunError::Callback),
-                |s, a| step(s, a).map_err(RunError::Callback),
-                |s, n| rollout_policy(s, n).map_err(RunError::Callback),
+                |s| {
+                    num_actions_calls.set(num_actions_calls.get() + 1);
+                    simulator
+                        .time(|| num_actions(s))
+                        .map_err(RunError::Callback)
+                },
+                |s, a| {
+                    step_calls.set(step_calls.get() + 1);
+                    let (next, r, is_terminal) =
+                        simulator.time(|| step(s, a)).map_err(RunError::Callback)?;
+                    check_step_reward(config, s, a, &r)?;
+                    extensions.borrow_mut().observe_step(&StepEvent {
+                        state_key: s,
+                        action_id: a,
+                        next_state_key: next,
+                        rewards: r.as_slice(),
+                        is_terminal,
+                        phase: StepPhase::Rollout,
+                    });
+                    Ok((next, r, is_terminal))
+                },
+                |s, legal| {
+                    rollout_kind.choose(rng, legal, || {
+                        rollout_policy(s, legal.num_actions()).map_err(RunError::Callback)
+                    })
+                },
+                |s| extensions.borrow_mut().action_mask(s),
+                |s| extensions.borrow_mut().value_bootstrap(s),
                 config.rollout_params(),
-            )?
+            )?;
+            if config.strict_rewards && !rollout_return.all_finite() {
+                return Err(TreeError::NonFiniteReturn {
+                    state_key: leaf_state_key,
+                }
+                .into());
+            }
+            if use_cache {
+                R::rollout_cache(self).record(
+                    leaf_state_key,
+                    &rollout_return,
+                    config.rollout_cache_capacity,
+                );
+            }
+            rollout_return
         };
-        let total_return = policy_result.reward + rollout_return;
+        // Rollouts score the leaf player's view; convert it back to the root's view.
+        let rollout_return = rollout_return.into_root_view(config.player_mode, leaf_player);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            state_key = leaf_state_key.raw(),
+            rollout_return = rollout_return.value_for(config.player_mode, leaf_player),
+            rollout_cache_hit,
+            "rollout"
+        );
+        let mut total_return = policy_result.reward.clone();
+        total_return.add_scaled(&rollout_return, 1.0);
+        let rollout_time = phase.elapsed();
 
-        self.backpropagate(&policy_result.path, total_return)?;
+        let phase = Stopwatch::start_if(config.phase_timing);
+        self.backpropagate_for(&policy_result.path, &total_return, config.player_mode)?;
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            path_len = policy_result.path.len(),
+            total_return = total_return.value_for(config.player_mode, leaf_player),
+            "backpropagated"
+        );
+        if config.solver {
+            self.propagate_proofs(&policy_result.path, policy_result.leaf, config.player_mode)?;
+        }
+        let backprop_time = phase.elapsed();
 
+        let root_player = self.node(self.root_id())?.player();
         Ok(IterationMetrics {
             leaf: policy_result.leaf,
             leaf_is_new: policy_result.leaf_is_new,
             path_len: policy_result.path.len(),
-            reward_prefix: policy_result.reward,
-            rollout_return,
-            total_return,
+            reward_prefix: policy_result
+                .reward
+                .value_for(config.player_mode, root_player),
+            rollout_return: rollout_return.value_for(config.player_mode, root_player),
+            total_return: total_return.value_for(config.player_mode, root_player),
             node_count: self.node_count(),
+            rollout_cache_hit,
+            nodes_pruned,
+            selection_capped: policy_result.selection_capped,
+            selection_time,
+            rollout_time,
+            backprop_time,
+            simulator_time: simulator.total(),
+            selection_num_actions_calls,
+            selection_step_calls,
+            rollout_num_actions_calls: num_actions_calls.get(),
+            rollout_step_calls: step_calls.get(),
         })
     }
 
@@ -435,6 +913,34 @@ impl Tree {
         })
     }
 
+    /// Run MCTS with the rollout policy named in `config.rollout`, without a rollout closure.
+    /// `Custom` and the greedy branch of `EpsilonGreedy` need a closure and return
+    /// `TreeError::MissingRolloutPolicy` here; use `run` for those.
+    pub fn run_configured<FNum, FStep>(
+        &mut self,
+        config: &SearchConfig,
+        mut num_actions: FNum,
+        mut step: FStep,
+    ) -> Result<RunMetrics, TreeError>
+    where
+        FNum: FnMut(crate::tree::ids::StateKey) -> usize,
+        FStep:
+            FnMut(crate::tree::ids::StateKey, ActionId) -> (crate::tree::ids::StateKey, f64, bool),
+    {
+        self.run_fallible(
+            config,
+            |state| Ok::<usize, TreeError>(num_actions(state)),
+            |state, action| {
+                Ok::<(crate::tree::ids::StateKey, f64, bool), TreeError>(step(state, action))
+            },
+            |_state, _n| Err(TreeError::MissingRolloutPolicy),
+        )
+        .map_err(|err| match err {
+            RunError::Tree(tree_err) => tree_err,
+            RunError::Callback(tree_err) => tree_err,
+        })
+    }
+
     /// Run MCTS and invoke a callback after each completed iteration.
     pub fn run_with_hook<FNum, FStep, FPolicy, FHook>(
         &mut self,
@@ -487,11 +993,71 @@ impl Tree {
 
     /// Run MCTS with fallible callbacks and invoke a hook per iteration.
     pub fn run_with_hook_fallible<FNum, FStep, FPolicy, FHook, E>(
+        &mut self,
+        config: &SearchConfig,
+        num_actions: FNum,
+        step: FStep,
+        rollout_policy: FPolicy,
+        on_iteration: FHook,
+    ) -> Result<RunMetrics, RunError<E>>
+    where
+        FNum: FnMut(crate::tree::ids::StateKey) -> Result<usize, E>,
+        FStep: FnMut(
+            crate::tree::ids::StateKey,
+            ActionId,
+        ) -> Result<(crate::tree::ids::StateKey, f64, bool), E>,
+        FPolicy: FnMut(crate::tree::ids::StateKey, usize) -> Result<ActionId, E>,
+        FHook: FnMut(&IterationMetrics),
+    {
+        self.run_with_extensions_fallible(
+            config,
+            num_actions,
+            step,
+            rollout_policy,
+            SearchExtensions::default(),
+            on_iteration,
+        )
+    }
+
+    /// Run MCTS with search extensions enabled.
+    pub fn run_with_extensions<FNum, FStep, FPolicy>(
         &mut self,
         config: &SearchConfig,
         mut num_actions: FNum,
         mut step: FStep,
         mut rollout_policy: FPolicy,
+        extensions: SearchExtensions<'_>,
+    ) -> Result<RunMetrics, TreeError>
+    where
+        FNum: FnMut(crate::tree::ids::StateKey) -> usize,
+        FStep:
+            FnMut(crate::tree::ids::StateKey, ActionId) -> (crate::tree::ids::StateKey, f64, bool),
+        FPolicy: FnMut(crate::tree::ids::StateKey, usize) -> ActionId,
+    {
+        self.run_with_extensions_fallible(
+            config,
+            |state| Ok::<usize, TreeError>(num_actions(state)),
+            |state, action| {
+                Ok::<(crate::tree::ids::StateKey, f64, bool), TreeError>(step(state, action))
+            },
+            |state, n| Ok::<ActionId, TreeError>(rollout_policy(state, n)),
+            extensions,
+            |_| {},
+        )
+        .map_err(|err| match err {
+            RunError::Tree(tree_err) => tree_err,
+            RunError::Callback(tree_err) => tree_err,
+        })
+    }
+
+    /// Run MCTS with fallible callbacks, search extensions, and a per-iteration hook.
+    pub fn run_with_extensions_fallible<FNum, FStep, FPolicy, FHook, E>(
+        &mut self,
+        config: &SearchConfig,
+        num_actions: FNum,
+        step: FStep,
+        rollout_policy: FPolicy,
+        extensions: SearchExtensions<'_>,
         mut on_iteration: FHook,
     ) -> Result<RunMetrics, RunError<E>>
     where
@@ -503,16 +1069,387 @@ impl Tree {
         FPolicy: FnMut(crate::tree::ids::StateKey, usize) -> Result<ActionId, E>,
         FHook: FnMut(&IterationMetrics),
     {
+        self.run_observed(
+            config,
+            num_actions,
+            step,
+            rollout_policy,
+            extensions,
+            |_tree, iteration_metrics, _metrics| on_iteration(iteration_metrics),
+        )
+    }
+
+    /// Shared loop of the scalar runners: `observe` sees the tree, the iteration's metrics,
+    /// and the running totals after every iteration, e.g. to publish progress.
+    pub(crate) fn run_observed<FNum, FStep, FPolicy, FObserve, E>(
+        &mut self,
+        config: &SearchConfig,
+        mut num_actions: FNum,
+        mut step: FStep,
+        mut rollout_policy: FPolicy,
+        mut extensions: SearchExtensions<'_>,
+        mut observe: FObserve,
+    ) -> Result<RunMetrics, RunError<E>>
+    where
+        FNum: FnMut(crate::tree::ids::StateKey) -> Result<usize, E>,
+        FStep: FnMut(
+            crate::tree::ids::StateKey,
+            ActionId,
+        ) -> Result<(crate::tree::ids::StateKey, f64, bool), E>,
+        FPolicy: FnMut(crate::tree::ids::StateKey, usize) -> Result<ActionId, E>,
+        FObserve: FnMut(&Self, &IterationMetrics, &RunMetrics),
+    {
+        let started = Stopwatch::start();
         let mut metrics = RunMetrics::new(config.iterations);
+        #[cfg(feature = "tracing")]
+        let _run_span = run_span(config).entered();
 
         for _ in 0..config.iterations {
-            let iteration_metrics =
-                self.iterate_fallible(config, &mut num_actions, &mut step, &mut rollout_policy)?;
+            let iteration_metrics = self.iterate_with_extensions_fallible(
+                config,
+                &mut num_actions,
+                &mut step,
+                &mut rollout_policy,
+                &mut extensions,
+            )?;
 
+            #[cfg(feature = "tracing")]
+            trace_iteration_completed(metrics.iterations_completed, &iteration_metrics);
+            metrics.record(iteration_metrics);
+            observe(self, &iteration_metrics, &metrics);
+        }
+
+        metrics.total_time = started.elapsed();
+        #[cfg(feature = "tracing")]
+        trace_run_completed(&metrics);
+        Ok(metrics)
+    }
+
+    /// Run information-set MCTS by determinization for domains with hidden information.
+    ///
+    /// Before every iteration `determinize(iteration)` samples a concrete world consistent
+    /// with what the searching agent knows, e.g. a deal of the unseen cards. The iteration
+    /// then simulates in that world: `step` advances it and returns the key of the resulting
+    /// information set, not of the hidden state, so iterations over different worlds share
+    /// tree nodes and the root statistics average over determinizations. Action counts of
+    /// tree nodes come from the world that first expands them, so `num_actions` should
+    /// depend on the information set only; rollouts see each world's own count.
+    pub fn run_determinized<D, FDet, FNum, FStep, FPolicy>(
+        &mut self,
+        config: &SearchConfig,
+        mut determinize: FDet,
+        mut num_actions: FNum,
+        mut step: FStep,
+        mut rollout_policy: FPolicy,
+    ) -> Result<RunMetrics, TreeError>
+    where
+        FDet: FnMut(usize) -> D,
+        FNum: FnMut(&D, crate::tree::ids::StateKey) -> usize,
+        FStep: FnMut(
+            &mut D,
+            crate::tree::ids::StateKey,
+            ActionId,
+        ) -> (crate::tree::ids::StateKey, f64, bool),
+        FPolicy: FnMut(&D, crate::tree::ids::StateKey, usize) -> ActionId,
+    {
+        self.run_determinized_fallible(
+            config,
+            |iteration| Ok::<D, TreeError>(determinize(iteration)),
+            |world, state| Ok::<usize, TreeError>(num_actions(world, state)),
+            |world, state, action| {
+                Ok::<(crate::tree::ids::StateKey, f64, bool), TreeError>(step(world, state, action))
+            },
+            |world, state, n| Ok::<ActionId, TreeError>(rollout_policy(world, state, n)),
+            SearchExtensions::new(),
+            |_| {},
+        )
+        .map_err(|err| match err {
+            RunError::Tree(tree_err) => tree_err,
+            RunError::Callback(tree_err) => tree_err,
+        })
+    }
+
+    /// Run information-set MCTS by determinization with fallible callbacks, optional
+    /// extensions, and a hook called with each iteration's metrics.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_determinized_fallible<D, FDet, FNum, FStep, FPolicy, FHook, E>(
+        &mut self,
+        config: &SearchConfig,
+        mut determinize: FDet,
+        mut num_actions: FNum,
+        mut step: FStep,
+        mut rollout_policy: FPolicy,
+        mut extensions: SearchExtensions<'_>,
+        mut on_iteration: FHook,
+    ) -> Result<RunMetrics, RunError<E>>
+    where
+        FDet: FnMut(usize) -> Result<D, E>,
+        FNum: FnMut(&D, crate::tree::ids::StateKey) -> Result<usize, E>,
+        FStep: FnMut(
+            &mut D,
+            crate::tree::ids::StateKey,
+            ActionId,
+        ) -> Result<(crate::tree::ids::StateKey, f64, bool), E>,
+        FPolicy: FnMut(&D, crate::tree::ids::StateKey, usize) -> Result<ActionId, E>,
+        FHook: FnMut(&IterationMetrics),
+    {
+        let started = Stopwatch::start();
+        let mut metrics = RunMetrics::new(config.iterations);
+        #[cfg(feature = "tracing")]
+        let _run_span = run_span(config).entered();
+
+        for iteration in 0..config.iterations {
+            let world = RefCell::new(determinize(iteration).map_err(RunError::Callback)?);
+            let iteration_metrics = self.iterate_with(
+                config,
+                &mut |state| num_actions(&world.borrow(), state),
+                &mut |state, action| step(&mut world.borrow_mut(), state, action),
+                &mut |state, n| rollout_policy(&world.borrow(), state, n),
+                &mut extensions,
+            )?;
+
+            #[cfg(feature = "tracing")]
+            trace_iteration_completed(metrics.iterations_completed, &iteration_metrics);
+            on_iteration(&iteration_metrics);
+            metrics.record(iteration_metrics);
+        }
+
+        metrics.total_time = started.elapsed();
+        #[cfg(feature = "tracing")]
+        trace_run_completed(&metrics);
+        Ok(metrics)
+    }
+
+    /// Run a MaxN search for N-player games.
+    /// `step` returns one reward per player, `player_to_move` names the player acting in a state,
+    /// and every node maximizes its own player's component of the return.
+    /// `config.player_mode` is ignored; run metrics report the root player's component.
+    pub fn run_max_n<FPlayer, FNum, FStep, FPolicy>(
+        &mut self,
+        config: &SearchConfig,
+        player_to_move: FPlayer,
+        mut num_actions: FNum,
+        mut step: FStep,
+        mut rollout_policy: FPolicy,
+    ) -> Result<RunMetrics, TreeError>
+    where
+        FPlayer: FnMut(crate::tree::ids::StateKey) -> usize,
+        FNum: FnMut(crate::tree::ids::StateKey) -> usize,
+        FStep: FnMut(
+            crate::tree::ids::StateKey,
+            ActionId,
+        ) -> (crate::tree::ids::StateKey, Vec<f64>, bool),
+        FPolicy: FnMut(crate::tree::ids::StateKey, usize) -> ActionId,
+    {
+        self.run_max_n_with_extensions_fallible(
+            config,
+            |state| Ok::<usize, TreeError>(num_actions(state)),
+            |state, action| {
+                Ok::<(crate::tree::ids::StateKey, Vec<f64>, bool), TreeError>(step(state, action))
+            },
+            |state, n| Ok::<ActionId, TreeError>(rollout_policy(state, n)),
+            SearchExtensions::new().with_player_to_move(player_to_move),
+            |_| {},
+        )
+        .map_err(|err| match err {
+            RunError::Tree(tree_err) => tree_err,
+            RunError::Callback(tree_err) => tree_err,
+        })
+    }
+
+    /// Run a MaxN search with fallible callbacks, search extensions, and a per-iteration hook.
+    /// Players are resolved through `SearchExtensions::with_player_to_move`.
+    /// Returns `TreeError::MaxNBootstrapUnsupported` for `ReturnType::NStepBootstrap` configs.
+    pub fn run_max_n_with_extensions_fallible<FNum, FStep, FPolicy, FHook, E>(
+        &mut self,
+        config: &SearchConfig,
+        mut num_actions: FNum,
+        mut step: FStep,
+        mut rollout_policy: FPolicy,
+        mut extensions: SearchExtensions<'_>,
+        mut on_iteration: FHook,
+    ) -> Result<RunMetrics, RunError<E>>
+    where
+        FNum: FnMut(crate::tree::ids::StateKey) -> Result<usize, E>,
+        FStep: FnMut(
+            crate::tree::ids::StateKey,
+            ActionId,
+        ) -> Result<(crate::tree::ids::StateKey, Vec<f64>, bool), E>,
+        FPolicy: FnMut(crate::tree::ids::StateKey, usize) -> Result<ActionId, E>,
+        FHook: FnMut(&IterationMetrics),
+    {
+        if config.return_type == ReturnType::NStepBootstrap {
+            return Err(RunError::Tree(TreeError::MaxNBootstrapUnsupported));
+        }
+        // Per-player vectors already carry every player's view; no sign flipping applies.
+        // Proofs need a single scalar value per node, so the solver is not supported here.
+        let config = &SearchConfig {
+            player_mode: PlayerMode::SingleAgent,
+            solver: false,
+            ..config.clone()
+        };
+        let started = Stopwatch::start();
+        let mut metrics = RunMetrics::new(config.iterations);
+        #[cfg(feature = "tracing")]
+        let _run_span = run_span(config).entered();
+
+        for _ in 0..config.iterations {
+            let iteration_metrics = self.iterate_with(
+                config,
+                &mut num_actions,
+                &mut step,
+                &mut rollout_policy,
+                &mut extensions,
+            )?;
+
+            #[cfg(feature = "tracing")]
+            trace_iteration_completed(metrics.iterations_completed, &iteration_metrics);
             on_iteration(&iteration_metrics);
             metrics.record(iteration_metrics);
         }
 
+        metrics.total_time = started.elapsed();
+        #[cfg(feature = "tracing")]
+        trace_run_completed(&metrics);
+        Ok(metrics)
+    }
+
+    /// Run a single-step (contextual) bandit on the root, with the root state as the context.
+    /// Every iteration picks a root action by UCB, draws `sample_reward(action)`, and records
+    /// it on that edge; no child nodes, simulator steps, or rollouts are involved.
+    /// The root is expanded with `num_actions` edges on first use. Exploration settings
+    /// (`c`, `value_normalization`) and `strict_rewards` apply; discounting, rollout,
+    /// and depth settings are ignored.
+    pub fn run_bandit<F>(
+        &mut self,
+        config: &SearchConfig,
+        num_actions: usize,
+        mut sample_reward: F,
+    ) -> Result<RunMetrics, TreeError>
+    where
+        F: FnMut(ActionId) -> f64,
+    {
+        let root_id = self.root_id();
+        let (state_key, is_terminal) = {
+            let root = self.node_mut(root_id)?;
+            if !root.is_expanded() && !root.is_terminal() {
+                root.expand(num_actions, None);
+            }
+            (root.state_key(), root.is_terminal())
+        };
+        let started = Stopwatch::start();
+        let mut metrics = RunMetrics::new(config.iterations);
+        #[cfg(feature = "tracing")]
+        let _run_span = run_span(config).entered();
+
+        for _ in 0..config.iterations {
+            // A terminal root or one without legal actions has nothing to pull.
+            let action = if is_terminal {
+                None
+            } else {
+                self.select_edge(root_id, config)?
+            };
+            let reward = match action {
+                Some(action) => {
+                    let reward = sample_reward(action);
+                    check_step_reward(config, state_key, action, &reward)?;
+                    self.backpropagate(&[(root_id, action)], reward)?;
+                    reward
+                }
+                None => 0.0,
+            };
+
+            let iteration_metrics = IterationMetrics {
+                leaf: root_id,
+                leaf_is_new: false,
+                path_len: usize::from(action.is_some()),
+                reward_prefix: reward,
+                rollout_return: 0.0,
+                total_return: reward,
+                node_count: self.node_count(),
+                rollout_cache_hit: false,
+                nodes_pruned: 0,
+                selection_capped: false,
+                selection_time: Duration::ZERO,
+                rollout_time: Duration::ZERO,
+                backprop_time: Duration::ZERO,
+                simulator_time: Duration::ZERO,
+                selection_num_actions_calls: 0,
+                selection_step_calls: 0,
+                rollout_num_actions_calls: 0,
+                rollout_step_calls: 0,
+            };
+            #[cfg(feature = "tracing")]
+            trace_iteration_completed(metrics.iterations_completed, &iteration_metrics);
+            metrics.record(iteration_metrics);
+        }
+
+        metrics.total_time = started.elapsed();
+        #[cfg(feature = "tracing")]
+        trace_run_completed(&metrics);
         Ok(metrics)
     }
 }
+
+/// With `strict_rewards`, reject a step reward that is NaN or infinite.
+fn check_step_reward<R: Reward>(
+    config: &SearchConfig,
+    state_key: crate::tree::ids::StateKey,
+    action_id: ActionId,
+    reward: &R,
+) -> Result<(), TreeError> {
+    if config.strict_rewards && !reward.all_finite() {
+        return Err(TreeError::NonFiniteReward {
+            state_key,
+            action_id,
+        });
+    }
+    Ok(())
+}
+
+/// Span covering a whole run, with fields mirroring `RunLogEvent::RunStarted`.
+#[cfg(feature = "tracing")]
+fn run_span(config: &SearchConfig) -> tracing::Span {
+    tracing::info_span!(
+        "weavetree.run",
+        iterations_requested = config.iterations,
+        c = config.c,
+        gamma = config.gamma,
+        max_steps = config.max_steps,
+        return_type = RunLogEvent::return_type_name(config.return_type),
+        fixed_horizon_steps = config.fixed_horizon_steps,
+    )
+}
+
+/// Event mirroring `RunLogEvent::IterationCompleted`.
+#[cfg(feature = "tracing")]
+fn trace_iteration_completed(iteration: usize, metrics: &IterationMetrics) {
+    tracing::debug!(
+        iteration,
+        leaf_node_id = metrics.leaf.index(),
+        leaf_is_new = metrics.leaf_is_new,
+        path_len = metrics.path_len,
+        reward_prefix = metrics.reward_prefix,
+        rollout_return = metrics.rollout_return,
+        total_return = metrics.total_return,
+        node_count = metrics.node_count,
+        "iteration_completed"
+    );
+}
+
+/// Event mirroring `RunLogEvent::RunCompleted`.
+#[cfg(feature = "tracing")]
+fn trace_run_completed(metrics: &RunMetrics) {
+    tracing::info!(
+        iterations_requested = metrics.iterations_requested,
+        iterations_completed = metrics.iterations_completed,
+        total_return_sum = metrics.total_return_sum,
+        average_total_return = metrics.average_total_return,
+        total_seconds = metrics.total_time.as_secs_f64(),
+        selection_seconds = metrics.selection_time.as_secs_f64(),
+        rollout_seconds = metrics.rollout_time.as_secs_f64(),
+        backprop_seconds = metrics.backprop_time.as_secs_f64(),
+        simulator_seconds = metrics.simulator_time.as_secs_f64(),
+        "run_completed"
+    );
+}
@@ -1,14 +1,36 @@
 mod arena;
+#[cfg(feature = "parquet")]
+pub mod columnar;
+pub mod compare;
+pub mod config_builder;
 mod edges;
+pub mod ensemble;
 pub mod error;
+pub mod extensions;
+#[cfg(feature = "http")]
+pub mod http;
 pub mod ids;
 pub mod mcts;
 mod node;
 mod outcomes;
+pub mod policy;
+mod pruning;
+mod reward;
 pub mod rollout;
+mod rollout_cache;
+pub mod run_log;
 pub mod search_tree;
+pub mod seed;
 pub mod snapshot;
+mod solver;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 mod stats;
+#[cfg(feature = "tensorboard")]
+pub mod tensorboard;
+mod timing;
+pub mod trace;
+pub mod training;
 
 #[cfg(test)]
 mod tests;
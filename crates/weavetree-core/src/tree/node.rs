@@ -1,8 +1,9 @@
 #![allow(dead_code)]
 
 use crate::tree::{
-    edges::ActionEdge,
+    edges::{self, ActionEdge, EdgeMut, EdgeRef},
     ids::{ActionId, NodeId, StateKey},
+    stats::{EdgeStats, ValueBounds},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,87 +18,170 @@ pub enum ExpansionState {
 #[derive(Debug, Clone)]
 /// represents a decision state in the search tree.
 /// This class iterates edges to select best UCB action and accesses an edge by action index
-pub struct Node {
+/// Edge data is split struct-of-arrays style: `edge_stats[i]` holds the hot numbers
+/// selection reads, `edges[i]` the cold outcome map and flags of the same action.
+/// `payload` is free-form user data attached to the node, `()` unless the tree is generic over it.
+pub struct Node<P = ()> {
     state_key: StateKey,
     depth: u64,
     parent: Option<(NodeId, ActionId)>,
+    edge_stats: Vec<EdgeStats>,
     edges: Vec<ActionEdge>,
     is_terminal: bool,
     expansion_state: ExpansionState,
+    player: usize,
+    entry_reward: f64,
+    proven_value: Option<f64>,
+    payload: P,
 }
 
-impl Node {
+impl<P> Node<P> {
     /// Create a new Node instance
     pub fn new(
         state_key: StateKey,
         depth: u64,
         parent: Option<(NodeId, ActionId)>,
         is_terminal: bool,
+        payload: P,
     ) -> Self {
         Node {
             state_key,
             depth,
             parent,
+            edge_stats: Vec::new(),
             edges: Vec::new(),
             is_terminal,
             expansion_state: ExpansionState::Unexpanded,
+            player: 0,
+            entry_reward: 0.0,
+            proven_value: None,
+            payload,
         }
     }
 
+    /// Return the user payload attached to this node.
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+
+    /// Return the user payload attached to this node as a mutable borrow.
+    pub fn payload_mut(&mut self) -> &mut P {
+        &mut self.payload
+    }
+
     /// Expand this node by creating an edge per legal action.
     /// The search loop determines `num_actions` from the environment.
-    pub fn expand(&mut self, num_actions: usize) {
+    /// When `priors` is given, edge `i` starts with the `(q, visits)` pseudo-counts in `priors[i]`.
+    pub fn expand(&mut self, num_actions: usize, priors: Option<&[(f64, u64)]>) {
         if self.expansion_state == ExpansionState::Expanded {
             return;
         }
 
-        self.edges = (0..num_actions)
+        self.edge_stats = match priors {
+            Some(priors) => priors
+                .iter()
+                .take(num_actions)
+                .map(|&(q, visits)| EdgeStats::with_prior(q, visits))
+                .collect(),
+            None => vec![EdgeStats::new(); num_actions],
+        };
+        self.edges = (0..self.edge_stats.len())
             .map(|i| ActionEdge::new(ActionId::from(i)))
             .collect();
 
         self.expansion_state = ExpansionState::Expanded;
     }
 
-    /// Select an edge based on UCB.
-    /// Returns the chosen `ActionId` (index in `edges`).
-    pub fn select_edge(&self, c: f64) -> Option<ActionId> {
-        if self.edges.is_empty() {
-            return None;
+    /// Apply a legality mask to the expanded edges.
+    /// `mask[i] == false` marks action `i` as illegal; indices are left untouched.
+    pub fn apply_action_mask(&mut self, mask: &[bool]) {
+        for (edge, legal) in self.edges.iter_mut().zip(mask.iter().copied()) {
+            edge.set_legal(legal);
         }
+    }
+
+    /// Check if at least one edge can still be selected.
+    pub fn has_legal_edges(&self) -> bool {
+        self.edges.iter().any(|edge| edge.is_legal())
+    }
 
-        // Parent visit count: sum of child edge visits
-        let n_parent: u64 = self.edges.iter().map(|e| e.visits()).sum::<u64>().max(1);
+    /// Select an edge based on UCB, skipping illegal edges.
+    /// `bounds` enables min/max normalization of the exploitation term.
+    /// `novelty_bonus` scales a bonus for edges that are still discovering new outcomes.
+    /// Returns the chosen `ActionId` (index in `edges`).
+    pub fn select_edge(
+        &self,
+        c: f64,
+        bounds: Option<&ValueBounds>,
+        novelty_bonus: f64,
+    ) -> Option<ActionId> {
+        // Parent visit count: sum of legal child edge visits
+        let n_parent: u64 = self
+            .edge_stats
+            .iter()
+            .zip(self.edges.iter())
+            .filter(|(_, e)| e.is_legal())
+            .map(|(stats, _)| stats.visits())
+            .sum::<u64>()
+            .max(1);
 
         // track best score + best index.
-        let mut best_idx: usize = 0;
-        let mut best_score: f64 = f64::NEG_INFINITY;
+        let mut best: Option<(usize, f64)> = None;
 
-        for (i, edge) in self.edges.iter().enumerate() {
-            let score = edge.ucb_score(n_parent, c);
+        for (i, (stats, edge)) in self.edge_stats.iter().zip(self.edges.iter()).enumerate() {
+            if !edge.is_legal() {
+                continue;
+            }
 
-            // tie breaker in case of similar scores prefer smaller index.
-            if score > best_score || (score == best_score && i < best_idx) {
-                best_score = score;
-                best_idx = i;
+            let mut score = edges::ucb_score(stats, edge.proven_value(), n_parent, c, bounds);
+            if novelty_bonus > 0.0 && edge.proven_value().is_none() {
+                score += novelty_bonus * edge.unseen_outcome_mass();
             }
+
+            // tie breaker in case of similar scores prefer smaller index.
+            best = match best {
+                Some((_, best_score)) if score <= best_score => best,
+                _ => Some((i, score)),
+            };
         }
 
-        Some(ActionId::from(best_idx))
+        best.map(|(i, _)| ActionId::from(i))
     }
 
     /// Using an action id, return the corresponding action edge
-    pub fn edge(&self, action_id: ActionId) -> Option<&ActionEdge> {
-        self.edges.get(action_id.index())
+    pub fn edge(&self, action_id: ActionId) -> Option<EdgeRef<'_>> {
+        let index = action_id.index();
+        Some(EdgeRef::new(
+            self.edges.get(index)?,
+            self.edge_stats.get(index)?,
+        ))
     }
 
     /// Using an action id, return the corresponding action edge as mutable
-    pub fn edge_mut(&mut self, action_id: ActionId) -> Option<&mut ActionEdge> {
-        self.edges.get_mut(action_id.index())
+    pub fn edge_mut(&mut self, action_id: ActionId) -> Option<EdgeMut<'_>> {
+        let index = action_id.index();
+        Some(EdgeMut::new(
+            self.edges.get_mut(index)?,
+            self.edge_stats.get_mut(index)?,
+        ))
+    }
+
+    /// Iterate all action edges for this node.
+    pub fn edges(&self) -> impl ExactSizeIterator<Item = EdgeRef<'_>> + '_ {
+        self.edges
+            .iter()
+            .zip(self.edge_stats.iter())
+            .map(|(edge, stats)| EdgeRef::new(edge, stats))
+    }
+
+    /// Return the amount of action edges for this node.
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
     }
 
-    /// Return all action edges for this node.
-    pub fn edges(&self) -> &[ActionEdge] {
-        &self.edges
+    /// Return the cold part of all action edges as mutable, e.g. to rewrite outcome ids.
+    pub fn action_edges_mut(&mut self) -> &mut [ActionEdge] {
+        &mut self.edges
     }
 
     /// Return the depth of a specific node
@@ -130,8 +214,54 @@ impl Node {
         self.expansion_state == ExpansionState::Expanded
     }
 
+    /// Return the index of the player to move at this node.
+    /// Single-agent searches always use player `0`.
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    /// Set the index of the player to move at this node.
+    pub fn set_player(&mut self, player: usize) {
+        self.player = player
+    }
+
+    /// Return the reward the parent's mover earned on the transition into this node.
+    pub fn entry_reward(&self) -> f64 {
+        self.entry_reward
+    }
+
+    /// Set the reward earned on the transition into this node, seen by the parent's mover.
+    pub fn set_entry_reward(&mut self, reward: f64) {
+        self.entry_reward = reward
+    }
+
+    /// Return the exact value of this node for its player, once the solver proved it.
+    pub fn proven_value(&self) -> Option<f64> {
+        self.proven_value
+    }
+
+    /// Mark this node as proven with an exact value for its player.
+    pub fn set_proven_value(&mut self, value: f64) {
+        self.proven_value = Some(value)
+    }
+
+    /// Return the exact value over all legal edges once every one of them is proven.
+    pub fn proven_edges_value(&self) -> Option<f64> {
+        let mut best: Option<f64> = None;
+        for edge in self.edges.iter().filter(|edge| edge.is_legal()) {
+            let value = edge.proven_value()?;
+            best = Some(best.map_or(value, |best| best.max(value)));
+        }
+        best
+    }
+
     /// Return the parent of a given node
     pub fn parent(&self) -> Option<(NodeId, ActionId)> {
         self.parent
     }
+
+    /// Re-point this node at its parent edge, e.g. after the arena was compacted.
+    pub fn set_parent(&mut self, parent: Option<(NodeId, ActionId)>) {
+        self.parent = parent
+    }
 }
@@ -1,8 +1,12 @@
 #![allow(dead_code)]
 
+use std::collections::HashMap;
+
 use crate::tree::ids::{NodeId, StateKey};
 
-//TODO: Potentially need to switch the set to a hashmap, lets see about that later
+/// Distinct outcomes an edge can hold before lookups switch from a linear scan to a hash index.
+/// Most edges are deterministic or have a handful of outcomes, where scanning is fastest.
+const INDEX_THRESHOLD: usize = 8;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// represents one observed next state under a given `(s,a)` edge.
@@ -42,8 +46,11 @@ impl Outcome {
 /// holds all outcomes observed for one action edge.
 /// Stores all observed outcomes for a single action edge.
 /// That’s how the tree “discovers” stochastic branches naturally.
+/// Small sets are scanned linearly; past `INDEX_THRESHOLD` outcomes a `StateKey -> position`
+/// index is maintained alongside the vector, which keeps insertion order for iteration.
 pub struct OutcomeSet {
     outcomes: Vec<Outcome>,
+    index: Option<HashMap<StateKey, usize>>,
 }
 
 impl OutcomeSet {
@@ -51,49 +58,70 @@ impl OutcomeSet {
     pub fn new() -> Self {
         OutcomeSet {
             outcomes: Vec::new(),
+            index: None,
+        }
+    }
+
+    /// Return the position of an outcome in `outcomes`.
+    fn position(&self, next_state_key: StateKey) -> Option<usize> {
+        match &self.index {
+            Some(index) => index.get(&next_state_key).copied(),
+            None => self
+                .outcomes
+                .iter()
+                .position(|outcome| outcome.next_state_key == next_state_key),
         }
     }
 
+    /// Build or drop the hash index depending on the current outcome count.
+    fn rebuild_index(&mut self) {
+        self.index = (self.outcomes.len() > INDEX_THRESHOLD).then(|| {
+            self.outcomes
+                .iter()
+                .enumerate()
+                .map(|(position, outcome)| (outcome.next_state_key, position))
+                .collect()
+        });
+    }
+
+    /// Check whether lookups currently go through the hash index.
+    pub fn is_indexed(&self) -> bool {
+        self.index.is_some()
+    }
+
     /// Find the next node associated to this state key
     /// If found returns `Some(NodeId)` else None
     pub fn get_child_for(&self, next_state_key: StateKey) -> Option<NodeId> {
-        let outcome = self
-            .outcomes
-            .iter()
-            .find(|outcome| outcome.next_state_key == next_state_key);
-        outcome.map(|outcome| outcome.child())
+        self.position(next_state_key)
+            .map(|position| self.outcomes[position].child())
     }
 
     /// Insert an outcome to the set
     /// We also make sure the Statekey has not been inserted yet
     /// Returns Option<NodeId>, with Some(child_id) in case the insert worked
     pub fn insert_outcome(&mut self, next_state_key: StateKey, child_id: NodeId) -> Option<NodeId> {
-        if !self
-            .outcomes
-            .iter()
-            .any(|outcome| outcome.next_state_key == next_state_key)
-        {
-            self.outcomes.push(Outcome::new(next_state_key, child_id));
-            Some(child_id)
-        } else {
-            None
+        if self.position(next_state_key).is_some() {
+            return None;
+        }
+
+        self.outcomes.push(Outcome::new(next_state_key, child_id));
+        match &mut self.index {
+            Some(index) => {
+                index.insert(next_state_key, self.outcomes.len() - 1);
+            }
+            None if self.outcomes.len() > INDEX_THRESHOLD => self.rebuild_index(),
+            None => {}
         }
+        Some(child_id)
     }
 
     /// Icrement the count on a single occurence
     /// Returns Option<NodeId>, with Some(child_id) in case the incrementing worked
     pub fn increment_outcome(&mut self, next_state_key: StateKey) -> Option<NodeId> {
-        let outcome = self
-            .outcomes
-            .iter_mut()
-            .find(|outcome| outcome.next_state_key == next_state_key);
-        match outcome {
-            Some(outcome) => {
-                outcome.increment_count();
-                Some(outcome.child())
-            }
-            None => None,
-        }
+        let position = self.position(next_state_key)?;
+        let outcome = &mut self.outcomes[position];
+        outcome.increment_count();
+        Some(outcome.child())
     }
 
     /// Return the amount of distinct outcomes seen for this edge.
@@ -103,10 +131,48 @@ impl OutcomeSet {
 
     /// Return how many times a specific next state has been observed.
     pub fn count_for(&self, next_state_key: StateKey) -> Option<u64> {
+        self.position(next_state_key)
+            .map(|position| self.outcomes[position].count())
+    }
+
+    /// Estimate the probability that the next sample is an outcome not seen yet.
+    /// Uses the Good-Turing estimate: outcomes seen exactly once over all samples.
+    /// An edge without samples is entirely unexplored and returns `1.0`.
+    pub fn unseen_mass(&self) -> f64 {
+        let (singletons, samples) =
+            self.outcomes
+                .iter()
+                .fold((0u64, 0u64), |(singletons, samples), outcome| {
+                    (
+                        singletons + u64::from(outcome.count() == 1),
+                        samples + outcome.count(),
+                    )
+                });
+        if samples == 0 {
+            1.0
+        } else {
+            singletons as f64 / samples as f64
+        }
+    }
+
+    /// Rewrite every outcome's child id, dropping outcomes for which `remap` returns `None`.
+    /// Dropped outcomes are simply rediscovered (with a fresh child) the next time they are sampled.
+    pub fn remap_children<F>(&mut self, mut remap: F)
+    where
+        F: FnMut(NodeId) -> Option<NodeId>,
+    {
+        let before = self.outcomes.len();
         self.outcomes
-            .iter()
-            .find(|outcome| outcome.next_state_key == next_state_key)
-            .map(|outcome| outcome.count())
+            .retain_mut(|outcome| match remap(outcome.child) {
+                Some(child) => {
+                    outcome.child = child;
+                    true
+                }
+                None => false,
+            });
+        if self.outcomes.len() != before {
+            self.rebuild_index();
+        }
     }
 
     /// Iterate over all observed outcomes as `(next_state_key, child_node_id, count)`.
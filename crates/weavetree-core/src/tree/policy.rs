@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tree::{
+    ids::{ActionId, StateKey},
+    search_tree::Tree,
+};
+
+/// Most visited action per searched state, extracted from a finished search.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GreedyPolicy {
+    actions: HashMap<StateKey, ActionId>,
+}
+
+impl GreedyPolicy {
+    /// Return the greedy action for a state, or `None` if the search never expanded it.
+    pub fn action(&self, state_key: StateKey) -> Option<ActionId> {
+        self.actions.get(&state_key).copied()
+    }
+
+    /// Return how many states have a greedy action.
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Iterate over `(state_key, action)` pairs in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (StateKey, ActionId)> + '_ {
+        self.actions.iter().map(|(state, action)| (*state, *action))
+    }
+}
+
+impl<P> Tree<P> {
+    /// Extract the greedy policy: for every expanded node with visited legal edges, its most
+    /// visited action (ties go to the lower action index). A state reached through several
+    /// nodes takes the action of its most visited node.
+    pub fn greedy_policy(&self) -> GreedyPolicy {
+        let mut best: HashMap<StateKey, (u64, ActionId)> = HashMap::new();
+
+        for (_, node) in self.nodes() {
+            let mut node_visits = 0;
+            let mut choice: Option<(u64, ActionId)> = None;
+            for edge in node.edges().filter(|edge| edge.is_legal()) {
+                node_visits += edge.visits();
+                if edge.visits() > 0 && choice.is_none_or(|(visits, _)| edge.visits() > visits) {
+                    choice = Some((edge.visits(), edge.action()));
+                }
+            }
+            let Some((_, action)) = choice else {
+                continue;
+            };
+            let entry = best.entry(node.state_key()).or_insert((0, action));
+            if node_visits > entry.0 {
+                *entry = (node_visits, action);
+            }
+        }
+
+        GreedyPolicy {
+            actions: best
+                .into_iter()
+                .map(|(state, (_, action))| (state, action))
+                .collect(),
+        }
+    }
+}
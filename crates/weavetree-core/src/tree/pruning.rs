@@ -0,0 +1,40 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::tree::{error::TreeError, ids::NodeId, search_tree::Tree};
+
+impl<P> Tree<P> {
+    /// Shrink the tree to at most `max_nodes` nodes by dropping its least visited subtrees.
+    /// Nodes are kept best-first by how often their outcome was sampled, so the kept part is
+    /// always a connected subtree around the root. Node ids are reassigned afterwards.
+    /// Returns how many nodes were removed.
+    pub fn prune_to(&mut self, max_nodes: usize) -> Result<usize, TreeError> {
+        let before = self.node_count();
+        let max_nodes = max_nodes.max(1);
+        if before <= max_nodes {
+            return Ok(0);
+        }
+
+        let mut keep = Vec::with_capacity(max_nodes);
+        // Max-heap on outcome count; ties go to the older (smaller) node id.
+        let mut frontier: BinaryHeap<(u64, Reverse<usize>)> = BinaryHeap::new();
+        let mut next = Some(self.root_id());
+
+        while let Some(node_id) = next {
+            keep.push(node_id);
+            if keep.len() == max_nodes {
+                break;
+            }
+            for edge in self.node(node_id)?.edges() {
+                for (_, child, count) in edge.outcomes_iter() {
+                    frontier.push((count, Reverse(child.index())));
+                }
+            }
+            next = frontier
+                .pop()
+                .map(|(_, Reverse(index))| NodeId::from(index));
+        }
+
+        self.compact(&keep);
+        Ok(before - keep.len())
+    }
+}
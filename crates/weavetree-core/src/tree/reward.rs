@@ -0,0 +1,134 @@
+use crate::tree::{
+    edges::EdgeMut, error::TreeError, mcts::PlayerMode, rollout_cache::RolloutCache,
+    search_tree::Tree,
+};
+
+/// Reward signal flowing through the search loop.
+/// Scalars serve single-agent and negamax searches, vectors (one component per player) serve MaxN.
+pub(crate) trait Reward: Clone {
+    /// Return the additive identity.
+    fn zero() -> Self;
+
+    /// Add `scale * reward` to this accumulator.
+    fn add_scaled(&mut self, reward: &Self, scale: f64);
+
+    /// Accumulate a step reward earned by `mover` into a root-perspective return.
+    fn add_from_mover(&mut self, reward: &Self, mode: PlayerMode, mover: usize);
+
+    /// Convert a return scored from `player`'s perspective into the root player's perspective.
+    fn into_root_view(self, mode: PlayerMode, player: usize) -> Self;
+
+    /// Return the scalar value credited to `player` for a root-perspective return.
+    fn value_for(&self, mode: PlayerMode, player: usize) -> f64;
+
+    /// Add `scale * value` for a scalar value estimate, e.g. an n-step rollout bootstrap.
+    /// Fails for signals that a single scalar can't describe.
+    fn add_value_estimate(&mut self, value: f64, scale: f64) -> Result<(), TreeError>;
+
+    /// Return the scalar step reward earned by `mover`, seen from `mover`'s perspective.
+    fn mover_value(&self, mover: usize) -> f64;
+
+    /// Return the reward components: one for scalars, one per player for vectors.
+    fn as_slice(&self) -> &[f64];
+
+    /// Check that every reward component is a finite number.
+    fn all_finite(&self) -> bool {
+        self.as_slice().iter().all(|value| value.is_finite())
+    }
+
+    /// Return the tree's rollout cache for this reward signal.
+    fn rollout_cache<P>(tree: &mut Tree<P>) -> &mut RolloutCache<Self>;
+
+    /// Record a root-perspective return on an edge owned by `player`.
+    fn record_on(&self, edge: &mut EdgeMut<'_>, mode: PlayerMode, player: usize) {
+        edge.record(self.value_for(mode, player));
+    }
+}
+
+impl Reward for f64 {
+    fn as_slice(&self) -> &[f64] {
+        std::slice::from_ref(self)
+    }
+
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn add_scaled(&mut self, reward: &Self, scale: f64) {
+        *self += scale * reward;
+    }
+
+    fn add_from_mover(&mut self, reward: &Self, mode: PlayerMode, mover: usize) {
+        *self += mode.value_for_player(mover, *reward);
+    }
+
+    fn into_root_view(self, mode: PlayerMode, player: usize) -> Self {
+        mode.value_for_player(player, self)
+    }
+
+    fn value_for(&self, mode: PlayerMode, player: usize) -> f64 {
+        mode.value_for_player(player, *self)
+    }
+
+    fn add_value_estimate(&mut self, value: f64, scale: f64) -> Result<(), TreeError> {
+        *self += scale * value;
+        Ok(())
+    }
+
+    fn mover_value(&self, _mover: usize) -> f64 {
+        *self
+    }
+
+    fn rollout_cache<P>(tree: &mut Tree<P>) -> &mut RolloutCache<Self> {
+        tree.rollout_cache_mut()
+    }
+}
+
+impl Reward for Vec<f64> {
+    fn as_slice(&self) -> &[f64] {
+        self
+    }
+
+    fn zero() -> Self {
+        Vec::new()
+    }
+
+    fn add_scaled(&mut self, reward: &Self, scale: f64) {
+        if self.len() < reward.len() {
+            self.resize(reward.len(), 0.0);
+        }
+        for (total, component) in self.iter_mut().zip(reward.iter()) {
+            *total += scale * component;
+        }
+    }
+
+    fn add_from_mover(&mut self, reward: &Self, _mode: PlayerMode, _mover: usize) {
+        self.add_scaled(reward, 1.0);
+    }
+
+    fn into_root_view(self, _mode: PlayerMode, _player: usize) -> Self {
+        self
+    }
+
+    fn value_for(&self, _mode: PlayerMode, player: usize) -> f64 {
+        self.get(player).copied().unwrap_or(0.0)
+    }
+
+    fn add_value_estimate(&mut self, _value: f64, _scale: f64) -> Result<(), TreeError> {
+        // A scalar bootstrap can't be split across players; MaxN runs reject it up front too.
+        Err(TreeError::MaxNBootstrapUnsupported)
+    }
+
+    fn mover_value(&self, mover: usize) -> f64 {
+        self.get(mover).copied().unwrap_or(0.0)
+    }
+
+    fn rollout_cache<P>(tree: &mut Tree<P>) -> &mut RolloutCache<Self> {
+        tree.player_rollout_cache_mut()
+    }
+
+    fn record_on(&self, edge: &mut EdgeMut<'_>, mode: PlayerMode, player: usize) {
+        edge.record(self.value_for(mode, player));
+        edge.record_player_values(self);
+    }
+}
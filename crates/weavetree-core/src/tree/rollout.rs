@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use crate::tree::{
     error::TreeError,
     ids::{ActionId, StateKey},
+    reward::Reward,
+    seed::{GOLDEN_GAMMA, mix64},
 };
 
 /// Controls how rollout rewards are aggregated into a return.
@@ -16,6 +18,175 @@ pub enum ReturnType {
     Discounted,
     /// Sum rewards but clamp to a strict fixed step horizon.
     FixedHorizon,
+    /// Sum discounted rewards for at most `bootstrap_steps` steps, then add the
+    /// discounted value estimate of the state where the rollout stopped.
+    NStepBootstrap,
+}
+
+/// Which default policy picks rollout actions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutPolicyKind {
+    /// Ask the `rollout_policy` closure passed to the run.
+    #[default]
+    Custom,
+    /// Always pick the lowest-numbered legal action.
+    FirstAction,
+    /// Pick uniformly among the legal actions.
+    UniformRandom {
+        #[serde(default)]
+        seed: u64,
+    },
+    /// Pick uniformly among the legal actions with probability `eps`, otherwise ask the
+    /// `rollout_policy` closure.
+    EpsilonGreedy {
+        eps: f64,
+        #[serde(default)]
+        seed: u64,
+    },
+}
+
+impl RolloutPolicyKind {
+    /// Return the seed of the random stream this policy draws from, if any.
+    pub fn seed(&self) -> Option<u64> {
+        match self {
+            RolloutPolicyKind::UniformRandom { seed }
+            | RolloutPolicyKind::EpsilonGreedy { seed, .. } => Some(*seed),
+            RolloutPolicyKind::Custom | RolloutPolicyKind::FirstAction => None,
+        }
+    }
+
+    /// Return this policy drawing from `seed` instead; unseeded policies are returned unchanged.
+    pub fn with_seed(self, seed: u64) -> Self {
+        match self {
+            RolloutPolicyKind::UniformRandom { .. } => RolloutPolicyKind::UniformRandom { seed },
+            RolloutPolicyKind::EpsilonGreedy { eps, .. } => {
+                RolloutPolicyKind::EpsilonGreedy { eps, seed }
+            }
+            RolloutPolicyKind::Custom | RolloutPolicyKind::FirstAction => self,
+        }
+    }
+
+    /// Pick one of the `legal` actions, deferring to `custom` where the policy needs it.
+    /// `legal` must not be empty.
+    pub(crate) fn choose<E>(
+        self,
+        rng: &mut RolloutRng,
+        legal: LegalActions<'_>,
+        custom: impl FnOnce() -> Result<ActionId, E>,
+    ) -> Result<ActionId, E> {
+        match self {
+            RolloutPolicyKind::Custom => custom(),
+            RolloutPolicyKind::FirstAction => Ok(legal.nth(0)),
+            RolloutPolicyKind::UniformRandom { .. } => Ok(legal.nth(rng.below(legal.len()))),
+            RolloutPolicyKind::EpsilonGreedy { eps, .. } => {
+                if rng.next_f64() < eps {
+                    Ok(legal.nth(rng.below(legal.len())))
+                } else {
+                    custom()
+                }
+            }
+        }
+    }
+}
+
+/// The actions a rollout may take in one state: every index below `num_actions`, minus
+/// the ones an action mask marks illegal. Indices keep their place in the dense action space.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LegalActions<'a> {
+    num_actions: usize,
+    mask: Option<&'a [bool]>,
+}
+
+impl<'a> LegalActions<'a> {
+    /// Every action below `num_actions` is legal.
+    pub(crate) fn all(num_actions: usize) -> Self {
+        LegalActions {
+            num_actions,
+            mask: None,
+        }
+    }
+
+    /// Only the actions whose `mask` entry is `true` are legal; `mask` holds one flag per action.
+    pub(crate) fn masked(mask: &'a [bool]) -> Self {
+        LegalActions {
+            num_actions: mask.len(),
+            mask: Some(mask),
+        }
+    }
+
+    /// Return the size of the dense action space, legal or not.
+    pub(crate) fn num_actions(&self) -> usize {
+        self.num_actions
+    }
+
+    /// Return the number of legal actions.
+    pub(crate) fn len(&self) -> usize {
+        match self.mask {
+            Some(mask) => mask.iter().filter(|&&legal| legal).count(),
+            None => self.num_actions,
+        }
+    }
+
+    /// Return whether no action is legal.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return whether `action` is one of the legal actions.
+    pub(crate) fn contains(&self, action: ActionId) -> bool {
+        match self.mask {
+            Some(mask) => mask.get(action.index()).copied().unwrap_or(false),
+            None => action.index() < self.num_actions,
+        }
+    }
+
+    /// Return the `n`-th legal action in index order; `n` must be below `len()`.
+    fn nth(&self, n: usize) -> ActionId {
+        let index = match self.mask {
+            Some(mask) => mask
+                .iter()
+                .enumerate()
+                .filter(|(_, legal)| **legal)
+                .nth(n)
+                .map_or(0, |(index, _)| index),
+            None => n,
+        };
+        ActionId::from(index)
+    }
+}
+
+/// Small deterministic generator (SplitMix64) behind the random rollout policies.
+/// The tree keeps one stream per seed so consecutive runs continue it instead of repeating it.
+#[derive(Debug, Clone)]
+pub(crate) struct RolloutRng {
+    seed: u64,
+    state: u64,
+}
+
+impl RolloutRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        RolloutRng { seed, state: seed }
+    }
+
+    pub(crate) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        mix64(self.state)
+    }
+
+    /// Uniform float in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform index in `[0, n)`; `n` must be positive.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_f64() * n as f64) as usize
+    }
 }
 
 /// Rollout parameters controlling return shape and stopping criteria.
@@ -25,6 +196,10 @@ pub struct RolloutParams {
     pub gamma: f64,
     pub max_steps: usize,
     pub fixed_horizon_steps: usize,
+    /// Steps simulated before bootstrapping in `ReturnType::NStepBootstrap`.
+    pub bootstrap_steps: usize,
+    /// Flip the reward sign every ply, scoring the rollout for the player to move at its start.
+    pub alternate_sign: bool,
 }
 
 impl RolloutParams {
@@ -32,6 +207,7 @@ impl RolloutParams {
     pub fn step_limit(&self) -> usize {
         match self.return_type {
             ReturnType::FixedHorizon => self.max_steps.min(self.fixed_horizon_steps),
+            ReturnType::NStepBootstrap => self.max_steps.min(self.bootstrap_steps),
             ReturnType::Discounted | ReturnType::EpisodicUndiscounted => self.max_steps,
         }
     }
@@ -68,9 +244,9 @@ where
 /// Fallible rollout variant where environment/policy callbacks may fail.
 pub fn rollout_fallible<FNum, FStep, FPolicy, E>(
     start_state_key: StateKey,
-    mut num_actions: FNum,
-    mut step: FStep,
-    mut rollout_policy: FPolicy,
+    num_actions: FNum,
+    step: FStep,
+    rollout_policy: FPolicy,
     params: RolloutParams,
 ) -> Result<f64, E>
 where
@@ -78,19 +254,74 @@ where
     FStep: FnMut(StateKey, ActionId) -> Result<(StateKey, f64, bool), E>,
     FPolicy: FnMut(StateKey, usize) -> Result<ActionId, E>,
     E: From<TreeError>,
+{
+    let mut rollout_policy = rollout_policy;
+    rollout_with(
+        start_state_key,
+        num_actions,
+        step,
+        |state, legal: LegalActions<'_>| rollout_policy(state, legal.num_actions),
+        |_| None,
+        |_| None,
+        params,
+    )
+}
+
+/// Rollout over any reward signal, scalar or per-player.
+/// `action_mask` and `value_bootstrap` return `None` when no such callback is configured.
+/// A state whose actions are all masked ends the rollout like a terminal state, and the
+/// policy is only offered the legal actions.
+pub(crate) fn rollout_with<FNum, FStep, FPolicy, FMask, FValue, E, R>(
+    start_state_key: StateKey,
+    mut num_actions: FNum,
+    mut step: FStep,
+    mut rollout_policy: FPolicy,
+    mut action_mask: FMask,
+    mut value_bootstrap: FValue,
+    params: RolloutParams,
+) -> Result<R, E>
+where
+    FNum: FnMut(StateKey) -> Result<usize, E>,
+    FStep: FnMut(StateKey, ActionId) -> Result<(StateKey, R, bool), E>,
+    FPolicy: FnMut(StateKey, LegalActions<'_>) -> Result<ActionId, E>,
+    FMask: FnMut(StateKey) -> Option<Vec<bool>>,
+    FValue: FnMut(StateKey) -> Option<f64>,
+    E: From<TreeError>,
+    R: Reward,
 {
     let mut state_key = start_state_key;
-    let mut total_return = 0.0;
+    let mut total_return = R::zero();
     let mut discount = 1.0;
+    let mut sign = 1.0;
+    let mut truncated = true;
 
     for _ in 0..params.step_limit() {
         let action_count = num_actions(state_key)?;
         if action_count == 0 {
+            truncated = false;
+            break;
+        }
+
+        let mask = action_mask(state_key);
+        let legal = match &mask {
+            Some(mask) if mask.len() != action_count => {
+                return Err(TreeError::InvalidRolloutActionMask {
+                    state_key,
+                    expected: action_count,
+                    actual: mask.len(),
+                }
+                .into());
+            }
+            Some(mask) => LegalActions::masked(mask),
+            None => LegalActions::all(action_count),
+        };
+        if legal.is_empty() {
+            truncated = false;
             break;
         }
 
-        let action_id = rollout_policy(state_key, action_count)?;
-        if action_id.index() >= action_count {
+        let action_id = rollout_policy(state_key, legal)?;
+        if !legal.contains(action_id) {
             return Err(TreeError::InvalidRolloutAction {
                 state_key,
                 action_id,
@@ -101,21 +332,33 @@ where
         let (next_state_key, reward, is_terminal) = step(state_key, action_id)?;
 
         match params.return_type {
-            ReturnType::Discounted => {
-                total_return += discount * reward;
+            ReturnType::Discounted | ReturnType::NStepBootstrap => {
+                total_return.add_scaled(&reward, sign * discount);
                 discount *= params.gamma;
             }
             ReturnType::EpisodicUndiscounted | ReturnType::FixedHorizon => {
-                total_return += reward;
+                total_return.add_scaled(&reward, sign);
             }
         }
 
+        if params.alternate_sign {
+            sign = -sign;
+        }
+
         state_key = next_state_key;
 
         if is_terminal {
+            truncated = false;
             break;
         }
     }
 
+    // Only a rollout cut off by the step limit has a future left to estimate.
+    if params.return_type == ReturnType::NStepBootstrap && truncated {
+        let value =
+            value_bootstrap(state_key).ok_or(TreeError::MissingValueBootstrap { state_key })?;
+        total_return.add_value_estimate(value, sign * discount)?;
+    }
+
     Ok(total_return)
 }
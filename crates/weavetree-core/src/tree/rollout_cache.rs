@@ -0,0 +1,81 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::tree::{ids::StateKey, reward::Reward};
+
+/// Running rollout statistics for one leaf state.
+#[derive(Debug, Clone)]
+struct CacheEntry<R> {
+    return_sum: R,
+    samples: usize,
+    last_used: u64,
+}
+
+/// Least-recently-used cache from leaf state to its mean rollout return.
+/// Returns are stored as produced by the rollout, i.e. from the leaf player's view.
+#[derive(Debug, Clone)]
+pub(crate) struct RolloutCache<R> {
+    entries: HashMap<StateKey, CacheEntry<R>>,
+    recency: BTreeMap<u64, StateKey>,
+    tick: u64,
+}
+
+impl<R: Reward> RolloutCache<R> {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        RolloutCache {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Return the amount of cached states.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return the mean cached return once `min_samples` rollouts were recorded for the state.
+    /// A hit also marks the state as most recently used.
+    pub fn lookup(&mut self, state_key: StateKey, min_samples: usize) -> Option<R> {
+        let entry = self.entries.get(&state_key)?;
+        if entry.samples < min_samples.max(1) {
+            return None;
+        }
+
+        let mut mean = R::zero();
+        mean.add_scaled(&entry.return_sum, 1.0 / entry.samples as f64);
+        self.touch(state_key);
+        Some(mean)
+    }
+
+    /// Record one rollout return for a state, evicting the least recently used states
+    /// until at most `capacity` remain.
+    pub fn record(&mut self, state_key: StateKey, rollout_return: &R, capacity: usize) {
+        let entry = self.entries.entry(state_key).or_insert_with(|| CacheEntry {
+            return_sum: R::zero(),
+            samples: 0,
+            last_used: 0,
+        });
+        entry.return_sum.add_scaled(rollout_return, 1.0);
+        entry.samples += 1;
+        self.touch(state_key);
+
+        while self.entries.len() > capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    /// Move a cached state to the most recently used position.
+    fn touch(&mut self, state_key: StateKey) {
+        let Some(entry) = self.entries.get_mut(&state_key) else {
+            return;
+        };
+        self.recency.remove(&entry.last_used);
+        self.tick += 1;
+        entry.last_used = self.tick;
+        self.recency.insert(self.tick, state_key);
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    fmt,
+    io::{self, BufWriter, Write},
+};
+
+use crate::tree::{
+    error::TreeError,
+    ids::{ActionId, StateKey},
+    mcts::{RunLogEvent, RunMetrics, SearchConfig},
+    search_tree::Tree,
+};
+
+/// Line format used by `RunLogger`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `key=value` text lines, see `RunLogEvent::to_text_line`.
+    #[default]
+    Text,
+    /// One JSON object per line, see `RunLogEvent::to_json_line`.
+    Jsonl,
+}
+
+/// Writes `RunLogEvent`s to a sink, one line per event.
+/// Writes are buffered; buffered lines are flushed when the logger is dropped.
+pub struct RunLogger<W: Write> {
+    writer: BufWriter<W>,
+    format: LogFormat,
+}
+
+impl<W: Write> RunLogger<W> {
+    /// Create a logger writing `format` lines into `sink`.
+    pub fn new(sink: W, format: LogFormat) -> Self {
+        RunLogger {
+            writer: BufWriter::new(sink),
+            format,
+        }
+    }
+
+    /// Write one event as a single line.
+    pub fn log(&mut self, event: &RunLogEvent) -> io::Result<()> {
+        let line = match self.format {
+            LogFormat::Text => event.to_text_line(),
+            LogFormat::Jsonl => event.to_json_line()?,
+        };
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Flush buffered lines to the sink.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Error type for `Tree::run_with_logger`.
+#[derive(Debug)]
+pub enum RunLogError {
+    Tree(TreeError),
+    Io(io::Error),
+}
+
+impl fmt::Display for RunLogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunLogError::Tree(err) => write!(f, "{err}"),
+            RunLogError::Io(err) => write!(f, "failed to write run log: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RunLogError {}
+
+impl From<TreeError> for RunLogError {
+    fn from(value: TreeError) -> Self {
+        RunLogError::Tree(value)
+    }
+}
+
+impl From<io::Error> for RunLogError {
+    fn from(value: io::Error) -> Self {
+        RunLogError::Io(value)
+    }
+}
+
+impl<P: Default> Tree<P> {
+    /// Run MCTS like `run`, writing `run_started`, `iteration_completed`, and `run_completed`
+    /// events to `sink`. After a failed write the search still finishes, but no further
+    /// events are written and the write error is returned.
+    pub fn run_with_logger<FNum, FStep, FPolicy, W>(
+        &mut self,
+        config: &SearchConfig,
+        num_actions: FNum,
+        step: FStep,
+        rollout_policy: FPolicy,
+        sink: W,
+        format: LogFormat,
+    ) -> Result<RunMetrics, RunLogError>
+    where
+        FNum: FnMut(StateKey) -> usize,
+        FStep: FnMut(StateKey, ActionId) -> (StateKey, f64, bool),
+        FPolicy: FnMut(StateKey, usize) -> ActionId,
+        W: Write,
+    {
+        let mut logger = RunLogger::new(sink, format);
+        logger.log(&RunLogEvent::run_started(config))?;
+
+        let mut write_result = Ok(());
+        let mut iteration = 0;
+        let metrics = self.run_with_hook(
+            config,
+            num_actions,
+            step,
+            rollout_policy,
+            |iteration_metrics| {
+                if write_result.is_ok() {
+                    write_result = logger.log(&RunLogEvent::iteration_completed(
+                        iteration,
+                        iteration_metrics,
+                    ));
+                }
+                iteration += 1;
+            },
+        )?;
+        write_result?;
+
+        logger.log(&RunLogEvent::run_completed(&metrics))?;
+        logger.flush()?;
+        Ok(metrics)
+    }
+}
@@ -1,35 +1,107 @@
 use crate::tree::{
     arena::Arena,
+    edges::EdgeRef,
     error::TreeError,
+    extensions::{SearchExtensions, StepEvent, StepPhase},
     ids::{ActionId, NodeId, StateKey},
+    mcts::{SearchConfig, ValueNormalization},
     node::Node,
+    reward::Reward,
+    rollout::RolloutRng,
+    rollout_cache::RolloutCache,
     snapshot::{ActionEdgeSnapshot, NodeSnapshot, OutcomeSnapshot, TreeSnapshot},
+    stats::ValueBounds,
 };
 
 #[derive(Debug, Clone)]
 /// Tree policy that keeps the core generic
 /// The tree doesn’t know the simulator type, only queries via closures.
-pub struct TreePolicyResult {
+pub struct TreePolicyResult<R = f64> {
     pub path: Vec<(NodeId, ActionId)>, // edges taken from root to leaf
     pub leaf: NodeId,                  // node where rollout should start (often newly created)
     pub leaf_is_new: bool,             // whether we just created this node
-    pub reward: f64,                   // reward accumulated along the selected path
+    pub reward: R,                     // path reward, seen from the root player
+    pub selection_capped: bool,        // whether `max_selection_depth` stopped the descent
+}
+
+/// Summary statistics for one root action, including uncertainty of its value estimate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootActionStat {
+    pub action_id: ActionId,
+    pub legal: bool,
+    pub visits: u64,
+    pub q: f64,
+    pub variance: f64,
+    pub stderr: f64,
+    /// Normal-approximation `q ± z * stderr` interval for the requested `z`.
+    pub confidence_interval: (f64, f64),
+    pub proven_value: Option<f64>,
+    /// Next states sampled for this action so far, with their child nodes and counts.
+    pub outcomes: Vec<OutcomeSnapshot>,
 }
 
 #[derive(Debug, Clone)]
 /// owns the arena (root is always at index 0)
 /// provides the tree search and operations
-pub struct Tree {
-    arena: Arena<Node>,
+/// Every node carries a user payload of type `P`; new nodes start from `P::default()`.
+pub struct Tree<P = ()> {
+    arena: Arena<Node<P>>,
+    value_bounds: ValueBounds,
+    rollout_cache: RolloutCache<f64>,
+    player_rollout_cache: RolloutCache<Vec<f64>>,
+    rollout_rng: RolloutRng,
 }
 
 impl Tree {
     /// Create a tree with a single root node.
     pub fn new(root_state_key: StateKey, root_is_terminal: bool) -> Self {
+        Tree::with_root_payload(root_state_key, root_is_terminal, ())
+    }
+
+    /// Create a tree whose root edges are warm-started from a previous search's root statistics.
+    /// Each stat seeds the edge at its `action_id` with its `(q, visits)` as pseudo-counts and
+    /// keeps its legality; actions without a stat start cold. The stats must describe the
+    /// action space of `root_state_key`, since the root is not re-expanded. Proven values are
+    /// not carried over. With no stats this is the same as `Tree::new(root_state_key, false)`.
+    pub fn with_root_priors(root_state_key: StateKey, stats: &[RootActionStat]) -> Self {
+        let mut tree = Tree::new(root_state_key, false);
+        let Some(num_actions) = stats.iter().map(|stat| stat.action_id.index() + 1).max() else {
+            return tree;
+        };
+
+        let mut priors = vec![(0.0, 0); num_actions];
+        let mut mask = vec![true; num_actions];
+        for stat in stats {
+            priors[stat.action_id.index()] = (stat.q, stat.visits);
+            mask[stat.action_id.index()] = stat.legal;
+        }
+
+        let root_id = tree.root_id();
+        if let Ok(root) = tree.node_mut(root_id) {
+            root.expand(num_actions, Some(&priors));
+            root.apply_action_mask(&mask);
+        }
+        tree
+    }
+}
+
+impl<P> Tree<P> {
+    /// Create a tree whose single root node carries `root_payload`.
+    pub fn with_root_payload(
+        root_state_key: StateKey,
+        root_is_terminal: bool,
+        root_payload: P,
+    ) -> Self {
         let mut arena = Arena::new();
-        let root = Node::new(root_state_key, 0, None, root_is_terminal);
+        let root = Node::new(root_state_key, 0, None, root_is_terminal, root_payload);
         let _ = arena.allocate(root);
-        Tree { arena }
+        Tree {
+            arena,
+            value_bounds: ValueBounds::new(),
+            rollout_cache: RolloutCache::new(),
+            player_rollout_cache: RolloutCache::new(),
+            rollout_rng: RolloutRng::new(0),
+        }
     }
 
     /// Return the root node id.
@@ -37,31 +109,175 @@ impl Tree {
         NodeId::from(0)
     }
 
+    /// Return the user payload attached to a node.
+    pub fn node_payload(&self, node_id: NodeId) -> Result<&P, TreeError> {
+        Ok(self.node(node_id)?.payload())
+    }
+
+    /// Return the user payload attached to a node as a mutable borrow.
+    pub fn node_payload_mut(&mut self, node_id: NodeId) -> Result<&mut P, TreeError> {
+        Ok(self.node_mut(node_id)?.payload_mut())
+    }
+
     /// Return how many nodes exist in the tree arena.
     pub fn node_count(&self) -> usize {
         self.arena.len()
     }
 
+    /// Pre-allocate room for `additional` more nodes, avoiding arena regrowth mid-search.
+    /// A good estimate is `config.iterations`, since every iteration adds at most one node.
+    pub fn reserve(&mut self, additional: usize) {
+        self.arena.reserve(additional);
+    }
+
+    /// Return how many more nodes fit before the arena has to grow.
+    pub fn node_capacity(&self) -> usize {
+        self.arena.capacity()
+    }
+
+    /// Return how many released arena slots are waiting to be reused by new nodes.
+    pub fn free_node_slots(&self) -> usize {
+        self.arena.free_count()
+    }
+
+    /// Remove `node_id` and all of its descendants, releasing their arena slots for reuse.
+    /// The parent edge forgets the outcome and keeps its statistics, so the outcome is
+    /// re-expanded if sampled again. Returns how many nodes were released.
+    pub fn prune_subtree(&mut self, node_id: NodeId) -> Result<usize, TreeError> {
+        if node_id == self.root_id() {
+            return Err(TreeError::CannotPruneRoot);
+        }
+
+        if let Some((parent_id, action_id)) = self.node(node_id)?.parent() {
+            let parent = self.node_mut(parent_id)?;
+            let mut edge = parent.edge_mut(action_id).ok_or(TreeError::MissingEdge {
+                node_id: parent_id,
+                action_id,
+            })?;
+            edge.remap_children(|child| (child != node_id).then_some(child));
+        }
+
+        let mut released = 0;
+        let mut stack = vec![node_id];
+        while let Some(current) = stack.pop() {
+            let node = self
+                .arena
+                .release(current)
+                .ok_or(TreeError::MissingNode { node_id: current })?;
+            for edge in node.edges() {
+                stack.extend(edge.outcomes_iter().map(|(_, child, _)| child));
+            }
+            released += 1;
+        }
+
+        Ok(released)
+    }
+
+    /// Return the `(min, max)` range of edge Q values observed during backpropagation.
+    pub fn value_bounds(&self) -> Option<(f64, f64)> {
+        self.value_bounds.range()
+    }
+
+    /// Widen the observed Q value range with a freshly backed-up value.
+    pub(crate) fn observe_value(&mut self, value: f64) {
+        self.value_bounds.update(value);
+    }
+
+    /// Return how many leaf states currently have cached rollout returns.
+    pub fn rollout_cache_len(&self) -> usize {
+        self.rollout_cache.len() + self.player_rollout_cache.len()
+    }
+
+    /// Drop all cached rollout returns, e.g. after the simulator changed.
+    pub fn clear_rollout_cache(&mut self) {
+        self.rollout_cache = RolloutCache::new();
+        self.player_rollout_cache = RolloutCache::new();
+    }
+
+    /// Return the scalar rollout cache.
+    pub(crate) fn rollout_cache_mut(&mut self) -> &mut RolloutCache<f64> {
+        &mut self.rollout_cache
+    }
+
+    /// Return the random stream for `seed`, restarting it if the seed changed since the last run.
+    pub(crate) fn rollout_rng_mut(&mut self, seed: u64) -> &mut RolloutRng {
+        if self.rollout_rng.seed() != seed {
+            self.rollout_rng = RolloutRng::new(seed);
+        }
+        &mut self.rollout_rng
+    }
+
+    /// Return the per-player (MaxN) rollout cache.
+    pub(crate) fn player_rollout_cache_mut(&mut self) -> &mut RolloutCache<Vec<f64>> {
+        &mut self.player_rollout_cache
+    }
+
+    /// Rebuild the arena with only the `keep` nodes, in that order, remapping every node id.
+    /// `keep` must start with the root and list parents before their children.
+    /// Outcomes leading to dropped nodes are forgotten; edge statistics are left untouched.
+    pub(crate) fn compact(&mut self, keep: &[NodeId]) {
+        let mut remap: Vec<Option<NodeId>> = vec![None; self.arena.slot_count()];
+        for (new_index, node_id) in keep.iter().enumerate() {
+            remap[node_id.index()] = Some(NodeId::from(new_index));
+        }
+
+        let mut old_nodes = std::mem::replace(&mut self.arena, Arena::new()).into_slots();
+
+        for node_id in keep {
+            let Some(mut node) = old_nodes[node_id.index()].take() else {
+                continue;
+            };
+            let parent = node
+                .parent()
+                .and_then(|(parent, action)| remap[parent.index()].map(|parent| (parent, action)));
+            node.set_parent(parent);
+            for edge in node.action_edges_mut() {
+                edge.remap_children(|child| remap[child.index()]);
+            }
+            let _ = self.arena.allocate(node);
+        }
+    }
+
     /// Return an immutable node handle.
-    pub(crate) fn node(&self, node_id: NodeId) -> Result<&Node, TreeError> {
+    pub(crate) fn node(&self, node_id: NodeId) -> Result<&Node<P>, TreeError> {
         self.arena
             .get(node_id)
             .ok_or(TreeError::MissingNode { node_id })
     }
 
+    /// Pick an edge of `node_id` by UCB with the exploration settings of `config`.
+    pub(crate) fn select_edge(
+        &self,
+        node_id: NodeId,
+        config: &SearchConfig,
+    ) -> Result<Option<ActionId>, TreeError> {
+        let bounds = match config.value_normalization {
+            ValueNormalization::None => None,
+            ValueNormalization::MinMax => Some(&self.value_bounds),
+        };
+        Ok(self
+            .node(node_id)?
+            .select_edge(config.c, bounds, config.novelty_bonus))
+    }
+
+    /// Iterate all live nodes with their ids, in arena order.
+    pub(crate) fn nodes(&self) -> impl Iterator<Item = (NodeId, &Node<P>)> + '_ {
+        self.arena.iter_with_ids()
+    }
+
     /// Return a mutable node handle.
-    pub(crate) fn node_mut(&mut self, node_id: NodeId) -> Result<&mut Node, TreeError> {
+    pub(crate) fn node_mut(&mut self, node_id: NodeId) -> Result<&mut Node<P>, TreeError> {
         self.arena
             .get_mut(node_id)
             .ok_or(TreeError::MissingNode { node_id })
     }
 
-    /// Pick the root action with the highest visit count.
+    /// Pick the legal root action with the highest visit count.
+    /// Solver proofs take precedence: proven wins are picked first and proven losses avoided.
     pub fn best_root_action_by_visits(&self) -> Result<Option<ActionId>, TreeError> {
-        let root = self.node(self.root_id())?;
         let mut best: Option<(ActionId, u64)> = None;
 
-        for edge in root.edges() {
+        for edge in self.recommendable_root_edges()? {
             let candidate = (edge.action(), edge.visits());
             best = match best {
                 Some((best_action, best_visits))
@@ -78,12 +294,12 @@ impl Tree {
         Ok(best.map(|(action, _)| action))
     }
 
-    /// Pick the root action with the highest mean value estimate.
+    /// Pick the legal root action with the highest mean value estimate.
+    /// Solver proofs take precedence: proven wins are picked first and proven losses avoided.
     pub fn best_root_action_by_value(&self) -> Result<Option<ActionId>, TreeError> {
-        let root = self.node(self.root_id())?;
         let mut best: Option<(ActionId, f64)> = None;
 
-        for edge in root.edges() {
+        for edge in self.recommendable_root_edges()? {
             let candidate = (edge.action(), edge.q());
             best = match best {
                 Some((best_action, best_q))
@@ -99,6 +315,60 @@ impl Tree {
         Ok(best.map(|(action, _)| action))
     }
 
+    /// Return per-action statistics for the root, with `q ± z * stderr` confidence intervals.
+    /// Use e.g. `z = 1.96` for ~95% intervals; overlapping intervals suggest searching longer.
+    pub fn root_action_stats(&self, z: f64) -> Result<Vec<RootActionStat>, TreeError> {
+        let root = self.node(self.root_id())?;
+        Ok(root
+            .edges()
+            .map(|edge| RootActionStat {
+                action_id: edge.action(),
+                legal: edge.is_legal(),
+                visits: edge.visits(),
+                q: edge.q(),
+                variance: edge.variance(),
+                stderr: edge.stderr(),
+                confidence_interval: edge.confidence_interval(z),
+                proven_value: edge.proven_value(),
+                outcomes: edge
+                    .outcomes_iter()
+                    .map(|(next_state_key, child_node_id, count)| OutcomeSnapshot {
+                        next_state_key: next_state_key.raw(),
+                        child_node_id: child_node_id.index(),
+                        count,
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+
+    /// Legal root edges a recommendation may pick from.
+    /// A proven win (positive proven value) beats everything else and proven losses are
+    /// dropped unless nothing else is left; without solver proofs this is every legal edge.
+    fn recommendable_root_edges(&self) -> Result<Vec<EdgeRef<'_>>, TreeError> {
+        let root = self.node(self.root_id())?;
+        let legal: Vec<EdgeRef<'_>> = root.edges().filter(|edge| edge.is_legal()).collect();
+
+        let mut best_win: Option<EdgeRef<'_>> = None;
+        for edge in legal.iter().copied() {
+            if edge.proven_value().is_some_and(|value| value > 0.0)
+                && best_win.is_none_or(|best| edge.proven_value() > best.proven_value())
+            {
+                best_win = Some(edge);
+            }
+        }
+        if let Some(edge) = best_win {
+            return Ok(vec![edge]);
+        }
+
+        let not_lost: Vec<EdgeRef<'_>> = legal
+            .iter()
+            .copied()
+            .filter(|edge| !edge.proven_value().is_some_and(|value| value < 0.0))
+            .collect();
+        Ok(if not_lost.is_empty() { legal } else { not_lost })
+    }
+
     pub fn tree_policy<FNum, FStep>(
         &mut self,
         c: f64,
@@ -108,6 +378,7 @@ impl Tree {
     where
         FNum: FnMut(StateKey) -> usize,
         FStep: FnMut(StateKey, ActionId) -> (StateKey, f64, bool),
+        P: Default,
     {
         self.tree_policy_fallible(
             c,
@@ -120,22 +391,66 @@ impl Tree {
     pub fn tree_policy_fallible<FNum, FStep, E>(
         &mut self,
         c: f64,
-        mut num_actions: FNum,
-        mut step: FStep,
+        num_actions: FNum,
+        step: FStep,
     ) -> Result<TreePolicyResult, E>
     where
         FNum: FnMut(StateKey) -> Result<usize, E>,
         FStep: FnMut(StateKey, ActionId) -> Result<(StateKey, f64, bool), E>,
         E: From<TreeError>,
+        P: Default,
+    {
+        let config = SearchConfig {
+            c,
+            ..SearchConfig::default()
+        };
+        self.tree_policy_with_extensions(
+            &config,
+            num_actions,
+            step,
+            &mut SearchExtensions::default(),
+        )
+    }
+
+    /// Fallible tree policy that also applies the optional search extensions on expansion.
+    /// The reward type is either a scalar or a per-player vector.
+    pub(crate) fn tree_policy_with_extensions<FNum, FStep, E, R>(
+        &mut self,
+        config: &SearchConfig,
+        mut num_actions: FNum,
+        mut step: FStep,
+        extensions: &mut SearchExtensions<'_>,
+    ) -> Result<TreePolicyResult<R>, E>
+    where
+        FNum: FnMut(StateKey) -> Result<usize, E>,
+        FStep: FnMut(StateKey, ActionId) -> Result<(StateKey, R, bool), E>,
+        E: From<TreeError>,
+        R: Reward,
+        P: Default,
     {
         let mut current = self.root_id();
         let mut path: Vec<(NodeId, ActionId)> = Vec::new();
-        let mut reward: f64 = 0.0;
+        let mut reward = R::zero();
+
+        // The root is created without a callback, so resolve its player before first expansion.
+        {
+            let root = self.node_mut(current)?;
+            if !root.is_expanded()
+                && let Some(player) = extensions.player_to_move(root.state_key())
+            {
+                root.set_player(player);
+            }
+        }
 
         loop {
-            let (state_key, depth, is_terminal) = {
+            let (state_key, depth, is_terminal, player) = {
                 let node = self.node(current)?;
-                (node.state_key(), node.depth(), node.is_terminal())
+                (
+                    node.state_key(),
+                    node.depth(),
+                    node.is_terminal(),
+                    node.player(),
+                )
             };
 
             if is_terminal {
@@ -144,6 +459,35 @@ impl Tree {
                     leaf: current,
                     leaf_is_new: false,
                     reward,
+                    selection_capped: false,
+                });
+            }
+
+            // Long self-loop chains can make a single descent arbitrarily deep; stop and roll out here
+            if config
+                .max_selection_depth
+                .is_some_and(|max_depth| path.len() >= max_depth)
+            {
+                return Ok(TreePolicyResult {
+                    path,
+                    leaf: current,
+                    leaf_is_new: false,
+                    reward,
+                    selection_capped: true,
+                });
+            }
+
+            // Nodes at the depth limit are never expanded; the rollout evaluates them instead
+            if config
+                .max_tree_depth
+                .is_some_and(|max_depth| depth >= max_depth)
+            {
+                return Ok(TreePolicyResult {
+                    path,
+                    leaf: current,
+                    leaf_is_new: false,
+                    reward,
+                    selection_capped: false,
                 });
             }
 
@@ -155,35 +499,90 @@ impl Tree {
 
                     // If no actions, treat as leaf/terminal-like stop
                     if n == 0 {
+                        if config.solver {
+                            node.set_proven_value(0.0);
+                        }
                         return Ok(TreePolicyResult {
                             path,
                             leaf: current,
                             leaf_is_new: false,
                             reward,
+                            selection_capped: false,
                         });
                     }
 
-                    node.expand(n);
+                    let priors = extensions.edge_priors(state_key, n);
+                    if let Some(priors) = &priors
+                        && priors.len() != n
+                    {
+                        return Err(TreeError::InvalidEdgePriors {
+                            node_id: current,
+                            expected: n,
+                            actual: priors.len(),
+                        }
+                        .into());
+                    }
+                    node.expand(n, priors.as_deref());
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(
+                        node_id = current.index(),
+                        state_key = state_key.raw(),
+                        depth,
+                        num_actions = n,
+                        "expanded node"
+                    );
+
+                    if let Some(mask) = extensions.action_mask(state_key) {
+                        if mask.len() != n {
+                            return Err(TreeError::InvalidActionMask {
+                                node_id: current,
+                                expected: n,
+                                actual: mask.len(),
+                            }
+                            .into());
+                        }
+                        node.apply_action_mask(&mask);
+                    }
+                }
+
+                // If every action is masked, treat as terminal: no rollout follows
+                if !node.has_legal_edges() {
+                    if config.solver {
+                        node.set_proven_value(0.0);
+                    }
+                    return Ok(TreePolicyResult {
+                        path,
+                        leaf: current,
+                        leaf_is_new: false,
+                        reward,
+                        selection_capped: false,
+                    });
                 }
             }
 
             // Pick action by UCB
-            let action = {
-                let node = self.node(current)?;
-                node.select_edge(c)
-                    .ok_or(TreeError::ActionSelectionFailed { node_id: current })?
-            };
+            let action = self
+                .select_edge(current, config)?
+                .ok_or(TreeError::ActionSelectionFailed { node_id: current })?;
 
             path.push((current, action));
 
             // Sample environment outcome (chance)
             let (next_key, r, next_terminal) = step(state_key, action)?;
-            reward += r;
+            extensions.observe_step(&StepEvent {
+                state_key,
+                action_id: action,
+                next_state_key: next_key,
+                rewards: r.as_slice(),
+                is_terminal: next_terminal,
+                phase: StepPhase::Selection,
+            });
+            reward.add_from_mover(&r, config.player_mode, player);
 
             // Update outcome counts / route to child
             let existing_child = {
                 let node = self.node_mut(current)?;
-                let edge = node.edge_mut(action).ok_or(TreeError::MissingEdge {
+                let mut edge = node.edge_mut(action).ok_or(TreeError::MissingEdge {
                     node_id: current,
                     action_id: action,
                 })?;
@@ -199,15 +598,25 @@ impl Tree {
 
             // New outcome: allocate child node
             let child_id = {
-                let child_node =
-                    Node::new(next_key, depth + 1, Some((current, action)), next_terminal);
+                let mut child_node = Node::new(
+                    next_key,
+                    depth + 1,
+                    Some((current, action)),
+                    next_terminal,
+                    P::default(),
+                );
+                let child_player = extensions
+                    .player_to_move(next_key)
+                    .unwrap_or_else(|| config.player_mode.player_at_depth(depth + 1));
+                child_node.set_player(child_player);
+                child_node.set_entry_reward(r.mover_value(player));
                 self.arena.allocate(child_node)
             };
 
             // Register new outcome (count starts at 1)
             {
                 let node = self.node_mut(current)?;
-                let edge = node.edge_mut(action).ok_or(TreeError::MissingEdge {
+                let mut edge = node.edge_mut(action).ok_or(TreeError::MissingEdge {
                     node_id: current,
                     action_id: action,
                 })?;
@@ -223,6 +632,7 @@ impl Tree {
                 leaf: child_id,
                 leaf_is_new: true,
                 reward,
+                selection_capped: false,
             });
         }
     }
@@ -231,7 +641,7 @@ impl Tree {
     pub fn snapshot(&self) -> TreeSnapshot {
         let mut nodes = Vec::with_capacity(self.arena.len());
 
-        for (node_idx, node) in self.arena.iter().enumerate() {
+        for (node_id, node) in self.arena.iter_with_ids() {
             let (parent_node_id, parent_action_id) = match node.parent() {
                 Some((p, a)) => (Some(p.index()), Some(a.index())),
                 None => (None, None),
@@ -239,16 +649,21 @@ impl Tree {
 
             let edges = node
                 .edges()
-                .iter()
                 .map(|edge| ActionEdgeSnapshot {
                     action_id: edge.action().index(),
+                    legal: edge.is_legal(),
                     visits: edge.visits(),
                     value_sum: edge.value_sum(),
                     q: edge.q(),
+                    variance: edge.variance(),
+                    stderr: edge.stderr(),
+                    player_q: edge.player_q(),
+                    proven_value: edge.proven_value(),
+                    label: None,
                     outcomes: edge
                         .outcomes_iter()
                         .map(|(next_state_key, child_node_id, count)| OutcomeSnapshot {
-                            next_state_key: next_state_key.value(),
+                            next_state_key: next_state_key.raw(),
                             child_node_id: child_node_id.index(),
                             count,
                         })
@@ -257,10 +672,13 @@ impl Tree {
                 .collect();
 
             nodes.push(NodeSnapshot {
-                node_id: node_idx,
-                state_key: node.state_key().value(),
+                node_id: node_id.index(),
+                state_key: node.state_key().raw(),
                 depth: node.depth(),
+                player: node.player(),
                 is_terminal: node.is_terminal(),
+                proven_value: node.proven_value(),
+                label: None,
                 parent_node_id,
                 parent_action_id,
                 edges,
@@ -1,5 +1,11 @@
+use std::fmt::Write;
+
 use serde::Serialize;
 
+#[cfg(feature = "wide-state-keys")]
+use crate::tree::ids::wide_key;
+use crate::tree::ids::{ActionId, StateKey, StateKeyValue};
+
 #[derive(Debug, Clone, Serialize)]
 pub struct TreeSnapshot {
     pub schema_version: u32,
@@ -8,12 +14,222 @@ pub struct TreeSnapshot {
     pub nodes: Vec<NodeSnapshot>,
 }
 
+impl TreeSnapshot {
+    /// Name the action of every edge with `label(state_key, action_id)`, e.g. a simulator's
+    /// action-label callback, so reports show actions instead of dense indices.
+    pub fn with_action_labels(
+        mut self,
+        mut label: impl FnMut(StateKey, ActionId) -> Option<String>,
+    ) -> Self {
+        for node in &mut self.nodes {
+            let state_key = StateKey::from_raw(node.state_key);
+            for edge in &mut node.edges {
+                edge.label = label(state_key, ActionId::from(edge.action_id));
+            }
+        }
+        self
+    }
+
+    /// Name the state of every node with `label(state_key)`, e.g. a model's state ids.
+    pub fn with_state_labels(mut self, mut label: impl FnMut(StateKey) -> Option<String>) -> Self {
+        for node in &mut self.nodes {
+            node.label = label(StateKey::from_raw(node.state_key));
+        }
+        self
+    }
+
+    /// Render the tree as a Graphviz DOT digraph, keeping only nodes up to `max_depth` below
+    /// the root when given.
+    ///
+    /// Nodes show their state label or key, visits, and depth; terminal nodes are drawn as
+    /// double boxes. Every sampled outcome becomes an arrow labelled with its action's label
+    /// or index, the action's visits and `q`, and how often the outcome was sampled.
+    pub fn to_dot(&self, max_depth: Option<u64>) -> String {
+        let root_depth = self
+            .nodes
+            .iter()
+            .find(|node| node.node_id == self.root_node_id)
+            .map_or(0, |node| node.depth);
+        let below_root = |node: &NodeSnapshot| node.depth.saturating_sub(root_depth);
+        let shown = |node: &&NodeSnapshot| max_depth.is_none_or(|max| below_root(node) <= max);
+
+        let mut dot = String::from("digraph tree {\n    node [shape=box];\n");
+        for node in self.nodes.iter().filter(shown) {
+            let name = node
+                .label
+                .clone()
+                .unwrap_or_else(|| format!("state {}", node.state_key));
+            let visits: u64 = node.edges.iter().map(|edge| edge.visits).sum();
+            let shape = if node.is_terminal {
+                ", peripheries=2"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\\nvisits {}, depth {}\"{}];",
+                node.node_id,
+                escape_dot(&name),
+                visits,
+                node.depth,
+                shape
+            );
+        }
+        // Children sit one level deeper, so only nodes above the cut get arrows.
+        for node in self
+            .nodes
+            .iter()
+            .filter(|node| max_depth.is_none_or(|max| below_root(node) < max))
+        {
+            for edge in &node.edges {
+                let action = edge
+                    .label
+                    .clone()
+                    .unwrap_or_else(|| edge.action_id.to_string());
+                for outcome in &edge.outcomes {
+                    let _ = writeln!(
+                        dot,
+                        "    n{} -> n{} [label=\"{}\\nn={} q={:.3}\\nsampled {}\"];",
+                        node.node_id,
+                        outcome.child_node_id,
+                        escape_dot(&action),
+                        edge.visits,
+                        edge.q,
+                        outcome.count
+                    );
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Serialize the tree as node-link JSON, the `{"nodes": [...], "links": [...]}` shape
+    /// that NetworkX's `node_link_graph` and d3 force layouts read directly.
+    ///
+    /// Every node has its node id as `id`, its state key, depth, player, terminal flag,
+    /// label, total `visits` over its edges, and `q`, the visit-weighted mean of its edge
+    /// values (`null` while unvisited). Every sampled outcome becomes a link from `source`
+    /// to `target` with the action's id, label, `visits`, and `q`, and the outcome's
+    /// sample `count`. The graph attributes name the `root`.
+    pub fn to_node_link_json(&self) -> Result<String, serde_json::Error> {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let legal = node.edges.iter().filter(|edge| edge.legal);
+                let visits: u64 = legal.clone().map(|edge| edge.visits).sum();
+                let value_sum: f64 = legal.map(|edge| edge.value_sum).sum();
+                NodeLinkNode {
+                    id: node.node_id,
+                    state_key: node.state_key,
+                    depth: node.depth,
+                    player: node.player,
+                    is_terminal: node.is_terminal,
+                    visits,
+                    q: (visits > 0).then(|| value_sum / visits as f64),
+                    proven_value: node.proven_value,
+                    label: node.label.as_deref(),
+                }
+            })
+            .collect();
+        let links = self
+            .nodes
+            .iter()
+            .flat_map(|node| {
+                node.edges.iter().flat_map(move |edge| {
+                    edge.outcomes.iter().map(move |outcome| NodeLinkLink {
+                        source: node.node_id,
+                        target: outcome.child_node_id,
+                        action_id: edge.action_id,
+                        label: edge.label.as_deref(),
+                        visits: edge.visits,
+                        q: edge.q,
+                        count: outcome.count,
+                    })
+                })
+            })
+            .collect();
+        serde_json::to_string(&NodeLinkGraph {
+            directed: true,
+            multigraph: false,
+            graph: NodeLinkAttributes {
+                root: self.root_node_id,
+            },
+            nodes,
+            links,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct NodeLinkGraph<'a> {
+    directed: bool,
+    multigraph: bool,
+    graph: NodeLinkAttributes,
+    nodes: Vec<NodeLinkNode<'a>>,
+    links: Vec<NodeLinkLink<'a>>,
+}
+
+#[derive(Serialize)]
+struct NodeLinkAttributes {
+    root: usize,
+}
+
+#[derive(Serialize)]
+struct NodeLinkNode<'a> {
+    id: usize,
+    #[cfg_attr(
+        feature = "wide-state-keys",
+        serde(serialize_with = "wide_key::serialize")
+    )]
+    state_key: StateKeyValue,
+    depth: u64,
+    player: usize,
+    is_terminal: bool,
+    visits: u64,
+    q: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proven_value: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct NodeLinkLink<'a> {
+    source: usize,
+    target: usize,
+    action_id: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    visits: u64,
+    q: f64,
+    count: u64,
+}
+
+/// Escape a string for a quoted DOT label.
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct NodeSnapshot {
     pub node_id: usize,
-    pub state_key: u64,
+    /// Written as a decimal string with `wide-state-keys`, like trace events.
+    #[cfg_attr(
+        feature = "wide-state-keys",
+        serde(serialize_with = "wide_key::serialize")
+    )]
+    pub state_key: StateKeyValue,
     pub depth: u64,
+    pub player: usize,
     pub is_terminal: bool,
+    /// Exact value for the node's player, only set by solver searches.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proven_value: Option<f64>,
+    /// Readable name of the state, only set by `TreeSnapshot::with_state_labels`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     pub parent_node_id: Option<usize>,
     pub parent_action_id: Option<usize>,
     pub edges: Vec<ActionEdgeSnapshot>,
@@ -22,15 +238,32 @@ pub struct NodeSnapshot {
 #[derive(Debug, Clone, Serialize)]
 pub struct ActionEdgeSnapshot {
     pub action_id: usize,
+    pub legal: bool,
     pub visits: u64,
     pub value_sum: f64,
     pub q: f64,
+    pub variance: f64,
+    pub stderr: f64,
+    /// Mean return per player, only populated by MaxN searches.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub player_q: Vec<f64>,
+    /// Exact value for the node's player, only set by solver searches.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proven_value: Option<f64>,
+    /// Readable name of the action, only set by `TreeSnapshot::with_action_labels`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     pub outcomes: Vec<OutcomeSnapshot>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct OutcomeSnapshot {
-    pub next_state_key: u64,
+    /// Written as a decimal string with `wide-state-keys`, like trace events.
+    #[cfg_attr(
+        feature = "wide-state-keys",
+        serde(serialize_with = "wide_key::serialize")
+    )]
+    pub next_state_key: StateKeyValue,
     pub child_node_id: usize,
     pub count: u64,
 }
@@ -0,0 +1,59 @@
+use crate::tree::{
+    error::TreeError,
+    ids::{ActionId, NodeId},
+    mcts::PlayerMode,
+    search_tree::Tree,
+};
+
+impl<P> Tree<P> {
+    /// MCTS-Solver backpropagation: push exact values from the leaf towards the root.
+    /// An edge is proven once its single observed child is proven (transitions are assumed deterministic),
+    /// and a node is proven once all of its legal edges are. Propagation stops at the first unproven link.
+    pub(crate) fn propagate_proofs(
+        &mut self,
+        path: &[(NodeId, ActionId)],
+        leaf: NodeId,
+        player_mode: PlayerMode,
+    ) -> Result<(), TreeError> {
+        let mut child_id = leaf;
+
+        for (node_id, action_id) in path.iter().rev() {
+            let (child_value, child_player, entry_reward) = {
+                let child = self.node(child_id)?;
+                let value = if child.is_terminal() {
+                    Some(0.0)
+                } else {
+                    child.proven_value()
+                };
+                match value {
+                    Some(value) => (value, child.player(), child.entry_reward()),
+                    None => break,
+                }
+            };
+
+            let node = self.node_mut(*node_id)?;
+            let player = node.player();
+            let mut edge = node.edge_mut(*action_id).ok_or(TreeError::MissingEdge {
+                node_id: *node_id,
+                action_id: *action_id,
+            })?;
+
+            // A second observed outcome means the action is stochastic and can't be proven.
+            if edge.outcomes_len() != 1 {
+                break;
+            }
+
+            // Child values are scored for the child's player; move them into this node's view.
+            let root_view = player_mode.value_for_player(child_player, child_value);
+            edge.set_proven_value(entry_reward + player_mode.value_for_player(player, root_view));
+
+            match node.proven_edges_value() {
+                Some(value) => node.set_proven_value(value),
+                None => break,
+            }
+            child_id = *node_id;
+        }
+
+        Ok(())
+    }
+}
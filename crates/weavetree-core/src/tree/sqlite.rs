@@ -0,0 +1,269 @@
+//! SQLite sink for runs, their iterations, and final root action statistics.
+//!
+//! Enabled by the `sqlite` feature. Every row is keyed by a caller-chosen run id, so one
+//! database can collect a whole sweep and be queried with SQL.
+
+use std::{io, path::Path};
+
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::tree::{
+    ids::{ActionId, StateKey},
+    mcts::{IterationMetrics, RunLogEvent, RunMetrics, SearchConfig},
+    run_log::RunLogError,
+    search_tree::{RootActionStat, Tree},
+};
+
+/// Tables created by `SqliteRunSink`. Run columns mirror the `run_started` and
+/// `run_completed` events, iteration columns the `iteration_completed` event, and root
+/// action columns `RootActionStat`.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    run_id TEXT PRIMARY KEY,
+    config_yaml TEXT NOT NULL,
+    iterations_requested INTEGER NOT NULL,
+    c REAL NOT NULL,
+    gamma REAL NOT NULL,
+    max_steps INTEGER NOT NULL,
+    return_type TEXT NOT NULL,
+    fixed_horizon_steps INTEGER NOT NULL,
+    iterations_completed INTEGER,
+    total_return_sum REAL,
+    average_total_return REAL,
+    total_seconds REAL,
+    selection_seconds REAL,
+    rollout_seconds REAL,
+    backprop_seconds REAL,
+    simulator_seconds REAL
+);
+CREATE TABLE IF NOT EXISTS iterations (
+    run_id TEXT NOT NULL REFERENCES runs (run_id),
+    iteration INTEGER NOT NULL,
+    leaf_node_id INTEGER NOT NULL,
+    leaf_is_new INTEGER NOT NULL,
+    path_len INTEGER NOT NULL,
+    reward_prefix REAL NOT NULL,
+    rollout_return REAL NOT NULL,
+    total_return REAL NOT NULL,
+    node_count INTEGER NOT NULL,
+    PRIMARY KEY (run_id, iteration)
+);
+CREATE TABLE IF NOT EXISTS root_actions (
+    run_id TEXT NOT NULL REFERENCES runs (run_id),
+    action_id INTEGER NOT NULL,
+    legal INTEGER NOT NULL,
+    visits INTEGER NOT NULL,
+    q REAL NOT NULL,
+    variance REAL NOT NULL,
+    stderr REAL NOT NULL,
+    proven_value REAL,
+    PRIMARY KEY (run_id, action_id)
+);
+";
+
+/// Writes runs into a SQLite database with `runs`, `iterations`, and `root_actions` tables.
+/// Opening a sink creates missing tables and leaves existing rows alone.
+pub struct SqliteRunSink {
+    connection: Connection,
+    record_iterations: bool,
+}
+
+impl SqliteRunSink {
+    /// Open or create the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Use an already open connection, e.g. `Connection::open_in_memory()`.
+    pub fn from_connection(connection: Connection) -> rusqlite::Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(SqliteRunSink {
+            connection,
+            record_iterations: true,
+        })
+    }
+
+    /// Skip `iteration_completed` rows, keeping only runs and root statistics.
+    /// Useful for large sweeps where per-iteration data would dominate the database.
+    pub fn without_iterations(mut self) -> Self {
+        self.record_iterations = false;
+        self
+    }
+
+    /// Return the underlying connection, for queries.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Return whether a run with `run_id` has been recorded.
+    pub fn contains_run(&self, run_id: &str) -> rusqlite::Result<bool> {
+        self.connection
+            .query_row("SELECT 1 FROM runs WHERE run_id = ?1", [run_id], |_| Ok(()))
+            .optional()
+            .map(|row| row.is_some())
+    }
+}
+
+impl<P: Default> Tree<P> {
+    /// Run MCTS like `run_with_logger`, recording the run under `run_id` in `sink`, followed
+    /// by `root_action_stats(z)` of the finished tree.
+    ///
+    /// The run is written in one transaction, so an interrupted or failed run leaves no
+    /// rows behind. Reusing a `run_id` fails before searching. Database errors are returned
+    /// as `RunLogError::Io`; as with `run_with_logger`, a failed iteration write still lets
+    /// the search finish.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_with_sqlite_sink<FNum, FStep, FPolicy>(
+        &mut self,
+        config: &SearchConfig,
+        num_actions: FNum,
+        step: FStep,
+        rollout_policy: FPolicy,
+        sink: &mut SqliteRunSink,
+        run_id: &str,
+        z: f64,
+    ) -> Result<RunMetrics, RunLogError>
+    where
+        FNum: FnMut(StateKey) -> usize,
+        FStep: FnMut(StateKey, ActionId) -> (StateKey, f64, bool),
+        FPolicy: FnMut(StateKey, usize) -> ActionId,
+    {
+        let record_iterations = sink.record_iterations;
+        let transaction = sink.connection.transaction().map_err(sqlite_err)?;
+        insert_run(&transaction, run_id, config)?;
+
+        let mut write_result = Ok(());
+        let mut iteration = 0;
+        let metrics = self.run_with_hook(
+            config,
+            num_actions,
+            step,
+            rollout_policy,
+            |iteration_metrics| {
+                if record_iterations && write_result.is_ok() {
+                    write_result =
+                        insert_iteration(&transaction, run_id, iteration, iteration_metrics);
+                }
+                iteration += 1;
+            },
+        )?;
+        write_result.map_err(sqlite_err)?;
+
+        complete_run(&transaction, run_id, &metrics)?;
+        insert_root_actions(&transaction, run_id, &self.root_action_stats(z)?)?;
+        transaction.commit().map_err(sqlite_err)?;
+        Ok(metrics)
+    }
+}
+
+fn sqlite_err(err: rusqlite::Error) -> RunLogError {
+    RunLogError::Io(io::Error::other(err))
+}
+
+fn insert_run(
+    connection: &Connection,
+    run_id: &str,
+    config: &SearchConfig,
+) -> Result<(), RunLogError> {
+    let config_yaml = config
+        .to_yaml_string()
+        .map_err(|err| RunLogError::Io(io::Error::other(err)))?;
+    connection
+        .execute(
+            "INSERT INTO runs (run_id, config_yaml, iterations_requested, c, gamma, max_steps, \
+             return_type, fixed_horizon_steps) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                run_id,
+                config_yaml,
+                config.iterations as i64,
+                config.c,
+                config.gamma,
+                config.max_steps as i64,
+                RunLogEvent::return_type_name(config.return_type),
+                config.fixed_horizon_steps as i64,
+            ],
+        )
+        .map_err(sqlite_err)?;
+    Ok(())
+}
+
+fn insert_iteration(
+    connection: &Connection,
+    run_id: &str,
+    iteration: usize,
+    metrics: &IterationMetrics,
+) -> rusqlite::Result<()> {
+    connection
+        .prepare_cached(
+            "INSERT INTO iterations (run_id, iteration, leaf_node_id, leaf_is_new, path_len, \
+             reward_prefix, rollout_return, total_return, node_count) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?
+        .execute(params![
+            run_id,
+            iteration as i64,
+            metrics.leaf.index() as i64,
+            metrics.leaf_is_new,
+            metrics.path_len as i64,
+            metrics.reward_prefix,
+            metrics.rollout_return,
+            metrics.total_return,
+            metrics.node_count as i64,
+        ])?;
+    Ok(())
+}
+
+fn complete_run(
+    connection: &Connection,
+    run_id: &str,
+    metrics: &RunMetrics,
+) -> Result<(), RunLogError> {
+    connection
+        .execute(
+            "UPDATE runs SET iterations_completed = ?2, total_return_sum = ?3, \
+             average_total_return = ?4, total_seconds = ?5, selection_seconds = ?6, \
+             rollout_seconds = ?7, backprop_seconds = ?8, simulator_seconds = ?9 \
+             WHERE run_id = ?1",
+            params![
+                run_id,
+                metrics.iterations_completed as i64,
+                metrics.total_return_sum,
+                metrics.average_total_return,
+                metrics.total_time.as_secs_f64(),
+                metrics.selection_time.as_secs_f64(),
+                metrics.rollout_time.as_secs_f64(),
+                metrics.backprop_time.as_secs_f64(),
+                metrics.simulator_time.as_secs_f64(),
+            ],
+        )
+        .map_err(sqlite_err)?;
+    Ok(())
+}
+
+fn insert_root_actions(
+    connection: &Connection,
+    run_id: &str,
+    stats: &[RootActionStat],
+) -> Result<(), RunLogError> {
+    let mut statement = connection
+        .prepare(
+            "INSERT INTO root_actions (run_id, action_id, legal, visits, q, variance, stderr, \
+             proven_value) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )
+        .map_err(sqlite_err)?;
+    for stat in stats {
+        statement
+            .execute(params![
+                run_id,
+                stat.action_id.index() as i64,
+                stat.legal,
+                stat.visits as i64,
+                stat.q,
+                stat.variance,
+                stat.stderr,
+                stat.proven_value,
+            ])
+            .map_err(sqlite_err)?;
+    }
+    Ok(())
+}
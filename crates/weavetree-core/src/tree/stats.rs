@@ -1,38 +1,80 @@
-/// TODO: Potential for memeory optimization to use u32 and f32 instead.
+/// Visit counter type. The `compact-stats` feature narrows it to `u32`.
+#[cfg(not(feature = "compact-stats"))]
+type Count = u64;
+#[cfg(feature = "compact-stats")]
+type Count = u32;
+
+/// Value accumulator type. The `compact-stats` feature narrows it to `f32`.
+#[cfg(not(feature = "compact-stats"))]
+type Value = f64;
+#[cfg(feature = "compact-stats")]
+type Value = f32;
+
 /// Stores the numbers MCTS updates constantly
+/// With the `compact-stats` feature the fields are stored as `u32`/`f32`, halving the
+/// per-edge footprint; the accessors still hand out `u64`/`f64`.
+/// The spread is tracked with Welford's update, as the sum of squared deviations from the
+/// running mean `value_sum / visits`, so the variance stays accurate in `f32` when returns
+/// sit far from 0.
 #[derive(Debug, Clone, Copy)]
 pub struct EdgeStats {
-    visits: u64,
-    value_sum: f64,
+    visits: Count,
+    value_sum: Value,
+    m2: Value,
 }
 
+// The casts are identities unless `compact-stats` narrows the storage types.
+#[allow(clippy::unnecessary_cast)]
 impl EdgeStats {
     pub fn new() -> Self {
         EdgeStats {
             visits: 0,
             value_sum: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    /// Create stats seeded with `visits` pseudo-counts whose mean value is `q`.
+    /// Used to warm-start edges from a heuristic or a previous search.
+    /// Pseudo-counts carry no spread, so they only pull the variance towards zero.
+    pub fn with_prior(q: f64, visits: u64) -> Self {
+        let visits = visits.min(Count::MAX as u64) as Count;
+        EdgeStats {
+            visits,
+            value_sum: (q * visits as f64) as Value,
+            m2: 0.0,
         }
     }
 
     /// Retrieve the amount of visits to a certain edge
     pub fn visits(&self) -> u64 {
-        self.visits
+        self.visits as u64
     }
 
     /// Increase the visit counter by 1.
     /// Typical during backpropagation.
     fn record_visit(&mut self) {
-        self.visits += 1;
+        self.visits = self.visits.saturating_add(1);
     }
 
     /// Retrieve the value sum of a certain edge.
     pub fn value_sum(&self) -> f64 {
-        self.value_sum
+        self.value_sum as f64
     }
 
-    /// Increase the value sum of an edge by a certain value.
+    /// Increase the value sum of an edge by a certain value and fold it into the squared
+    /// deviations. Expects the visit that carries it to be counted already.
     fn record_value(&mut self, rollout_return: f64) {
-        self.value_sum += rollout_return
+        let n = self.visits as f64;
+        let old_mean = if n > 1.0 {
+            self.value_sum() / (n - 1.0)
+        } else {
+            0.0
+        };
+        let delta = rollout_return - old_mean;
+        let mean = old_mean + delta / n;
+        self.value_sum += rollout_return as Value;
+        self.m2 += (delta * (rollout_return - mean)) as Value;
     }
 
     /// Function to be used for backpropagation.
@@ -52,7 +94,76 @@ impl EdgeStats {
         if self.is_unvisited() {
             0.0
         } else {
-            self.value_sum / self.visits as f64
+            self.value_sum() / self.visits as f64
+        }
+    }
+
+    /// Unbiased sample variance of the recorded returns.
+    /// Returns 0.0 until the edge has at least two visits.
+    pub fn variance(&self) -> f64 {
+        if self.visits < 2 {
+            return 0.0;
+        }
+        (self.m2 as f64 / (self.visits as f64 - 1.0)).max(0.0)
+    }
+
+    /// Standard error of the Q estimate.
+    pub fn stderr(&self) -> f64 {
+        if self.is_unvisited() {
+            0.0
+        } else {
+            (self.variance() / self.visits as f64).sqrt()
+        }
+    }
+
+    /// Normal-approximation confidence interval `q ± z * stderr`.
+    /// For example `z = 1.96` gives a ~95% interval.
+    pub fn confidence_interval(&self, z: f64) -> (f64, f64) {
+        let q = self.q();
+        let half_width = z * self.stderr();
+        (q - half_width, q + half_width)
+    }
+}
+
+/// Running minimum and maximum of the Q values observed during backpropagation.
+/// Used to rescale Q into `[0, 1]` before it is combined with the UCB exploration term.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueBounds {
+    min: f64,
+    max: f64,
+}
+
+impl ValueBounds {
+    /// Create empty bounds that have not observed any value yet.
+    pub fn new() -> Self {
+        ValueBounds {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Widen the bounds to include `value`.
+    pub fn update(&mut self, value: f64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Return `(min, max)` once at least one value has been observed.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        if self.min <= self.max {
+            Some((self.min, self.max))
+        } else {
+            None
+        }
+    }
+
+    /// Rescale `value` into `[0, 1]`.
+    /// Values are returned unchanged until the bounds span a non-empty interval.
+    pub fn normalize(&self, value: f64) -> f64 {
+        if self.max > self.min {
+            (value - self.min) / (self.max - self.min)
+        } else {
+            value
         }
     }
 }
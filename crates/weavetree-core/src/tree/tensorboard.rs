@@ -0,0 +1,210 @@
+//! TensorBoard event files with scalar summaries of a running search.
+//!
+//! Enabled by the `tensorboard` feature. Events are written as TFRecords of hand-encoded
+//! `tensorflow.Event` protos, which is all TensorBoard's scalar dashboard reads, so no
+//! protobuf or TensorFlow dependency is needed.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::tree::{
+    ids::{ActionId, StateKey},
+    mcts::{RunMetrics, SearchConfig},
+    run_log::RunLogError,
+    search_tree::Tree,
+    timing::Stopwatch,
+};
+
+/// Writes scalar summaries as a TensorBoard event file.
+/// Writes are buffered; `flush` makes them visible to a running TensorBoard.
+pub struct TensorBoardWriter<W: Write> {
+    writer: BufWriter<W>,
+}
+
+impl TensorBoardWriter<File> {
+    /// Create `log_dir` if needed and a new `events.out.tfevents.*` file in it.
+    /// Point `tensorboard --logdir` at `log_dir` or a parent directory.
+    pub fn create(log_dir: impl AsRef<Path>) -> io::Result<Self> {
+        let log_dir = log_dir.as_ref();
+        fs::create_dir_all(log_dir)?;
+        let name = format!(
+            "events.out.tfevents.{}.weavetree.{}",
+            wall_time() as u64,
+            std::process::id()
+        );
+        Self::new(File::create(log_dir.join(name))?)
+    }
+}
+
+impl<W: Write> TensorBoardWriter<W> {
+    /// Start an event file in `sink` by writing its file version record.
+    pub fn new(sink: W) -> io::Result<Self> {
+        let mut writer = TensorBoardWriter {
+            writer: BufWriter::new(sink),
+        };
+        let mut event = event_header(0);
+        put_bytes(&mut event, 3, b"brain.Event:2");
+        writer.write_record(&event)?;
+        Ok(writer)
+    }
+
+    /// Write one scalar under `tag` at `step`.
+    pub fn add_scalar(&mut self, tag: &str, value: f64, step: u64) -> io::Result<()> {
+        self.add_scalars(step, &[(tag, value)])
+    }
+
+    /// Write several scalars at `step` as one event.
+    /// TensorBoard stores scalars as `f32`, so values lose precision beyond that.
+    pub fn add_scalars(&mut self, step: u64, scalars: &[(&str, f64)]) -> io::Result<()> {
+        let mut summary = Vec::new();
+        for &(tag, value) in scalars {
+            let mut entry = Vec::new();
+            put_bytes(&mut entry, 1, tag.as_bytes());
+            put_key(&mut entry, 2, WIRE_FIXED32);
+            entry.extend_from_slice(&(value as f32).to_le_bytes());
+            put_bytes(&mut summary, 1, &entry);
+        }
+        let mut event = event_header(step);
+        put_bytes(&mut event, 5, &summary);
+        self.write_record(&event)
+    }
+
+    /// Flush buffered events to the sink.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Frame `data` as a TFRecord: length, masked CRC of the length, data, masked CRC of
+    /// the data.
+    fn write_record(&mut self, data: &[u8]) -> io::Result<()> {
+        let len = (data.len() as u64).to_le_bytes();
+        self.writer.write_all(&len)?;
+        self.writer.write_all(&masked_crc(&len).to_le_bytes())?;
+        self.writer.write_all(data)?;
+        self.writer.write_all(&masked_crc(data).to_le_bytes())
+    }
+}
+
+impl<P: Default> Tree<P> {
+    /// Run MCTS like `run`, writing scalar summaries to `writer` after every `every`-th
+    /// iteration (and after the last one), flushing each time so TensorBoard can follow
+    /// the search live. `every` is raised to at least one.
+    ///
+    /// The step of each summary is the number of completed iterations. Scalars:
+    /// `search/average_return`, the running `RunMetrics::average_total_return`;
+    /// `search/node_count`; and `search/best_root_q`, the mean value of
+    /// `best_root_action_by_value`, once the root has one. After a failed write the search
+    /// still finishes, but nothing more is written and the error is returned as
+    /// `RunLogError::Io`.
+    pub fn run_with_tensorboard<FNum, FStep, FPolicy, W>(
+        &mut self,
+        config: &SearchConfig,
+        mut num_actions: FNum,
+        mut step: FStep,
+        mut rollout_policy: FPolicy,
+        writer: &mut TensorBoardWriter<W>,
+        every: usize,
+    ) -> Result<RunMetrics, RunLogError>
+    where
+        FNum: FnMut(StateKey) -> usize,
+        FStep: FnMut(StateKey, ActionId) -> (StateKey, f64, bool),
+        FPolicy: FnMut(StateKey, usize) -> ActionId,
+        W: Write,
+    {
+        let every = every.max(1);
+        let started = Stopwatch::start();
+        let mut metrics = RunMetrics::new(config.iterations);
+        let mut write_result = Ok(());
+
+        for _ in 0..config.iterations {
+            let iteration_metrics =
+                self.iterate(config, &mut num_actions, &mut step, &mut rollout_policy)?;
+            metrics.record(iteration_metrics);
+
+            let completed = metrics.iterations_completed;
+            if write_result.is_ok()
+                && (completed.is_multiple_of(every) || completed == config.iterations)
+            {
+                write_result = self.write_scalars(writer, &metrics);
+            }
+        }
+
+        metrics.total_time = started.elapsed();
+        write_result?;
+        Ok(metrics)
+    }
+
+    fn write_scalars<W: Write>(
+        &self,
+        writer: &mut TensorBoardWriter<W>,
+        metrics: &RunMetrics,
+    ) -> Result<(), RunLogError> {
+        let mut scalars = vec![
+            ("search/average_return", metrics.average_total_return),
+            ("search/node_count", self.node_count() as f64),
+        ];
+        if let Some(best) = self.best_root_action_by_value()? {
+            let best_q = self
+                .root_action_stats(0.0)?
+                .into_iter()
+                .find(|stat| stat.action_id == best)
+                .map(|stat| stat.q);
+            if let Some(q) = best_q {
+                scalars.push(("search/best_root_q", q));
+            }
+        }
+        writer.add_scalars(metrics.iterations_completed as u64, &scalars)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+const WIRE_FIXED32: u8 = 5;
+
+/// Start an `Event` with its `wall_time` (field 1) and `step` (field 2).
+fn event_header(step: u64) -> Vec<u8> {
+    let mut event = Vec::new();
+    put_key(&mut event, 1, WIRE_FIXED64);
+    event.extend_from_slice(&wall_time().to_le_bytes());
+    put_key(&mut event, 2, WIRE_VARINT);
+    put_varint(&mut event, step);
+    event
+}
+
+fn wall_time() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64())
+        .unwrap_or_default()
+}
+
+fn put_key(buf: &mut Vec<u8>, field: u8, wire_type: u8) {
+    buf.push(field << 3 | wire_type);
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_bytes(buf: &mut Vec<u8>, field: u8, bytes: &[u8]) {
+    put_key(buf, field, WIRE_LEN);
+    put_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+/// CRC32-C as masked by the TFRecord format.
+fn masked_crc(data: &[u8]) -> u32 {
+    let crc = crc32c::crc32c(data);
+    crc.rotate_right(15).wrapping_add(0xa282_ead8)
+}
@@ -1,4 +1,5 @@
-use crate::{ActionId, ReturnType, SearchConfig, StateKey, Tree};
+use crate::tree::stats::EdgeStats;
+use crate::{ActionId, ReturnType, SearchConfig, StateKey, Tree, ValueNormalization};
 
 #[test]
 fn deterministic_iterations_backpropagate_visits() {
@@ -10,6 +11,7 @@ fn deterministic_iterations_backpropagate_visits() {
         max_steps: 8,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 8,
+        ..SearchConfig::default()
     };
 
     let mut num_actions = |state: StateKey| match state.value() {
@@ -54,6 +56,7 @@ fn stochastic_transitions_create_distinct_outcomes_and_count_occurrences() {
         max_steps: 4,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 4,
+        ..SearchConfig::default()
     };
 
     let mut sequence = vec![1_u64, 2_u64, 1_u64].into_iter();
@@ -86,6 +89,7 @@ fn stochastic_transitions_create_distinct_outcomes_and_count_occurrences() {
     assert_eq!(edge.outcomes_len(), 2);
     assert_eq!(edge.outcome_count_for(StateKey::from(1)), Some(2));
     assert_eq!(edge.outcome_count_for(StateKey::from(2)), Some(1));
+    assert_eq!(edge.unseen_outcome_mass(), 1.0 / 3.0);
     assert_eq!(tree.node_count(), 3);
 }
 
@@ -95,3 +99,91 @@ fn default_config_yaml_parses() {
     assert_eq!(config.return_type, ReturnType::Discounted);
     assert!(config.iterations > 0);
 }
+
+#[test]
+fn min_max_normalization_tracks_bounds_and_keeps_best_action() {
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 40,
+        c: 1.4,
+        value_normalization: ValueNormalization::MinMax,
+        ..SearchConfig::default()
+    };
+
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let step = |_state: StateKey, action: ActionId| {
+        let reward = if action.index() == 1 { 1_000.0 } else { 900.0 };
+        (StateKey::from(action.index() as u64 + 1), reward, true)
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+
+    tree.run(&config, num_actions, step, rollout_policy)
+        .expect("run should succeed");
+
+    assert_eq!(tree.value_bounds(), Some((900.0, 1_000.0)));
+
+    let root = tree.node(tree.root_id()).expect("root exists");
+    let worse = root.edge(ActionId::from(0)).expect("action 0 exists");
+    let better = root.edge(ActionId::from(1)).expect("action 1 exists");
+    assert!(better.visits() > worse.visits());
+}
+
+#[test]
+fn value_normalization_parses_from_yaml() {
+    let config = SearchConfig::from_yaml_str("value_normalization: min_max\n")
+        .expect("yaml with normalization should parse");
+    assert_eq!(config.value_normalization, ValueNormalization::MinMax);
+}
+
+#[test]
+fn edge_stats_track_sample_variance_and_interval() {
+    let mut stats = EdgeStats::new();
+    assert_eq!(stats.variance(), 0.0);
+    assert_eq!(stats.confidence_interval(1.96), (0.0, 0.0));
+
+    for value in [1.0, 2.0, 3.0, 4.0] {
+        stats.record(value);
+    }
+    // Sample variance of 1..=4 is 5/3.
+    assert!((stats.variance() - 5.0 / 3.0).abs() < 1e-12);
+    let stderr = (5.0_f64 / 3.0 / 4.0).sqrt();
+    assert!((stats.stderr() - stderr).abs() < 1e-12);
+
+    let (lower, upper) = stats.confidence_interval(2.0);
+    assert!((lower - (2.5 - 2.0 * stderr)).abs() < 1e-12);
+    assert!((upper - (2.5 + 2.0 * stderr)).abs() < 1e-12);
+
+    // Prior pseudo-counts carry no spread.
+    assert_eq!(EdgeStats::with_prior(3.0, 10).variance(), 0.0);
+}
+
+#[test]
+fn edge_stats_variance_survives_large_offsets() {
+    // Squared sums of returns near 1e6 lose every digit of the spread in `f32`;
+    // the running update only squares the deviations.
+    let mut stats = EdgeStats::new();
+    for value in [1.0, 2.0, 3.0, 4.0] {
+        stats.record(1e6 + value);
+    }
+    assert!((stats.q() - (1e6 + 2.5)).abs() < 1e-3);
+    assert!((stats.variance() - 5.0 / 3.0).abs() < 1e-3);
+}
+
+#[test]
+fn edge_stats_storage_width_follows_compact_feature() {
+    let expected = if cfg!(feature = "compact-stats") {
+        12
+    } else {
+        24
+    };
+    assert_eq!(std::mem::size_of::<EdgeStats>(), expected);
+
+    let mut stats = EdgeStats::with_prior(0.5, u64::MAX);
+    stats.record(0.5);
+    let max_visits = if cfg!(feature = "compact-stats") {
+        u32::MAX as u64
+    } else {
+        u64::MAX
+    };
+    assert_eq!(stats.visits(), max_visits);
+}
@@ -10,6 +10,7 @@ fn terminal_root_iteration_has_empty_path_and_zero_return() {
         max_steps: 8,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 8,
+        ..SearchConfig::default()
     };
 
     let mut num_actions = |_state: StateKey| 0;
@@ -34,6 +35,7 @@ fn zero_action_state_stops_safely() {
         max_steps: 8,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 8,
+        ..SearchConfig::default()
     };
 
     let mut num_actions = |_state: StateKey| 0;
@@ -64,6 +66,7 @@ fn invalid_rollout_policy_action_index_returns_error() {
         max_steps: 8,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 8,
+        ..SearchConfig::default()
     };
 
     let mut num_actions = |_state: StateKey| 1;
@@ -93,6 +96,7 @@ fn run_log_event_jsonl_contains_event_tag() {
         max_steps: 8,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 8,
+        ..SearchConfig::default()
     };
 
     let event = RunLogEvent::run_started(&config);
@@ -112,6 +116,7 @@ fn tree_snapshot_exports_nodes_edges_and_outcomes() {
         max_steps: 4,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 4,
+        ..SearchConfig::default()
     };
 
     let mut num_actions = |_state: StateKey| 1;
@@ -143,3 +148,89 @@ fn tree_snapshot_exports_nodes_edges_and_outcomes() {
         .expect("snapshot json serialization should succeed");
     assert!(json.contains("\"schema_version\": 1"));
 }
+
+#[cfg(feature = "tracing")]
+#[test]
+fn tracing_feature_emits_run_spans_and_search_events() {
+    use std::sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    };
+
+    use tracing::{
+        Event, Metadata,
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+    };
+
+    /// Collects span names and event messages.
+    #[derive(Default)]
+    struct Collector {
+        next_id: AtomicU64,
+        names: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct MessageVisitor<'a>(&'a mut Vec<String>);
+
+    impl Visit for MessageVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0.push(format!("{value:?}"));
+            }
+        }
+    }
+
+    impl tracing::Subscriber for Collector {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            self.names
+                .lock()
+                .unwrap()
+                .push(span.metadata().name().to_string());
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut MessageVisitor(&mut self.names.lock().unwrap()));
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    let collector = Collector::default();
+    let names = Arc::clone(&collector.names);
+    let config = SearchConfig {
+        iterations: 2,
+        ..SearchConfig::default()
+    };
+
+    tracing::subscriber::with_default(collector, || {
+        let mut tree = Tree::new(StateKey::from(0), false);
+        tree.run(
+            &config,
+            |state| if state.value() == 0 { 2 } else { 0 },
+            |_state, action| (StateKey::from(action.index() as u64 + 1), 1.0, false),
+            |_state, _n| ActionId::from(0),
+        )
+        .expect("run should succeed");
+    });
+
+    let names = names.lock().unwrap();
+    let count = |name: &str| names.iter().filter(|seen| *seen == name).count();
+    assert_eq!(count("weavetree.run"), 1);
+    assert_eq!(count("weavetree.iteration"), 2);
+    assert_eq!(count("expanded node"), 1);
+    assert_eq!(count("rollout"), 2);
+    assert_eq!(count("backpropagated"), 2);
+    assert_eq!(count("iteration_completed"), 2);
+    assert_eq!(count("run_completed"), 1);
+}
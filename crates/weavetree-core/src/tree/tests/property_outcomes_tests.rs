@@ -35,3 +35,37 @@ proptest! {
         }
     }
 }
+
+proptest! {
+    #[test]
+    fn outcome_set_index_agrees_with_linear_scan(
+        sequence in proptest::collection::vec(0u8..64u8, 1..256),
+        dropped in 0u8..64u8,
+    ) {
+        let mut set = OutcomeSet::new();
+        let mut children: HashMap<u64, (NodeId, u64)> = HashMap::new();
+
+        for (idx, raw_key) in sequence.iter().copied().enumerate() {
+            let state_key = StateKey::from(raw_key as u64);
+            if set.increment_outcome(state_key).is_none() {
+                prop_assert_eq!(set.insert_outcome(state_key, NodeId::from(idx)), Some(NodeId::from(idx)));
+            }
+            let entry = children.entry(raw_key as u64).or_insert((NodeId::from(idx), 0));
+            entry.1 += 1;
+        }
+
+        prop_assert_eq!(set.is_indexed(), set.len() > 8);
+
+        // Dropping one outcome must keep lookups of the others intact.
+        let dropped_child = children.remove(&(dropped as u64)).map(|(child, _)| child);
+        set.remap_children(|child| (Some(child) != dropped_child).then_some(child));
+        prop_assert_eq!(set.len(), children.len());
+        prop_assert_eq!(set.get_child_for(StateKey::from(dropped as u64)), None);
+
+        for (raw_key, (child, count)) in children {
+            let state_key = StateKey::from(raw_key);
+            prop_assert_eq!(set.get_child_for(state_key), Some(child));
+            prop_assert_eq!(set.count_for(state_key), Some(count));
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use crate::tree::{
+    error::TreeError,
     ids::{ActionId, StateKey},
-    rollout::{ReturnType, RolloutParams, rollout},
+    rollout::{
+        LegalActions, ReturnType, RolloutParams, RolloutPolicyKind, RolloutRng, rollout,
+        rollout_with,
+    },
+    rollout_cache::RolloutCache,
 };
 
 #[test]
@@ -26,6 +31,8 @@ fn return_modes_are_applied_correctly() {
             gamma: 0.5,
             max_steps: 8,
             fixed_horizon_steps: 8,
+            bootstrap_steps: 8,
+            alternate_sign: false,
         },
     )
     .expect("discounted rollout should succeed");
@@ -41,6 +48,8 @@ fn return_modes_are_applied_correctly() {
             gamma: 0.5,
             max_steps: 8,
             fixed_horizon_steps: 8,
+            bootstrap_steps: 8,
+            alternate_sign: false,
         },
     )
     .expect("episodic rollout should succeed");
@@ -56,8 +65,153 @@ fn return_modes_are_applied_correctly() {
             gamma: 0.5,
             max_steps: 8,
             fixed_horizon_steps: 1,
+            bootstrap_steps: 8,
+            alternate_sign: false,
         },
     )
     .expect("fixed horizon rollout should succeed");
     assert!((fixed_horizon - 2.0).abs() < f64::EPSILON);
 }
+
+#[test]
+fn alternate_sign_scores_rollout_for_starting_player() {
+    let num_actions = |state: StateKey| match state.value() {
+        0 | 1 => 1,
+        _ => 0,
+    };
+    let step = |state: StateKey, _action: ActionId| match state.value() {
+        0 => (StateKey::from(1), 2.0, false),
+        1 => (StateKey::from(2), 4.0, true),
+        _ => (state, 0.0, true),
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+
+    let alternating = rollout(
+        StateKey::from(0),
+        num_actions,
+        step,
+        rollout_policy,
+        RolloutParams {
+            return_type: ReturnType::EpisodicUndiscounted,
+            gamma: 1.0,
+            max_steps: 8,
+            fixed_horizon_steps: 8,
+            bootstrap_steps: 8,
+            alternate_sign: true,
+        },
+    )
+    .expect("alternating rollout should succeed");
+    assert!((alternating - (-2.0)).abs() < f64::EPSILON);
+}
+
+#[test]
+fn n_step_bootstrap_adds_discounted_value_estimate() {
+    // An endless corridor paying 1.0 per step.
+    let num_actions = |_state: StateKey| 1;
+    let step = |state: StateKey, _action: ActionId| {
+        Ok::<_, TreeError>((StateKey::from(state.value() + 1), 1.0, false))
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| Ok(ActionId::from(0));
+    let params = RolloutParams {
+        return_type: ReturnType::NStepBootstrap,
+        gamma: 0.5,
+        max_steps: 8,
+        fixed_horizon_steps: 8,
+        bootstrap_steps: 2,
+        alternate_sign: false,
+    };
+
+    let mut bootstrapped_at = None;
+    let bootstrapped: f64 = rollout_with(
+        StateKey::from(0),
+        |state| Ok(num_actions(state)),
+        step,
+        |state, legal: LegalActions<'_>| rollout_policy(state, legal.num_actions()),
+        |_| None,
+        |state| {
+            bootstrapped_at = Some(state);
+            Some(10.0)
+        },
+        params,
+    )
+    .expect("bootstrapped rollout should succeed");
+    // 1.0 + 0.5 * 1.0 + 0.25 * 10.0
+    assert!((bootstrapped - 4.0).abs() < f64::EPSILON);
+    assert_eq!(bootstrapped_at, Some(StateKey::from(2)));
+
+    let missing = rollout(
+        StateKey::from(0),
+        num_actions,
+        |state, _action| (StateKey::from(state.value() + 1), 1.0, false),
+        |_state, _num_actions| ActionId::from(0),
+        params,
+    );
+    assert!(matches!(
+        missing,
+        Err(TreeError::MissingValueBootstrap { state_key }) if state_key == StateKey::from(2)
+    ));
+}
+
+#[test]
+fn n_step_bootstrap_of_per_player_rewards_is_an_error() {
+    let params = RolloutParams {
+        return_type: ReturnType::NStepBootstrap,
+        gamma: 0.5,
+        max_steps: 8,
+        fixed_horizon_steps: 8,
+        bootstrap_steps: 2,
+        alternate_sign: false,
+    };
+    let result: Result<Vec<f64>, TreeError> = rollout_with(
+        StateKey::from(0),
+        |_state| Ok(1),
+        |state, _action| Ok((StateKey::from(state.value() + 1), vec![1.0, 0.0], false)),
+        |_state, _legal: LegalActions<'_>| Ok(ActionId::from(0)),
+        |_| None,
+        |_| Some(10.0),
+        params,
+    );
+    assert_eq!(result, Err(TreeError::MaxNBootstrapUnsupported));
+}
+
+#[test]
+fn rollout_cache_trusts_mean_after_min_samples_and_evicts_lru() {
+    let mut cache: RolloutCache<f64> = RolloutCache::new();
+    let (a, b, c) = (StateKey::from(1), StateKey::from(2), StateKey::from(3));
+
+    cache.record(a, &1.0, 2);
+    assert_eq!(cache.lookup(a, 2), None);
+    cache.record(a, &3.0, 2);
+    assert_eq!(cache.lookup(a, 2), Some(2.0));
+
+    // `a` was used most recently, so inserting `c` evicts `b`.
+    cache.record(b, &5.0, 2);
+    assert_eq!(cache.lookup(a, 1), Some(2.0));
+    cache.record(c, &7.0, 2);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.lookup(b, 1), None);
+    assert_eq!(cache.lookup(c, 1), Some(7.0));
+}
+
+#[test]
+fn built_in_rollout_policies_choose_only_legal_actions() {
+    let mask = [false, false, true, false, true];
+    let legal = LegalActions::masked(&mask);
+    let mut rng = RolloutRng::new(11);
+    let no_custom = || Err::<ActionId, TreeError>(TreeError::MissingRolloutPolicy);
+
+    let first = RolloutPolicyKind::FirstAction
+        .choose(&mut rng, legal, no_custom)
+        .expect("first action needs no closure");
+    assert_eq!(first.index(), 2);
+
+    let mut seen = [0; 5];
+    for _ in 0..200 {
+        let action = RolloutPolicyKind::UniformRandom { seed: 11 }
+            .choose(&mut rng, legal, no_custom)
+            .expect("uniform random needs no closure");
+        seen[action.index()] += 1;
+    }
+    assert_eq!(seen[0] + seen[1] + seen[3], 0);
+    assert!(seen[2] > 0 && seen[4] > 0);
+}
@@ -0,0 +1,73 @@
+//! Wall-clock measurement for run and phase timing in metrics.
+//!
+//! `std::time::Instant::now` panics on `wasm32-unknown-unknown`, which has no clock, so
+//! there every stopwatch reads zero instead.
+
+use std::{cell::Cell, time::Duration};
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
+
+/// Measures the time since it was started, or nothing when disabled.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Stopwatch {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    started: Option<Instant>,
+}
+
+impl Stopwatch {
+    /// Start measuring now.
+    pub(crate) fn start() -> Self {
+        Self::start_if(true)
+    }
+
+    /// Start measuring now if `enabled`; otherwise `elapsed` is always zero and no clock
+    /// is read.
+    pub(crate) fn start_if(enabled: bool) -> Self {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        return Stopwatch {
+            started: enabled.then(Instant::now),
+        };
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        {
+            let _ = enabled;
+            Stopwatch {}
+        }
+    }
+
+    pub(crate) fn elapsed(self) -> Duration {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        return self
+            .started
+            .map_or(Duration::ZERO, |started| started.elapsed());
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        Duration::ZERO
+    }
+}
+
+/// Accumulates the time spent inside simulator callbacks during one iteration.
+pub(crate) struct CallbackClock {
+    enabled: bool,
+    total: Cell<Duration>,
+}
+
+impl CallbackClock {
+    pub(crate) fn new(enabled: bool) -> Self {
+        CallbackClock {
+            enabled,
+            total: Cell::new(Duration::ZERO),
+        }
+    }
+
+    /// Call `callback`, adding its duration to the total when enabled.
+    pub(crate) fn time<T>(&self, callback: impl FnOnce() -> T) -> T {
+        let stopwatch = Stopwatch::start_if(self.enabled);
+        let result = callback();
+        self.total.set(self.total.get() + stopwatch.elapsed());
+        result
+    }
+
+    pub(crate) fn total(&self) -> Duration {
+        self.total.get()
+    }
+}
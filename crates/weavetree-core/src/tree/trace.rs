@@ -0,0 +1,235 @@
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "wide-state-keys")]
+use crate::tree::ids::wide_key;
+use crate::tree::{
+    error::TreeError,
+    ids::{ActionId, StateKey, StateKeyValue},
+    mcts::{RunError, RunMetrics, SearchConfig},
+    search_tree::Tree,
+};
+
+/// One recorded callback exchange between the search and the environment.
+/// Tree-policy `step` events are the selection decisions, the remaining `step` events and
+/// every `rollout_action` belong to rollouts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEvent {
+    NumActions {
+        #[cfg_attr(feature = "wide-state-keys", serde(with = "wide_key"))]
+        state_key: StateKeyValue,
+        num_actions: usize,
+    },
+    Step {
+        #[cfg_attr(feature = "wide-state-keys", serde(with = "wide_key"))]
+        state_key: StateKeyValue,
+        action_id: usize,
+        #[cfg_attr(feature = "wide-state-keys", serde(with = "wide_key"))]
+        next_state_key: StateKeyValue,
+        reward: f64,
+        is_terminal: bool,
+    },
+    RolloutAction {
+        #[cfg_attr(feature = "wide-state-keys", serde(with = "wide_key"))]
+        state_key: StateKeyValue,
+        num_actions: usize,
+        action_id: usize,
+    },
+}
+
+/// Everything needed to rebuild a search without the original simulator:
+/// the config, the root, and every callback answer in call order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchTrace {
+    pub config: SearchConfig,
+    pub root_state_key: StateKeyValue,
+    pub root_is_terminal: bool,
+    pub events: Vec<TraceEvent>,
+}
+
+impl SearchTrace {
+    /// Serialize the trace as compact JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Parse a trace previously written by `to_json`.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Hands recorded callback answers back to a replayed search, checking that it asks the same questions.
+struct TraceCursor<'a> {
+    events: &'a [TraceEvent],
+    position: usize,
+}
+
+impl<'a> TraceCursor<'a> {
+    fn new(events: &'a [TraceEvent]) -> Self {
+        TraceCursor {
+            events,
+            position: 0,
+        }
+    }
+
+    fn next(&mut self) -> Result<&'a TraceEvent, TreeError> {
+        let event = self
+            .events
+            .get(self.position)
+            .ok_or(TreeError::TraceDivergence {
+                position: self.position,
+            })?;
+        self.position += 1;
+        Ok(event)
+    }
+
+    fn divergence(&self) -> TreeError {
+        TreeError::TraceDivergence {
+            position: self.position - 1,
+        }
+    }
+
+    fn num_actions(&mut self, state: StateKey) -> Result<usize, TreeError> {
+        match self.next()? {
+            TraceEvent::NumActions {
+                state_key,
+                num_actions,
+            } if *state_key == state.raw() => Ok(*num_actions),
+            _ => Err(self.divergence()),
+        }
+    }
+
+    fn step(
+        &mut self,
+        state: StateKey,
+        action: ActionId,
+    ) -> Result<(StateKey, f64, bool), TreeError> {
+        match self.next()? {
+            TraceEvent::Step {
+                state_key,
+                action_id,
+                next_state_key,
+                reward,
+                is_terminal,
+            } if *state_key == state.raw() && *action_id == action.index() => {
+                Ok((StateKey::from_raw(*next_state_key), *reward, *is_terminal))
+            }
+            _ => Err(self.divergence()),
+        }
+    }
+
+    fn rollout_action(&mut self, state: StateKey, n: usize) -> Result<ActionId, TreeError> {
+        match self.next()? {
+            TraceEvent::RolloutAction {
+                state_key,
+                num_actions,
+                action_id,
+            } if *state_key == state.raw() && *num_actions == n => Ok(ActionId::from(*action_id)),
+            _ => Err(self.divergence()),
+        }
+    }
+
+    /// Fail if the replayed search stopped before consuming the whole trace.
+    fn finish(self) -> Result<(), TreeError> {
+        if self.position == self.events.len() {
+            Ok(())
+        } else {
+            Err(TreeError::TraceDivergence {
+                position: self.position,
+            })
+        }
+    }
+}
+
+impl<P: Default> Tree<P> {
+    /// Run MCTS like `run`, additionally recording every callback answer into a `SearchTrace`.
+    /// Record on a fresh tree: `Tree::replay` rebuilds the search from the root alone.
+    pub fn run_traced<FNum, FStep, FPolicy>(
+        &mut self,
+        config: &SearchConfig,
+        mut num_actions: FNum,
+        mut step: FStep,
+        mut rollout_policy: FPolicy,
+    ) -> Result<(RunMetrics, SearchTrace), TreeError>
+    where
+        FNum: FnMut(StateKey) -> usize,
+        FStep: FnMut(StateKey, ActionId) -> (StateKey, f64, bool),
+        FPolicy: FnMut(StateKey, usize) -> ActionId,
+    {
+        let (root_state_key, root_is_terminal) = {
+            let root = self.node(self.root_id())?;
+            (root.state_key(), root.is_terminal())
+        };
+        let events = RefCell::new(Vec::new());
+
+        let metrics = self.run(
+            config,
+            |state| {
+                let n = num_actions(state);
+                events.borrow_mut().push(TraceEvent::NumActions {
+                    state_key: state.raw(),
+                    num_actions: n,
+                });
+                n
+            },
+            |state, action| {
+                let (next, reward, is_terminal) = step(state, action);
+                events.borrow_mut().push(TraceEvent::Step {
+                    state_key: state.raw(),
+                    action_id: action.index(),
+                    next_state_key: next.raw(),
+                    reward,
+                    is_terminal,
+                });
+                (next, reward, is_terminal)
+            },
+            |state, n| {
+                let action = rollout_policy(state, n);
+                events.borrow_mut().push(TraceEvent::RolloutAction {
+                    state_key: state.raw(),
+                    num_actions: n,
+                    action_id: action.index(),
+                });
+                action
+            },
+        )?;
+
+        let trace = SearchTrace {
+            config: config.clone(),
+            root_state_key: root_state_key.raw(),
+            root_is_terminal,
+            events: events.into_inner(),
+        };
+        Ok((metrics, trace))
+    }
+}
+
+impl Tree {
+    /// Rebuild the tree recorded by `run_traced` without the original simulator.
+    /// Returns `TreeError::TraceDivergence` if the replayed search asks for a callback
+    /// the trace doesn't hold at that position, e.g. after the search code changed.
+    pub fn replay(trace: &SearchTrace) -> Result<Tree, TreeError> {
+        let mut tree = Tree::new(
+            StateKey::from_raw(trace.root_state_key),
+            trace.root_is_terminal,
+        );
+        let cursor = RefCell::new(TraceCursor::new(&trace.events));
+
+        tree.run_fallible(
+            &trace.config,
+            |state| cursor.borrow_mut().num_actions(state),
+            |state, action| cursor.borrow_mut().step(state, action),
+            |state, n| cursor.borrow_mut().rollout_action(state, n),
+        )
+        .map_err(|err| match err {
+            RunError::Tree(tree_err) => tree_err,
+            RunError::Callback(tree_err) => tree_err,
+        })?;
+
+        cursor.into_inner().finish()?;
+        Ok(tree)
+    }
+}
@@ -0,0 +1,83 @@
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::tree::{ids::StateKey, search_tree::Tree};
+
+/// Policy and value targets for one searched state, as consumed by AlphaZero-style training.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrainingSample {
+    pub state_key: StateKey,
+    /// Player to move at the node; `policy` and `value` are from this player's perspective.
+    pub player: usize,
+    /// Total visits over the node's edges.
+    pub visits: u64,
+    /// Share of `visits` per action index; illegal actions get `0.0`.
+    pub policy: Vec<f64>,
+    /// Visit-weighted mean of the edge values.
+    pub value: f64,
+}
+
+impl TrainingSample {
+    /// Serialize as a single JSON line without the trailing newline.
+    pub fn to_json_line(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+}
+
+impl<P> Tree<P> {
+    /// Collect a training sample for every expanded node with at least `min_visits` visits
+    /// (and at least one), in arena order. Edge pseudo-counts from priors count as visits.
+    pub fn training_samples(&self, min_visits: u64) -> Vec<TrainingSample> {
+        let mut samples = Vec::new();
+
+        for (_, node) in self.nodes() {
+            let visits: u64 = node
+                .edges()
+                .filter(|edge| edge.is_legal())
+                .map(|edge| edge.visits())
+                .sum();
+            if visits == 0 || visits < min_visits {
+                continue;
+            }
+
+            let mut value_sum = 0.0;
+            let policy = node
+                .edges()
+                .map(|edge| {
+                    if !edge.is_legal() {
+                        return 0.0;
+                    }
+                    value_sum += edge.value_sum();
+                    edge.visits() as f64 / visits as f64
+                })
+                .collect();
+
+            samples.push(TrainingSample {
+                state_key: node.state_key(),
+                player: node.player(),
+                visits,
+                policy,
+                value: value_sum / visits as f64,
+            });
+        }
+
+        samples
+    }
+
+    /// Write `training_samples(min_visits)` to `writer` as JSONL, one sample per line.
+    /// Returns how many samples were written.
+    pub fn write_training_jsonl<W: Write>(
+        &self,
+        min_visits: u64,
+        mut writer: W,
+    ) -> io::Result<usize> {
+        let samples = self.training_samples(min_visits);
+        for sample in &samples {
+            writer.write_all(sample.to_json_line()?.as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(samples.len())
+    }
+}
@@ -1,4 +1,10 @@
-use weavetree_core::{ActionId, ReturnType, SearchConfig, StateKey, Tree};
+use std::cell::{Cell, RefCell};
+
+use weavetree_core::{
+    ActionId, NodeId, PlayerMode, ReturnType, RolloutPolicyKind, RunError, RunMetrics,
+    SearchConfig, SearchConfigBuilder, SearchConfigError, SearchExtensions, StateKey, StepEvent,
+    StepPhase, Tree, TreeError, ValueNormalization,
+};
 
 #[test]
 fn public_run_prefers_higher_value_root_action() {
@@ -10,6 +16,7 @@ fn public_run_prefers_higher_value_root_action() {
         max_steps: 4,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 4,
+        ..SearchConfig::default()
     };
 
     let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
@@ -53,3 +60,1233 @@ fn public_default_yaml_config_parses() {
     assert_eq!(config.return_type, ReturnType::Discounted);
     assert!(config.iterations > 0);
 }
+
+#[test]
+fn public_config_builder_validates_and_presets_load_by_name() {
+    let config = SearchConfig::builder()
+        .iterations(32)
+        .c(0.5)
+        .solver(true)
+        .build()
+        .expect("builder config should be valid");
+    assert_eq!(config.iterations, 32);
+    assert_eq!(config.c, 0.5);
+    assert!(config.solver);
+    assert_eq!(config.max_steps, SearchConfig::default().max_steps);
+
+    let invalid = SearchConfig::builder().iterations(0).build();
+    assert!(matches!(invalid, Err(SearchConfigError::Invalid(_))));
+
+    for name in SearchConfig::preset_names() {
+        SearchConfig::preset(name).expect("bundled presets should be valid");
+    }
+    let balanced = SearchConfig::preset("balanced").expect("balanced preset");
+    assert_eq!(balanced.iterations, SearchConfig::default().iterations);
+    let fast = SearchConfigBuilder::from_preset("fast")
+        .expect("fast preset")
+        .max_nodes(1_000)
+        .build()
+        .expect("fast preset with a node budget should be valid");
+    assert!(fast.iterations < balanced.iterations);
+    assert_eq!(fast.max_nodes, Some(1_000));
+
+    assert!(matches!(
+        SearchConfig::preset("exhaustive"),
+        Err(SearchConfigError::Invalid(_))
+    ));
+}
+
+#[test]
+fn public_action_mask_keeps_masked_actions_unvisited() {
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 12,
+        c: 1.4,
+        gamma: 1.0,
+        max_steps: 4,
+        return_type: ReturnType::Discounted,
+        fixed_horizon_steps: 4,
+        ..SearchConfig::default()
+    };
+
+    let num_actions = |state: StateKey| if state.value() == 0 { 3 } else { 0 };
+    let step = |_state: StateKey, action: ActionId| {
+        let reward = if action.index() == 1 { 10.0 } else { 1.0 };
+        (StateKey::from(action.index() as u64 + 1), reward, true)
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+    let extensions = SearchExtensions::new().with_action_mask(|_state| vec![true, false, true]);
+
+    tree.run_with_extensions(&config, num_actions, step, rollout_policy, extensions)
+        .expect("run should succeed");
+
+    let snapshot = tree.snapshot();
+    let root = &snapshot.nodes[0];
+    assert_eq!(root.edges.len(), 3);
+    assert!(!root.edges[1].legal);
+    assert_eq!(root.edges[1].visits, 0);
+    assert_eq!(root.edges[0].visits + root.edges[2].visits, 12);
+
+    let best = tree
+        .best_root_action_by_visits()
+        .expect("root action lookup should succeed")
+        .expect("an action should be available");
+    assert_ne!(best.index(), 1);
+}
+
+#[test]
+fn public_action_mask_length_mismatch_returns_error() {
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 1,
+        c: 1.4,
+        gamma: 1.0,
+        max_steps: 4,
+        return_type: ReturnType::Discounted,
+        fixed_horizon_steps: 4,
+        ..SearchConfig::default()
+    };
+
+    let err = tree
+        .run_with_extensions(
+            &config,
+            |_state| 2,
+            |_state, _action| (StateKey::from(1), 0.0, true),
+            |_state, _num_actions| ActionId::from(0),
+            SearchExtensions::new().with_action_mask(|_state| vec![true]),
+        )
+        .expect_err("mismatched mask should fail");
+
+    assert!(matches!(
+        err,
+        TreeError::InvalidActionMask {
+            expected: 2,
+            actual: 1,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn public_action_mask_applies_to_rollouts() {
+    // Every state offers three actions but only action 1 is legal, so any rollout policy
+    // that ignored the mask would step with action 0 or 2.
+    let run = |rollout: RolloutPolicyKind| {
+        let rollout_actions = RefCell::new(Vec::new());
+        let config = SearchConfig {
+            iterations: 8,
+            max_steps: 4,
+            rollout,
+            ..SearchConfig::default()
+        };
+        let extensions = SearchExtensions::new()
+            .with_action_mask(|_state| vec![false, true, false])
+            .with_step_hook(|event: &StepEvent| {
+                if event.phase == StepPhase::Rollout {
+                    rollout_actions.borrow_mut().push(event.action_id.index());
+                }
+            });
+        let result = Tree::new(StateKey::from(0), false).run_with_extensions(
+            &config,
+            |_state| 3,
+            |state, _action| (StateKey::from(state.value() + 1), 1.0, false),
+            |_state, _num_actions| ActionId::from(0),
+            extensions,
+        );
+        (result, rollout_actions.into_inner())
+    };
+
+    for rollout in [
+        RolloutPolicyKind::FirstAction,
+        RolloutPolicyKind::UniformRandom { seed: 3 },
+        RolloutPolicyKind::EpsilonGreedy { eps: 1.0, seed: 3 },
+    ] {
+        let (result, actions) = run(rollout);
+        result.expect("run should succeed");
+        assert!(!actions.is_empty());
+        assert!(actions.iter().all(|&action| action == 1), "{rollout:?}");
+    }
+
+    let (result, actions) = run(RolloutPolicyKind::Custom);
+    assert!(matches!(
+        result,
+        Err(TreeError::InvalidRolloutAction { action_id, .. }) if action_id.index() == 0
+    ));
+    assert!(actions.is_empty());
+}
+
+#[test]
+fn public_fully_masked_node_is_terminal() {
+    // Both children of the root have every action masked, so nothing is rolled out.
+    let config = SearchConfig {
+        iterations: 6,
+        max_steps: 4,
+        ..SearchConfig::default()
+    };
+    let run = Tree::new(StateKey::from(0), false)
+        .run_with_extensions(
+            &config,
+            |_state| 2,
+            |state, action| {
+                (
+                    StateKey::from(state.value() * 2 + action.index() as u64 + 1),
+                    1.0,
+                    false,
+                )
+            },
+            |_state, _num_actions| ActionId::from(0),
+            SearchExtensions::new().with_action_mask(|state| vec![state.value() == 0; 2]),
+        )
+        .expect("run should succeed");
+
+    // New children are rolled out before they are expanded; their mask stops the rollout
+    // at once. Once expanded they are treated as terminal and not rolled out at all.
+    assert_eq!(run.rollout_num_actions_calls, 2);
+    assert_eq!(run.rollout_step_calls, 0);
+    assert_eq!(run.iterations_completed, 6);
+}
+
+#[test]
+fn public_edge_priors_warm_start_root_edges() {
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 10,
+        c: 0.0,
+        gamma: 1.0,
+        max_steps: 4,
+        return_type: ReturnType::Discounted,
+        fixed_horizon_steps: 4,
+        ..SearchConfig::default()
+    };
+
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let step = |_state: StateKey, action: ActionId| {
+        let reward = if action.index() == 1 { 5.0 } else { 1.0 };
+        (StateKey::from(action.index() as u64 + 1), reward, true)
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+    let extensions = SearchExtensions::new()
+        .with_edge_priors(|_state, n| vec![(10.0, 100), (0.0, 1)][..n].to_vec());
+
+    tree.run_with_extensions(&config, num_actions, step, rollout_policy, extensions)
+        .expect("run should succeed");
+
+    let snapshot = tree.snapshot();
+    let root = &snapshot.nodes[0];
+    assert_eq!(root.edges[0].visits, 110);
+    assert_eq!(root.edges[1].visits, 1);
+    assert!((root.edges[0].value_sum - 1010.0).abs() < 1e-9);
+}
+
+#[test]
+fn public_negamax_mode_models_opponent_as_adversary() {
+    // Root (player 0) picks between two positions where player 1 moves next.
+    // Step rewards are credited to the mover, so player 1 grabs 5.0 in state 1
+    // but only 2.0 in state 2: the root should prefer state 2.
+    let num_actions = |state: StateKey| match state.value() {
+        0..=2 => 2,
+        _ => 0,
+    };
+    let step = |state: StateKey, action: ActionId| match (state.value(), action.index()) {
+        (0, a) => (StateKey::from(a as u64 + 1), 0.0, false),
+        (1, 0) => (StateKey::from(3), 5.0, true),
+        (1, _) => (StateKey::from(4), -1.0, true),
+        (2, 0) => (StateKey::from(5), 1.0, true),
+        (_, _) => (StateKey::from(6), 2.0, true),
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 200,
+        c: 1.0,
+        player_mode: PlayerMode::Negamax,
+        ..SearchConfig::default()
+    };
+    tree.run(&config, num_actions, step, rollout_policy)
+        .expect("negamax run should succeed");
+
+    let best = tree
+        .best_root_action_by_value()
+        .expect("root action lookup should succeed")
+        .expect("an action should be available");
+    assert_eq!(best.index(), 1);
+
+    let snapshot = tree.snapshot();
+    assert!(snapshot.nodes.iter().any(|node| node.player == 1));
+    assert!(snapshot.nodes[0].edges[1].q < 0.0);
+}
+
+#[test]
+fn public_max_n_maximizes_each_players_component() {
+    // Player 1 moves in state 1 and player 2 in state 2. Both pick the outcome that is best
+    // for themselves, which leaves the root (player 0) better off choosing state 2.
+    let player_to_move = |state: StateKey| match state.value() {
+        1 => 1,
+        2 => 2,
+        _ => 0,
+    };
+    let num_actions = |state: StateKey| if state.value() <= 2 { 2 } else { 0 };
+    let step = |state: StateKey, action: ActionId| match (state.value(), action.index()) {
+        (0, a) => (StateKey::from(a as u64 + 1), vec![0.0, 0.0, 0.0], false),
+        (1, 0) => (StateKey::from(3), vec![10.0, 1.0, 0.0], true),
+        (1, _) => (StateKey::from(4), vec![0.0, 5.0, 0.0], true),
+        (2, 0) => (StateKey::from(5), vec![2.0, 0.0, 1.0], true),
+        (_, _) => (StateKey::from(6), vec![-1.0, 0.0, 0.5], true),
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 300,
+        c: 1.0,
+        ..SearchConfig::default()
+    };
+    let run = tree
+        .run_max_n(&config, player_to_move, num_actions, step, rollout_policy)
+        .expect("max-n run should succeed");
+    assert_eq!(run.iterations_completed, 300);
+
+    let best = tree
+        .best_root_action_by_value()
+        .expect("root action lookup should succeed")
+        .expect("an action should be available");
+    assert_eq!(best.index(), 1);
+
+    let snapshot = tree.snapshot();
+    let state_two = snapshot
+        .nodes
+        .iter()
+        .find(|node| node.state_key == 2)
+        .expect("state 2 should be expanded");
+    assert_eq!(state_two.player, 2);
+    assert_eq!(snapshot.nodes[0].edges[1].player_q.len(), 3);
+}
+
+#[test]
+fn public_max_n_rejects_n_step_bootstrap() {
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        return_type: ReturnType::NStepBootstrap,
+        ..SearchConfig::default()
+    };
+    let err = tree
+        .run_max_n(
+            &config,
+            |_state: StateKey| 0,
+            |_state: StateKey| 2,
+            |_state: StateKey, _action: ActionId| (StateKey::from(1), vec![1.0, 0.0], false),
+            |_state: StateKey, _num_actions: usize| ActionId::from(0),
+        )
+        .expect_err("max-n run should reject n-step bootstrap");
+    assert_eq!(err, TreeError::MaxNBootstrapUnsupported);
+    assert_eq!(tree.node_count(), 1);
+}
+
+#[test]
+fn public_solver_prefers_proven_win_over_visit_counts() {
+    // Root action 0 lets player 1 win on the spot, action 1 wins immediately for the root,
+    // and action 2 walks into a draw. Priors pile fake visits onto the losing action 0.
+    let num_actions = |state: StateKey| match state.value() {
+        0 => 3,
+        1 => 2,
+        2 => 1,
+        _ => 0,
+    };
+    let step = |state: StateKey, action: ActionId| match (state.value(), action.index()) {
+        (0, 0) => (StateKey::from(1), 0.0, false),
+        (0, 1) => (StateKey::from(3), 1.0, true),
+        (0, _) => (StateKey::from(2), 0.0, false),
+        (1, a) => (StateKey::from(4 + a as u64), 1.0, true),
+        (_, _) => (StateKey::from(6), 0.0, true),
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+    let extensions = SearchExtensions::new().with_edge_priors(|state, n| {
+        let mut priors = vec![(0.0, 0); n];
+        if state.value() == 0 {
+            priors[0] = (5.0, 1_000);
+        }
+        priors
+    });
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 50,
+        c: 1.0,
+        player_mode: PlayerMode::Negamax,
+        solver: true,
+        ..SearchConfig::default()
+    };
+    tree.run_with_extensions(&config, num_actions, step, rollout_policy, extensions)
+        .expect("solver run should succeed");
+
+    let snapshot = tree.snapshot();
+    let root = &snapshot.nodes[0];
+    assert!(root.edges[0].visits > root.edges[1].visits);
+    assert_eq!(root.edges[0].proven_value, Some(-1.0));
+    assert_eq!(root.edges[1].proven_value, Some(1.0));
+    assert_eq!(root.edges[2].proven_value, Some(0.0));
+    assert_eq!(root.proven_value, Some(1.0));
+
+    let by_visits = tree
+        .best_root_action_by_visits()
+        .expect("root action lookup should succeed")
+        .expect("an action should be available");
+    let by_value = tree
+        .best_root_action_by_value()
+        .expect("root action lookup should succeed")
+        .expect("an action should be available");
+    assert_eq!(by_visits.index(), 1);
+    assert_eq!(by_value.index(), 1);
+}
+
+#[test]
+fn public_root_action_stats_report_uncertainty() {
+    // Action 0 pays a noisy 0.0/2.0, action 1 always pays 1.0.
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let mut flip = false;
+    let step = move |_state: StateKey, action: ActionId| {
+        flip = !flip;
+        match action.index() {
+            0 => (
+                StateKey::from(1 + flip as u64),
+                2.0 * flip as u64 as f64,
+                true,
+            ),
+            _ => (StateKey::from(3), 1.0, true),
+        }
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 100,
+        c: 2.0,
+        ..SearchConfig::default()
+    };
+    tree.run(&config, num_actions, step, rollout_policy)
+        .expect("run should succeed");
+
+    let stats = tree
+        .root_action_stats(1.96)
+        .expect("root stats should be available");
+    assert_eq!(stats.len(), 2);
+    assert!(stats[0].visits > 1);
+    assert!(stats[0].variance > 0.0);
+    assert!(stats[0].confidence_interval.0 < stats[0].q);
+    assert!(stats[0].confidence_interval.1 > stats[0].q);
+    assert_eq!(stats[1].variance, 0.0);
+    assert_eq!(stats[1].confidence_interval, (1.0, 1.0));
+
+    let snapshot = tree.snapshot();
+    assert_eq!(snapshot.nodes[0].edges[0].stderr, stats[0].stderr);
+    assert_eq!(stats[0].outcomes.len(), 2);
+    assert_eq!(snapshot.nodes[0].edges[0].outcomes, stats[0].outcomes);
+    assert_eq!(
+        stats[1]
+            .outcomes
+            .iter()
+            .map(|outcome| outcome.count)
+            .sum::<u64>(),
+        stats[1].visits
+    );
+}
+
+#[test]
+fn public_rollout_cache_skips_repeated_rollouts() {
+    // Both root actions lead to the same leaf state, whose rollout is the expensive part.
+    let num_actions = |state: StateKey| match state.value() {
+        0 => 2,
+        1 => 1,
+        _ => 0,
+    };
+    let step = |state: StateKey, _action: ActionId| match state.value() {
+        0 => (StateKey::from(1), 0.0, false),
+        _ => (StateKey::from(2), 1.0, true),
+    };
+    let mut rollouts = 0;
+    let rollout_policy = |_state: StateKey, _num_actions: usize| {
+        rollouts += 1;
+        ActionId::from(0)
+    };
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 2,
+        rollout_cache_capacity: 16,
+        ..SearchConfig::default()
+    };
+    let run = tree
+        .run(&config, num_actions, step, rollout_policy)
+        .expect("cached run should succeed");
+
+    assert_eq!(rollouts, 1);
+    assert_eq!(run.rollout_cache_hits, 1);
+    assert_eq!(tree.rollout_cache_len(), 1);
+    assert_eq!(tree.snapshot().nodes[0].edges[1].q, 1.0);
+}
+
+#[test]
+fn public_max_tree_depth_stops_expansion_but_keeps_rollouts() {
+    // A 10-step corridor paying 1.0 per step.
+    let num_actions = |state: StateKey| if state.value() < 10 { 1 } else { 0 };
+    let step = |state: StateKey, _action: ActionId| {
+        let next = state.value() + 1;
+        (StateKey::from(next), 1.0, next == 10)
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 20,
+        max_tree_depth: Some(2),
+        ..SearchConfig::default()
+    };
+    let run = tree
+        .run(&config, num_actions, step, rollout_policy)
+        .expect("depth-limited run should succeed");
+
+    let snapshot = tree.snapshot();
+    assert_eq!(snapshot.node_count, 3);
+    assert!(snapshot.nodes.iter().all(|node| node.depth <= 2));
+    assert!(snapshot.nodes[2].edges.is_empty());
+    assert_eq!(run.average_total_return, 10.0);
+
+    let invalid = SearchConfig::from_yaml_str("max_tree_depth: 0\n");
+    assert!(matches!(invalid, Err(SearchConfigError::Invalid(_))));
+}
+
+#[test]
+fn public_max_nodes_bounds_tree_size_by_pruning() {
+    // Wide random-ish domain: every state has 3 actions and states never repeat.
+    let num_actions = |_state: StateKey| 3;
+    let step = |state: StateKey, action: ActionId| {
+        let next = state.value() * 3 + action.index() as u64 + 1;
+        (StateKey::from(next), (action.index() as f64) * 0.1, false)
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let config = SearchConfig {
+        iterations: 300,
+        max_steps: 4,
+        max_nodes: Some(40),
+        ..SearchConfig::default()
+    };
+    let mut max_seen = 0;
+    let run = tree
+        .run_with_hook(&config, num_actions, step, rollout_policy, |metrics| {
+            max_seen = max_seen.max(metrics.node_count);
+        })
+        .expect("budgeted run should succeed");
+
+    assert!(max_seen <= 40);
+    assert!(tree.node_count() <= 40);
+    assert!(run.nodes_pruned > 0);
+
+    // Pruned trees stay consistent: every outcome points at a node whose parent is that edge.
+    let snapshot = tree.snapshot();
+    for node in &snapshot.nodes {
+        for edge in &node.edges {
+            for outcome in &edge.outcomes {
+                let child = &snapshot.nodes[outcome.child_node_id];
+                assert_eq!(child.parent_node_id, Some(node.node_id));
+                assert_eq!(child.parent_action_id, Some(edge.action_id));
+            }
+        }
+    }
+
+    // Explicit pruning keeps the most visited root branch.
+    let best = tree
+        .best_root_action_by_visits()
+        .expect("root action lookup should succeed")
+        .expect("an action should be available");
+    let removed = tree.prune_to(2).expect("pruning should succeed");
+    assert!(removed > 0);
+    let snapshot = tree.snapshot();
+    assert_eq!(snapshot.node_count, 2);
+    assert_eq!(snapshot.nodes[1].parent_action_id, Some(best.index()));
+}
+
+#[test]
+fn public_prune_subtree_releases_slots_for_reuse() {
+    // Two-action binary tree of depth 3.
+    let num_actions = |state: StateKey| if state.value() < 7 { 2 } else { 0 };
+    let step = |state: StateKey, action: ActionId| {
+        let next = state.value() * 2 + action.index() as u64 + 1;
+        (StateKey::from(next), 0.0, next >= 7)
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+    let config = SearchConfig {
+        iterations: 50,
+        ..SearchConfig::default()
+    };
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run(&config, num_actions, step, rollout_policy)
+        .expect("run should succeed");
+    assert_eq!(tree.node_count(), 15);
+
+    let left = tree.snapshot().nodes[0].edges[0].outcomes[0].child_node_id;
+    let released = tree
+        .prune_subtree(NodeId::from(left))
+        .expect("pruning a child subtree should succeed");
+    assert_eq!(released, 7);
+    assert_eq!(tree.node_count(), 8);
+    assert_eq!(tree.free_node_slots(), 7);
+
+    let snapshot = tree.snapshot();
+    assert!(snapshot.nodes[0].edges[0].outcomes.is_empty());
+    assert!(snapshot.nodes[0].edges[0].visits > 0);
+
+    // Searching again re-expands the pruned branch into the released slots.
+    tree.run(&config, num_actions, step, rollout_policy)
+        .expect("second run should succeed");
+    assert_eq!(tree.node_count(), 15);
+    assert_eq!(tree.free_node_slots(), 0);
+    assert!(tree.snapshot().nodes.iter().all(|node| node.node_id < 15));
+
+    assert!(matches!(
+        tree.prune_subtree(tree.root_id()),
+        Err(TreeError::CannotPruneRoot)
+    ));
+}
+
+#[test]
+fn public_reserve_preallocates_node_storage() {
+    let num_actions = |state: StateKey| if state.value() < 100 { 2 } else { 0 };
+    let step = |state: StateKey, action: ActionId| {
+        let next = state.value() * 2 + action.index() as u64 + 1;
+        (StateKey::from(next), 0.0, next >= 100)
+    };
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+    let config = SearchConfig {
+        iterations: 64,
+        ..SearchConfig::default()
+    };
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.reserve(config.iterations);
+    let reserved = tree.node_capacity();
+    assert!(reserved >= config.iterations);
+
+    tree.run(&config, num_actions, step, rollout_policy)
+        .expect("run should succeed");
+    assert_eq!(tree.node_capacity(), reserved + 1 - tree.node_count());
+}
+
+#[test]
+fn public_node_payload_is_attached_to_every_node() {
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let step =
+        |_state: StateKey, action: ActionId| (StateKey::from(action.index() as u64 + 1), 1.0, true);
+    let rollout_policy = |_state: StateKey, _num_actions: usize| ActionId::from(0);
+    let config = SearchConfig {
+        iterations: 4,
+        ..SearchConfig::default()
+    };
+
+    let mut tree: Tree<Option<&str>> =
+        Tree::with_root_payload(StateKey::from(0), false, Some("root"));
+    tree.run(&config, num_actions, step, rollout_policy)
+        .expect("run should succeed");
+    assert_eq!(tree.node_count(), 3);
+
+    // New nodes start from the payload's default value.
+    let child = NodeId::from(1);
+    assert_eq!(tree.node_payload(child), Ok(&None));
+    *tree.node_payload_mut(child).expect("child should exist") = Some("cached");
+    assert_eq!(tree.node_payload(child), Ok(&Some("cached")));
+    assert_eq!(tree.node_payload(tree.root_id()), Ok(&Some("root")));
+
+    assert!(matches!(
+        tree.node_payload(NodeId::from(99)),
+        Err(TreeError::MissingNode { .. })
+    ));
+}
+
+#[test]
+fn public_step_hook_sees_every_simulator_call_by_phase() {
+    let simulator_calls = Cell::new(0);
+    let num_actions = |state: StateKey| if state.value() < 6 { 2 } else { 0 };
+    let step = |state: StateKey, action: ActionId| {
+        simulator_calls.set(simulator_calls.get() + 1);
+        let next = state.value() + action.index() as u64 + 1;
+        Ok::<_, TreeError>((StateKey::from(next), next as f64, next >= 6))
+    };
+    let rollout_policy = |_state: StateKey, _n: usize| Ok::<_, TreeError>(ActionId::from(1));
+    let config = SearchConfig {
+        iterations: 16,
+        ..SearchConfig::default()
+    };
+
+    let selection_steps = Cell::new(0);
+    let rollout_steps = Cell::new(0);
+    let mut path_steps = 0;
+    let extensions = SearchExtensions::new().with_step_hook(|event| {
+        assert_eq!(event.reward(), event.next_state_key.value() as f64);
+        match event.phase {
+            StepPhase::Selection => selection_steps.set(selection_steps.get() + 1),
+            StepPhase::Rollout => rollout_steps.set(rollout_steps.get() + 1),
+        }
+    });
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run_with_extensions_fallible(
+        &config,
+        |state| Ok::<_, TreeError>(num_actions(state)),
+        step,
+        rollout_policy,
+        extensions,
+        |metrics| path_steps += metrics.path_len,
+    )
+    .map_err(|err: RunError<TreeError>| err.to_string())
+    .expect("run should succeed");
+
+    assert_eq!(selection_steps.get(), path_steps);
+    assert!(rollout_steps.get() > 0);
+    assert_eq!(
+        selection_steps.get() + rollout_steps.get(),
+        simulator_calls.get()
+    );
+}
+
+#[test]
+fn public_max_selection_depth_caps_self_loop_descents() {
+    // Every step loops back to the same state, so each iteration only extends one long chain.
+    let num_actions = |_state: StateKey| 1;
+    let step = |state: StateKey, _action: ActionId| (state, 1.0, false);
+    let rollout_policy = |_state: StateKey, _n: usize| ActionId::from(0);
+    let config = SearchConfig {
+        iterations: 10,
+        max_steps: 4,
+        max_selection_depth: Some(3),
+        ..SearchConfig::default()
+    };
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let mut max_path_len = 0;
+    let run = tree
+        .run_with_hook(&config, num_actions, step, rollout_policy, |metrics| {
+            max_path_len = max_path_len.max(metrics.path_len);
+        })
+        .expect("run should succeed");
+
+    assert_eq!(max_path_len, 3);
+    assert_eq!(tree.node_count(), 4);
+    assert_eq!(run.selection_caps, 7);
+
+    let invalid = SearchConfig::from_yaml_str("max_selection_depth: 0");
+    assert!(matches!(invalid, Err(SearchConfigError::Invalid(_))));
+}
+
+#[test]
+fn public_config_yaml_round_trips_and_strict_parsing_rejects_unknown_fields() {
+    let config = SearchConfig::builder()
+        .iterations(77)
+        .max_nodes(500)
+        .value_normalization(ValueNormalization::MinMax)
+        .build()
+        .expect("config should be valid");
+
+    let yaml = config.to_yaml_string().expect("config should serialize");
+    assert_eq!(
+        SearchConfig::from_yaml_str_strict(&yaml).ok(),
+        Some(config.clone())
+    );
+    assert_eq!(
+        SearchConfig::default().to_yaml_string().ok().as_deref(),
+        Some(SearchConfig::default_yaml())
+    );
+
+    let path = std::env::temp_dir().join(format!("weavetree-config-{}.yaml", std::process::id()));
+    config.save_yaml(&path).expect("config should save");
+    let loaded = SearchConfig::from_yaml_path_strict(&path).expect("saved config should load");
+    std::fs::remove_file(&path).expect("temp config should be removable");
+    assert_eq!(loaded, config);
+
+    let typo = "iteration: 512\n";
+    assert_eq!(
+        SearchConfig::from_yaml_str(typo)
+            .map(|config| config.iterations)
+            .ok(),
+        Some(SearchConfig::default().iterations)
+    );
+    match SearchConfig::from_yaml_str_strict(typo) {
+        Err(SearchConfigError::Invalid(message)) => assert!(message.contains("iteration")),
+        other => panic!("expected unknown field error, got {other:?}"),
+    }
+}
+
+#[test]
+fn public_configured_rollout_policies_run_without_closures() {
+    let num_actions = |_state: StateKey| 3;
+    let step = |state: StateKey, action: ActionId| {
+        let next = state.value() * 3 + action.index() as u64 + 1;
+        (StateKey::from(next), action.index() as f64, next > 40)
+    };
+    let root_visits = |tree: &Tree| -> Vec<u64> {
+        tree.snapshot().nodes[0]
+            .edges
+            .iter()
+            .map(|edge| edge.visits)
+            .collect()
+    };
+
+    let config = SearchConfig::builder()
+        .iterations(30)
+        .rollout(RolloutPolicyKind::UniformRandom { seed: 7 })
+        .build()
+        .expect("config should be valid");
+    let mut first = Tree::new(StateKey::from(0), false);
+    let mut second = Tree::new(StateKey::from(0), false);
+    first
+        .run_configured(&config, num_actions, step)
+        .expect("uniform rollouts should not need a closure");
+    second
+        .run_configured(&config, num_actions, step)
+        .expect("uniform rollouts should not need a closure");
+    assert_eq!(root_visits(&first), root_visits(&second));
+
+    let first_action = SearchConfig::from_yaml_str("iterations: 12\nrollout: first_action\n")
+        .expect("first_action should parse");
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let run = tree
+        .run_configured(&first_action, num_actions, step)
+        .expect("first_action rollouts should not need a closure");
+    assert_eq!(run.iterations_completed, 12);
+
+    let custom = SearchConfig {
+        iterations: 4,
+        ..SearchConfig::default()
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    assert!(matches!(
+        tree.run_configured(&custom, num_actions, step),
+        Err(TreeError::MissingRolloutPolicy)
+    ));
+
+    let parsed = SearchConfig::from_yaml_str("rollout: !uniform_random\n  seed: 3\n")
+        .expect("uniform_random should parse");
+    assert_eq!(parsed.rollout, RolloutPolicyKind::UniformRandom { seed: 3 });
+    let invalid = SearchConfig::from_yaml_str("rollout: !epsilon_greedy { eps: 1.5 }\n");
+    assert!(matches!(invalid, Err(SearchConfigError::Invalid(_))));
+}
+
+#[test]
+fn public_novelty_bonus_revisits_edges_with_sparse_outcomes() {
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let root_visits = |novelty_bonus: f64| {
+        let mut next_key = 1;
+        let step = |_state: StateKey, action: ActionId| {
+            if action.index() == 0 {
+                (StateKey::from(1), 0.5, true)
+            } else {
+                next_key += 1;
+                (StateKey::from(next_key), 0.5, true)
+            }
+        };
+        let config = SearchConfig {
+            iterations: 40,
+            c: 0.0,
+            novelty_bonus,
+            ..SearchConfig::default()
+        };
+        let mut tree = Tree::new(StateKey::from(0), false);
+        tree.run(&config, num_actions, step, |_state, _n| ActionId::from(0))
+            .expect("run should succeed");
+        tree.snapshot().nodes[0]
+            .edges
+            .iter()
+            .map(|edge| edge.visits)
+            .collect::<Vec<_>>()
+    };
+
+    let plain = root_visits(0.0);
+    assert!(plain[0] > plain[1]);
+    let novel = root_visits(1.0);
+    assert!(novel[1] > novel[0]);
+
+    let invalid = SearchConfig::from_yaml_str("novelty_bonus: -1.0");
+    assert!(matches!(invalid, Err(SearchConfigError::Invalid(_))));
+}
+
+#[test]
+fn public_root_priors_warm_start_a_fresh_tree() {
+    let config = SearchConfig {
+        iterations: 30,
+        c: 0.5,
+        ..SearchConfig::default()
+    };
+    let num_actions = |state: StateKey| if state.value() == 0 { 3 } else { 0 };
+    let step = |_state: StateKey, action: ActionId| {
+        (
+            StateKey::from(action.index() as u64 + 1),
+            action.index() as f64,
+            true,
+        )
+    };
+    let rollout_policy = |_state: StateKey, _n: usize| ActionId::from(0);
+    let extensions = SearchExtensions::new().with_action_mask(|_state| vec![true, true, false]);
+
+    let mut previous = Tree::new(StateKey::from(0), false);
+    previous
+        .run_with_extensions(&config, num_actions, step, rollout_policy, extensions)
+        .expect("run should succeed");
+    let stats = previous.root_action_stats(1.96).expect("root stats");
+
+    let mut tree = Tree::with_root_priors(StateKey::from(0), &stats);
+    let warm = tree.root_action_stats(1.96).expect("root stats");
+    for (seeded, stat) in warm.iter().zip(&stats) {
+        assert_eq!(seeded.visits, stat.visits);
+        assert!((seeded.q - stat.q).abs() < 1e-9);
+        assert_eq!(seeded.legal, stat.legal);
+    }
+    assert_eq!(
+        tree.best_root_action_by_visits().expect("root exists"),
+        Some(ActionId::from(1))
+    );
+
+    tree.run(&config, num_actions, step, rollout_policy)
+        .expect("warm run should succeed");
+    let after = tree.root_action_stats(1.96).expect("root stats");
+    assert_eq!(after[2].visits, 0);
+    assert_eq!(
+        after.iter().map(|stat| stat.visits).sum::<u64>(),
+        2 * config.iterations as u64
+    );
+
+    let cold = Tree::with_root_priors(StateKey::from(0), &[]);
+    assert_eq!(cold.snapshot().nodes[0].edges.len(), 0);
+}
+
+#[test]
+fn public_run_ensemble_aggregates_root_decisions_across_seeds() {
+    let config = SearchConfig {
+        iterations: 20,
+        c: 0.5,
+        ..SearchConfig::default()
+    };
+    let report = Tree::run_ensemble(StateKey::from(0), &config, &[1, 2, 3], |seed| {
+        let good_action = (seed % 2) as usize;
+        (
+            |state: StateKey| if state.value() == 0 { 2 } else { 0 },
+            move |_state: StateKey, action: ActionId| {
+                let reward = if action.index() == good_action {
+                    1.0
+                } else {
+                    0.0
+                };
+                (StateKey::from(action.index() as u64 + 1), reward, true)
+            },
+            |_state: StateKey, _n: usize| ActionId::from(0),
+        )
+    })
+    .expect("ensemble should run");
+
+    assert_eq!(report.runs.len(), 3);
+    assert_eq!(
+        report.best_actions,
+        vec![
+            Some(ActionId::from(1)),
+            Some(ActionId::from(0)),
+            Some(ActionId::from(1))
+        ]
+    );
+    assert_eq!(report.majority_action, Some(ActionId::from(1)));
+    assert_eq!(report.most_visited_action, Some(ActionId::from(1)));
+    assert_eq!(report.actions[1].votes, 2);
+    assert_eq!(
+        report.actions.iter().map(|stat| stat.visits).sum::<u64>(),
+        60
+    );
+    assert!((report.disagreement - 1.0 / 3.0).abs() < 1e-12);
+}
+
+#[test]
+fn public_run_determinized_averages_over_hidden_states() {
+    // The hidden card is 0 in seven of every ten determinizations; guessing it ends the game.
+    let config = SearchConfig {
+        iterations: 200,
+        c: 0.5,
+        ..SearchConfig::default()
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let mut determinizations = 0;
+    let metrics = tree
+        .run_determinized(
+            &config,
+            |iteration| {
+                determinizations += 1;
+                usize::from(iteration % 10 >= 7)
+            },
+            |_card: &usize, state: StateKey| if state.value() == 0 { 2 } else { 0 },
+            |card: &mut usize, _state: StateKey, action: ActionId| {
+                let reward = if action.index() == *card { 1.0 } else { 0.0 };
+                (StateKey::from(action.index() as u64 + 1), reward, true)
+            },
+            |_card: &usize, _state: StateKey, _n: usize| ActionId::from(0),
+        )
+        .expect("determinized run should succeed");
+
+    assert_eq!(determinizations, config.iterations);
+    assert_eq!(metrics.iterations_completed, config.iterations);
+    assert_eq!(
+        tree.best_root_action_by_visits().expect("root exists"),
+        Some(ActionId::from(0))
+    );
+    let stats = tree.root_action_stats(0.0).expect("root exists");
+    assert!((0.5..0.9).contains(&stats[0].q));
+    assert!(stats[1].q < 0.5);
+}
+
+#[test]
+fn public_run_determinized_fallible_applies_extensions_and_hook() {
+    let config = SearchConfig {
+        iterations: 40,
+        ..SearchConfig::default()
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let mut hook_calls = 0;
+    let metrics = tree
+        .run_determinized_fallible(
+            &config,
+            |iteration| Ok::<usize, TreeError>(iteration % 2),
+            |_card: &usize, state: StateKey| Ok(if state.value() == 0 { 3 } else { 0 }),
+            |_card: &mut usize, _state: StateKey, action: ActionId| {
+                Ok((StateKey::from(action.index() as u64 + 1), 1.0, true))
+            },
+            |_card: &usize, _state: StateKey, _n: usize| Ok(ActionId::from(1)),
+            SearchExtensions::new().with_action_mask(|_state| vec![false, true, false]),
+            |_| hook_calls += 1,
+        )
+        .expect("determinized run should succeed");
+
+    assert_eq!(hook_calls, config.iterations);
+    assert_eq!(metrics.iterations_completed, config.iterations);
+    let stats = tree.root_action_stats(0.0).expect("root exists");
+    for stat in &stats {
+        let legal = stat.action_id == ActionId::from(1);
+        assert_eq!(stat.legal, legal);
+        assert_eq!(stat.visits > 0, legal);
+    }
+}
+
+#[test]
+fn public_run_metrics_absorb_sums_chunked_runs() {
+    let chunk = SearchConfig {
+        iterations: 5,
+        ..SearchConfig::default()
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let run_chunk = |tree: &mut Tree| {
+        tree.run(
+            &chunk,
+            |state| if state.value() < 3 { 2 } else { 0 },
+            |state, action| {
+                let next = state.value() + 1;
+                (StateKey::from(next), action.index() as f64, next >= 3)
+            },
+            |_state, _n| ActionId::from(1),
+        )
+        .expect("run should succeed")
+    };
+    let first = run_chunk(&mut tree);
+    let second = run_chunk(&mut tree);
+
+    let mut metrics = RunMetrics::new(10);
+    metrics.absorb(&first);
+    metrics.absorb(&second);
+    assert_eq!(metrics.iterations_requested, 10);
+    assert_eq!(metrics.iterations_completed, 10);
+    assert_eq!(
+        metrics.total_return_sum,
+        first.total_return_sum + second.total_return_sum
+    );
+    assert_eq!(
+        metrics.average_total_return,
+        metrics.total_return_sum / 10.0
+    );
+    assert_eq!(
+        metrics.selection_step_calls,
+        first.selection_step_calls + second.selection_step_calls
+    );
+    assert_eq!(
+        metrics.rollout_step_calls,
+        first.rollout_step_calls + second.rollout_step_calls
+    );
+    assert_eq!(metrics.total_time, first.total_time + second.total_time);
+}
+
+#[test]
+fn public_compare_runs_reports_returns_agreement_and_significance() {
+    let simulator = |seed: u64| {
+        (
+            |state: StateKey| if state.value() == 0 { 2 } else { 0 },
+            move |_state: StateKey, action: ActionId| {
+                let noise = (seed % 3) as f64 * 0.1;
+                let reward = if action.index() == 1 {
+                    1.0 + noise
+                } else {
+                    noise
+                };
+                (StateKey::from(action.index() as u64 + 1), reward, true)
+            },
+            |_state: StateKey, _n: usize| ActionId::from(0),
+        )
+    };
+    let greedy = SearchConfig {
+        iterations: 30,
+        c: 0.0,
+        ..SearchConfig::default()
+    };
+    let exploring = SearchConfig {
+        c: 10.0,
+        ..greedy.clone()
+    };
+    let seeds = [1, 2, 3, 4, 5];
+
+    let comparison = Tree::compare_runs(StateKey::from(0), &greedy, &exploring, &seeds, simulator)
+        .expect("comparison should run");
+    assert_eq!(comparison.a.runs.len(), seeds.len());
+    assert_eq!(comparison.best_action_agreement, 1.0);
+    assert!(comparison.mean_return_a > comparison.mean_return_b);
+    assert!(comparison.t_statistic < 0.0);
+    assert!(comparison.significant);
+
+    let same = Tree::compare_runs(StateKey::from(0), &greedy, &greedy, &seeds, simulator)
+        .expect("comparison should run");
+    assert_eq!(same.mean_return_a, same.mean_return_b);
+    assert_eq!(same.t_statistic, 0.0);
+    assert!(!same.significant);
+}
+
+#[test]
+fn public_run_bandit_pulls_root_arms_without_children() {
+    let config = SearchConfig {
+        iterations: 200,
+        c: 0.5,
+        ..SearchConfig::default()
+    };
+    let means = [0.2, 0.8, 0.5];
+    let mut pulls = 0;
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let run = tree
+        .run_bandit(&config, means.len(), |action| {
+            pulls += 1;
+            means[action.index()]
+        })
+        .expect("bandit should run");
+
+    assert_eq!(pulls, 200);
+    assert_eq!(run.iterations_completed, 200);
+    assert_eq!(tree.node_count(), 1);
+    assert_eq!(
+        tree.best_root_action_by_visits().expect("root exists"),
+        Some(ActionId::from(1))
+    );
+    let stats = tree.root_action_stats(1.96).expect("root stats");
+    assert_eq!(stats.iter().map(|stat| stat.visits).sum::<u64>(), 200);
+    assert!((stats[1].q - 0.8).abs() < 1e-4);
+
+    let strict = SearchConfig {
+        strict_rewards: true,
+        ..config
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    assert!(matches!(
+        tree.run_bandit(&strict, 2, |_action| f64::NAN),
+        Err(TreeError::NonFiniteReward { .. })
+    ));
+}
+
+#[test]
+fn public_snapshot_renders_labelled_dot_up_to_a_depth() {
+    let config = SearchConfig {
+        iterations: 30,
+        max_steps: 4,
+        ..SearchConfig::default()
+    };
+    // A two-level chain: state `n` steps to `n + 1`, and state 2 is terminal.
+    let num_actions = |state: StateKey| if state.value() < 2 { 1 } else { 0 };
+    let step = |state: StateKey, _action: ActionId| {
+        let next = StateKey::from(state.value() + 1);
+        (next, 1.0, next.value() == 2)
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run(&config, num_actions, step, |_state, _n| ActionId::from(0))
+        .expect("run should succeed");
+
+    let snapshot = tree
+        .snapshot()
+        .with_state_labels(|state| Some(format!("room \"{}\"", state.value())))
+        .with_action_labels(|_state, _action| Some("go".to_string()));
+    assert_eq!(snapshot.nodes[0].label.as_deref(), Some("room \"0\""));
+
+    let full = snapshot.to_dot(None);
+    assert!(full.starts_with("digraph tree {\n"));
+    assert!(full.ends_with("}\n"));
+    assert!(full.contains("n0 [label=\"room \\\"0\\\"\\nvisits 30, depth 0\"];"));
+    assert!(full.contains("[label=\"room \\\"2\\\"\\nvisits 0, depth 2\", peripheries=2];"));
+    assert_eq!(full.matches(" -> ").count(), 2);
+    assert!(full.contains("n0 -> n1 [label=\"go\\nn=30 q="));
+
+    let shallow = snapshot.to_dot(Some(1));
+    assert_eq!(shallow.matches(" -> ").count(), 1);
+    assert!(!shallow.contains("room \\\"2\\\""));
+
+    let unlabelled = tree.snapshot().to_dot(Some(0));
+    assert!(unlabelled.contains("n0 [label=\"state 0\\nvisits 30, depth 0\"];"));
+    assert!(!unlabelled.contains(" -> "));
+}
+
+#[test]
+fn public_snapshot_exports_node_link_json() {
+    let config = SearchConfig {
+        iterations: 30,
+        max_steps: 4,
+        ..SearchConfig::default()
+    };
+    let num_actions = |state: StateKey| if state.value() < 2 { 1 } else { 0 };
+    let step = |state: StateKey, _action: ActionId| {
+        let next = StateKey::from(state.value() + 1);
+        (next, 1.0, next.value() == 2)
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run(&config, num_actions, step, |_state, _n| ActionId::from(0))
+        .expect("run should succeed");
+
+    let snapshot = tree
+        .snapshot()
+        .with_action_labels(|_state, _action| Some("go".to_string()));
+    let json: serde_json::Value = serde_json::from_str(
+        &snapshot
+            .to_node_link_json()
+            .expect("node-link json serializes"),
+    )
+    .expect("node-link json parses");
+
+    assert_eq!(json["directed"], true);
+    assert_eq!(json["multigraph"], false);
+    assert_eq!(json["graph"]["root"], 0);
+    let nodes = json["nodes"].as_array().expect("nodes");
+    assert_eq!(nodes.len(), snapshot.node_count);
+    assert_eq!(nodes[0]["id"], 0);
+    assert_eq!(nodes[0]["visits"], 30);
+    assert_eq!(nodes[0]["q"], 2.0);
+    assert!(nodes[0].get("label").is_none());
+    let terminal = nodes
+        .iter()
+        .find(|node| node["is_terminal"] == true)
+        .expect("terminal node");
+    assert_eq!(terminal["visits"], 0);
+    assert!(terminal["q"].is_null());
+
+    let links = json["links"].as_array().expect("links");
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0]["source"], 0);
+    assert_eq!(links[0]["target"], 1);
+    assert_eq!(links[0]["label"], "go");
+    assert_eq!(links[0]["visits"], 30);
+    assert_eq!(links[0]["count"], 30);
+}
@@ -1,4 +1,10 @@
-use weavetree_core::{ActionId, ReturnType, RunError, SearchConfig, StateKey, Tree, TreeError};
+use std::io::{self, Write};
+
+use weavetree_core::{
+    ActionId, IterationMetrics, LogFormat, NodeId, ReturnType, RunError, RunLogError, RunLogEvent,
+    RunMetrics, SearchConfig, SearchTrace, SeedSequence, StateKey, TraceEvent, TrainingSample,
+    Tree, TreeError,
+};
 
 #[test]
 fn public_terminal_root_iteration_is_stable() {
@@ -10,6 +16,7 @@ fn public_terminal_root_iteration_is_stable() {
         max_steps: 4,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 4,
+        ..SearchConfig::default()
     };
 
     let mut num_actions = |_state: StateKey| 0;
@@ -34,6 +41,7 @@ fn public_zero_action_run_completes_without_error() {
         max_steps: 4,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 4,
+        ..SearchConfig::default()
     };
 
     let num_actions = |_state: StateKey| 0;
@@ -56,6 +64,7 @@ fn public_invalid_rollout_action_returns_error() {
         max_steps: 8,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 8,
+        ..SearchConfig::default()
     };
 
     let mut num_actions = |_state: StateKey| 1;
@@ -86,6 +95,7 @@ fn public_run_fallible_propagates_callback_error() {
         max_steps: 4,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 4,
+        ..SearchConfig::default()
     };
 
     let err = tree
@@ -104,3 +114,946 @@ fn public_run_fallible_propagates_callback_error() {
         RunError::Callback(msg) if msg == "rollout callback failed"
     ));
 }
+
+#[test]
+fn public_replay_rebuilds_identical_tree_from_trace() {
+    // A tiny LCG stands in for a simulator RNG that replay has no access to.
+    let mut seed = 17_u64;
+    let mut next_random = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        seed >> 33
+    };
+    let num_actions = |state: StateKey| if state.value() < 50 { 3 } else { 0 };
+    let step = |state: StateKey, action: ActionId| {
+        let next = state.value() * 3 + action.index() as u64 + 1 + next_random() % 2;
+        (StateKey::from(next), (next % 5) as f64, next >= 50)
+    };
+    let rollout_policy = |state: StateKey, n: usize| ActionId::from(state.value() as usize % n);
+    let config = SearchConfig {
+        iterations: 40,
+        ..SearchConfig::default()
+    };
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let (metrics, trace) = tree
+        .run_traced(&config, num_actions, step, rollout_policy)
+        .expect("traced run should succeed");
+    assert_eq!(metrics.iterations_completed, config.iterations);
+
+    let json = trace.to_json().expect("trace should serialize");
+    let trace = SearchTrace::from_json(&json).expect("trace should parse");
+    let replayed = Tree::replay(&trace).expect("replay should succeed");
+
+    assert_eq!(
+        replayed
+            .snapshot_json_pretty()
+            .expect("snapshot should serialize"),
+        tree.snapshot_json_pretty()
+            .expect("snapshot should serialize")
+    );
+}
+
+#[test]
+fn public_replay_reports_divergence_position() {
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let step =
+        |_state: StateKey, action: ActionId| (StateKey::from(action.index() as u64 + 1), 1.0, true);
+    let rollout_policy = |_state: StateKey, _n: usize| ActionId::from(0);
+    let config = SearchConfig {
+        iterations: 2,
+        ..SearchConfig::default()
+    };
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let (_, mut trace) = tree
+        .run_traced(&config, num_actions, step, rollout_policy)
+        .expect("traced run should succeed");
+    assert!(matches!(
+        trace.events[0],
+        TraceEvent::NumActions { num_actions: 2, .. }
+    ));
+
+    // The search will ask about state 0 first; a trace for another state must be rejected.
+    trace.events[0] = TraceEvent::NumActions {
+        state_key: 9,
+        num_actions: 2,
+    };
+    assert_eq!(
+        Tree::replay(&trace).unwrap_err(),
+        TreeError::TraceDivergence { position: 0 }
+    );
+
+    trace.events.clear();
+    assert_eq!(
+        Tree::replay(&trace).unwrap_err(),
+        TreeError::TraceDivergence { position: 0 }
+    );
+}
+
+#[test]
+fn public_run_with_logger_writes_jsonl_events() {
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let step =
+        |_state: StateKey, action: ActionId| (StateKey::from(action.index() as u64 + 1), 1.0, true);
+    let rollout_policy = |_state: StateKey, _n: usize| ActionId::from(0);
+    let config = SearchConfig {
+        iterations: 3,
+        ..SearchConfig::default()
+    };
+
+    let mut sink = Vec::new();
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run_with_logger(
+        &config,
+        num_actions,
+        step,
+        rollout_policy,
+        &mut sink,
+        LogFormat::Jsonl,
+    )
+    .expect("logged run should succeed");
+
+    let log = String::from_utf8(sink).expect("log should be utf-8");
+    let events: Vec<serde_json::Value> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line should be json"))
+        .collect();
+    let names: Vec<&str> = events
+        .iter()
+        .map(|event| event["event"].as_str().expect("event tag"))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "run_started",
+            "iteration_completed",
+            "iteration_completed",
+            "iteration_completed",
+            "run_completed"
+        ]
+    );
+    assert_eq!(events[3]["iteration"], 2);
+}
+
+#[test]
+fn public_run_with_logger_reports_write_errors() {
+    struct FailingSink;
+
+    impl Write for FailingSink {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let config = SearchConfig {
+        iterations: 2,
+        ..SearchConfig::default()
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let result = tree.run_with_logger(
+        &config,
+        |_state| 0,
+        |state, _action| (state, 0.0, true),
+        |_state, _n| ActionId::from(0),
+        FailingSink,
+        LogFormat::Text,
+    );
+
+    assert!(matches!(result, Err(RunLogError::Io(_))));
+}
+
+#[test]
+fn public_metrics_round_trip_through_json() {
+    let config = SearchConfig {
+        iterations: 3,
+        ..SearchConfig::default()
+    };
+    let mut iterations = Vec::new();
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let run = tree
+        .run_with_hook(
+            &config,
+            |state| if state.value() == 0 { 2 } else { 0 },
+            |_state, action| (StateKey::from(action.index() as u64 + 1), 1.0, true),
+            |_state, _n| ActionId::from(0),
+            |metrics| iterations.push(*metrics),
+        )
+        .expect("run should succeed");
+
+    let json = serde_json::to_value(iterations[0]).expect("iteration metrics should serialize");
+    assert_eq!(json["leaf"], iterations[0].leaf.index());
+    let parsed: IterationMetrics =
+        serde_json::from_value(json).expect("iteration metrics should parse");
+    assert_eq!(parsed, iterations[0]);
+
+    let json = serde_json::to_string(&run).expect("run metrics should serialize");
+    let parsed: RunMetrics = serde_json::from_str(&json).expect("run metrics should parse");
+    assert_eq!(parsed, run);
+}
+
+#[test]
+fn public_phase_timing_splits_run_time_by_phase() {
+    use std::time::Duration;
+
+    // Every step sleeps, so the simulator dominates the measured time.
+    let num_actions = |state: StateKey| if state.value() < 3 { 2 } else { 0 };
+    let step = |state: StateKey, _action: ActionId| {
+        std::thread::sleep(Duration::from_micros(200));
+        let next = StateKey::from(state.value() + 1);
+        (next, 1.0, next.value() == 3)
+    };
+    let mut config = SearchConfig {
+        iterations: 8,
+        ..SearchConfig::default()
+    };
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let untimed = tree
+        .run(&config, num_actions, step, |_state, _n| ActionId::from(0))
+        .expect("run should succeed");
+    assert!(untimed.total_time >= Duration::from_micros(200 * 3 * 8));
+    assert_eq!(untimed.simulator_time, Duration::ZERO);
+    assert_eq!(
+        untimed.selection_time + untimed.rollout_time,
+        Duration::ZERO
+    );
+
+    config.phase_timing = true;
+    let mut iterations = Vec::new();
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let timed = tree
+        .run_with_hook(
+            &config,
+            num_actions,
+            step,
+            |_state, _n| ActionId::from(0),
+            |metrics| iterations.push(*metrics),
+        )
+        .expect("run should succeed");
+
+    let phases = timed.selection_time + timed.rollout_time + timed.backprop_time;
+    assert!(timed.simulator_time >= Duration::from_micros(200 * 3 * 8));
+    assert!(timed.simulator_time <= timed.selection_time + timed.rollout_time);
+    assert!(phases <= timed.total_time);
+    assert_eq!(
+        iterations
+            .iter()
+            .map(|metrics| metrics.simulator_time)
+            .sum::<Duration>(),
+        timed.simulator_time
+    );
+
+    let RunLogEvent::RunCompleted {
+        total_seconds,
+        simulator_seconds,
+        ..
+    } = RunLogEvent::run_completed(&timed)
+    else {
+        panic!("run_completed should build a RunCompleted event");
+    };
+    assert_eq!(total_seconds, timed.total_time.as_secs_f64());
+    assert_eq!(simulator_seconds, timed.simulator_time.as_secs_f64());
+}
+
+#[test]
+fn public_run_metrics_count_simulator_calls_by_phase() {
+    use std::cell::Cell;
+
+    let num_actions_calls = Cell::new(0);
+    let step_calls = Cell::new(0);
+    let num_actions = |state: StateKey| {
+        num_actions_calls.set(num_actions_calls.get() + 1);
+        if state.value() < 3 { 2 } else { 0 }
+    };
+    let step = |state: StateKey, _action: ActionId| {
+        step_calls.set(step_calls.get() + 1);
+        let next = StateKey::from(state.value() + 1);
+        (next, 1.0, next.value() == 3)
+    };
+    let config = SearchConfig {
+        iterations: 12,
+        ..SearchConfig::default()
+    };
+
+    let mut iterations = Vec::new();
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let run = tree
+        .run_with_hook(
+            &config,
+            num_actions,
+            step,
+            |_state, _n| ActionId::from(0),
+            |metrics| iterations.push(*metrics),
+        )
+        .expect("run should succeed");
+
+    // The first iteration expands the root with one step and rolls out the other two.
+    assert_eq!(iterations[0].selection_step_calls, 1);
+    assert_eq!(iterations[0].rollout_step_calls, 2);
+    assert_eq!(
+        run.selection_step_calls + run.rollout_step_calls,
+        step_calls.get()
+    );
+    assert_eq!(
+        run.selection_num_actions_calls + run.rollout_num_actions_calls,
+        num_actions_calls.get()
+    );
+    assert_eq!(
+        iterations
+            .iter()
+            .map(|metrics| metrics.rollout_step_calls)
+            .sum::<usize>(),
+        run.rollout_step_calls
+    );
+}
+
+#[test]
+fn public_strict_rewards_rejects_non_finite_step_rewards() {
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let step = |_state: StateKey, action: ActionId| {
+        let reward = if action.index() == 1 { f64::NAN } else { 1.0 };
+        (StateKey::from(action.index() as u64 + 1), reward, true)
+    };
+    let rollout_policy = |_state: StateKey, _n: usize| ActionId::from(0);
+
+    let lenient = SearchConfig {
+        iterations: 4,
+        ..SearchConfig::default()
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run(&lenient, num_actions, step, rollout_policy)
+        .expect("lenient runs accept NaN rewards");
+
+    let strict = SearchConfig {
+        strict_rewards: true,
+        ..lenient
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    assert_eq!(
+        tree.run(&strict, num_actions, step, rollout_policy),
+        Err(TreeError::NonFiniteReward {
+            state_key: StateKey::from(0),
+            action_id: ActionId::from(1),
+        })
+    );
+}
+
+#[test]
+fn public_strict_rewards_rejects_non_finite_rollout_returns() {
+    let num_actions = |state: StateKey| if state.value() < 3 { 1 } else { 0 };
+    let step = |state: StateKey, _action: ActionId| {
+        // Every reward is finite, but two of them overflow the rollout sum.
+        let next = state.value() + 1;
+        (StateKey::from(next), f64::MAX, next == 3)
+    };
+    let rollout_policy = |_state: StateKey, _n: usize| ActionId::from(0);
+    let config = SearchConfig {
+        iterations: 1,
+        strict_rewards: true,
+        ..SearchConfig::default()
+    };
+
+    let mut tree = Tree::new(StateKey::from(0), false);
+    assert_eq!(
+        tree.run(&config, num_actions, step, rollout_policy)
+            .unwrap_err(),
+        TreeError::NonFiniteReturn {
+            state_key: StateKey::from(1),
+        }
+    );
+}
+
+#[test]
+fn public_training_samples_export_visit_policies_as_jsonl() {
+    let config = SearchConfig {
+        iterations: 40,
+        max_steps: 4,
+        ..SearchConfig::default()
+    };
+    let num_actions = |state: StateKey| if state.value() < 3 { 2 } else { 0 };
+    let step = |state: StateKey, action: ActionId| {
+        let next = state.value() * 2 + action.index() as u64 + 1;
+        (StateKey::from(next), action.index() as f64, next >= 3)
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run(&config, num_actions, step, |_state, _n| ActionId::from(0))
+        .expect("run should succeed");
+
+    let samples = tree.training_samples(5);
+    let root = &samples[0];
+    assert_eq!(root.state_key, StateKey::from(0));
+    assert_eq!(root.visits, 40);
+    assert!((root.policy.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    assert!(samples.iter().all(|sample| sample.visits >= 5));
+    assert!(tree.training_samples(41).is_empty());
+
+    let mut buffer = Vec::new();
+    let written = tree
+        .write_training_jsonl(5, &mut buffer)
+        .expect("export should succeed");
+    let text = String::from_utf8(buffer).expect("jsonl is utf-8");
+    let parsed: Vec<TrainingSample> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line is one sample"))
+        .collect();
+    assert_eq!(written, samples.len());
+    assert_eq!(parsed.len(), samples.len());
+    for (parsed, sample) in parsed.iter().zip(&samples) {
+        assert_eq!(parsed.state_key, sample.state_key);
+        assert_eq!(parsed.visits, sample.visits);
+        assert!((parsed.value - sample.value).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn public_seed_sequence_derives_stable_distinct_child_seeds() {
+    let sequence = SeedSequence::new(42);
+    // Pinned: the derivation is part of the public contract for reproducible experiments.
+    assert_eq!(
+        sequence.seeds(3),
+        vec![
+            0x0505_4191_edd9_7663,
+            0x98e8_9d22_97cb_b069,
+            0x3352_b640_3ef0_9e74
+        ]
+    );
+
+    let mut seeds = sequence.seeds(1_000);
+    seeds.sort_unstable();
+    seeds.dedup();
+    assert_eq!(seeds.len(), 1_000);
+    assert_ne!(sequence.spawn(0).seeds(2), sequence.spawn(1).seeds(2));
+    assert_ne!(sequence.spawn(0).child_seed(0), sequence.child_seed(0));
+
+    let report = Tree::run_ensemble_from_master_seed(
+        StateKey::from(0),
+        &SearchConfig {
+            iterations: 4,
+            ..SearchConfig::default()
+        },
+        42,
+        3,
+        |_seed| {
+            (
+                |state: StateKey| if state.value() == 0 { 2 } else { 0 },
+                |_state: StateKey, action: ActionId| {
+                    (StateKey::from(action.index() as u64 + 1), 1.0, true)
+                },
+                |_state: StateKey, _n: usize| ActionId::from(0),
+            )
+        },
+    )
+    .expect("ensemble should run");
+    assert_eq!(report.seeds, sequence.seeds(3));
+}
+
+#[test]
+fn public_errors_serialize_with_stable_codes() {
+    let node_id = NodeId::from(3);
+    let action_id = ActionId::from(1);
+    let state_key = StateKey::from(7);
+    let errors = [
+        TreeError::MissingNode { node_id },
+        TreeError::MissingEdge { node_id, action_id },
+        TreeError::ActionSelectionFailed { node_id },
+        TreeError::OutcomeInsertFailed { node_id, action_id },
+        TreeError::InvalidRolloutAction {
+            state_key,
+            action_id,
+            num_actions: 1,
+        },
+        TreeError::InvalidActionMask {
+            node_id,
+            expected: 2,
+            actual: 1,
+        },
+        TreeError::InvalidRolloutActionMask {
+            state_key,
+            expected: 2,
+            actual: 1,
+        },
+        TreeError::InvalidEdgePriors {
+            node_id,
+            expected: 2,
+            actual: 1,
+        },
+        TreeError::MissingValueBootstrap { state_key },
+        TreeError::CannotPruneRoot,
+        TreeError::NonFiniteReward {
+            state_key,
+            action_id,
+        },
+        TreeError::NonFiniteReturn { state_key },
+        TreeError::MissingRolloutPolicy,
+        TreeError::TraceDivergence { position: 4 },
+        TreeError::MaxNBootstrapUnsupported,
+    ];
+    for err in &errors {
+        let json = serde_json::to_value(err).expect("tree error should serialize");
+        assert_eq!(json["code"], err.code());
+    }
+
+    assert_eq!(
+        serde_json::to_value(&errors[1]).expect("tree error should serialize"),
+        serde_json::json!({ "code": "missing_edge", "node_id": 3, "action_id": 1 })
+    );
+    assert_eq!(
+        serde_json::to_value(TreeError::CannotPruneRoot).expect("tree error should serialize"),
+        serde_json::json!({ "code": "cannot_prune_root" })
+    );
+
+    let tree_failure: RunError<String> = RunError::Tree(TreeError::MissingRolloutPolicy);
+    assert_eq!(tree_failure.code(), "missing_rollout_policy");
+    assert_eq!(
+        serde_json::to_value(&tree_failure).expect("run error should serialize"),
+        serde_json::json!({ "code": "missing_rollout_policy" })
+    );
+    let callback_failure: RunError<io::Error> = RunError::Callback(io::Error::other("offline"));
+    assert_eq!(callback_failure.code(), "callback_failed");
+    assert_eq!(
+        serde_json::to_value(&callback_failure).expect("run error should serialize"),
+        serde_json::json!({ "code": "callback_failed", "message": "offline" })
+    );
+}
+
+#[cfg(feature = "wide-state-keys")]
+#[test]
+fn public_wide_state_keys_keep_full_128_bit_hashes() {
+    let root = StateKey::from_raw(u128::MAX - 2);
+    let config = SearchConfig {
+        iterations: 6,
+        ..SearchConfig::default()
+    };
+    let mut tree = Tree::new(root, false);
+    let (_, trace) = tree
+        .run_traced(
+            &config,
+            |state: StateKey| if state == root { 2 } else { 0 },
+            |_state: StateKey, action: ActionId| {
+                let next = StateKey::from_raw(u128::MAX - action.index() as u128);
+                (next, 1.0, true)
+            },
+            |_state: StateKey, _n: usize| ActionId::from(0),
+        )
+        .expect("run should succeed");
+
+    assert_eq!(trace.root_state_key, u128::MAX - 2);
+    let snapshot = tree.snapshot();
+    assert!(
+        snapshot
+            .nodes
+            .iter()
+            .any(|node| node.state_key == u128::MAX)
+    );
+
+    let wide = u128::MAX.to_string();
+    let snapshot_json: serde_json::Value = serde_json::from_str(
+        &tree
+            .snapshot_json_pretty()
+            .expect("snapshot should serialize"),
+    )
+    .expect("snapshot json should parse");
+    let nodes = snapshot_json["nodes"].as_array().expect("nodes array");
+    assert!(nodes.iter().any(|node| node["state_key"] == wide.as_str()));
+    assert!(
+        nodes
+            .iter()
+            .any(|node| { node["edges"][0]["outcomes"][0]["next_state_key"] == wide.as_str() })
+    );
+    let node_link: serde_json::Value = serde_json::from_str(
+        &snapshot
+            .to_node_link_json()
+            .expect("node-link should serialize"),
+    )
+    .expect("node-link json should parse");
+    assert!(
+        node_link["nodes"]
+            .as_array()
+            .expect("nodes array")
+            .iter()
+            .any(|node| node["state_key"] == wide.as_str())
+    );
+
+    let json = trace.to_json().expect("trace should serialize");
+    let replayed = Tree::replay(&SearchTrace::from_json(&json).expect("trace should parse"))
+        .expect("replay should succeed");
+    assert_eq!(
+        replayed
+            .snapshot_json_pretty()
+            .expect("snapshot should serialize"),
+        tree.snapshot_json_pretty()
+            .expect("snapshot should serialize")
+    );
+}
+
+/// Read every record batch of a Parquet file written into `bytes`.
+#[cfg(feature = "parquet")]
+fn read_parquet(bytes: Vec<u8>, name: &str) -> Vec<arrow_array::RecordBatch> {
+    let path =
+        std::env::temp_dir().join(format!("weavetree-{name}-{}.parquet", std::process::id()));
+    std::fs::write(&path, bytes).expect("parquet file should be writable");
+    let file = std::fs::File::open(&path).expect("parquet file should open");
+    let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)
+        .expect("parquet footer should parse")
+        .build()
+        .expect("reader should build");
+    let batches = reader
+        .collect::<Result<Vec<_>, _>>()
+        .expect("batches should decode");
+    std::fs::remove_file(&path).ok();
+    batches
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn public_run_with_parquet_logger_writes_one_row_per_event() {
+    use arrow_array::{Array, StringArray, UInt64Array};
+
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let step =
+        |_state: StateKey, action: ActionId| (StateKey::from(action.index() as u64 + 1), 1.0, true);
+    let config = SearchConfig {
+        iterations: 3,
+        ..SearchConfig::default()
+    };
+
+    let mut sink = Vec::new();
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let metrics = tree
+        .run_with_parquet_logger(
+            &config,
+            num_actions,
+            step,
+            |_state: StateKey, _n: usize| ActionId::from(0),
+            &mut sink,
+        )
+        .expect("logged run should succeed");
+
+    let batches = read_parquet(sink, "run-log");
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    assert_eq!(batch.schema(), weavetree_core::run_log_schema());
+    let events = batch
+        .column_by_name("event")
+        .and_then(|column| column.as_any().downcast_ref::<StringArray>())
+        .expect("event column is utf-8");
+    let names: Vec<&str> = events.iter().map(|name| name.expect("event tag")).collect();
+    assert_eq!(
+        names,
+        [
+            "run_started",
+            "iteration_completed",
+            "iteration_completed",
+            "iteration_completed",
+            "run_completed"
+        ]
+    );
+    let iterations = batch
+        .column_by_name("iteration")
+        .and_then(|column| column.as_any().downcast_ref::<UInt64Array>())
+        .expect("iteration column is u64");
+    assert!(iterations.is_null(0));
+    assert_eq!(iterations.value(3), 2);
+    let completed = batch
+        .column_by_name("iterations_completed")
+        .and_then(|column| column.as_any().downcast_ref::<UInt64Array>())
+        .expect("iterations_completed column is u64");
+    assert_eq!(completed.value(4), metrics.iterations_completed as u64);
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn public_training_parquet_matches_training_samples() {
+    use arrow_array::{Array, ListArray, UInt64Array};
+
+    let config = SearchConfig {
+        iterations: 40,
+        ..SearchConfig::default()
+    };
+    let num_actions = |state: StateKey| if state.value() < 3 { 2 } else { 0 };
+    let step = |state: StateKey, action: ActionId| {
+        let next = state.value() * 2 + action.index() as u64 + 1;
+        (StateKey::from(next), action.index() as f64, next >= 3)
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run(&config, num_actions, step, |_state, _n| ActionId::from(0))
+        .expect("run should succeed");
+
+    let samples = tree.training_samples(5);
+    let mut buffer = Vec::new();
+    let written = tree
+        .write_training_parquet(5, &mut buffer)
+        .expect("export should succeed");
+    assert_eq!(written, samples.len());
+
+    let batches = read_parquet(buffer, "training");
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    assert_eq!(batch.schema(), weavetree_core::training_schema());
+    assert_eq!(batch.num_rows(), samples.len());
+    let visits = batch
+        .column_by_name("visits")
+        .and_then(|column| column.as_any().downcast_ref::<UInt64Array>())
+        .expect("visits column is u64");
+    let policy = batch
+        .column_by_name("policy")
+        .and_then(|column| column.as_any().downcast_ref::<ListArray>())
+        .expect("policy column is a list");
+    for (row, sample) in samples.iter().enumerate() {
+        assert_eq!(visits.value(row), sample.visits);
+        assert_eq!(policy.value(row).len(), sample.policy.len());
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn public_run_with_sqlite_sink_records_queryable_runs() {
+    use weavetree_core::SqliteRunSink;
+
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let step = |_state: StateKey, action: ActionId| {
+        (
+            StateKey::from(action.index() as u64 + 1),
+            action.index() as f64,
+            true,
+        )
+    };
+    let connection = rusqlite::Connection::open_in_memory().expect("in-memory database");
+    let mut sink = SqliteRunSink::from_connection(connection).expect("schema should apply");
+
+    for (run_id, iterations) in [("short", 3), ("long", 8)] {
+        let config = SearchConfig {
+            iterations,
+            ..SearchConfig::default()
+        };
+        let mut tree = Tree::new(StateKey::from(0), false);
+        tree.run_with_sqlite_sink(
+            &config,
+            num_actions,
+            step,
+            |_state: StateKey, _n: usize| ActionId::from(0),
+            &mut sink,
+            run_id,
+            1.96,
+        )
+        .expect("recorded run should succeed");
+    }
+
+    let db = sink.connection();
+    let completed: i64 = db
+        .query_row(
+            "SELECT iterations_completed FROM runs WHERE run_id = 'long'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("long run is recorded");
+    assert_eq!(completed, 8);
+    let iterations: i64 = db
+        .query_row(
+            "SELECT COUNT(*) FROM iterations WHERE run_id = 'short'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("iterations are recorded");
+    assert_eq!(iterations, 3);
+    let (best_action, visits): (i64, i64) = db
+        .query_row(
+            "SELECT action_id, visits FROM root_actions WHERE run_id = 'long' \
+             ORDER BY q DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .expect("root actions are recorded");
+    assert_eq!(best_action, 1);
+    assert!(visits > 0);
+
+    // A duplicate run id is rejected before searching and leaves the database unchanged.
+    let mut tree = Tree::new(StateKey::from(0), false);
+    let result = tree.run_with_sqlite_sink(
+        &SearchConfig::default(),
+        num_actions,
+        step,
+        |_state: StateKey, _n: usize| ActionId::from(0),
+        &mut sink,
+        "short",
+        1.96,
+    );
+    assert!(matches!(result, Err(RunLogError::Io(_))));
+    assert_eq!(tree.node_count(), 1);
+    assert!(sink.contains_run("short").expect("query should succeed"));
+    assert!(!sink.contains_run("missing").expect("query should succeed"));
+}
+
+#[cfg(feature = "tensorboard")]
+#[test]
+fn public_run_with_tensorboard_writes_framed_scalar_events() {
+    use weavetree_core::TensorBoardWriter;
+
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let step = |_state: StateKey, action: ActionId| {
+        (
+            StateKey::from(action.index() as u64 + 1),
+            action.index() as f64,
+            true,
+        )
+    };
+    let config = SearchConfig {
+        iterations: 10,
+        ..SearchConfig::default()
+    };
+
+    let mut sink = Vec::new();
+    let mut writer = TensorBoardWriter::new(&mut sink).expect("header should be written");
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run_with_tensorboard(
+        &config,
+        num_actions,
+        step,
+        |_state: StateKey, _n: usize| ActionId::from(0),
+        &mut writer,
+        4,
+    )
+    .expect("monitored run should succeed");
+    drop(writer);
+
+    // TFRecord framing: u64 length, masked CRC32-C of the length, data, masked CRC of the data.
+    let masked = |data: &[u8]| {
+        crc32c::crc32c(data)
+            .rotate_right(15)
+            .wrapping_add(0xa282_ead8)
+    };
+    let mut records = Vec::new();
+    let mut rest = sink.as_slice();
+    while !rest.is_empty() {
+        let (len_bytes, tail) = rest.split_at(8);
+        let len = u64::from_le_bytes(len_bytes.try_into().expect("8 bytes")) as usize;
+        assert_eq!(tail[..4], masked(len_bytes).to_le_bytes());
+        let (data, tail) = tail[4..].split_at(len);
+        assert_eq!(tail[..4], masked(data).to_le_bytes());
+        records.push(data.to_vec());
+        rest = &tail[4..];
+    }
+
+    let contains =
+        |record: &[u8], needle: &[u8]| record.windows(needle.len()).any(|window| window == needle);
+    // The file version record, then summaries after iterations 4, 8, and 10.
+    assert_eq!(records.len(), 4);
+    assert!(contains(&records[0], b"brain.Event:2"));
+    for record in &records[1..] {
+        assert!(contains(record, b"search/average_return"));
+        assert!(contains(record, b"search/node_count"));
+        assert!(contains(record, b"search/best_root_q"));
+    }
+    // Field 2 (`step`) follows the 8-byte wall time of field 1.
+    let steps: Vec<u8> = records[1..].iter().map(|record| record[10]).collect();
+    assert_eq!(steps, [4, 8, 10]);
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn public_http_inspector_serves_published_snapshot_and_metrics() {
+    use std::{io::Read, net::TcpStream};
+
+    use weavetree_core::{HttpInspector, SearchExtensions};
+
+    let inspector = HttpInspector::bind("127.0.0.1:0").expect("server should bind");
+    let get = |path: &str| {
+        let mut stream = TcpStream::connect(inspector.local_addr()).expect("server accepts");
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").expect("request sent");
+        let mut response = String::new();
+        stream.read_to_string(&mut response).expect("response read");
+        let (head, body) = response.split_once("\r\n\r\n").expect("headers end");
+        let status = head.split(' ').nth(1).expect("status code").to_string();
+        (status, body.to_string())
+    };
+
+    assert_eq!(get("/metrics").0, "503");
+
+    let num_actions = |state: StateKey| if state.value() == 0 { 2 } else { 0 };
+    let step = |_state: StateKey, action: ActionId| {
+        (
+            StateKey::from(action.index() as u64 + 1),
+            action.index() as f64,
+            true,
+        )
+    };
+    let config = SearchConfig {
+        iterations: 10,
+        ..SearchConfig::default()
+    };
+    let mut tree = Tree::new(StateKey::from(0), false);
+    tree.run_with_http_inspector(
+        &config,
+        |state| Ok::<usize, TreeError>(num_actions(state)),
+        |state, action| Ok(step(state, action)),
+        |_state: StateKey, _n: usize| Ok(ActionId::from(0)),
+        SearchExtensions::new(),
+        &inspector,
+        4,
+    )
+    .expect("inspected run should succeed");
+
+    let (status, body) = get("/metrics");
+    assert_eq!(status, "200");
+    let metrics: RunMetrics = serde_json::from_str(&body).expect("metrics parse");
+    assert_eq!(metrics.iterations_completed, 10);
+
+    let (status, body) = get("/snapshot?pretty=0");
+    assert_eq!(status, "200");
+    let snapshot: serde_json::Value = serde_json::from_str(&body).expect("snapshot parses");
+    assert_eq!(snapshot["node_count"], tree.node_count());
+
+    assert_eq!(get("/missing").0, "404");
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn public_http_inspector_on_wildcard_address_shuts_down() {
+    let inspector = weavetree_core::HttpInspector::bind("0.0.0.0:0").expect("server should bind");
+    assert!(inspector.local_addr().ip().is_unspecified());
+    // Dropping joins the server thread, so this returns only once it has stopped.
+    drop(inspector);
+}
+
+#[cfg(feature = "testing")]
+proptest::proptest! {
+    #[test]
+    fn public_testing_configs_are_valid_and_round_trip(
+        config in weavetree_core::testing::search_config(),
+    ) {
+        let yaml = config.to_yaml_string().expect("config should serialize");
+        let parsed = SearchConfig::from_yaml_str(&yaml).expect("generated configs are valid");
+        proptest::prop_assert_eq!(parsed, config);
+    }
+
+    #[test]
+    fn public_testing_trees_respect_their_config(
+        env in weavetree_core::testing::random_env(),
+        config in weavetree_core::testing::search_config(),
+    ) {
+        let (tree, metrics) = env.grow_tree(&config).expect("search should succeed");
+        proptest::prop_assert_eq!(metrics.iterations_completed, config.iterations);
+        if let Some(max_nodes) = config.max_nodes {
+            proptest::prop_assert!(tree.node_count() <= max_nodes);
+        }
+        if let Some(max_depth) = config.max_tree_depth {
+            proptest::prop_assert!(tree.snapshot().nodes.iter().all(|node| node.depth <= max_depth));
+        }
+
+        // Clones replay the same transitions.
+        let replay = env.clone();
+        let root = env.root();
+        for action in 0..env.num_actions(root) {
+            proptest::prop_assert_eq!(
+                env.step(root, ActionId::from(action)),
+                replay.step(root, ActionId::from(action))
+            );
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Criterion benchmarks of compiled-MDP sampling, compilation, and state interning.
+//!
+//! Run with `cargo bench -p weavetree-mdp`.
+
+use std::hint::black_box;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use weavetree_mdp::benchmarks::{inventory, taxi};
+use weavetree_mdp::{FingerprintInterner, KeyInterner, MdpSimulator, StateInterner};
+
+/// Synthetic domain state: a grid position and a step counter, so every state is distinct.
+type GridState = (u32, u32, u64);
+
+fn grid_states(n: usize) -> Vec<GridState> {
+    (0..n as u64)
+        .map(|i| ((i % 97) as u32, (i % 89) as u32, i))
+        .collect()
+}
+
+fn sampling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sample_outcome");
+    for capacity in [10, 100] {
+        let compiled = inventory(capacity).compile().expect("inventory compiles");
+        let start = compiled.start();
+        // Ordering up to capacity leaves every demand its own outcome.
+        let action = capacity;
+
+        let mut cdf = MdpSimulator::new(compiled.clone(), 7);
+        group.bench_with_input(BenchmarkId::new("cdf", capacity + 1), &(), |b, _| {
+            b.iter(|| cdf.step(black_box(start), black_box(action)));
+        });
+
+        let mut alias = MdpSimulator::new(compiled.with_alias_tables(1), 7);
+        group.bench_with_input(BenchmarkId::new("alias", capacity + 1), &(), |b, _| {
+            b.iter(|| alias.step(black_box(start), black_box(action)));
+        });
+    }
+    group.finish();
+
+    let compiled = taxi().compile().expect("taxi compiles");
+    let num_actions = compiled
+        .num_actions(compiled.start())
+        .expect("start state exists");
+    let mut simulator = MdpSimulator::new(compiled, 7);
+    c.bench_function("taxi_episode_step", |b| {
+        let mut state = simulator.mdp().start();
+        let mut action = 0;
+        b.iter(|| {
+            let (next, reward, terminal) = simulator.step(state, action);
+            action = (action + 1) % num_actions;
+            state = if terminal {
+                simulator.mdp().start()
+            } else {
+                next
+            };
+            black_box(reward)
+        });
+    });
+}
+
+fn compile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compile");
+    let spec = taxi();
+    group.bench_function("taxi", |b| {
+        b.iter(|| spec.compile().expect("taxi compiles"))
+    });
+    let spec = inventory(50);
+    group.bench_function("inventory_50", |b| {
+        b.iter(|| spec.compile().expect("inventory compiles"))
+    });
+    group.finish();
+}
+
+fn interning(c: &mut Criterion) {
+    let states = grid_states(10_000);
+    let replay: &dyn Fn(&GridState, usize, f64) -> GridState = &|state, _, _| *state;
+    let mut group = c.benchmark_group("intern");
+
+    group.bench_function("state_interner_insert", |b| {
+        b.iter_batched(
+            StateInterner::<GridState>::new,
+            |mut interner| {
+                for &state in &states {
+                    black_box(interner.intern(state));
+                }
+                interner
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    let mut warm = StateInterner::new();
+    for &state in &states {
+        warm.intern(state);
+    }
+    group.bench_function("state_interner_hit", |b| {
+        b.iter(|| {
+            for &state in &states {
+                black_box(warm.intern(state));
+            }
+        });
+    });
+
+    group.bench_function("fingerprint_interner_insert", |b| {
+        b.iter_batched(
+            FingerprintInterner::<GridState>::new,
+            |mut interner| {
+                let start = interner
+                    .intern_start(states[0])
+                    .expect("fingerprint interner is unbounded");
+                for &state in &states[1..] {
+                    black_box(
+                        interner
+                            .intern_successor(state, start, 0, 0.0, replay)
+                            .expect("fingerprint interner is unbounded"),
+                    );
+                }
+                interner
+            },
+            BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sampling, compile, interning);
+criterion_main!(benches);
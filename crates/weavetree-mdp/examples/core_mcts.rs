@@ -22,6 +22,7 @@ fn main() {
         max_steps: 4,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 4,
+        ..SearchConfig::default()
     };
 
     let run = tree
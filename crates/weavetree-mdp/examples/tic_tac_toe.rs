@@ -1,4 +1,4 @@
-use weavetree_core::{ActionId, ReturnType, SearchConfig, Tree};
+use weavetree_core::{ActionId, PlayerMode, ReturnType, SearchConfig, Tree};
 use weavetree_mdp::{DomainSimulator, MdpDomain};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -54,6 +54,12 @@ impl TicTacToeDomain {
         moves
     }
 
+    fn to_move(board: &[Cell; 9]) -> Cell {
+        let x_count = board.iter().filter(|cell| **cell == Cell::X).count();
+        let o_count = board.iter().filter(|cell| **cell == Cell::O).count();
+        if x_count == o_count { Cell::X } else { Cell::O }
+    }
+
     fn winner(board: &[Cell; 9]) -> Option<Cell> {
         const LINES: [[usize; 3]; 8] = [
             [0, 1, 2],
@@ -126,7 +132,12 @@ impl MdpDomain for TicTacToeDomain {
         }
     }
 
-    fn step(&self, state: &Self::State, action_id: usize, sample: f64) -> (Self::State, f64, bool) {
+    fn step(
+        &self,
+        state: &Self::State,
+        action_id: usize,
+        _sample: f64,
+    ) -> (Self::State, f64, bool) {
         // This is the core simulator callback users provide to define dynamics.
         // It can be deterministic or stochastic.
         if self.is_terminal(state) {
@@ -134,33 +145,18 @@ impl MdpDomain for TicTacToeDomain {
         }
 
         let legal_moves = self.legal_moves(state);
-        let Some(&x_move) = legal_moves.get(action_id) else {
+        let Some(&cell) = legal_moves.get(action_id) else {
             return (state.clone(), 0.0, true);
         };
 
+        // Both sides are played by the search. The reward is credited to the
+        // player who just moved; negamax backpropagation flips it per ply.
+        let mover = Self::to_move(&state.board);
         let mut next = state.clone();
-        next.board[x_move] = Cell::X;
-
-        if let Some(winner) = Self::winner(&next.board) {
-            let reward = if winner == Cell::X { 1.0 } else { -1.0 };
-            return (next, reward, true);
-        }
-        if Self::is_full(&next.board) {
-            return (next, 0.0, true);
-        }
-
-        // Opponent plays uniformly at random over legal moves.
-        let opp_moves = self.legal_moves(&next);
-        if opp_moves.is_empty() {
-            return (next, 0.0, true);
-        }
-        let idx = ((sample * opp_moves.len() as f64) as usize).min(opp_moves.len() - 1);
-        let o_move = opp_moves[idx];
-        next.board[o_move] = Cell::O;
+        next.board[cell] = mover;
 
-        if let Some(winner) = Self::winner(&next.board) {
-            let reward = if winner == Cell::X { 1.0 } else { -1.0 };
-            return (next, reward, true);
+        if Self::winner(&next.board).is_some() {
+            return (next, 1.0, true);
         }
         if Self::is_full(&next.board) {
             return (next, 0.0, true);
@@ -189,9 +185,12 @@ fn main() {
         iterations: 800,
         c: 1.0,
         gamma: 1.0,
-        max_steps: 6,
+        max_steps: 9,
         return_type: ReturnType::Discounted,
-        fixed_horizon_steps: 6,
+        fixed_horizon_steps: 9,
+        player_mode: PlayerMode::Negamax,
+        solver: true,
+        ..SearchConfig::default()
     };
 
     // Step 4: Run search by plugging simulator closures directly into MCTS.
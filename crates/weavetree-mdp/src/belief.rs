@@ -0,0 +1,268 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rand::{RngCore, SeedableRng};
+use weavetree_core::StateKeyValue;
+
+use crate::simulator::uniform_sample;
+use crate::{CompiledMdp, DefaultRng, MdpError, StateKey};
+
+/// Default quantum beliefs are rounded to before interning.
+const DEFAULT_RESOLUTION: f64 = 1e-6;
+
+#[derive(Debug, Clone)]
+/// Seeded simulator over a partially observable model, whose states are beliefs: probability
+/// distributions over the states of a compiled MDP, updated by Bayes' rule on the
+/// observations of sampled outcomes.
+///
+/// Beliefs are interned, so keys handed to the tree are small integers; two beliefs whose
+/// probabilities round to the same multiples of the resolution share a key. Action `a` of a
+/// belief is action `a` of every state it covers, so models should give all non-terminal
+/// states the same actions in the same order.
+/// Generic over its RNG `R`, which defaults to `DefaultRng` (ChaCha8).
+pub struct BeliefSimulator<R = DefaultRng> {
+    mdp: CompiledMdp,
+    rng: R,
+    resolution: f64,
+    /// Interned beliefs as `(state, probability)` pairs in key order, indexed by belief key.
+    beliefs: Vec<Vec<(StateKey, f64)>>,
+    keys: HashMap<Vec<(usize, u64)>, StateKeyValue>,
+    start_key: StateKeyValue,
+}
+
+impl BeliefSimulator {
+    /// Create a belief simulator starting with certainty in the start state and a
+    /// deterministic RNG seed.
+    pub fn new(mdp: CompiledMdp, seed: u64) -> Self {
+        Self::with_seed(mdp, seed)
+    }
+}
+
+impl<R> BeliefSimulator<R>
+where
+    R: RngCore + SeedableRng,
+{
+    /// Create a belief simulator whose RNG of type `R` is seeded from `seed`.
+    pub fn with_seed(mdp: CompiledMdp, seed: u64) -> Self {
+        Self::with_rng(mdp, R::seed_from_u64(seed))
+    }
+
+    /// Replace the RNG with a fresh one seeded from `seed`. Interned beliefs stay valid.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = R::seed_from_u64(seed);
+    }
+}
+
+impl<R> BeliefSimulator<R>
+where
+    R: RngCore,
+{
+    /// Create a belief simulator drawing from an already constructed RNG.
+    pub fn with_rng(mdp: CompiledMdp, rng: R) -> Self {
+        let start = vec![(mdp.start(), 1.0)];
+        let mut simulator = Self {
+            mdp,
+            rng,
+            resolution: DEFAULT_RESOLUTION,
+            beliefs: Vec::new(),
+            keys: HashMap::new(),
+            start_key: 0,
+        };
+        simulator.reset(start);
+        simulator
+    }
+
+    /// Start episodes from `belief` instead, given as `(state, probability)` pairs that are
+    /// normalized to sum to one. Previously interned beliefs are dropped.
+    pub fn with_initial_belief(mut self, belief: &[(StateKey, f64)]) -> Result<Self, MdpError> {
+        let invalid = |message: String| MdpError::InvalidBelief { message };
+        let mut merged = BTreeMap::new();
+        for &(state, prob) in belief {
+            if state.index() >= self.mdp.state_count() {
+                return Err(invalid(format!("unknown state key {}", state.index())));
+            }
+            if !prob.is_finite() || prob < 0.0 {
+                return Err(invalid(format!(
+                    "probability {prob} of state key {} is not a probability",
+                    state.index()
+                )));
+            }
+            *merged.entry(state.index()).or_insert(0.0) += prob;
+        }
+        let belief =
+            normalized(merged).ok_or_else(|| invalid("no probability mass".to_string()))?;
+        self.reset(belief);
+        Ok(self)
+    }
+
+    /// Round beliefs to multiples of `resolution` when interning them: coarser resolutions
+    /// merge more beliefs into one tree node. Previously interned beliefs are dropped.
+    pub fn with_resolution(mut self, resolution: f64) -> Self {
+        self.resolution = resolution;
+        let start = self.beliefs[self.start_key as usize].clone();
+        self.reset(start);
+        self
+    }
+
+    fn reset(&mut self, start: Vec<(StateKey, f64)>) {
+        self.beliefs.clear();
+        self.keys.clear();
+        self.start_key = self.intern(start);
+    }
+
+    /// Borrow the RNG driving this simulator.
+    pub fn rng(&self) -> &R {
+        &self.rng
+    }
+
+    /// Borrow the underlying compiled MDP.
+    pub fn mdp(&self) -> &CompiledMdp {
+        &self.mdp
+    }
+
+    /// Return the key of the initial belief.
+    pub fn start_state_key(&self) -> StateKeyValue {
+        self.start_key
+    }
+
+    /// Return a belief as `(state, probability)` pairs in key order.
+    pub fn belief(&self, belief_key: StateKeyValue) -> Option<&[(StateKey, f64)]> {
+        self.beliefs.get(belief_key as usize).map(Vec::as_slice)
+    }
+
+    /// Return how many distinct beliefs have been interned.
+    pub fn belief_count(&self) -> usize {
+        self.beliefs.len()
+    }
+
+    /// Return whether every state a belief covers is terminal. Unknown keys count as terminal.
+    pub fn is_terminal_by_key(&self, belief_key: StateKeyValue) -> bool {
+        self.belief(belief_key).is_none_or(|belief| {
+            belief
+                .iter()
+                .all(|&(state, _)| self.mdp.is_terminal(state) != Some(false))
+        })
+    }
+
+    /// Return how many actions every non-terminal state of a belief has.
+    pub fn num_actions_by_key(&self, belief_key: StateKeyValue) -> usize {
+        self.belief(belief_key)
+            .and_then(|belief| {
+                belief
+                    .iter()
+                    .filter(|&&(state, _)| self.mdp.is_terminal(state) == Some(false))
+                    .filter_map(|&(state, _)| self.mdp.num_actions(state))
+                    .min()
+            })
+            .unwrap_or(0)
+    }
+
+    /// Sample one `(next_belief_key, reward, terminal)` transition: draw a hidden state from
+    /// the belief, sample its transition and the observation of the outcome, and condition
+    /// the belief on that observation. Reward and terminal flag are those of the hidden
+    /// transition. Invalid belief/action inputs are treated as a no-op terminal transition.
+    pub fn step_by_key(
+        &mut self,
+        belief_key: StateKeyValue,
+        action_id: usize,
+    ) -> (StateKeyValue, f64, bool) {
+        let Some(belief) = self.beliefs.get(belief_key as usize) else {
+            return (belief_key, 0.0, true);
+        };
+        let mut remaining = uniform_sample(&mut self.rng);
+        let state = belief
+            .iter()
+            .find(|(_, prob)| {
+                remaining -= prob;
+                remaining < 0.0
+            })
+            .or(belief.last())
+            .map(|&(state, _)| state)
+            .expect("interned beliefs are not empty");
+
+        let sample = uniform_sample(&mut self.rng);
+        let rng = &mut self.rng;
+        let Some((_, reward, terminal, observation)) =
+            self.mdp
+                .sample_observed_transition(state, action_id, sample, || uniform_sample(rng))
+        else {
+            return (belief_key, 0.0, true);
+        };
+        match self.update_by_observation_key(belief_key, action_id, observation) {
+            Some(next_key) => (next_key, reward, terminal),
+            None => (belief_key, 0.0, true),
+        }
+    }
+
+    /// Condition a belief on taking `action_id` and receiving `observation`, e.g. to follow
+    /// the real system between searches. Returns `None` for unknown beliefs or observations
+    /// and for observations the belief deems impossible.
+    pub fn update_belief(
+        &mut self,
+        belief_key: StateKeyValue,
+        action_id: usize,
+        observation: &str,
+    ) -> Option<StateKeyValue> {
+        let observation = self.mdp.observation_key(observation)?;
+        self.update_by_observation_key(belief_key, action_id, observation)
+    }
+
+    fn update_by_observation_key(
+        &mut self,
+        belief_key: StateKeyValue,
+        action_id: usize,
+        observation: usize,
+    ) -> Option<StateKeyValue> {
+        let belief = self.beliefs.get(belief_key as usize)?;
+        let mut next = BTreeMap::new();
+        for &(state, prob) in belief {
+            if self.mdp.is_terminal(state) == Some(true) {
+                // Terminal states stay put and emit the empty observation.
+                if observation == 0 {
+                    *next.entry(state.index()).or_insert(0.0) += prob;
+                }
+                continue;
+            }
+            for (successor, transition_prob, observations) in
+                self.mdp.observed_transitions(state, action_id)
+            {
+                let likelihood: f64 = observations
+                    .iter()
+                    .filter(|(key, _)| *key == observation)
+                    .map(|(_, p)| p)
+                    .sum();
+                if likelihood > 0.0 {
+                    *next.entry(successor.index()).or_insert(0.0) +=
+                        prob * transition_prob * likelihood;
+                }
+            }
+        }
+        let next = normalized(next)?;
+        Some(self.intern(next))
+    }
+
+    fn intern(&mut self, belief: Vec<(StateKey, f64)>) -> StateKeyValue {
+        let quantized: Vec<(usize, u64)> = belief
+            .iter()
+            .map(|&(state, prob)| (state.index(), (prob / self.resolution).round() as u64))
+            .filter(|&(_, units)| units > 0)
+            .collect();
+        let next_key = self.beliefs.len() as StateKeyValue;
+        let key = *self.keys.entry(quantized).or_insert(next_key);
+        if key == next_key {
+            self.beliefs.push(belief);
+        }
+        key
+    }
+}
+
+/// Scale masses to sum to one, dropping zero entries; `None` if there is no mass.
+fn normalized(masses: BTreeMap<usize, f64>) -> Option<Vec<(StateKey, f64)>> {
+    let total: f64 = masses.values().sum();
+    (total > 0.0).then(|| {
+        masses
+            .into_iter()
+            .filter(|&(_, mass)| mass > 0.0)
+            .map(|(state, mass)| (StateKey::from(state), mass / total))
+            .collect()
+    })
+}
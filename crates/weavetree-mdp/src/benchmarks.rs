@@ -0,0 +1,319 @@
+use crate::generators::{GridWorldConfig, grid_world};
+use crate::{ActionSpec, MdpSpec, OutcomeSpec, StateSpec};
+
+/// The standard 4x4 FrozenLake map: `S` start, `F` frozen, `H` hole, `G` goal.
+pub const FROZEN_LAKE_4X4: [&str; 4] = ["SFFF", "FHFH", "FFFH", "HFFG"];
+
+/// The standard 8x8 FrozenLake map.
+pub const FROZEN_LAKE_8X8: [&str; 8] = [
+    "SFFFFFFF", "FFFFFFFF", "FFFHFFFF", "FFFFFHFF", "FFFHFFFF", "FHHFFFHF", "FHFFHFHF", "FFFHFFFG",
+];
+
+/// Action ids of `taxi`, in action-index order.
+pub const TAXI_ACTIONS: [&str; 6] = ["south", "north", "east", "west", "pickup", "dropoff"];
+
+/// Strens' chain with states `s0..s{n - 1}`, starting in `s0`.
+///
+/// `forward` moves one state along the chain for reward `0`, or stays in the last state for
+/// reward `10`; `back` returns to `s0` for reward `2`. Each action does the other one's
+/// move with probability `0.2`, so a myopic agent settles for `back`.
+///
+/// # Panics
+///
+/// Panics if `n < 2`.
+pub fn chain(n: usize) -> MdpSpec {
+    assert!(n >= 2, "chain needs at least two states");
+    let forward = |i: usize| {
+        if i + 1 == n { (i, 10.0) } else { (i + 1, 0.0) }
+    };
+    let back = (0, 2.0);
+
+    let states = (0..n)
+        .map(|i| {
+            let actions = vec![
+                action("forward", [(forward(i), 0.8), (back, 0.2)]),
+                action("back", [(back, 0.8), (forward(i), 0.2)]),
+            ];
+            running_state(format!("s{i}"), actions)
+        })
+        .collect();
+    spec("s0", states)
+}
+
+/// RiverSwim with states `s0..s{n - 1}`, starting in `s0`.
+///
+/// `left` always drifts one state downstream and pays `0.005` in `s0`. `right` swims
+/// against the current: it reaches the next state with probability `0.35` (`0.4` from
+/// `s0`), stays with `0.6`, and is pushed back with `0.05`. In the last state it stays
+/// with probability `0.6` for reward `1`, so exploration has to commit to swimming upstream.
+///
+/// # Panics
+///
+/// Panics if `n < 2`.
+pub fn river_swim(n: usize) -> MdpSpec {
+    assert!(n >= 2, "river swim needs at least two states");
+    let states = (0..n)
+        .map(|i| {
+            let left = if i == 0 {
+                action("left", [((0, 0.005), 1.0)])
+            } else {
+                action("left", [((i - 1, 0.0), 1.0)])
+            };
+            let right = if i == 0 {
+                action("right", [((1, 0.0), 0.4), ((0, 0.0), 0.6)])
+            } else if i + 1 == n {
+                action("right", [((i, 1.0), 0.6), ((i - 1, 0.0), 0.4)])
+            } else {
+                action(
+                    "right",
+                    [((i + 1, 0.0), 0.35), ((i, 0.0), 0.6), ((i - 1, 0.0), 0.05)],
+                )
+            };
+            running_state(format!("s{i}"), vec![left, right])
+        })
+        .collect();
+    spec("s0", states)
+}
+
+/// FrozenLake on a map such as `FROZEN_LAKE_4X4`, as a `grid_world`.
+///
+/// Holes are terminal with reward `0` and the goal is terminal with reward `1`. On slippery
+/// ice the agent moves in the chosen direction or either perpendicular one with probability
+/// `1/3` each. Actions are `GRID_ACTIONS` and states are named by `grid_cell_id`.
+///
+/// # Panics
+///
+/// Panics if the map is empty, ragged, has a cell other than `S`, `F`, `H`, or `G`,
+/// or does not have exactly one `S`.
+pub fn frozen_lake(map: &[&str], slippery: bool) -> MdpSpec {
+    let width = map.first().map_or(0, |row| row.len());
+    assert!(
+        width > 0 && map.iter().all(|row| row.len() == width),
+        "frozen lake map must be a non-empty rectangle"
+    );
+
+    let mut start = Vec::new();
+    let mut terminals = Vec::new();
+    for (y, row) in map.iter().enumerate() {
+        for (x, cell) in row.bytes().enumerate() {
+            match cell {
+                b'S' => start.push((x, y)),
+                b'F' => {}
+                b'H' => terminals.push(((x, y), 0.0)),
+                b'G' => terminals.push(((x, y), 1.0)),
+                other => panic!("unknown frozen lake cell '{}'", other as char),
+            }
+        }
+    }
+    assert!(start.len() == 1, "frozen lake map needs exactly one start");
+
+    grid_world(&GridWorldConfig {
+        width,
+        height: map.len(),
+        start: start[0],
+        walls: Vec::new(),
+        terminals,
+        slip: if slippery { 2.0 / 3.0 } else { 0.0 },
+        step_reward: 0.0,
+    })
+    .expect("a well-formed map is a valid grid world")
+}
+
+const TAXI_SIZE: usize = 5;
+const TAXI_STANDS: [(char, (usize, usize)); 4] =
+    [('R', (0, 0)), ('G', (0, 4)), ('Y', (4, 0)), ('B', (4, 3))];
+/// Cells `(row, col)` with a wall on their east side.
+const TAXI_WALLS: [(usize, usize); 6] = [(0, 1), (1, 1), (3, 0), (4, 0), (3, 2), (4, 2)];
+
+/// Dietterich's Taxi on the standard 5x5 map with stands `R`, `G`, `Y`, and `B`.
+///
+/// States are named `row,col,passenger,destination`, e.g. `2,2,R,B`, where the passenger is
+/// at a stand or `T` for in the taxi. The taxi starts in the centre with the passenger at
+/// `R` and destination `B`; set `start` to pick another. Every action in `TAXI_ACTIONS`
+/// costs `1`, a wrong `pickup` or `dropoff` costs `10`, and delivering the passenger pays
+/// `20` and ends the episode.
+pub fn taxi() -> MdpSpec {
+    let id = |(row, col): (usize, usize), passenger: char, destination: char| {
+        format!("{row},{col},{passenger},{destination}")
+    };
+    let stand_at = |cell: (usize, usize)| {
+        TAXI_STANDS
+            .iter()
+            .find(|(_, stand)| *stand == cell)
+            .map(|(name, _)| *name)
+    };
+    let moved = |(row, col): (usize, usize), direction: usize| match direction {
+        0 => (usize::min(row + 1, TAXI_SIZE - 1), col),
+        1 => (row.saturating_sub(1), col),
+        2 if col + 1 < TAXI_SIZE && !TAXI_WALLS.contains(&(row, col)) => (row, col + 1),
+        3 if col > 0 && !TAXI_WALLS.contains(&(row, col - 1)) => (row, col - 1),
+        _ => (row, col),
+    };
+
+    let mut states = Vec::new();
+    for row in 0..TAXI_SIZE {
+        for col in 0..TAXI_SIZE {
+            let cell = (row, col);
+            for passenger in TAXI_STANDS.iter().map(|(name, _)| *name).chain(['T']) {
+                for &(destination, _) in &TAXI_STANDS {
+                    let state_id = id(cell, passenger, destination);
+                    if passenger == destination {
+                        states.push(StateSpec {
+                            id: state_id,
+                            terminal: Some(true),
+                            actions: None,
+                            entry_reward: None,
+                            terminal_reward: None,
+                            labels: None,
+                            metadata: None,
+                        });
+                        continue;
+                    }
+
+                    let mut actions: Vec<ActionSpec> = (0..4)
+                        .map(|direction| {
+                            let next = id(moved(cell, direction), passenger, destination);
+                            deterministic(TAXI_ACTIONS[direction], next, -1.0)
+                        })
+                        .collect();
+                    let pickup = match stand_at(cell) {
+                        Some(stand) if stand == passenger => {
+                            deterministic("pickup", id(cell, 'T', destination), -1.0)
+                        }
+                        _ => deterministic("pickup", state_id.clone(), -10.0),
+                    };
+                    let dropoff = match stand_at(cell) {
+                        Some(stand) if passenger == 'T' => {
+                            let reward = if stand == destination { 20.0 } else { -1.0 };
+                            deterministic("dropoff", id(cell, stand, destination), reward)
+                        }
+                        _ => deterministic("dropoff", state_id.clone(), -10.0),
+                    };
+                    actions.extend([pickup, dropoff]);
+                    states.push(running_state(state_id, actions));
+                }
+            }
+        }
+    }
+    spec(&id((2, 2), 'R', 'B'), states)
+}
+
+/// Inventory control with stock `0..=capacity`, starting empty.
+///
+/// States are named `stock=n`. Each period `order{k}` buys `k` units, up to the capacity,
+/// at cost `2` each; then a demand uniform in `0..=capacity` is served at price `4` per
+/// unit, and every unsold unit costs `0.5` to hold. Unmet demand is lost.
+///
+/// # Panics
+///
+/// Panics if `capacity == 0`.
+pub fn inventory(capacity: usize) -> MdpSpec {
+    assert!(capacity > 0, "inventory needs a capacity of at least one");
+    let demand_prob = 1.0 / (capacity + 1) as f64;
+
+    let states = (0..=capacity)
+        .map(|stock| {
+            let actions = (0..=capacity - stock)
+                .map(|order| {
+                    let available = stock + order;
+                    let mut outcomes: Vec<OutcomeSpec> = Vec::new();
+                    for demand in 0..=capacity {
+                        let sold = demand.min(available);
+                        let left = available - sold;
+                        let next = format!("stock={left}");
+                        match outcomes.iter_mut().find(|outcome| outcome.next == next) {
+                            Some(outcome) => {
+                                outcome.prob =
+                                    (outcome.prob.value().unwrap_or(0.0) + demand_prob).into()
+                            }
+                            None => outcomes.push(OutcomeSpec {
+                                next,
+                                prob: demand_prob.into(),
+                                reward: (4.0 * sold as f64 - 0.5 * left as f64).into(),
+                                observations: None,
+                            }),
+                        }
+                    }
+                    ActionSpec {
+                        id: format!("order{order}"),
+                        outcomes,
+                        cost: (order > 0).then_some(2.0 * order as f64),
+                        labels: None,
+                        metadata: None,
+                        available_when: None,
+                    }
+                })
+                .collect();
+            running_state(format!("stock={stock}"), actions)
+        })
+        .collect();
+    spec("stock=0", states)
+}
+
+/// Build an action over `s{i}` states from `((next, reward), prob)` moves, merging moves that
+/// land in the same state with the same reward.
+fn action<const N: usize>(id: &str, moves: [((usize, f64), f64); N]) -> ActionSpec {
+    let mut outcomes: Vec<OutcomeSpec> = Vec::new();
+    for ((next, reward), prob) in moves {
+        let next = format!("s{next}");
+        match outcomes
+            .iter_mut()
+            .find(|outcome| outcome.next == next && outcome.reward.value() == reward)
+        {
+            Some(outcome) => outcome.prob = (outcome.prob.value().unwrap_or(0.0) + prob).into(),
+            None => outcomes.push(OutcomeSpec {
+                next,
+                prob: prob.into(),
+                reward: reward.into(),
+                observations: None,
+            }),
+        }
+    }
+    ActionSpec {
+        id: id.to_string(),
+        outcomes,
+        cost: None,
+        labels: None,
+        metadata: None,
+        available_when: None,
+    }
+}
+
+fn deterministic(id: &str, next: String, reward: f64) -> ActionSpec {
+    ActionSpec {
+        id: id.to_string(),
+        outcomes: vec![OutcomeSpec {
+            next,
+            prob: 1.0.into(),
+            reward: reward.into(),
+            observations: None,
+        }],
+        cost: None,
+        labels: None,
+        metadata: None,
+        available_when: None,
+    }
+}
+
+fn running_state(id: String, actions: Vec<ActionSpec>) -> StateSpec {
+    StateSpec {
+        id,
+        terminal: None,
+        actions: Some(actions),
+        entry_reward: None,
+        terminal_reward: None,
+        labels: None,
+        metadata: None,
+    }
+}
+
+fn spec(start: &str, states: Vec<StateSpec>) -> MdpSpec {
+    MdpSpec {
+        version: Some(1),
+        params: None,
+        weights: None,
+        include: None,
+        start: start.to_string(),
+        states,
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use crate::{ActionSpec, CompiledMdp, MdpError, MdpSpec, OutcomeSpec, StateSpec};
 
 #[derive(Debug, Clone, Default)]
@@ -5,6 +8,10 @@ use crate::{ActionSpec, CompiledMdp, MdpError, MdpSpec, OutcomeSpec, StateSpec};
 pub struct MdpBuilder {
     start: Option<String>,
     states: Vec<StateSpec>,
+    /// Position of each state id in `states`, so lookups do not scan.
+    state_index: HashMap<String, usize>,
+    /// Position of each action id in its state's actions, parallel to `states`.
+    action_index: Vec<HashMap<String, usize>>,
 }
 
 impl MdpBuilder {
@@ -22,36 +29,71 @@ impl MdpBuilder {
     /// Add a new state
     /// Terminal flag if this state is the final one
     pub fn add_state(&mut self, id: impl Into<String>, terminal: bool) -> &mut Self {
+        let id = id.into();
+        // Duplicate ids keep resolving to the first state; `build_spec` rejects them.
+        self.state_index
+            .entry(id.clone())
+            .or_insert(self.states.len());
+        self.action_index.push(HashMap::new());
         self.states.push(StateSpec {
-            id: id.into(),
+            id,
             terminal: Some(terminal),
             actions: Some(Vec::new()),
+            entry_reward: None,
+            terminal_reward: None,
+            labels: None,
+            metadata: None,
         });
         self
     }
 
+    /// Add several states at once from `(id, terminal)` pairs
+    pub fn add_states<I, S>(&mut self, states: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (S, bool)>,
+        S: Into<String>,
+    {
+        for (id, terminal) in states {
+            self.add_state(id, terminal);
+        }
+        self
+    }
+
     /// Add an action to a state
     pub fn add_action(
         &mut self,
         state_id: impl AsRef<str>,
         action_id: impl Into<String>,
     ) -> Result<&mut Self, MdpError> {
-        let state_id = state_id.as_ref();
-        let action_id = action_id.into();
-
-        let state = self
-            .states
-            .iter_mut()
-            .find(|s| s.id == state_id)
-            .ok_or_else(|| MdpError::BuilderUnknownState {
-                state: state_id.to_string(),
-            })?;
+        self.add_actions(state_id, [action_id])
+    }
 
-        let actions = state.actions.get_or_insert_with(Vec::new);
-        actions.push(ActionSpec {
-            id: action_id,
-            outcomes: Vec::new(),
-        });
+    /// Add several actions to one state, looking the state up once
+    pub fn add_actions<I, A>(
+        &mut self,
+        state_id: impl AsRef<str>,
+        action_ids: I,
+    ) -> Result<&mut Self, MdpError>
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<String>,
+    {
+        let state = self.state_position(state_id.as_ref())?;
+        let actions = self.states[state].actions.get_or_insert_with(Vec::new);
+        for action_id in action_ids {
+            let action_id = action_id.into();
+            self.action_index[state]
+                .entry(action_id.clone())
+                .or_insert(actions.len());
+            actions.push(ActionSpec {
+                id: action_id,
+                outcomes: Vec::new(),
+                cost: None,
+                labels: None,
+                metadata: None,
+                available_when: None,
+            });
+        }
 
         Ok(self)
     }
@@ -65,41 +107,232 @@ impl MdpBuilder {
         next: impl Into<String>,
         prob: f64,
         reward: f64,
+    ) -> Result<&mut Self, MdpError> {
+        self.add_outcomes(state_id, action_id, [(next, prob, reward)])
+    }
+
+    /// Add several `(next, prob, reward)` outcomes to one action, looking it up once
+    pub fn add_outcomes<I, N>(
+        &mut self,
+        state_id: impl AsRef<str>,
+        action_id: impl AsRef<str>,
+        outcomes: I,
+    ) -> Result<&mut Self, MdpError>
+    where
+        I: IntoIterator<Item = (N, f64, f64)>,
+        N: Into<String>,
+    {
+        let action = self.action_mut(state_id.as_ref(), action_id.as_ref())?;
+        action.outcomes.extend(
+            outcomes
+                .into_iter()
+                .map(|(next, prob, reward)| OutcomeSpec {
+                    next: next.into(),
+                    prob: prob.into(),
+                    reward: reward.into(),
+                    observations: None,
+                }),
+        );
+
+        Ok(self)
+    }
+
+    /// Add an action that always leads to `next` with `reward`
+    pub fn add_deterministic_transition(
+        &mut self,
+        state_id: impl AsRef<str>,
+        action_id: impl Into<String>,
+        next: impl Into<String>,
+        reward: f64,
     ) -> Result<&mut Self, MdpError> {
         let state_id = state_id.as_ref();
-        let action_id = action_id.as_ref();
+        let action_id = action_id.into();
+        self.add_actions(state_id, [action_id.as_str()])?
+            .add_outcomes(state_id, action_id, [(next, 1.0, reward)])
+    }
 
-        let state = self
-            .states
-            .iter_mut()
-            .find(|s| s.id == state_id)
+    fn state_position(&self, state_id: &str) -> Result<usize, MdpError> {
+        self.state_index
+            .get(state_id)
+            .copied()
             .ok_or_else(|| MdpError::BuilderUnknownState {
                 state: state_id.to_string(),
-            })?;
+            })
+    }
 
-        let actions = state.actions.get_or_insert_with(Vec::new);
-        let action = actions
-            .iter_mut()
-            .find(|a| a.id == action_id)
+    fn action_mut(&mut self, state_id: &str, action_id: &str) -> Result<&mut ActionSpec, MdpError> {
+        let state = self.state_position(state_id)?;
+        let action = self.action_index[state]
+            .get(action_id)
+            .copied()
             .ok_or_else(|| MdpError::BuilderUnknownAction {
                 state: state_id.to_string(),
                 action: action_id.to_string(),
             })?;
+        let actions = self.states[state].actions.get_or_insert_with(Vec::new);
+        Ok(&mut actions[action])
+    }
+
+    pub fn build_spec(self) -> Result<MdpSpec, MdpError> {
+        let start = self.start.ok_or(MdpError::MissingStart)?;
+        let spec = MdpSpec {
+            version: Some(1),
+            params: None,
+            weights: None,
+            include: None,
+            start,
+            states: self.states,
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    pub fn compile(self) -> Result<CompiledMdp, MdpError> {
+        let spec = self.build_spec()?;
+        spec.compile()
+    }
+}
+
+/// Source of builder ids, so handles can be told apart from another builder's.
+static NEXT_BUILDER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A state added to a `TypedMdpBuilder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StateHandle {
+    builder: u64,
+    index: usize,
+}
+
+/// An action added to a `TypedMdpBuilder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActionHandle {
+    builder: u64,
+    state: usize,
+    index: usize,
+}
+
+impl ActionHandle {
+    /// Return the state this action belongs to.
+    pub fn state(&self) -> StateHandle {
+        StateHandle {
+            builder: self.builder,
+            index: self.state,
+        }
+    }
+}
 
-        action.outcomes.push(OutcomeSpec {
-            next: next.into(),
-            prob,
-            reward,
+#[derive(Debug, Clone)]
+/// Builder whose states and actions are referenced by handles instead of ids.
+///
+/// `add_state` and `add_action` return handles that later calls take, so a misspelled
+/// state can not be referenced at all. Ids are still checked for duplicates by `build_spec`.
+///
+/// # Panics
+/// Methods taking a handle panic if it was issued by another builder.
+pub struct TypedMdpBuilder {
+    id: u64,
+    start: Option<usize>,
+    states: Vec<StateSpec>,
+}
+
+impl Default for TypedMdpBuilder {
+    fn default() -> Self {
+        Self {
+            id: NEXT_BUILDER_ID.fetch_add(1, Ordering::Relaxed),
+            start: None,
+            states: Vec::new(),
+        }
+    }
+}
+
+impl TypedMdpBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a new state and return its handle.
+    /// Terminal flag if this state is the final one
+    pub fn add_state(&mut self, id: impl Into<String>, terminal: bool) -> StateHandle {
+        self.states.push(StateSpec {
+            id: id.into(),
+            terminal: Some(terminal),
+            actions: Some(Vec::new()),
+            entry_reward: None,
+            terminal_reward: None,
+            labels: None,
+            metadata: None,
         });
+        StateHandle {
+            builder: self.id,
+            index: self.states.len() - 1,
+        }
+    }
 
-        Ok(self)
+    /// Define the start state of the MDP
+    pub fn set_start(&mut self, state: StateHandle) -> &mut Self {
+        self.check(state.builder);
+        self.start = Some(state.index);
+        self
+    }
+
+    /// Add an action to a state and return its handle.
+    pub fn add_action(&mut self, state: StateHandle, action_id: impl Into<String>) -> ActionHandle {
+        self.check(state.builder);
+        let actions = self.states[state.index]
+            .actions
+            .get_or_insert_with(Vec::new);
+        actions.push(ActionSpec {
+            id: action_id.into(),
+            outcomes: Vec::new(),
+            cost: None,
+            labels: None,
+            metadata: None,
+            available_when: None,
+        });
+        ActionHandle {
+            builder: self.id,
+            state: state.index,
+            index: actions.len() - 1,
+        }
+    }
+
+    /// Add an outcome leading to `next` to an action.
+    pub fn add_outcome(
+        &mut self,
+        action: ActionHandle,
+        next: StateHandle,
+        prob: f64,
+        reward: f64,
+    ) -> &mut Self {
+        self.check(action.builder);
+        self.check(next.builder);
+        let next = self.states[next.index].id.clone();
+        let actions = self.states[action.state]
+            .actions
+            .get_or_insert_with(Vec::new);
+        actions[action.index].outcomes.push(OutcomeSpec {
+            next,
+            prob: prob.into(),
+            reward: reward.into(),
+            observations: None,
+        });
+        self
+    }
+
+    /// Return the id a state was added with.
+    pub fn state_id(&self, state: StateHandle) -> &str {
+        self.check(state.builder);
+        &self.states[state.index].id
     }
 
     pub fn build_spec(self) -> Result<MdpSpec, MdpError> {
         let start = self.start.ok_or(MdpError::MissingStart)?;
         let spec = MdpSpec {
             version: Some(1),
-            start,
+            params: None,
+            weights: None,
+            include: None,
+            start: self.states[start].id.clone(),
             states: self.states,
         };
         spec.validate()?;
@@ -110,4 +343,11 @@ impl MdpBuilder {
         let spec = self.build_spec()?;
         spec.compile()
     }
+
+    fn check(&self, builder: u64) {
+        assert!(
+            builder == self.id,
+            "handle was issued by a different TypedMdpBuilder"
+        );
+    }
 }
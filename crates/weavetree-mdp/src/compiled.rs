@@ -1,9 +1,40 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
-use crate::{MdpError, MdpSpec};
+use serde::{Deserialize, Serialize};
+use weavetree_core::StateKey as CoreStateKey;
+
+use crate::{MdpError, MdpSpec, Reward, RewardDistribution};
 
 /// Floating point tolerance used when validating probability sums.
-pub(crate) const PROB_TOLERANCE: f64 = 1e-9;
+pub const PROB_TOLERANCE: f64 = 1e-9;
+
+/// Settings for `MdpSpec::compile_with_options` and the `*_with_options` loaders.
+/// The default compiles exactly like `MdpSpec::compile`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompileOptions {
+    /// How far an action's probabilities may sum from 1.0; `PROB_TOLERANCE` by default.
+    pub tolerance: f64,
+    /// Rescale actions whose probabilities sum to within `tolerance` of 1.0 so they sum to
+    /// exactly 1.0, as `MdpSpec::normalized` does, instead of compiling them as written.
+    pub normalize: bool,
+    /// Drop the states unreachable from `start`; see `CompiledMdp::prune_unreachable`.
+    pub prune_unreachable: bool,
+    /// Build alias tables for actions with at least this many outcomes; see
+    /// `CompiledMdp::with_alias_tables`.
+    pub alias_tables: Option<usize>,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: PROB_TOLERANCE,
+            normalize: false,
+            prune_unreachable: false,
+            alias_tables: None,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 /// Dense index for states in a compiled MDP.
@@ -22,6 +53,14 @@ impl From<usize> for StateKey {
     }
 }
 
+/// Search-tree keys that do not fit a `usize` map to an out-of-range index, which every
+/// lookup treats as an unknown state.
+impl From<CoreStateKey> for StateKey {
+    fn from(value: CoreStateKey) -> Self {
+        Self(usize::try_from(value.raw()).unwrap_or(usize::MAX))
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Runtime form of an MDP with resolved state references and precomputed CDFs.
 pub struct CompiledMdp {
@@ -29,30 +68,174 @@ pub struct CompiledMdp {
     states: Vec<StateRec>,
     state_ids: Vec<String>,
     state_id_to_key: HashMap<String, StateKey>,
+    objectives: Vec<String>,
+    weights: Vec<f64>,
+    /// Observation ids, the empty observation first.
+    observation_ids: Vec<String>,
+}
+
+/// Outcome of `CompiledMdp::prune_unreachable`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PruneReport {
+    /// New key of every original state, indexed by the original key; `None` if it was removed.
+    pub remap: Vec<Option<StateKey>>,
+    /// Ids of the removed states, in original order.
+    pub removed: Vec<String>,
+}
+
+impl PruneReport {
+    /// Return the key a state has after pruning, or `None` if it was removed.
+    pub fn new_key(&self, old: StateKey) -> Option<StateKey> {
+        self.remap.get(old.index()).copied().flatten()
+    }
 }
 
 #[derive(Debug, Clone)]
 struct StateRec {
     terminal: bool,
     actions: Vec<ActionRec>,
+    labels: Vec<String>,
+    metadata: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
 struct ActionRec {
+    id: String,
     outcomes: Vec<OutcomeRec>,
     cdf: Vec<f64>,
+    alias: Option<AliasTable>,
+    labels: Vec<String>,
+    metadata: BTreeMap<String, String>,
+}
+
+/// Walker/Vose alias table: column `i` keeps outcome `i` with probability `keep[i]`
+/// and otherwise yields `alias[i]`.
+#[derive(Debug, Clone)]
+struct AliasTable {
+    keep: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    fn from_cdf(cdf: &[f64]) -> Self {
+        let n = cdf.len();
+        let total = cdf.last().copied().unwrap_or(0.0);
+        let mut previous = 0.0;
+        let mut scaled: Vec<f64> = cdf
+            .iter()
+            .map(|cumulative| {
+                let prob = cumulative - previous;
+                previous = *cumulative;
+                prob / total * n as f64
+            })
+            .collect();
+
+        let mut alias: Vec<usize> = (0..n).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|&i| scaled[i] < 1.0);
+        while let (Some(&less), Some(&more)) = (small.last(), large.last()) {
+            small.pop();
+            alias[less] = more;
+            scaled[more] -= 1.0 - scaled[less];
+            if scaled[more] < 1.0 {
+                large.pop();
+                small.push(more);
+            }
+        }
+        // Leftovers differ from 1.0 only by rounding error.
+        for i in small.into_iter().chain(large) {
+            scaled[i] = 1.0;
+        }
+
+        Self {
+            keep: scaled,
+            alias,
+        }
+    }
+
+    fn sample(&self, sample: f64) -> usize {
+        let position = sample * self.keep.len() as f64;
+        let column = (position as usize).min(self.keep.len() - 1);
+        if position - (column as f64) < self.keep[column] {
+            column
+        } else {
+            self.alias[column]
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 struct OutcomeRec {
     next: StateKey,
+    /// Expected reward.
     reward: f64,
+    noise: Option<RewardNoise>,
+    vector: Option<Box<VectorReward>>,
+    /// `(observation index, probability)` pairs; `None` emits the empty observation.
+    observations: Option<Box<[(usize, f64)]>>,
+}
+
+/// Observation distribution of outcomes that declare none.
+const EMPTY_OBSERVATION: [(usize, f64); 1] = [(0, 1.0)];
+
+/// Unweighted parts of a vector reward, kept to re-scalarize under new weights.
+#[derive(Debug, Clone)]
+struct VectorReward {
+    /// Cost and arrival rewards, which are not weighted.
+    base: f64,
+    /// Entry per objective of `CompiledMdp::objectives`, zero where the outcome names none.
+    values: Vec<f64>,
+}
+
+/// Zero-mean deviation from an outcome's expected reward, drawn at every sampled step.
+#[derive(Debug, Clone, Copy)]
+enum RewardNoise {
+    Normal { std: f64 },
+    Uniform { half_width: f64 },
+}
+
+impl RewardNoise {
+    fn from_distribution(distribution: RewardDistribution) -> Option<Self> {
+        match distribution {
+            RewardDistribution::Normal { std, .. } if std > 0.0 => Some(Self::Normal { std }),
+            RewardDistribution::Uniform { low, high } if high > low => Some(Self::Uniform {
+                half_width: (high - low) / 2.0,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Draw a deviation from uniform samples in `[0, 1)`.
+    fn sample(self, uniform: &mut impl FnMut() -> f64) -> f64 {
+        match self {
+            // Box-Muller; `1 - u` keeps the logarithm finite.
+            Self::Normal { std } => {
+                let radius = (-2.0 * (1.0 - uniform()).ln()).sqrt();
+                std * radius * (std::f64::consts::TAU * uniform()).cos()
+            }
+            Self::Uniform { half_width } => half_width * (2.0 * uniform() - 1.0),
+        }
+    }
 }
 
 impl CompiledMdp {
     /// Compile and validate a spec into a fast runtime representation.
     pub(crate) fn from_spec(spec: &MdpSpec) -> Result<Self, MdpError> {
-        spec.validate_with_tolerance(PROB_TOLERANCE)?;
+        Self::from_spec_with_tolerance(spec, PROB_TOLERANCE)
+    }
+
+    /// Compile a spec, accepting probability sums within `tolerance` of 1.0.
+    pub(crate) fn from_spec_with_tolerance(
+        spec: &MdpSpec,
+        tolerance: f64,
+    ) -> Result<Self, MdpError> {
+        let spec = spec.resolve_params()?;
+        spec.validate_with_tolerance(tolerance)?;
+        let empty = BTreeMap::new();
+        let weights = spec.weights.as_ref().unwrap_or(&empty);
+        let objectives: Vec<String> = weights.keys().cloned().collect();
+        let mut observation_ids = vec![String::new()];
+        let mut observation_index = HashMap::from([(String::new(), 0)]);
 
         let mut state_id_to_key = HashMap::with_capacity(spec.states.len());
         let mut state_ids = Vec::with_capacity(spec.states.len());
@@ -79,8 +262,8 @@ impl CompiledMdp {
                 let mut cdf = Vec::with_capacity(action.outcomes.len());
                 let mut cumulative = 0.0_f64;
 
-                for outcome in &action.outcomes {
-                    cumulative += outcome.prob;
+                for (outcome, prob) in action.outcomes.iter().zip(action.resolved_probabilities()) {
+                    cumulative += prob;
                     cdf.push(cumulative);
                     let next = state_id_to_key.get(&outcome.next).copied().ok_or_else(|| {
                         MdpError::UnknownNextState {
@@ -90,16 +273,60 @@ impl CompiledMdp {
                         }
                     })?;
 
+                    let base =
+                        spec.states[next.index()].arrival_reward() - action.cost.unwrap_or(0.0);
+                    let (noise, vector) = match &outcome.reward {
+                        Reward::Fixed(_) => (None, None),
+                        Reward::Distribution(distribution) => {
+                            (RewardNoise::from_distribution(*distribution), None)
+                        }
+                        Reward::Vector(entries) => {
+                            let values = objectives
+                                .iter()
+                                .map(|objective| entries.get(objective).copied().unwrap_or(0.0))
+                                .collect();
+                            (None, Some(Box::new(VectorReward { base, values })))
+                        }
+                    };
+                    let observations = outcome.observations.as_ref().map(|observations| {
+                        observations
+                            .iter()
+                            .filter(|(_, prob)| **prob > 0.0)
+                            .map(|(id, prob)| {
+                                let idx =
+                                    *observation_index.entry(id.clone()).or_insert_with(|| {
+                                        observation_ids.push(id.clone());
+                                        observation_ids.len() - 1
+                                    });
+                                (idx, *prob)
+                            })
+                            .collect()
+                    });
                     outcomes.push(OutcomeRec {
                         next,
-                        reward: outcome.reward,
+                        reward: outcome.reward.scalarized(weights) + base,
+                        noise,
+                        vector,
+                        observations,
                     });
                 }
 
-                actions.push(ActionRec { outcomes, cdf });
+                actions.push(ActionRec {
+                    id: action.id.clone(),
+                    outcomes,
+                    cdf,
+                    alias: None,
+                    labels: action.labels.clone().unwrap_or_default(),
+                    metadata: action.metadata.clone().unwrap_or_default(),
+                });
             }
 
-            states.push(StateRec { terminal, actions });
+            states.push(StateRec {
+                terminal,
+                actions,
+                labels: state.labels.clone().unwrap_or_default(),
+                metadata: state.metadata.clone().unwrap_or_default(),
+            });
         }
 
         Ok(Self {
@@ -107,9 +334,157 @@ impl CompiledMdp {
             states,
             state_ids,
             state_id_to_key,
+            weights: weights.values().copied().collect(),
+            objectives,
+            observation_ids,
         })
     }
 
+    /// Build alias tables for every action with at least `min_outcomes` outcomes, making their
+    /// sampling O(1) instead of a binary search over the CDF.
+    ///
+    /// Alias sampling maps a uniform sample to outcomes differently than the CDF does, so a
+    /// seeded simulator produces different (equally distributed) trajectories with and
+    /// without tables. Tables cost two words per outcome; the search is already fast for a
+    /// handful of outcomes, so a threshold in the hundreds is a sensible default.
+    pub fn with_alias_tables(mut self, min_outcomes: usize) -> Self {
+        for action in self.states.iter_mut().flat_map(|state| &mut state.actions) {
+            action.alias = (!action.outcomes.is_empty() && action.outcomes.len() >= min_outcomes)
+                .then(|| AliasTable::from_cdf(&action.cdf));
+        }
+        self
+    }
+
+    /// Return the states reachable from the start state through outcomes with any probability,
+    /// including the start state, in key order. Terminal states are not expanded.
+    pub fn reachable_states(&self) -> Vec<StateKey> {
+        let mut seen = vec![false; self.states.len()];
+        let mut queue = VecDeque::from([self.start]);
+        seen[self.start.index()] = true;
+        while let Some(key) = queue.pop_front() {
+            let state = &self.states[key.index()];
+            if state.terminal {
+                continue;
+            }
+            for outcome in state.actions.iter().flat_map(|action| &action.outcomes) {
+                if !seen[outcome.next.index()] {
+                    seen[outcome.next.index()] = true;
+                    queue.push_back(outcome.next);
+                }
+            }
+        }
+        seen.iter()
+            .enumerate()
+            .filter(|(_, reached)| **reached)
+            .map(|(idx, _)| StateKey::from(idx))
+            .collect()
+    }
+
+    /// Drop every state `reachable_states` does not return. Kept states are renumbered densely
+    /// in their original order, so keys from before pruning must be translated with the report.
+    pub fn prune_unreachable(self) -> (Self, PruneReport) {
+        let mut remap = vec![None; self.states.len()];
+        for (new_idx, key) in self.reachable_states().into_iter().enumerate() {
+            remap[key.index()] = Some(StateKey::from(new_idx));
+        }
+
+        let mut states = Vec::new();
+        let mut state_ids = Vec::new();
+        let mut removed = Vec::new();
+        for ((mut state, id), new_key) in self.states.into_iter().zip(self.state_ids).zip(&remap) {
+            if new_key.is_none() {
+                removed.push(id);
+                continue;
+            }
+            for outcome in state
+                .actions
+                .iter_mut()
+                .flat_map(|action| &mut action.outcomes)
+            {
+                outcome.next = remap[outcome.next.index()]
+                    .expect("successors of reachable states are reachable");
+            }
+            states.push(state);
+            state_ids.push(id);
+        }
+        let state_id_to_key = state_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.clone(), StateKey::from(idx)))
+            .collect();
+
+        let pruned = Self {
+            start: remap[self.start.index()].expect("the start state is reachable"),
+            states,
+            state_ids,
+            state_id_to_key,
+            objectives: self.objectives,
+            weights: self.weights,
+            observation_ids: self.observation_ids,
+        };
+        (pruned, PruneReport { remap, removed })
+    }
+
+    /// Return the objectives of vector rewards, in the order of `weights`.
+    pub fn objectives(&self) -> &[String] {
+        &self.objectives
+    }
+
+    /// Return the current weight of every objective.
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Re-scalarize vector rewards under new objective weights without recompiling, e.g. to
+    /// sweep trade-offs. Objectives not named in `weights` keep their current weight.
+    pub fn with_weights(mut self, weights: &BTreeMap<String, f64>) -> Result<Self, MdpError> {
+        for (objective, &weight) in weights {
+            let idx = self
+                .objectives
+                .iter()
+                .position(|known| known == objective)
+                .ok_or_else(|| MdpError::UnknownObjective {
+                    objective: objective.clone(),
+                })?;
+            if !weight.is_finite() {
+                return Err(MdpError::InvalidObjectiveWeight {
+                    objective: objective.clone(),
+                    value: weight,
+                });
+            }
+            self.weights[idx] = weight;
+        }
+
+        let outcomes = self
+            .states
+            .iter_mut()
+            .flat_map(|state| &mut state.actions)
+            .flat_map(|action| &mut action.outcomes);
+        for outcome in outcomes {
+            if let Some(vector) = &outcome.vector {
+                let weighted: f64 = vector
+                    .values
+                    .iter()
+                    .zip(&self.weights)
+                    .map(|(v, w)| v * w)
+                    .sum();
+                outcome.reward = vector.base + weighted;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Return every observation outcomes can emit, the empty observation `""` first.
+    /// Observation keys used by `BeliefSimulator` index this slice.
+    pub fn observations(&self) -> &[String] {
+        &self.observation_ids
+    }
+
+    /// Convert an observation id into its key.
+    pub fn observation_key(&self, id: &str) -> Option<usize> {
+        self.observation_ids.iter().position(|known| known == id)
+    }
+
     /// Return the start state key.
     pub fn start(&self) -> StateKey {
         self.start
@@ -142,16 +517,116 @@ impl CompiledMdp {
         self.state_id_to_key.get(id).copied()
     }
 
+    /// Return the labels of a state, empty if it has none.
+    pub fn state_labels(&self, key: StateKey) -> Option<&[String]> {
+        self.states
+            .get(key.index())
+            .map(|state| state.labels.as_slice())
+    }
+
+    /// Return the value of a state's metadata entry.
+    pub fn state_metadata(&self, key: StateKey, name: &str) -> Option<&str> {
+        self.states
+            .get(key.index())?
+            .metadata
+            .get(name)
+            .map(String::as_str)
+    }
+
+    /// Return every state carrying `label`, in key order.
+    pub fn states_with_label(&self, label: &str) -> Vec<StateKey> {
+        self.states
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| state.labels.iter().any(|l| l == label))
+            .map(|(idx, _)| StateKey::from(idx))
+            .collect()
+    }
+
+    /// Return the id an action has in the spec, e.g. to print a recommended action.
+    pub fn action_name(&self, key: StateKey, action_id: usize) -> Option<&str> {
+        self.states
+            .get(key.index())?
+            .actions
+            .get(action_id)
+            .map(|action| action.id.as_str())
+    }
+
+    /// Return the labels of an action, empty if it has none.
+    pub fn action_labels(&self, key: StateKey, action_id: usize) -> Option<&[String]> {
+        self.states
+            .get(key.index())?
+            .actions
+            .get(action_id)
+            .map(|action| action.labels.as_slice())
+    }
+
+    /// Return the value of an action's metadata entry.
+    pub fn action_metadata(&self, key: StateKey, action_id: usize, name: &str) -> Option<&str> {
+        self.states
+            .get(key.index())?
+            .actions
+            .get(action_id)?
+            .metadata
+            .get(name)
+            .map(String::as_str)
+    }
+
     /// Sample one transition for `(state_key, action_id)` using a uniform sample in `[0, 1)`.
+    /// Outcomes with a reward distribution draw further uniform samples from `uniform`.
     pub(crate) fn sample_transition(
         &self,
         state_key: StateKey,
         action_id: usize,
         sample: f64,
+        mut uniform: impl FnMut() -> f64,
     ) -> Option<(StateKey, f64, bool)> {
+        let (next, reward, terminal, _) =
+            self.sample_outcome(state_key, action_id, sample, &mut uniform)?;
+        Some((next, reward, terminal))
+    }
+
+    /// Like `sample_transition`, but also draw the observation key the outcome emits.
+    /// Only outcomes with more than one possible observation draw a further uniform sample,
+    /// and terminal states emit the empty observation.
+    pub(crate) fn sample_observed_transition(
+        &self,
+        state_key: StateKey,
+        action_id: usize,
+        sample: f64,
+        mut uniform: impl FnMut() -> f64,
+    ) -> Option<(StateKey, f64, bool, usize)> {
+        let (next, reward, terminal, outcome) =
+            self.sample_outcome(state_key, action_id, sample, &mut uniform)?;
+        let observation = match outcome.and_then(|outcome| outcome.observations.as_deref()) {
+            None => 0,
+            Some([(only, _)]) => *only,
+            Some(observations) => {
+                let mut remaining = uniform();
+                observations
+                    .iter()
+                    .find(|(_, prob)| {
+                        remaining -= prob;
+                        remaining < 0.0
+                    })
+                    .or(observations.last())
+                    .map_or(0, |(observation, _)| *observation)
+            }
+        };
+        Some((next, reward, terminal, observation))
+    }
+
+    /// Sample a transition and return it with the chosen outcome, `None` for terminal states.
+    fn sample_outcome(
+        &self,
+        state_key: StateKey,
+        action_id: usize,
+        sample: f64,
+        uniform: &mut impl FnMut() -> f64,
+    ) -> Option<(StateKey, f64, bool, Option<&OutcomeRec>)> {
         let state = self.states.get(state_key.index())?;
         if state.terminal {
-            return Some((state_key, 0.0, true));
+            return Some((state_key, 0.0, true, None));
         }
 
         let action = state.actions.get(action_id)?;
@@ -159,12 +634,102 @@ impl CompiledMdp {
             return None;
         }
 
-        let mut chosen_idx = action.cdf.partition_point(|p| *p < sample);
-        if chosen_idx >= action.outcomes.len() {
-            chosen_idx = action.outcomes.len() - 1;
-        }
+        let chosen_idx = match &action.alias {
+            Some(table) => table.sample(sample),
+            None => action
+                .cdf
+                .partition_point(|p| *p < sample)
+                .min(action.outcomes.len() - 1),
+        };
 
         let outcome = &action.outcomes[chosen_idx];
+        let (next, reward, terminal) = self.outcome_transition(outcome)?;
+        let deviation = outcome.noise.map_or(0.0, |noise| noise.sample(uniform));
+        Some((next, reward + deviation, terminal, Some(outcome)))
+    }
+
+    /// Return the highest-probability transition for `(state_key, action_id)` without sampling.
+    /// Ties go to the outcome listed first, and reward distributions yield their mean.
+    /// Terminal states return a no-op terminal transition, and invalid inputs return `None`.
+    pub fn most_likely_transition(
+        &self,
+        state_key: StateKey,
+        action_id: usize,
+    ) -> Option<(StateKey, f64, bool)> {
+        let state = self.states.get(state_key.index())?;
+        if state.terminal {
+            return Some((state_key, 0.0, true));
+        }
+
+        let action = state.actions.get(action_id)?;
+        let mut best: Option<(usize, f64)> = None;
+        let mut previous = 0.0;
+        for (idx, cumulative) in action.cdf.iter().enumerate() {
+            let prob = cumulative - previous;
+            previous = *cumulative;
+            if best.is_none_or(|(_, best_prob)| prob > best_prob) {
+                best = Some((idx, prob));
+            }
+        }
+
+        let (chosen_idx, _) = best?;
+        self.outcome_transition(&action.outcomes[chosen_idx])
+    }
+
+    /// Iterate `(next, probability, reward)` over the outcomes of `(state_key, action_id)`,
+    /// empty for invalid inputs.
+    pub(crate) fn transitions(
+        &self,
+        state_key: StateKey,
+        action_id: usize,
+    ) -> impl Iterator<Item = (StateKey, f64, f64)> + '_ {
+        self.weighted_outcomes(state_key, action_id)
+            .map(|(outcome, prob)| (outcome.next, prob, outcome.reward))
+    }
+
+    /// Iterate `(next, probability, observations)` over the outcomes of
+    /// `(state_key, action_id)`, where `observations` holds `(observation key, probability)`
+    /// pairs; empty for invalid inputs.
+    pub(crate) fn observed_transitions(
+        &self,
+        state_key: StateKey,
+        action_id: usize,
+    ) -> impl Iterator<Item = (StateKey, f64, &[(usize, f64)])> + '_ {
+        self.weighted_outcomes(state_key, action_id)
+            .map(|(outcome, prob)| {
+                let observations = outcome
+                    .observations
+                    .as_deref()
+                    .unwrap_or(&EMPTY_OBSERVATION);
+                (outcome.next, prob, observations)
+            })
+    }
+
+    /// Iterate the outcomes of `(state_key, action_id)` with their probabilities.
+    fn weighted_outcomes(
+        &self,
+        state_key: StateKey,
+        action_id: usize,
+    ) -> impl Iterator<Item = (&OutcomeRec, f64)> + '_ {
+        let action = self
+            .states
+            .get(state_key.index())
+            .and_then(|state| state.actions.get(action_id));
+        action.into_iter().flat_map(|action| {
+            let mut previous = 0.0;
+            action
+                .outcomes
+                .iter()
+                .zip(&action.cdf)
+                .map(move |(outcome, cumulative)| {
+                    let prob = cumulative - previous;
+                    previous = *cumulative;
+                    (outcome, prob)
+                })
+        })
+    }
+
+    fn outcome_transition(&self, outcome: &OutcomeRec) -> Option<(StateKey, f64, bool)> {
         let next_terminal = self.states.get(outcome.next.index())?.terminal;
         Some((outcome.next, outcome.reward, next_terminal))
     }
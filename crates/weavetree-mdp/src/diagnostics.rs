@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use weavetree_core::Tree;
+
+use crate::{CompiledMdp, StateKey};
+
+/// Exact action values of a `CompiledMdp`, computed by `value_iteration`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptimalValues {
+    /// `q[state][action]`: expected discounted return of taking `action` in `state` and
+    /// acting optimally afterwards. Terminal states and states without actions have no entries.
+    pub q: Vec<Vec<f64>>,
+    /// Sweeps performed.
+    pub iterations: usize,
+    /// Largest change of a state value in the last sweep.
+    pub residual: f64,
+}
+
+impl OptimalValues {
+    /// Return the optimal value of a state, `0.0` if it is terminal, has no actions, or is unknown.
+    pub fn value(&self, state: StateKey) -> f64 {
+        self.q
+            .get(state.index())
+            .map_or(0.0, |actions| state_value(actions))
+    }
+
+    /// Return the optimal action value of `(state, action)`.
+    pub fn q(&self, state: StateKey, action: usize) -> Option<f64> {
+        self.q.get(state.index())?.get(action).copied()
+    }
+
+    /// Return an optimal action of a state; ties go to the lower action index.
+    pub fn best_action(&self, state: StateKey) -> Option<usize> {
+        let actions = self.q.get(state.index())?;
+        (0..actions.len()).reduce(|best, action| {
+            if actions[action] > actions[best] {
+                action
+            } else {
+                best
+            }
+        })
+    }
+}
+
+fn state_value(actions: &[f64]) -> f64 {
+    actions.iter().copied().reduce(f64::max).unwrap_or(0.0)
+}
+
+/// Compute exact Q* by value iteration with discount `gamma`, stopping once no state value
+/// changes by more than `tolerance` or after `max_iterations` sweeps.
+///
+/// Rewards match `MdpSimulator::step`, terminal states are worth `0.0`, and so are states
+/// without actions, where episodes end. With `gamma = 1.0` this converges only if every
+/// policy eventually reaches such a state, so check `residual` afterwards.
+pub fn value_iteration(
+    mdp: &CompiledMdp,
+    gamma: f64,
+    tolerance: f64,
+    max_iterations: usize,
+) -> OptimalValues {
+    let state_count = mdp.state_count();
+    let keys = || (0..state_count).map(StateKey::from);
+    let num_actions = |state: StateKey| {
+        if mdp.is_terminal(state) == Some(true) {
+            0
+        } else {
+            mdp.num_actions(state).unwrap_or(0)
+        }
+    };
+
+    let mut values = vec![0.0; state_count];
+    let mut q: Vec<Vec<f64>> = keys().map(|state| vec![0.0; num_actions(state)]).collect();
+    let mut iterations = 0;
+    let mut residual = f64::INFINITY;
+
+    while iterations < max_iterations && residual > tolerance {
+        for state in keys() {
+            for (action, value) in q[state.index()].iter_mut().enumerate() {
+                *value = mdp
+                    .transitions(state, action)
+                    .map(|(next, prob, reward)| prob * (reward + gamma * values[next.index()]))
+                    .sum();
+            }
+        }
+        residual = 0.0;
+        for (value, actions) in values.iter_mut().zip(&q) {
+            let updated = state_value(actions);
+            residual = f64::max(residual, (updated - *value).abs());
+            *value = updated;
+        }
+        iterations += 1;
+    }
+
+    OptimalValues {
+        q,
+        iterations,
+        residual,
+    }
+}
+
+/// Tree estimate and exact value of one action at one node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionQComparison {
+    pub action_id: usize,
+    pub visits: u64,
+    /// Mean return the search observed; `None` for unvisited actions.
+    pub estimate: Option<f64>,
+    /// Exact Q*.
+    pub optimal: f64,
+    /// `estimate - optimal`; `None` for unvisited actions.
+    pub error: Option<f64>,
+    /// `V*(state) - Q*(state, action)`: what committing to this action loses.
+    pub regret: f64,
+}
+
+/// Comparison of every action edge of one tree node against Q*.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeQComparison {
+    pub node_id: usize,
+    pub state_id: String,
+    pub depth: u64,
+    pub actions: Vec<ActionQComparison>,
+    /// Regret of the most visited action, the one the search would recommend;
+    /// `None` if no action was visited.
+    pub recommended_regret: Option<f64>,
+}
+
+/// Result of `compare_to_optimal`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QComparison {
+    pub root_node_id: usize,
+    /// Compared nodes, root first, then the rest in tree arena order.
+    pub nodes: Vec<NodeQComparison>,
+    /// Largest `|error|` over all visited actions of the compared nodes.
+    pub max_abs_error: f64,
+}
+
+impl QComparison {
+    /// Return the root comparison, if the root is a comparable state.
+    pub fn root(&self) -> Option<&NodeQComparison> {
+        self.nodes
+            .first()
+            .filter(|node| node.node_id == self.root_node_id)
+    }
+}
+
+/// Compare a tree's Q estimates against exact values from `value_iteration` over `mdp`.
+///
+/// The tree must come from searching an `MdpSimulator` over the same `mdp`, whose search keys
+/// are compiled state indices. Only the root is compared unless `include_interior` is set,
+/// in which case every node with a visited action is. Nodes whose state is terminal or
+/// unknown to `mdp` are skipped. Tree estimates are only comparable to Q* when the search
+/// discounted with the same `gamma` and its horizon was long enough not to cut returns short.
+pub fn compare_to_optimal<P>(
+    tree: &Tree<P>,
+    mdp: &CompiledMdp,
+    optimal: &OptimalValues,
+    include_interior: bool,
+) -> QComparison {
+    let snapshot = tree.snapshot();
+    let root = snapshot
+        .nodes
+        .iter()
+        .filter(|node| node.node_id == snapshot.root_node_id);
+    let interior = snapshot.nodes.iter().filter(|node| {
+        include_interior
+            && node.node_id != snapshot.root_node_id
+            && node.edges.iter().any(|edge| edge.visits > 0)
+    });
+
+    let mut nodes = Vec::new();
+    let mut max_abs_error: f64 = 0.0;
+    for node in root.chain(interior) {
+        let Some(state) = usize::try_from(node.state_key).ok().map(StateKey::from) else {
+            continue;
+        };
+        let (Some(state_id), Some(optimal_q)) = (mdp.state_id(state), optimal.q.get(state.index()))
+        else {
+            continue;
+        };
+        if optimal_q.is_empty() {
+            continue;
+        }
+        let best = state_value(optimal_q);
+
+        let actions: Vec<ActionQComparison> = node
+            .edges
+            .iter()
+            .filter(|edge| edge.legal)
+            .filter_map(|edge| {
+                let optimal = *optimal_q.get(edge.action_id)?;
+                let estimate = (edge.visits > 0).then_some(edge.q);
+                let error = estimate.map(|estimate| estimate - optimal);
+                if let Some(error) = error {
+                    max_abs_error = max_abs_error.max(error.abs());
+                }
+                Some(ActionQComparison {
+                    action_id: edge.action_id,
+                    visits: edge.visits,
+                    estimate,
+                    optimal,
+                    error,
+                    regret: best - optimal,
+                })
+            })
+            .collect();
+        let recommended_regret = actions
+            .iter()
+            .filter(|action| action.visits > 0)
+            .reduce(|chosen, action| {
+                if action.visits > chosen.visits {
+                    action
+                } else {
+                    chosen
+                }
+            })
+            .map(|action| action.regret);
+
+        nodes.push(NodeQComparison {
+            node_id: node.node_id,
+            state_id: state_id.to_string(),
+            depth: node.depth,
+            actions,
+            recommended_regret,
+        });
+    }
+
+    QComparison {
+        root_node_id: snapshot.root_node_id,
+        nodes,
+        max_abs_error,
+    }
+}
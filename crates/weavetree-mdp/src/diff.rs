@@ -0,0 +1,308 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ActionSpec, MdpError, MdpSpec, PROB_TOLERANCE, StateSpec};
+
+/// Structural difference between two specs, from `MdpSpec::diff`.
+///
+/// States and actions are matched by id and outcomes by their `next` state, so reordering
+/// any of them is not a change. Outcomes that share a `next` state are merged into one with
+/// their total probability and probability-weighted mean reward.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpecDiff {
+    /// Start state before and after, if it changed.
+    pub start: Option<(String, String)>,
+    /// Ids of states only the new spec has, in its order.
+    pub added_states: Vec<String>,
+    /// Ids of states only the old spec has, in its order.
+    pub removed_states: Vec<String>,
+    /// States both specs have that differ, in the old spec's order.
+    pub changed_states: Vec<StateDiff>,
+}
+
+/// Changes to one state present in both specs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateDiff {
+    pub id: String,
+    /// Terminal flag before and after, if it changed.
+    pub terminal: Option<(bool, bool)>,
+    /// Entry reward before and after, if it changed; a missing reward counts as `0.0`.
+    pub entry_reward: Option<(f64, f64)>,
+    /// Terminal reward before and after, if it changed; a missing reward counts as `0.0`.
+    pub terminal_reward: Option<(f64, f64)>,
+    pub added_actions: Vec<String>,
+    pub removed_actions: Vec<String>,
+    pub changed_actions: Vec<ActionDiff>,
+}
+
+/// Changes to one action present in both specs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionDiff {
+    pub id: String,
+    /// Cost before and after, if it changed; a missing cost counts as `0.0`.
+    pub cost: Option<(f64, f64)>,
+    /// Added, removed, and changed outcomes, ordered by `next` state.
+    pub outcomes: Vec<OutcomeDiff>,
+}
+
+/// Change of the transition into one `next` state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutcomeDiff {
+    pub next: String,
+    /// Transition in the old spec; `None` if the outcome was added.
+    pub before: Option<OutcomeSummary>,
+    /// Transition in the new spec; `None` if the outcome was removed.
+    pub after: Option<OutcomeSummary>,
+}
+
+/// Probability and expected reward of the transition into one `next` state.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OutcomeSummary {
+    pub prob: f64,
+    pub reward: f64,
+}
+
+impl OutcomeDiff {
+    /// Return the change in probability; a missing side counts as `0.0`.
+    pub fn prob_delta(&self) -> f64 {
+        self.after.map_or(0.0, |o| o.prob) - self.before.map_or(0.0, |o| o.prob)
+    }
+
+    /// Return the change in expected reward, or `None` if the outcome was added or removed.
+    pub fn reward_delta(&self) -> Option<f64> {
+        Some(self.after?.reward - self.before?.reward)
+    }
+}
+
+impl SpecDiff {
+    /// Return whether the specs are structurally equal.
+    pub fn is_empty(&self) -> bool {
+        self.start.is_none()
+            && self.added_states.is_empty()
+            && self.removed_states.is_empty()
+            && self.changed_states.is_empty()
+    }
+}
+
+impl StateDiff {
+    fn has_changes(&self) -> bool {
+        self.terminal.is_some()
+            || self.entry_reward.is_some()
+            || self.terminal_reward.is_some()
+            || !self.added_actions.is_empty()
+            || !self.removed_actions.is_empty()
+            || !self.changed_actions.is_empty()
+    }
+}
+
+impl fmt::Display for SpecDiff {
+    /// One line per change: `+` for added, `-` for removed, and `~` for changed items.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((before, after)) = &self.start {
+            writeln!(f, "~ start: {before} -> {after}")?;
+        }
+        for id in &self.added_states {
+            writeln!(f, "+ state {id}")?;
+        }
+        for id in &self.removed_states {
+            writeln!(f, "- state {id}")?;
+        }
+        for state in &self.changed_states {
+            let id = &state.id;
+            if let Some((before, after)) = state.terminal {
+                writeln!(f, "~ state {id}: terminal {before} -> {after}")?;
+            }
+            if let Some((before, after)) = state.entry_reward {
+                writeln!(f, "~ state {id}: entry_reward {before} -> {after}")?;
+            }
+            if let Some((before, after)) = state.terminal_reward {
+                writeln!(f, "~ state {id}: terminal_reward {before} -> {after}")?;
+            }
+            for action in &state.added_actions {
+                writeln!(f, "+ action {id}/{action}")?;
+            }
+            for action in &state.removed_actions {
+                writeln!(f, "- action {id}/{action}")?;
+            }
+            for action in &state.changed_actions {
+                let action_id = &action.id;
+                if let Some((before, after)) = action.cost {
+                    writeln!(f, "~ action {id}/{action_id}: cost {before} -> {after}")?;
+                }
+                for outcome in &action.outcomes {
+                    let next = &outcome.next;
+                    match (outcome.before, outcome.after) {
+                        (None, Some(after)) => writeln!(
+                            f,
+                            "+ outcome {id}/{action_id} -> {next}: prob {}, reward {}",
+                            after.prob, after.reward
+                        )?,
+                        (Some(before), None) => writeln!(
+                            f,
+                            "- outcome {id}/{action_id} -> {next}: prob {}, reward {}",
+                            before.prob, before.reward
+                        )?,
+                        (Some(before), Some(after)) => writeln!(
+                            f,
+                            "~ outcome {id}/{action_id} -> {next}: prob {} -> {}, reward {} -> {}",
+                            before.prob, after.prob, before.reward, after.reward
+                        )?,
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MdpSpec {
+    /// Compare this spec against `other`, the newer version, after resolving `params` in
+    /// both. Numbers within `PROB_TOLERANCE` of each other count as equal, and vector rewards
+    /// are scalarized by each spec's own weights.
+    pub fn diff(&self, other: &MdpSpec) -> Result<SpecDiff, MdpError> {
+        let old = self.resolve_params()?;
+        let new = other.resolve_params()?;
+        let empty = BTreeMap::new();
+        let old_weights = old.weights.as_ref().unwrap_or(&empty);
+        let new_weights = new.weights.as_ref().unwrap_or(&empty);
+
+        let old_states: HashMap<&str, &StateSpec> =
+            old.states.iter().map(|s| (s.id.as_str(), s)).collect();
+        let new_states: HashMap<&str, &StateSpec> =
+            new.states.iter().map(|s| (s.id.as_str(), s)).collect();
+
+        let mut diff = SpecDiff {
+            start: (old.start != new.start).then(|| (old.start.clone(), new.start.clone())),
+            added_states: new
+                .states
+                .iter()
+                .filter(|s| !old_states.contains_key(s.id.as_str()))
+                .map(|s| s.id.clone())
+                .collect(),
+            removed_states: old
+                .states
+                .iter()
+                .filter(|s| !new_states.contains_key(s.id.as_str()))
+                .map(|s| s.id.clone())
+                .collect(),
+            changed_states: Vec::new(),
+        };
+
+        for before in &old.states {
+            let Some(after) = new_states.get(before.id.as_str()) else {
+                continue;
+            };
+            let no_actions = Vec::new();
+            let old_actions = before.actions.as_ref().unwrap_or(&no_actions);
+            let new_actions = after.actions.as_ref().unwrap_or(&no_actions);
+            let old_by_id: HashMap<&str, &ActionSpec> =
+                old_actions.iter().map(|a| (a.id.as_str(), a)).collect();
+            let new_by_id: HashMap<&str, &ActionSpec> =
+                new_actions.iter().map(|a| (a.id.as_str(), a)).collect();
+
+            let terminal = (
+                before.terminal.unwrap_or(false),
+                after.terminal.unwrap_or(false),
+            );
+            let state = StateDiff {
+                id: before.id.clone(),
+                terminal: (terminal.0 != terminal.1).then_some(terminal),
+                entry_reward: changed(before.entry_reward, after.entry_reward),
+                terminal_reward: changed(before.terminal_reward, after.terminal_reward),
+                added_actions: new_actions
+                    .iter()
+                    .filter(|a| !old_by_id.contains_key(a.id.as_str()))
+                    .map(|a| a.id.clone())
+                    .collect(),
+                removed_actions: old_actions
+                    .iter()
+                    .filter(|a| !new_by_id.contains_key(a.id.as_str()))
+                    .map(|a| a.id.clone())
+                    .collect(),
+                changed_actions: old_actions
+                    .iter()
+                    .filter_map(|action| {
+                        let other = new_by_id.get(action.id.as_str())?;
+                        let diff = ActionDiff {
+                            id: action.id.clone(),
+                            cost: changed(action.cost, other.cost),
+                            outcomes: outcome_diffs(
+                                summarize(action, old_weights),
+                                summarize(other, new_weights),
+                            ),
+                        };
+                        (diff.cost.is_some() || !diff.outcomes.is_empty()).then_some(diff)
+                    })
+                    .collect(),
+            };
+            if state.has_changes() {
+                diff.changed_states.push(state);
+            }
+        }
+
+        Ok(diff)
+    }
+}
+
+/// Merge an action's outcomes by `next` state.
+fn summarize<'a>(
+    action: &'a ActionSpec,
+    weights: &BTreeMap<String, f64>,
+) -> BTreeMap<&'a str, OutcomeSummary> {
+    let mut merged: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    for (outcome, prob) in action.outcomes.iter().zip(action.resolved_probabilities()) {
+        let (total, weighted) = merged.entry(outcome.next.as_str()).or_default();
+        *total += prob;
+        *weighted += prob * outcome.reward.scalarized(weights);
+    }
+    merged
+        .into_iter()
+        .map(|(next, (prob, weighted))| {
+            let reward = if prob > 0.0 { weighted / prob } else { 0.0 };
+            (next, OutcomeSummary { prob, reward })
+        })
+        .collect()
+}
+
+fn outcome_diffs(
+    old: BTreeMap<&str, OutcomeSummary>,
+    new: BTreeMap<&str, OutcomeSummary>,
+) -> Vec<OutcomeDiff> {
+    let mut nexts: Vec<&str> = old.keys().chain(new.keys()).copied().collect();
+    nexts.sort_unstable();
+    nexts.dedup();
+    nexts
+        .into_iter()
+        .filter_map(|next| {
+            let before = old.get(next).copied();
+            let after = new.get(next).copied();
+            if let (Some(b), Some(a)) = (before, after)
+                && !differs(b.prob, a.prob)
+                && !differs(b.reward, a.reward)
+            {
+                return None;
+            }
+            Some(OutcomeDiff {
+                next: next.to_string(),
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+/// Return `(before, after)` if two optional numbers differ, treating `None` as `0.0`.
+fn changed(before: Option<f64>, after: Option<f64>) -> Option<(f64, f64)> {
+    let (before, after) = (before.unwrap_or(0.0), after.unwrap_or(0.0));
+    differs(before, after).then_some((before, after))
+}
+
+fn differs(a: f64, b: f64) -> bool {
+    if a.is_nan() || b.is_nan() {
+        return a.is_nan() != b.is_nan();
+    }
+    a != b && (a - b).abs() > PROB_TOLERANCE
+}
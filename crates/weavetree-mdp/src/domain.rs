@@ -1,5 +1,7 @@
 use std::hash::Hash;
 
+use serde::{Deserialize, Serialize};
+
 /// Generic interface for user-defined MDP domains with arbitrary state types.
 pub trait MdpDomain {
     type State: Clone + Eq + Hash;
@@ -15,4 +17,134 @@ pub trait MdpDomain {
 
     /// Sample one transition using a uniform random sample in `[0, 1)`.
     fn step(&self, state: &Self::State, action_id: usize, sample: f64) -> (Self::State, f64, bool);
+
+    /// Return a readable name for an action, used in reports such as labelled snapshots.
+    /// Defaults to the action index.
+    fn action_label(&self, _state: &Self::State, action_id: usize) -> String {
+        action_id.to_string()
+    }
+
+    /// Return the deterministic effect of an action as `(afterstate, reward)`, before chance
+    /// resolves it, e.g. a 2048 board after sliding and before a tile spawns. `None`, the
+    /// default, means the action has no separate afterstate.
+    ///
+    /// Domains implementing this should keep `step` equal to `afterstate` followed by
+    /// `resolve_chance`; `AfterstateDomain` exposes the split to the search.
+    fn afterstate(&self, _state: &Self::State, _action_id: usize) -> Option<(Self::State, f64)> {
+        None
+    }
+
+    /// Sample how chance resolves an afterstate using a uniform random sample in `[0, 1)`.
+    /// The default leaves it unchanged for reward `0`.
+    fn resolve_chance(&self, afterstate: &Self::State, _sample: f64) -> (Self::State, f64, bool) {
+        (afterstate.clone(), 0.0, self.is_terminal(afterstate))
+    }
+}
+
+/// State of an `AfterstateDomain`: a decision state of the wrapped domain, or an afterstate
+/// waiting for chance.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AfterstatePhase<S> {
+    Decision(S),
+    Afterstate(S),
+}
+
+/// Adapter that splits every transition of a domain with afterstates into two search steps:
+/// the action leads to its afterstate, whose single action resolves chance.
+///
+/// Actions reaching the same afterstate then reach the same state key, so interned
+/// simulators share everything keyed by state, such as cached rollouts and greedy policies.
+/// Actions whose `afterstate` is `None` step the wrapped domain directly. Each transition
+/// takes two tree steps, so discounting with `gamma < 1` counts it twice.
+#[derive(Debug, Clone)]
+pub struct AfterstateDomain<D> {
+    inner: D,
+}
+
+impl<D: MdpDomain> AfterstateDomain<D> {
+    pub fn new(inner: D) -> Self {
+        Self { inner }
+    }
+
+    /// Borrow the wrapped domain.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+}
+
+impl<D: MdpDomain> MdpDomain for AfterstateDomain<D> {
+    type State = AfterstatePhase<D::State>;
+
+    fn start_state(&self) -> Self::State {
+        AfterstatePhase::Decision(self.inner.start_state())
+    }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        match state {
+            AfterstatePhase::Decision(state) => self.inner.is_terminal(state),
+            AfterstatePhase::Afterstate(_) => false,
+        }
+    }
+
+    fn num_actions(&self, state: &Self::State) -> usize {
+        match state {
+            AfterstatePhase::Decision(state) => self.inner.num_actions(state),
+            AfterstatePhase::Afterstate(_) => 1,
+        }
+    }
+
+    fn action_label(&self, state: &Self::State, action_id: usize) -> String {
+        match state {
+            AfterstatePhase::Decision(state) => self.inner.action_label(state, action_id),
+            AfterstatePhase::Afterstate(_) => "chance".to_string(),
+        }
+    }
+
+    fn step(&self, state: &Self::State, action_id: usize, sample: f64) -> (Self::State, f64, bool) {
+        match state {
+            AfterstatePhase::Decision(state) => match self.inner.afterstate(state, action_id) {
+                Some((afterstate, reward)) => {
+                    (AfterstatePhase::Afterstate(afterstate), reward, false)
+                }
+                None => {
+                    let (next, reward, terminal) = self.inner.step(state, action_id, sample);
+                    (AfterstatePhase::Decision(next), reward, terminal)
+                }
+            },
+            AfterstatePhase::Afterstate(afterstate) => {
+                let (next, reward, terminal) = self.inner.resolve_chance(afterstate, sample);
+                (AfterstatePhase::Decision(next), reward, terminal)
+            }
+        }
+    }
+}
+
+/// Interface for turn-based multi-agent domains, where one player acts per state and every
+/// transition rewards each player separately.
+pub trait TurnBasedDomain {
+    type State: Clone + Eq + Hash;
+
+    /// Return how many players take part; players are numbered `0..num_players`.
+    fn num_players(&self) -> usize;
+
+    /// Return the initial state of the domain.
+    fn start_state(&self) -> Self::State;
+
+    /// Return whether a state is terminal.
+    fn is_terminal(&self, state: &Self::State) -> bool;
+
+    /// Return the player choosing the action in a state.
+    fn current_player(&self, state: &Self::State) -> usize;
+
+    /// Return the number of available actions for a state.
+    fn num_actions(&self, state: &Self::State) -> usize;
+
+    /// Sample one transition using a uniform random sample in `[0, 1)`, with one reward
+    /// per player.
+    fn step(
+        &self,
+        state: &Self::State,
+        action_id: usize,
+        sample: f64,
+    ) -> (Self::State, Vec<f64>, bool);
 }
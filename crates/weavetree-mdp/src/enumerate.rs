@@ -0,0 +1,100 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+
+use crate::{ActionSpec, MdpDomain, MdpError, MdpSpec, OutcomeSpec, StateSpec};
+
+/// Explore every state of a finite domain reachable from its start state, breadth-first,
+/// into a flat spec that can be solved exactly, e.g. by `value_iteration`, or saved.
+///
+/// `outcomes(state, action_id)` lists every `(next, probability, reward)` of an action, since
+/// `MdpDomain::step` only samples one. States are named by their `Debug` form and actions
+/// `a0..`; whether a state is terminal comes from `MdpDomain::is_terminal`. Fails with
+/// `MdpError::TooManyStates` once more than `max_states` states are found, and with
+/// `MdpError::DuplicateStateId` if two distinct states print the same.
+pub fn enumerate_domain<D, F>(
+    domain: &D,
+    max_states: usize,
+    mut outcomes: F,
+) -> Result<MdpSpec, MdpError>
+where
+    D: MdpDomain,
+    D::State: Debug,
+    F: FnMut(&D::State, usize) -> Vec<(D::State, f64, f64)>,
+{
+    let mut ids: HashMap<D::State, String> = HashMap::new();
+    let mut taken_ids = HashSet::new();
+    let mut queue = VecDeque::new();
+    let mut discover =
+        |state: D::State, queue: &mut VecDeque<D::State>| -> Result<String, MdpError> {
+            if let Some(id) = ids.get(&state) {
+                return Ok(id.clone());
+            }
+            if ids.len() == max_states {
+                return Err(MdpError::TooManyStates { limit: max_states });
+            }
+            let id = format!("{state:?}");
+            if !taken_ids.insert(id.clone()) {
+                return Err(MdpError::DuplicateStateId { id });
+            }
+            ids.insert(state.clone(), id.clone());
+            queue.push_back(state);
+            Ok(id)
+        };
+
+    let start = discover(domain.start_state(), &mut queue)?;
+    let mut states = Vec::new();
+    while let Some(state) = queue.pop_front() {
+        let id = format!("{state:?}");
+        if domain.is_terminal(&state) {
+            states.push(StateSpec {
+                id,
+                terminal: Some(true),
+                actions: None,
+                entry_reward: None,
+                terminal_reward: None,
+                labels: None,
+                metadata: None,
+            });
+            continue;
+        }
+
+        let mut actions = Vec::new();
+        for action_id in 0..domain.num_actions(&state) {
+            let mut action_outcomes = Vec::new();
+            for (next, prob, reward) in outcomes(&state, action_id) {
+                action_outcomes.push(OutcomeSpec {
+                    next: discover(next, &mut queue)?,
+                    prob: prob.into(),
+                    reward: reward.into(),
+                    observations: None,
+                });
+            }
+            actions.push(ActionSpec {
+                id: format!("a{action_id}"),
+                outcomes: action_outcomes,
+                cost: None,
+                labels: None,
+                metadata: None,
+                available_when: None,
+            });
+        }
+        states.push(StateSpec {
+            id,
+            terminal: None,
+            actions: Some(actions),
+            entry_reward: None,
+            terminal_reward: None,
+            labels: None,
+            metadata: None,
+        });
+    }
+
+    Ok(MdpSpec {
+        version: Some(1),
+        params: None,
+        weights: None,
+        include: None,
+        start,
+        states,
+    })
+}
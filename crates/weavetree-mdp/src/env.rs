@@ -0,0 +1,212 @@
+use rand::{RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use weavetree_core::{ActionId, StateKey as CoreStateKey, StateKeyValue};
+
+use crate::{DomainSimulator, EpisodeSimulator, KeyInterner, MdpDomain, MdpSimulator, StateKey};
+
+/// Shape of an action or observation space, in Gymnasium terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Space {
+    /// Integers `0..n`, like `gymnasium.spaces.Discrete(n)`.
+    Discrete { n: usize },
+    /// Non-negative integer keys without a known bound, e.g. interned domain states.
+    Unbounded,
+}
+
+/// Result of one `Env::step`, mirroring Gymnasium's `(obs, reward, terminated, truncated)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvStep<O> {
+    pub observation: O,
+    pub reward: f64,
+    /// The episode reached a terminal state, or a state without actions.
+    pub terminated: bool,
+    /// The episode hit the step limit before terminating.
+    pub truncated: bool,
+}
+
+/// Reinforcement-learning environment with Gymnasium's `reset`/`step` semantics, so agent
+/// loops written against Gymnasium can drive weavetree models.
+pub trait Env {
+    type Observation;
+
+    /// Start a new episode, reseeding the RNG first if `seed` is given, and return the first
+    /// observation.
+    fn reset(&mut self, seed: Option<u64>) -> Self::Observation;
+
+    /// Take `action` in the current state. Once an episode has ended, further steps return
+    /// the final observation with zero reward until the next `reset`.
+    fn step(&mut self, action: usize) -> EnvStep<Self::Observation>;
+
+    /// Return the actions available in the current state; `Discrete { n: 0 }` once the
+    /// episode has ended. Weavetree states may offer different action counts, unlike
+    /// Gymnasium's fixed action spaces.
+    fn action_space(&self) -> Space;
+
+    /// Return the space observations are drawn from.
+    fn observation_space(&self) -> Space;
+}
+
+/// Stateful `Env` over a simulator, which itself only maps `(state, action)` to a sampled
+/// transition. Implemented for `MdpSimulator`, observing state indices, and for
+/// `DomainSimulator`, observing interned state keys.
+#[derive(Debug, Clone)]
+pub struct SimulatorEnv<S> {
+    simulator: S,
+    state: CoreStateKey,
+    steps: usize,
+    max_steps: Option<usize>,
+    done: bool,
+}
+
+impl<S> SimulatorEnv<S>
+where
+    S: EpisodeSimulator,
+{
+    /// Wrap a simulator, starting an episode in its start state.
+    pub fn new(simulator: S) -> Self {
+        let state = simulator.start_key();
+        let done = simulator.is_terminal_key(state);
+        Self {
+            simulator,
+            state,
+            steps: 0,
+            max_steps: None,
+            done,
+        }
+    }
+
+    /// Truncate episodes after `max_steps` steps, like Gymnasium's `TimeLimit` wrapper.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Borrow the wrapped simulator, e.g. to decode states.
+    pub fn simulator(&self) -> &S {
+        &self.simulator
+    }
+
+    /// Unwrap the simulator.
+    pub fn into_inner(self) -> S {
+        self.simulator
+    }
+
+    /// Return the current state key.
+    pub fn state(&self) -> CoreStateKey {
+        self.state
+    }
+
+    /// Return how many steps the current episode has taken.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    fn restart(&mut self) {
+        self.state = self.simulator.start_key();
+        self.steps = 0;
+        self.done = self.simulator.is_terminal_key(self.state);
+    }
+
+    fn advance(&mut self, action: usize) -> EnvStep<CoreStateKey> {
+        if self.done {
+            return EnvStep {
+                observation: self.state,
+                reward: 0.0,
+                terminated: true,
+                truncated: false,
+            };
+        }
+        let (next, reward, terminal) = self.simulator.step_key(self.state, ActionId::from(action));
+        self.state = next;
+        self.steps += 1;
+        let terminated = terminal || self.simulator.num_actions_key(next) == 0;
+        let truncated = !terminated && self.max_steps.is_some_and(|max| self.steps >= max);
+        self.done = terminated || truncated;
+        EnvStep {
+            observation: next,
+            reward,
+            terminated,
+            truncated,
+        }
+    }
+
+    fn current_actions(&self) -> Space {
+        let n = if self.done {
+            0
+        } else {
+            self.simulator.num_actions_key(self.state)
+        };
+        Space::Discrete { n }
+    }
+}
+
+impl<R> Env for SimulatorEnv<MdpSimulator<R>>
+where
+    R: RngCore + SeedableRng,
+{
+    type Observation = usize;
+
+    fn reset(&mut self, seed: Option<u64>) -> usize {
+        if let Some(seed) = seed {
+            self.simulator.reseed(seed);
+        }
+        self.restart();
+        StateKey::from(self.state).index()
+    }
+
+    fn step(&mut self, action: usize) -> EnvStep<usize> {
+        let step = self.advance(action);
+        EnvStep {
+            observation: StateKey::from(step.observation).index(),
+            reward: step.reward,
+            terminated: step.terminated,
+            truncated: step.truncated,
+        }
+    }
+
+    fn action_space(&self) -> Space {
+        self.current_actions()
+    }
+
+    fn observation_space(&self) -> Space {
+        Space::Discrete {
+            n: self.simulator.mdp().state_count(),
+        }
+    }
+}
+
+impl<D, R, I> Env for SimulatorEnv<DomainSimulator<D, R, I>>
+where
+    D: MdpDomain,
+    R: RngCore + SeedableRng,
+    I: KeyInterner<D::State>,
+{
+    type Observation = StateKeyValue;
+
+    fn reset(&mut self, seed: Option<u64>) -> StateKeyValue {
+        if let Some(seed) = seed {
+            self.simulator.reseed(seed);
+        }
+        self.restart();
+        self.state.raw()
+    }
+
+    fn step(&mut self, action: usize) -> EnvStep<StateKeyValue> {
+        let step = self.advance(action);
+        EnvStep {
+            observation: step.observation.raw(),
+            reward: step.reward,
+            terminated: step.terminated,
+            truncated: step.truncated,
+        }
+    }
+
+    fn action_space(&self) -> Space {
+        self.current_actions()
+    }
+
+    fn observation_space(&self) -> Space {
+        Space::Unbounded
+    }
+}
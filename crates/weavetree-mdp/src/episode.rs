@@ -0,0 +1,238 @@
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use weavetree_core::{ActionId, GreedyPolicy, StateKey as CoreStateKey};
+
+use crate::{BeliefSimulator, DomainSimulator, KeyInterner, MdpDomain, MdpSimulator, StateKey};
+
+/// Simulator interface in terms of search-tree keys, so a policy derived from a `Tree`
+/// can drive it directly. Implemented by `MdpSimulator`, `DomainSimulator`, and
+/// `BeliefSimulator`.
+pub trait EpisodeSimulator {
+    /// Return the key episodes start from.
+    fn start_key(&self) -> CoreStateKey;
+
+    /// Return whether a state is terminal.
+    fn is_terminal_key(&self, state: CoreStateKey) -> bool;
+
+    /// Return how many actions are available in a state.
+    fn num_actions_key(&self, state: CoreStateKey) -> usize;
+
+    /// Sample one `(next_state, reward, terminal)` transition.
+    fn step_key(&mut self, state: CoreStateKey, action: ActionId) -> (CoreStateKey, f64, bool);
+}
+
+impl<R: RngCore> EpisodeSimulator for MdpSimulator<R> {
+    fn start_key(&self) -> CoreStateKey {
+        CoreStateKey::from(self.mdp().start().index() as u64)
+    }
+
+    fn is_terminal_key(&self, state: CoreStateKey) -> bool {
+        self.mdp()
+            .is_terminal(StateKey::from(state))
+            .unwrap_or(true)
+    }
+
+    fn num_actions_key(&self, state: CoreStateKey) -> usize {
+        self.num_actions(StateKey::from(state))
+    }
+
+    fn step_key(&mut self, state: CoreStateKey, action: ActionId) -> (CoreStateKey, f64, bool) {
+        let (next, reward, terminal) = self.step(StateKey::from(state), action.index());
+        (CoreStateKey::from(next.index() as u64), reward, terminal)
+    }
+}
+
+impl<D, R, I> EpisodeSimulator for DomainSimulator<D, R, I>
+where
+    D: MdpDomain,
+    R: RngCore,
+    I: KeyInterner<D::State>,
+{
+    fn start_key(&self) -> CoreStateKey {
+        CoreStateKey::from_raw(self.start_state_key())
+    }
+
+    fn is_terminal_key(&self, state: CoreStateKey) -> bool {
+        self.is_terminal_by_key(state.raw())
+    }
+
+    fn num_actions_key(&self, state: CoreStateKey) -> usize {
+        self.num_actions_by_key(state.raw())
+    }
+
+    fn step_key(&mut self, state: CoreStateKey, action: ActionId) -> (CoreStateKey, f64, bool) {
+        let (next, reward, terminal) = self.step_by_key(state.raw(), action.index());
+        (CoreStateKey::from_raw(next), reward, terminal)
+    }
+}
+
+impl<R: RngCore> EpisodeSimulator for BeliefSimulator<R> {
+    fn start_key(&self) -> CoreStateKey {
+        CoreStateKey::from_raw(self.start_state_key())
+    }
+
+    fn is_terminal_key(&self, state: CoreStateKey) -> bool {
+        self.is_terminal_by_key(state.raw())
+    }
+
+    fn num_actions_key(&self, state: CoreStateKey) -> usize {
+        self.num_actions_by_key(state.raw())
+    }
+
+    fn step_key(&mut self, state: CoreStateKey, action: ActionId) -> (CoreStateKey, f64, bool) {
+        let (next, reward, terminal) = self.step_by_key(state.raw(), action.index());
+        (CoreStateKey::from_raw(next), reward, terminal)
+    }
+}
+
+/// One simulated episode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trajectory {
+    /// Visited states, starting with the start state; one longer than `actions`.
+    pub states: Vec<CoreStateKey>,
+    /// Action taken in `states[i]`.
+    pub actions: Vec<ActionId>,
+    /// Reward received for `actions[i]`.
+    pub rewards: Vec<f64>,
+    /// Whether the episode ended in a terminal state rather than at the step limit
+    /// or in a state without actions.
+    pub terminal: bool,
+}
+
+impl Trajectory {
+    /// Return the number of steps taken.
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Return the undiscounted sum of rewards.
+    pub fn total_return(&self) -> f64 {
+        self.rewards.iter().sum()
+    }
+
+    /// Return the sum of rewards discounted by `gamma` per step.
+    pub fn discounted_return(&self, gamma: f64) -> f64 {
+        self.rewards
+            .iter()
+            .rev()
+            .fold(0.0, |acc, r| r + gamma * acc)
+    }
+
+    /// Return the last visited state.
+    pub fn final_state(&self) -> CoreStateKey {
+        *self
+            .states
+            .last()
+            .expect("a trajectory always contains its start state")
+    }
+}
+
+/// Play one episode from the simulator's start state, asking `policy(state, num_actions)` for
+/// every action, until a terminal state, a state without actions, or `max_steps` steps.
+pub fn simulate_episode<S, P>(simulator: &mut S, mut policy: P, max_steps: usize) -> Trajectory
+where
+    S: EpisodeSimulator + ?Sized,
+    P: FnMut(CoreStateKey, usize) -> ActionId,
+{
+    let mut state = simulator.start_key();
+    let mut trajectory = Trajectory {
+        states: vec![state],
+        actions: Vec::new(),
+        rewards: Vec::new(),
+        terminal: simulator.is_terminal_key(state),
+    };
+
+    while !trajectory.terminal && trajectory.len() < max_steps {
+        let num_actions = simulator.num_actions_key(state);
+        if num_actions == 0 {
+            break;
+        }
+        let action = policy(state, num_actions);
+        let (next, reward, terminal) = simulator.step_key(state, action);
+        trajectory.states.push(next);
+        trajectory.actions.push(action);
+        trajectory.rewards.push(reward);
+        trajectory.terminal = terminal;
+        state = next;
+    }
+
+    trajectory
+}
+
+/// Summary of `evaluate_policy` over several episodes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyEvaluation {
+    pub episodes: usize,
+    /// Mean undiscounted return per episode.
+    pub mean_return: f64,
+    /// Sample standard deviation of the episode returns; `0.0` for fewer than two episodes.
+    pub return_std_dev: f64,
+    /// Mean number of steps per episode.
+    pub mean_length: f64,
+    /// Share of episodes that ended in a terminal state.
+    pub terminal_rate: f64,
+    /// Steps taken in states the policy has no action for, where action `0` was played.
+    pub off_policy_steps: usize,
+}
+
+/// Play `episodes` episodes of at most `max_steps` steps with a greedy policy extracted by
+/// `Tree::greedy_policy` and summarize them. States the search never expanded fall back to
+/// action `0` and are counted in `off_policy_steps`.
+pub fn evaluate_policy<S>(
+    simulator: &mut S,
+    policy: &GreedyPolicy,
+    episodes: usize,
+    max_steps: usize,
+) -> PolicyEvaluation
+where
+    S: EpisodeSimulator + ?Sized,
+{
+    let mut returns = Vec::with_capacity(episodes);
+    let mut total_length = 0;
+    let mut terminal_episodes = 0;
+    let mut off_policy_steps = 0;
+
+    for _ in 0..episodes {
+        let trajectory = simulate_episode(
+            simulator,
+            |state, _num_actions| {
+                policy.action(state).unwrap_or_else(|| {
+                    off_policy_steps += 1;
+                    ActionId::from(0)
+                })
+            },
+            max_steps,
+        );
+        returns.push(trajectory.total_return());
+        total_length += trajectory.len();
+        terminal_episodes += usize::from(trajectory.terminal);
+    }
+
+    let per_episode = |total: f64| {
+        if episodes == 0 {
+            0.0
+        } else {
+            total / episodes as f64
+        }
+    };
+    let mean_return = per_episode(returns.iter().sum());
+    let return_std_dev = if episodes < 2 {
+        0.0
+    } else {
+        let squares: f64 = returns.iter().map(|r| (r - mean_return).powi(2)).sum();
+        (squares / (episodes - 1) as f64).sqrt()
+    };
+
+    PolicyEvaluation {
+        episodes,
+        mean_return,
+        return_std_dev,
+        mean_length: per_episode(total_length as f64),
+        terminal_rate: per_episode(terminal_episodes as f64),
+        off_policy_steps,
+    }
+}
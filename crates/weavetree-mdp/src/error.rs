@@ -1,14 +1,36 @@
 use thiserror::Error;
+use weavetree_core::StateKeyValue;
+
+use crate::RewardDistribution;
 
 #[derive(Debug, Error)]
 /// Error type for MDP loading, validation, compilation, and builder operations.
 pub enum MdpError {
-    #[error("failed to read YAML file: {0}")]
+    #[error("failed to read or write MDP file: {0}")]
     Io(#[from] std::io::Error),
 
     #[error("failed to parse YAML: {0}")]
     Yaml(#[from] serde_yaml::Error),
 
+    #[error("failed to parse JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "toml")]
+    #[error("failed to parse TOML: {0}")]
+    TomlParse(#[from] toml::de::Error),
+
+    #[cfg(feature = "toml")]
+    #[error("failed to write TOML: {0}")]
+    TomlWrite(#[from] toml::ser::Error),
+
+    #[cfg(feature = "binary")]
+    #[error("failed to parse MessagePack: {0}")]
+    BinaryParse(#[from] rmp_serde::decode::Error),
+
+    #[cfg(feature = "binary")]
+    #[error("failed to write MessagePack: {0}")]
+    BinaryWrite(#[from] rmp_serde::encode::Error),
+
     #[error("missing start state")]
     MissingStart,
 
@@ -51,6 +73,80 @@ pub enum MdpError {
         value: f64,
     },
 
+    #[error("state '{id}' from included file '{path}' is already declared")]
+    DuplicateIncludedState { id: String, path: String },
+
+    #[error("include cycle through '{path}'")]
+    IncludeCycle { path: String },
+
+    #[error("include '{path}' is only resolved when loading from a file")]
+    UnresolvedInclude { path: String },
+
+    #[error("unknown state variable '{variable}'")]
+    UnknownVariable { variable: String },
+
+    #[error("invalid state variable '{variable}': {message}")]
+    InvalidVariable { variable: String, message: String },
+
+    #[error(
+        "variable '{variable}' would take value {value} in state '{state}', outside its integer range"
+    )]
+    VariableOutOfRange {
+        variable: String,
+        value: f64,
+        state: String,
+    },
+
+    #[error("invalid generator configuration: {message}")]
+    InvalidGeneratorConfig { message: String },
+
+    #[error("domain has more than {limit} reachable states")]
+    TooManyStates { limit: usize },
+
+    #[error("invalid expression '{expression}': {message}")]
+    InvalidExpression { expression: String, message: String },
+
+    #[error(
+        "invalid reward distribution for state '{state}', action '{action}', outcome {outcome_index}: {distribution:?}"
+    )]
+    InvalidRewardDistribution {
+        state: String,
+        action: String,
+        outcome_index: usize,
+        distribution: RewardDistribution,
+    },
+
+    #[error(
+        "observation probabilities for state '{state}', action '{action}', outcome {outcome_index} must be non-negative and sum to 1.0"
+    )]
+    InvalidObservations {
+        state: String,
+        action: String,
+        outcome_index: usize,
+    },
+
+    #[error("invalid belief: {message}")]
+    InvalidBelief { message: String },
+
+    #[error("vector reward names objective '{objective}', which has no entry in weights")]
+    UnknownObjective { objective: String },
+
+    #[error("invalid weight for objective '{objective}': {value}")]
+    InvalidObjectiveWeight { objective: String, value: f64 },
+
+    #[error("invalid cost in state '{state}', action '{action}': {value}")]
+    InvalidActionCost {
+        state: String,
+        action: String,
+        value: f64,
+    },
+
+    #[error("invalid entry or terminal reward in state '{state}': {value}")]
+    InvalidStateReward { state: String, value: f64 },
+
+    #[error("state '{state}' declares a terminal_reward but is not terminal")]
+    TerminalRewardOnNonTerminal { state: String },
+
     #[error(
         "probability sum for state '{state}', action '{action}' must be within {tolerance} of 1.0, got {sum}"
     )]
@@ -61,6 +157,9 @@ pub enum MdpError {
         tolerance: f64,
     },
 
+    #[error("state '{state}' action '{action}' declares more than one `prob: rest` outcome")]
+    MultipleRestOutcomes { state: String, action: String },
+
     #[error("state '{state}' action '{action}' must contain at least one outcome")]
     EmptyOutcomes { state: String, action: String },
 
@@ -69,4 +168,22 @@ pub enum MdpError {
 
     #[error("builder referenced unknown action '{action}' in state '{state}'")]
     BuilderUnknownAction { state: String, action: String },
+
+    #[error("state interner is full ({capacity} states)")]
+    InternerFull { capacity: usize },
+
+    #[error("state type has suspicious `Eq`/`Hash` behavior: {message}")]
+    SuspiciousStateEquality { message: String },
+
+    #[error("state interner is inconsistent: {message}")]
+    InconsistentInterner { message: String },
+
+    #[error("state key {key} is unknown or its state was evicted")]
+    UnknownStateKey { key: StateKeyValue },
+
+    #[error("two distinct states share the fingerprint of key {key}")]
+    FingerprintCollision { key: StateKeyValue },
+
+    #[error("failed to encode state for hashing: {message}")]
+    StateEncoding { message: String },
 }
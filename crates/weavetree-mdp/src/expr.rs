@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+/// Deepest nesting of parentheses and unary operators accepted, so adversarial input fails
+/// with an error instead of overflowing the stack.
+const MAX_NESTING: usize = 256;
+
+/// Evaluate an arithmetic expression over `${name}` parameter references.
+///
+/// Supports numbers, `+ - * /`, unary minus, and parentheses, e.g. `1 - ${p}` or
+/// `${base} * (1 + ${bonus})`. Errors are plain messages, wrapped by the caller.
+pub(crate) fn evaluate(expression: &str, params: &BTreeMap<String, f64>) -> Result<f64, String> {
+    evaluate_with(expression, params, &|_| None)
+}
+
+/// Like `evaluate`, but bare identifiers resolve through `variables`, and comparisons
+/// (`== != < <= > >=`), `&&`, `||`, and `!` are available. Booleans are `1.0` and `0.0`;
+/// any non-zero value counts as true.
+pub(crate) fn evaluate_with(
+    expression: &str,
+    params: &BTreeMap<String, f64>,
+    variables: &dyn Fn(&str) -> Option<f64>,
+) -> Result<f64, String> {
+    let mut parser = Parser {
+        input: expression.as_bytes(),
+        pos: 0,
+        depth: 0,
+        params,
+        variables,
+    };
+    let value = parser.or()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.input.len() {
+        return Err(format!("unexpected '{}'", parser.rest()));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    depth: usize,
+    params: &'a BTreeMap<String, f64>,
+    variables: &'a dyn Fn(&str) -> Option<f64>,
+}
+
+fn truth(value: bool) -> f64 {
+    if value { 1.0 } else { 0.0 }
+}
+
+impl Parser<'_> {
+    fn or(&mut self) -> Result<f64, String> {
+        let mut value = self.and()?;
+        while self.eat_str("||") {
+            let rhs = self.and()?;
+            value = truth(value != 0.0 || rhs != 0.0);
+        }
+        Ok(value)
+    }
+
+    fn and(&mut self) -> Result<f64, String> {
+        let mut value = self.comparison()?;
+        while self.eat_str("&&") {
+            let rhs = self.comparison()?;
+            value = truth(value != 0.0 && rhs != 0.0);
+        }
+        Ok(value)
+    }
+
+    fn comparison(&mut self) -> Result<f64, String> {
+        let lhs = self.sum()?;
+        let value = if self.eat_str("==") {
+            truth(lhs == self.sum()?)
+        } else if self.eat_str("!=") {
+            truth(lhs != self.sum()?)
+        } else if self.eat_str("<=") {
+            truth(lhs <= self.sum()?)
+        } else if self.eat_str(">=") {
+            truth(lhs >= self.sum()?)
+        } else if self.eat(b'<') {
+            truth(lhs < self.sum()?)
+        } else if self.eat(b'>') {
+            truth(lhs > self.sum()?)
+        } else {
+            lhs
+        };
+        Ok(value)
+    }
+
+    fn sum(&mut self) -> Result<f64, String> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat(b'+') {
+                value += self.product()?;
+            } else if self.eat(b'-') {
+                value -= self.product()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<f64, String> {
+        let mut value = self.factor()?;
+        loop {
+            if self.eat(b'*') {
+                value *= self.factor()?;
+            } else if self.eat(b'/') {
+                value /= self.factor()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Result<f64, String> {
+        if self.depth == MAX_NESTING {
+            return Err(format!("nested deeper than {MAX_NESTING} levels"));
+        }
+        self.depth += 1;
+        let value = self.operand();
+        self.depth -= 1;
+        value
+    }
+
+    fn operand(&mut self) -> Result<f64, String> {
+        if self.eat(b'-') {
+            return Ok(-self.factor()?);
+        }
+        if self.peek() == Some(b'!') && self.input.get(self.pos + 1) != Some(&b'=') {
+            self.pos += 1;
+            return Ok(truth(self.factor()? == 0.0));
+        }
+        if self.eat(b'(') {
+            let value = self.or()?;
+            if !self.eat(b')') {
+                return Err("missing ')'".to_string());
+            }
+            return Ok(value);
+        }
+        if self.eat(b'$') {
+            return self.param();
+        }
+        if self
+            .peek()
+            .is_some_and(|byte| byte.is_ascii_alphabetic() || byte == b'_')
+        {
+            return self.variable();
+        }
+        self.number()
+    }
+
+    fn param(&mut self) -> Result<f64, String> {
+        if !self.eat(b'{') {
+            return Err("expected '{' after '$'".to_string());
+        }
+        let start = self.pos;
+        while self.pos < self.input.len() && self.input[self.pos] != b'}' {
+            self.pos += 1;
+        }
+        if self.pos == self.input.len() {
+            return Err("missing '}'".to_string());
+        }
+        let name = String::from_utf8_lossy(&self.input[start..self.pos])
+            .trim()
+            .to_string();
+        self.pos += 1;
+        self.params
+            .get(&name)
+            .copied()
+            .ok_or_else(|| format!("unknown parameter '{name}'"))
+    }
+
+    fn variable(&mut self) -> Result<f64, String> {
+        let start = self.pos;
+        while self
+            .input
+            .get(self.pos)
+            .is_some_and(|byte| byte.is_ascii_alphanumeric() || *byte == b'_')
+        {
+            self.pos += 1;
+        }
+        let name = String::from_utf8_lossy(&self.input[start..self.pos]);
+        (self.variables)(&name).ok_or_else(|| format!("unknown variable '{name}'"))
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        let start = self.pos;
+        while self.pos < self.input.len() {
+            let byte = self.input[self.pos];
+            let exponent_sign = matches!(byte, b'+' | b'-')
+                && self.pos > start
+                && matches!(self.input[self.pos - 1], b'e' | b'E');
+            let exponent = matches!(byte, b'e' | b'E') && self.pos > start;
+            if byte.is_ascii_digit() || byte == b'.' || exponent || exponent_sign {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        if start == self.pos {
+            return Err(if self.pos < self.input.len() {
+                format!("unexpected '{}'", self.rest())
+            } else {
+                "unexpected end of expression".to_string()
+            });
+        }
+        let text = String::from_utf8_lossy(&self.input[start..self.pos]);
+        text.parse().map_err(|_| format!("invalid number '{text}'"))
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.input.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with(token.as_bytes()) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .input
+            .get(self.pos)
+            .is_some_and(|byte| byte.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn rest(&self) -> String {
+        String::from_utf8_lossy(&self.input[self.pos..]).into_owned()
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ActionSpec, CompiledMdp, MdpError, MdpSpec, OutcomeSpec, Probability, Reward, Scalar,
+    StateSpec, expr,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Higher-level MDP schema whose states are assignments of integer variables.
+///
+/// Actions are written once with a `when` guard and per-outcome `set` updates instead of
+/// being repeated for every state. `ground` enumerates the states reachable from `start`
+/// into a flat `MdpSpec`, so the cross product is never written out by hand.
+pub struct FactoredSpec {
+    /// Named values that expressions can reference as `${name}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<BTreeMap<String, f64>>,
+    /// Objective weights for vector rewards, copied into the grounded spec.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<BTreeMap<String, f64>>,
+    /// State variables, in the order they appear in grounded state ids.
+    pub variables: Vec<VariableSpec>,
+    /// Initial value of every variable.
+    pub start: BTreeMap<String, i64>,
+    /// Condition under which a state is terminal, e.g. `fuel == 0 || pos == 4`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<String>,
+    pub actions: Vec<FactoredActionSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An integer state variable ranging over `min..=max`.
+pub struct VariableSpec {
+    pub name: String,
+    pub min: i64,
+    pub max: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An action available in every non-terminal state where `when` holds.
+pub struct FactoredActionSpec {
+    pub id: String,
+    /// Guard over the state variables; the action is always available if omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    /// Cost subtracted from every outcome's reward.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    pub outcomes: Vec<FactoredOutcomeSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// One probabilistic update of the state variables.
+pub struct FactoredOutcomeSpec {
+    /// Probability; expressions may read the state variables.
+    pub prob: Probability,
+    /// New variable values, evaluated in the current state, e.g. `fuel: fuel - 1`.
+    /// Variables not listed keep their value.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub set: BTreeMap<String, Scalar>,
+    /// Reward; expressions may read the state variables.
+    pub reward: Reward,
+}
+
+impl FactoredSpec {
+    /// Enumerate the states reachable from `start` into a flat spec. States are named
+    /// `name=value` pairs joined by commas, e.g. `fuel=2,pos=1`, in breadth-first order.
+    pub fn ground(&self) -> Result<MdpSpec, MdpError> {
+        let empty = BTreeMap::new();
+        let params = self.params.as_ref().unwrap_or(&empty);
+        let index = self.variable_index()?;
+
+        let start =
+            self.variables
+                .iter()
+                .map(|variable| {
+                    let value = *self.start.get(&variable.name).ok_or_else(|| {
+                        MdpError::InvalidVariable {
+                            variable: variable.name.clone(),
+                            message: "missing start value".to_string(),
+                        }
+                    })?;
+                    check_range(variable, value as f64, "start")
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+        if let Some(name) = self
+            .start
+            .keys()
+            .find(|name| !index.contains_key(name.as_str()))
+        {
+            return Err(MdpError::UnknownVariable {
+                variable: name.clone(),
+            });
+        }
+
+        let mut ids = HashMap::from([(start.clone(), self.state_id(&start))]);
+        let mut queue = VecDeque::from([start.clone()]);
+        let mut states = Vec::new();
+
+        while let Some(values) = queue.pop_front() {
+            let id = ids[&values].clone();
+            let lookup = |name: &str| index.get(name).map(|&i| values[i] as f64);
+            let eval = |expression: &str| {
+                expr::evaluate_with(expression, params, &lookup).map_err(|message| {
+                    MdpError::InvalidExpression {
+                        expression: expression.to_string(),
+                        message,
+                    }
+                })
+            };
+
+            let terminal = match &self.terminal {
+                Some(condition) => eval(condition)? != 0.0,
+                None => false,
+            };
+            if terminal {
+                states.push(StateSpec {
+                    id,
+                    terminal: Some(true),
+                    actions: None,
+                    entry_reward: None,
+                    terminal_reward: None,
+                    labels: None,
+                    metadata: None,
+                });
+                continue;
+            }
+
+            let mut actions = Vec::new();
+            for action in &self.actions {
+                if let Some(guard) = &action.when
+                    && eval(guard)? == 0.0
+                {
+                    continue;
+                }
+
+                let mut outcomes = Vec::with_capacity(action.outcomes.len());
+                for outcome in &action.outcomes {
+                    let mut next = values.clone();
+                    for (name, update) in &outcome.set {
+                        let &i =
+                            index
+                                .get(name.as_str())
+                                .ok_or_else(|| MdpError::UnknownVariable {
+                                    variable: name.clone(),
+                                })?;
+                        let value = match update {
+                            Scalar::Value(value) => *value,
+                            Scalar::Expr(expression) => eval(expression)?,
+                        };
+                        next[i] = check_range(&self.variables[i], value, &id)?;
+                    }
+
+                    let next_id = ids
+                        .entry(next.clone())
+                        .or_insert_with(|| {
+                            queue.push_back(next.clone());
+                            self.state_id(&next)
+                        })
+                        .clone();
+                    let prob = match &outcome.prob {
+                        Probability::Expr(expression) => Probability::Value(eval(expression)?),
+                        other => other.clone(),
+                    };
+                    let reward = match &outcome.reward {
+                        Reward::Fixed(Scalar::Expr(expression)) => eval(expression)?.into(),
+                        other => other.clone(),
+                    };
+                    outcomes.push(OutcomeSpec {
+                        next: next_id,
+                        prob,
+                        reward,
+                        observations: None,
+                    });
+                }
+
+                actions.push(ActionSpec {
+                    id: action.id.clone(),
+                    outcomes,
+                    cost: action.cost,
+                    labels: None,
+                    metadata: None,
+                    available_when: None,
+                });
+            }
+
+            states.push(StateSpec {
+                id,
+                terminal: None,
+                actions: Some(actions),
+                entry_reward: None,
+                terminal_reward: None,
+                labels: None,
+                metadata: None,
+            });
+        }
+
+        Ok(MdpSpec {
+            version: Some(1),
+            params: None,
+            weights: self.weights.clone(),
+            include: None,
+            start: self.state_id(&start),
+            states,
+        })
+    }
+
+    /// Ground and compile this spec.
+    pub fn compile(&self) -> Result<CompiledMdp, MdpError> {
+        self.ground()?.compile()
+    }
+
+    fn variable_index(&self) -> Result<HashMap<&str, usize>, MdpError> {
+        let mut index = HashMap::with_capacity(self.variables.len());
+        for (i, variable) in self.variables.iter().enumerate() {
+            if variable.min > variable.max {
+                return Err(MdpError::InvalidVariable {
+                    variable: variable.name.clone(),
+                    message: format!("min {} exceeds max {}", variable.min, variable.max),
+                });
+            }
+            if index.insert(variable.name.as_str(), i).is_some() {
+                return Err(MdpError::InvalidVariable {
+                    variable: variable.name.clone(),
+                    message: "declared twice".to_string(),
+                });
+            }
+        }
+        Ok(index)
+    }
+
+    fn state_id(&self, values: &[i64]) -> String {
+        self.variables
+            .iter()
+            .zip(values)
+            .map(|(variable, value)| format!("{}={value}", variable.name))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+fn check_range(variable: &VariableSpec, value: f64, state: &str) -> Result<i64, MdpError> {
+    if value.fract() != 0.0 || value < variable.min as f64 || value > variable.max as f64 {
+        return Err(MdpError::VariableOutOfRange {
+            variable: variable.name.clone(),
+            value,
+            state: state.to_string(),
+        });
+    }
+    Ok(value as i64)
+}
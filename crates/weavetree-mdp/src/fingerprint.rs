@@ -0,0 +1,219 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use weavetree_core::StateKeyValue;
+
+use crate::{
+    MdpError,
+    interner::{InternerStats, KeyInterner, ReplayFn},
+};
+
+/// How a fingerprinted state was first reached.
+#[derive(Debug, Clone, Copy)]
+struct Origin {
+    parent: StateKeyValue,
+    action_id: usize,
+    sample: f64,
+}
+
+/// Interner that keeps a 128-bit fingerprint and the producing transition of each state
+/// instead of a full clone, for domains whose states are large.
+///
+/// Only the start state is stored. Any other state is rebuilt by replaying its recorded
+/// `(parent, action_id, sample)` chain from the start state, so `MdpDomain::step` must be a
+/// pure function of its inputs. The most recently produced or rebuilt state is cached, which
+/// makes the step-after-step access pattern of tree descents and rollouts replay-free.
+///
+/// Two distinct states with the same fingerprint get the same key. With `verifying()`, a
+/// fingerprint match is confirmed by rebuilding the stored state and comparing it, and a
+/// mismatch fails with `MdpError::FingerprintCollision`; this costs a replay per match.
+#[derive(Debug, Clone)]
+pub struct FingerprintInterner<S>
+where
+    S: Clone + Eq + Hash,
+{
+    start: Option<S>,
+    fingerprint_to_key: HashMap<u128, StateKeyValue>,
+    /// Origin per key; `None` for the start state.
+    origins: Vec<Option<Origin>>,
+    verify: bool,
+    cache: RefCell<Option<(StateKeyValue, S)>>,
+}
+
+impl<S> Default for FingerprintInterner<S>
+where
+    S: Clone + Eq + Hash,
+{
+    fn default() -> Self {
+        Self {
+            start: None,
+            fingerprint_to_key: HashMap::new(),
+            origins: Vec::new(),
+            verify: false,
+            cache: RefCell::new(None),
+        }
+    }
+}
+
+impl<S> FingerprintInterner<S>
+where
+    S: Clone + Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an interner that checks every fingerprint match for a collision.
+    pub fn verifying() -> Self {
+        Self {
+            verify: true,
+            ..Self::default()
+        }
+    }
+
+    /// Return the key of a state's fingerprint, if it was interned.
+    pub fn key_of(&self, state: &S) -> Option<StateKeyValue> {
+        self.fingerprint_to_key.get(&fingerprint(state)).copied()
+    }
+
+    fn rebuild(&self, key: StateKeyValue, replay: ReplayFn<'_, S>) -> Option<S> {
+        let mut path = Vec::new();
+        let mut current = key;
+        while let Some(origin) = *self.origins.get(current as usize)? {
+            path.push(origin);
+            current = origin.parent;
+        }
+
+        let mut state = self.start.clone()?;
+        for origin in path.iter().rev() {
+            state = replay(&state, origin.action_id, origin.sample);
+        }
+        Some(state)
+    }
+}
+
+impl<S> KeyInterner<S> for FingerprintInterner<S>
+where
+    S: Clone + Eq + Hash,
+{
+    fn intern_start(&mut self, state: S) -> Result<StateKeyValue, MdpError> {
+        let fingerprint = fingerprint(&state);
+        if let Some(key) = self.fingerprint_to_key.get(&fingerprint) {
+            return Ok(*key);
+        }
+        let key = self.origins.len() as StateKeyValue;
+        self.origins.push(None);
+        self.fingerprint_to_key.insert(fingerprint, key);
+        self.start = Some(state);
+        Ok(key)
+    }
+
+    fn intern_successor(
+        &mut self,
+        next: S,
+        parent: StateKeyValue,
+        action_id: usize,
+        sample: f64,
+        replay: ReplayFn<'_, S>,
+    ) -> Result<StateKeyValue, MdpError> {
+        let fingerprint = fingerprint(&next);
+        let key = match self.fingerprint_to_key.get(&fingerprint) {
+            Some(&key) => {
+                if self.verify
+                    && self.with_state(key, replay, |stored| *stored != next) == Some(true)
+                {
+                    return Err(MdpError::FingerprintCollision { key });
+                }
+                key
+            }
+            None => {
+                let key = self.origins.len() as StateKeyValue;
+                self.origins.push(Some(Origin {
+                    parent,
+                    action_id,
+                    sample,
+                }));
+                self.fingerprint_to_key.insert(fingerprint, key);
+                key
+            }
+        };
+        *self.cache.borrow_mut() = Some((key, next));
+        Ok(key)
+    }
+
+    fn with_state<T>(
+        &self,
+        key: StateKeyValue,
+        replay: ReplayFn<'_, S>,
+        f: impl FnOnce(&S) -> T,
+    ) -> Option<T> {
+        if self.origins.get(key as usize).copied()?.is_none() {
+            return self.start.as_ref().map(f);
+        }
+        if let Some((cached_key, state)) = self.cache.borrow().as_ref()
+            && *cached_key == key
+        {
+            return Some(f(state));
+        }
+
+        let state = self.rebuild(key, replay)?;
+        let result = f(&state);
+        *self.cache.borrow_mut() = Some((key, state));
+        Some(result)
+    }
+
+    fn len(&self) -> usize {
+        self.origins.len()
+    }
+
+    fn stats(&self) -> InternerStats {
+        InternerStats {
+            interned: self.origins.len(),
+            peak_interned: self.origins.len(),
+            inserted: self.origins.len() as u64,
+            evicted: 0,
+        }
+    }
+
+    fn check_consistency(&self) -> Result<(), MdpError> {
+        let inconsistent = |message: String| Err(MdpError::InconsistentInterner { message });
+        let mut seen = vec![false; self.origins.len()];
+        for &key in self.fingerprint_to_key.values() {
+            match seen.get_mut(key as usize) {
+                Some(seen @ false) => *seen = true,
+                Some(true) => return inconsistent(format!("key {key} has two fingerprints")),
+                None => return inconsistent(format!("key {key} was never handed out")),
+            }
+        }
+        if let Some(key) = seen.iter().position(|seen| !seen) {
+            return inconsistent(format!("key {key} has no fingerprint"));
+        }
+        for (key, origin) in self.origins.iter().enumerate() {
+            match origin {
+                Some(origin) if origin.parent >= key as StateKeyValue => {
+                    return inconsistent(format!(
+                        "key {key} was reached from later key {}",
+                        origin.parent
+                    ));
+                }
+                None if self.start.is_none() => {
+                    return inconsistent(format!("key {key} is a start state that is not stored"));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Two independent SipHash passes over the state, domain-separated by a prefix byte.
+fn fingerprint<S: Hash>(state: &S) -> u128 {
+    let half = |prefix: u8| {
+        let mut hasher = DefaultHasher::new();
+        prefix.hash(&mut hasher);
+        state.hash(&mut hasher);
+        hasher.finish()
+    };
+    (u128::from(half(0)) << 64) | u128::from(half(1))
+}
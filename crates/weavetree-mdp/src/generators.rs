@@ -0,0 +1,268 @@
+use std::ops::RangeInclusive;
+
+use rand::{Rng, SeedableRng, seq::index};
+
+use crate::{ActionSpec, DefaultRng, MdpError, MdpSpec, OutcomeSpec, Probability, StateSpec};
+
+/// Layout and dynamics of a `grid_world`. Cells are `(x, y)` with `x < width`, `y < height`,
+/// and `y = 0` as the top row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridWorldConfig {
+    pub width: usize,
+    pub height: usize,
+    pub start: (usize, usize),
+    /// Blocked cells; moving into one leaves the agent where it is.
+    pub walls: Vec<(usize, usize)>,
+    /// Terminal cells and the reward for entering each.
+    pub terminals: Vec<((usize, usize), f64)>,
+    /// Probability of sliding to one of the two perpendicular directions instead,
+    /// split evenly between them.
+    pub slip: f64,
+    /// Reward of every move, e.g. `-0.04` to favour short paths.
+    pub step_reward: f64,
+}
+
+impl Default for GridWorldConfig {
+    /// A 4x4 grid from the top-left corner to a goal worth `1.0` in the bottom-right corner.
+    fn default() -> Self {
+        Self {
+            width: 4,
+            height: 4,
+            start: (0, 0),
+            walls: Vec::new(),
+            terminals: vec![((3, 3), 1.0)],
+            slip: 0.0,
+            step_reward: 0.0,
+        }
+    }
+}
+
+/// Action ids of a `grid_world`, in action-index order.
+pub const GRID_ACTIONS: [&str; 4] = ["up", "down", "left", "right"];
+
+/// Return the state id `grid_world` uses for a cell, e.g. `2,1`.
+pub fn grid_cell_id(x: usize, y: usize) -> String {
+    format!("{x},{y}")
+}
+
+/// Build a grid world with the four `GRID_ACTIONS`. Moves off the grid or into a wall
+/// leave the agent in place; wall cells are not states.
+pub fn grid_world(config: &GridWorldConfig) -> Result<MdpSpec, MdpError> {
+    let in_bounds = |(x, y): (usize, usize)| x < config.width && y < config.height;
+    let is_wall = |cell: &(usize, usize)| config.walls.contains(cell);
+    let invalid = |message: String| MdpError::InvalidGeneratorConfig { message };
+
+    if config.width == 0 || config.height == 0 {
+        return Err(invalid("grid must have at least one cell".to_string()));
+    }
+    if !(0.0..=1.0).contains(&config.slip) {
+        return Err(invalid(format!(
+            "slip {} is not a probability",
+            config.slip
+        )));
+    }
+    if !in_bounds(config.start) || is_wall(&config.start) {
+        return Err(invalid(format!(
+            "start {:?} is outside the grid or a wall",
+            config.start
+        )));
+    }
+    if let Some((cell, _)) = config
+        .terminals
+        .iter()
+        .find(|(cell, _)| !in_bounds(*cell) || is_wall(cell))
+    {
+        return Err(invalid(format!(
+            "terminal {cell:?} is outside the grid or a wall"
+        )));
+    }
+
+    let terminal_reward = |cell: (usize, usize)| {
+        config
+            .terminals
+            .iter()
+            .find(|(terminal, _)| *terminal == cell)
+            .map(|(_, reward)| *reward)
+    };
+    let moved = |(x, y): (usize, usize), direction: usize| {
+        let next = match direction {
+            0 => (x, y.wrapping_sub(1)),
+            1 => (x, y + 1),
+            2 => (x.wrapping_sub(1), y),
+            _ => (x + 1, y),
+        };
+        if in_bounds(next) && !is_wall(&next) {
+            next
+        } else {
+            (x, y)
+        }
+    };
+
+    let mut states = Vec::new();
+    for y in 0..config.height {
+        for x in 0..config.width {
+            let cell = (x, y);
+            if is_wall(&cell) {
+                continue;
+            }
+            if let Some(reward) = terminal_reward(cell) {
+                states.push(StateSpec {
+                    id: grid_cell_id(x, y),
+                    terminal: Some(true),
+                    actions: None,
+                    entry_reward: None,
+                    terminal_reward: Some(reward),
+                    labels: None,
+                    metadata: None,
+                });
+                continue;
+            }
+
+            let actions = GRID_ACTIONS
+                .iter()
+                .enumerate()
+                .map(|(direction, id)| {
+                    let perpendicular = if direction < 2 { [2, 3] } else { [0, 1] };
+                    let moves = [
+                        (direction, 1.0 - config.slip),
+                        (perpendicular[0], config.slip / 2.0),
+                        (perpendicular[1], config.slip / 2.0),
+                    ];
+                    let mut outcomes: Vec<OutcomeSpec> = Vec::new();
+                    for (direction, prob) in moves {
+                        if prob == 0.0 {
+                            continue;
+                        }
+                        let (nx, ny) = moved(cell, direction);
+                        let next = grid_cell_id(nx, ny);
+                        match outcomes.iter_mut().find(|outcome| outcome.next == next) {
+                            Some(outcome) => {
+                                outcome.prob = (outcome.prob.value().unwrap_or(0.0) + prob).into()
+                            }
+                            None => outcomes.push(OutcomeSpec {
+                                next,
+                                prob: prob.into(),
+                                reward: config.step_reward.into(),
+                                observations: None,
+                            }),
+                        }
+                    }
+                    ActionSpec {
+                        id: id.to_string(),
+                        outcomes,
+                        cost: None,
+                        labels: None,
+                        metadata: None,
+                        available_when: None,
+                    }
+                })
+                .collect();
+
+            states.push(StateSpec {
+                id: grid_cell_id(x, y),
+                terminal: None,
+                actions: Some(actions),
+                entry_reward: None,
+                terminal_reward: None,
+                labels: None,
+                metadata: None,
+            });
+        }
+    }
+
+    Ok(MdpSpec {
+        version: Some(1),
+        params: None,
+        weights: None,
+        include: None,
+        start: grid_cell_id(config.start.0, config.start.1),
+        states,
+    })
+}
+
+/// Build a random MDP with states `s0..s{n_states - 1}`, starting in `s0`, where each of the
+/// `n_actions` actions of every state leads to `branching` distinct random states.
+///
+/// Outcome probabilities are random weights normalized to one, with the last outcome
+/// written as `rest`, and rewards are uniform in `reward_range`. No state is terminal.
+/// The same arguments always produce the same spec.
+pub fn random_mdp(
+    seed: u64,
+    n_states: usize,
+    n_actions: usize,
+    branching: usize,
+    reward_range: RangeInclusive<f64>,
+) -> Result<MdpSpec, MdpError> {
+    let invalid = |message: String| MdpError::InvalidGeneratorConfig { message };
+    if n_states == 0 || n_actions == 0 {
+        return Err(invalid(
+            "need at least one state and one action".to_string(),
+        ));
+    }
+    if branching == 0 || branching > n_states {
+        return Err(invalid(format!(
+            "branching {branching} must be between 1 and the number of states {n_states}"
+        )));
+    }
+    let (low, high) = (*reward_range.start(), *reward_range.end());
+    if !low.is_finite() || !high.is_finite() || low > high {
+        return Err(invalid(format!(
+            "reward range {low}..={high} must be finite and non-empty"
+        )));
+    }
+
+    let mut rng = DefaultRng::seed_from_u64(seed);
+    let states = (0..n_states)
+        .map(|state| {
+            let actions = (0..n_actions)
+                .map(|action| {
+                    let targets = index::sample(&mut rng, n_states, branching);
+                    let weights: Vec<f64> =
+                        (0..branching).map(|_| 1.0 - rng.r#gen::<f64>()).collect();
+                    let total: f64 = weights.iter().sum();
+                    let outcomes = targets
+                        .iter()
+                        .zip(&weights)
+                        .enumerate()
+                        .map(|(i, (next, weight))| OutcomeSpec {
+                            next: format!("s{next}"),
+                            prob: if i + 1 == branching {
+                                Probability::REST
+                            } else {
+                                Probability::Value(weight / total)
+                            },
+                            reward: rng.gen_range(low..=high).into(),
+                            observations: None,
+                        })
+                        .collect();
+                    ActionSpec {
+                        id: format!("a{action}"),
+                        outcomes,
+                        cost: None,
+                        labels: None,
+                        metadata: None,
+                        available_when: None,
+                    }
+                })
+                .collect();
+            StateSpec {
+                id: format!("s{state}"),
+                terminal: None,
+                actions: Some(actions),
+                entry_reward: None,
+                terminal_reward: None,
+                labels: None,
+                metadata: None,
+            }
+        })
+        .collect();
+
+    Ok(MdpSpec {
+        version: Some(1),
+        params: None,
+        weights: None,
+        include: None,
+        start: "s0".to_string(),
+        states,
+    })
+}
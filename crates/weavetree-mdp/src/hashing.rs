@@ -0,0 +1,426 @@
+use std::fmt;
+
+use serde::{Serialize, Serializer, ser};
+use weavetree_core::{StateKey as CoreStateKey, StateKeyValue};
+
+use crate::MdpError;
+
+const FNV64_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV64_PRIME: u64 = 0x0000_0100_0000_01b3;
+const FNV128_OFFSET: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+const FNV128_PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+const TAG_UNIT: u8 = 0;
+const TAG_BOOL: u8 = 1;
+const TAG_INT: u8 = 2;
+const TAG_UINT: u8 = 3;
+const TAG_FLOAT: u8 = 4;
+const TAG_CHAR: u8 = 5;
+const TAG_STR: u8 = 6;
+const TAG_BYTES: u8 = 7;
+const TAG_NONE: u8 = 8;
+const TAG_SOME: u8 = 9;
+const TAG_VARIANT: u8 = 10;
+const TAG_SEQ: u8 = 11;
+const TAG_MAP: u8 = 12;
+
+/// Marks another element of a sequence, map, or struct.
+const MORE: u8 = 1;
+/// Closes a sequence, map, or struct.
+const END: u8 = 0;
+
+/// Hash a state to 64 bits with FNV-1a over its canonical encoding.
+///
+/// The encoding is fixed, so the same state gives the same hash on every platform, process, and
+/// release, unlike `std::hash::Hash` with `RandomState`. It depends on field order and values,
+/// not on type or field names. Integers hash by value regardless of width (`5u8` and `5u64`
+/// match), floats are widened to `f64` with `-0.0` folded into `0.0` and every NaN folded into
+/// one. Maps hash in iteration order, so use ordered maps such as `BTreeMap` in hashed states.
+///
+/// FNV-1a is not cryptographic; it resists accidental collisions, not crafted ones.
+pub fn stable_hash64<T: Serialize + ?Sized>(state: &T) -> Result<u64, MdpError> {
+    let bytes = canonical_bytes(state)?;
+    Ok(bytes.iter().fold(FNV64_OFFSET, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV64_PRIME)
+    }))
+}
+
+/// Hash a state to 128 bits with FNV-1a over the same encoding as `stable_hash64`.
+pub fn stable_hash128<T: Serialize + ?Sized>(state: &T) -> Result<u128, MdpError> {
+    let bytes = canonical_bytes(state)?;
+    Ok(bytes.iter().fold(FNV128_OFFSET, |hash, &byte| {
+        (hash ^ u128::from(byte)).wrapping_mul(FNV128_PRIME)
+    }))
+}
+
+/// Build a search `StateKey` from a stable hash of the state, as an alternative to interning.
+/// Uses `stable_hash128` when core's `wide-state-keys` feature is enabled and `stable_hash64`
+/// otherwise.
+pub fn hashed_state_key<T: Serialize + ?Sized>(state: &T) -> Result<CoreStateKey, MdpError> {
+    let value = if StateKeyValue::BITS == 128 {
+        stable_hash128(state)? as StateKeyValue
+    } else {
+        stable_hash64(state)? as StateKeyValue
+    };
+    Ok(CoreStateKey::from_raw(value))
+}
+
+fn canonical_bytes<T: Serialize + ?Sized>(state: &T) -> Result<Vec<u8>, MdpError> {
+    let mut encoder = CanonicalEncoder { bytes: Vec::new() };
+    state
+        .serialize(&mut encoder)
+        .map_err(|err| MdpError::StateEncoding { message: err.0 })?;
+    Ok(encoder.bytes)
+}
+
+#[derive(Debug)]
+struct EncodeError(String);
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+impl ser::Error for EncodeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        EncodeError(msg.to_string())
+    }
+}
+
+/// Writes a self-delimiting, type-tagged byte encoding of a value.
+struct CanonicalEncoder {
+    bytes: Vec<u8>,
+}
+
+impl CanonicalEncoder {
+    fn tag(&mut self, tag: u8) {
+        self.bytes.push(tag);
+    }
+
+    fn len_prefixed(&mut self, tag: u8, data: &[u8]) {
+        self.tag(tag);
+        self.bytes
+            .extend_from_slice(&(data.len() as u64).to_le_bytes());
+        self.bytes.extend_from_slice(data);
+    }
+
+    fn variant(&mut self, variant_index: u32) {
+        self.tag(TAG_VARIANT);
+        self.bytes.extend_from_slice(&variant_index.to_le_bytes());
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        self.tag(MORE);
+        value.serialize(&mut *self)
+    }
+}
+
+impl Serializer for &mut CanonicalEncoder {
+    type Ok = ();
+    type Error = EncodeError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), EncodeError> {
+        self.tag(TAG_BOOL);
+        self.bytes.push(u8::from(v));
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), EncodeError> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), EncodeError> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), EncodeError> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), EncodeError> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), EncodeError> {
+        // Non-negative values hash like the same unsigned value.
+        if v >= 0 {
+            return self.serialize_u128(v as u128);
+        }
+        self.tag(TAG_INT);
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), EncodeError> {
+        self.serialize_u128(u128::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), EncodeError> {
+        self.serialize_u128(u128::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), EncodeError> {
+        self.serialize_u128(u128::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), EncodeError> {
+        self.serialize_u128(u128::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), EncodeError> {
+        self.tag(TAG_UINT);
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), EncodeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), EncodeError> {
+        let canonical = if v.is_nan() {
+            f64::NAN
+        } else if v == 0.0 {
+            0.0
+        } else {
+            v
+        };
+        self.tag(TAG_FLOAT);
+        self.bytes
+            .extend_from_slice(&canonical.to_bits().to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), EncodeError> {
+        self.tag(TAG_CHAR);
+        self.bytes.extend_from_slice(&u32::from(v).to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), EncodeError> {
+        self.len_prefixed(TAG_STR, v.as_bytes());
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), EncodeError> {
+        self.len_prefixed(TAG_BYTES, v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), EncodeError> {
+        self.tag(TAG_NONE);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), EncodeError> {
+        self.tag(TAG_SOME);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), EncodeError> {
+        self.tag(TAG_UNIT);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), EncodeError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), EncodeError> {
+        self.variant(variant_index);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        self.variant(variant_index);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, EncodeError> {
+        self.tag(TAG_SEQ);
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, EncodeError> {
+        self.tag(TAG_SEQ);
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, EncodeError> {
+        self.tag(TAG_SEQ);
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, EncodeError> {
+        self.variant(variant_index);
+        self.tag(TAG_SEQ);
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self, EncodeError> {
+        self.tag(TAG_MAP);
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, EncodeError> {
+        self.tag(TAG_SEQ);
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, EncodeError> {
+        self.variant(variant_index);
+        self.tag(TAG_SEQ);
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for &mut CanonicalEncoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut CanonicalEncoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut CanonicalEncoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut CanonicalEncoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut CanonicalEncoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), EncodeError> {
+        self.element(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut CanonicalEncoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut CanonicalEncoder {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), EncodeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.tag(END);
+        Ok(())
+    }
+}
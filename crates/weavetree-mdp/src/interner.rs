@@ -1,14 +1,104 @@
-use std::collections::HashMap;
-use std::hash::Hash;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use weavetree_core::StateKeyValue;
+
+use crate::MdpError;
+
+/// What a bounded `StateInterner` does when a new state would exceed its capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionPolicy {
+    /// Refuse the new state: `try_intern` returns `MdpError::InternerFull`.
+    Reject,
+    /// Forget the oldest unpinned state to make room. Its key is never reused and no longer resolves.
+    /// Evicted keys must not be revisited: `DomainSimulator::try_step_by_key` fails on them
+    /// with `MdpError::UnknownStateKey`, and a search tree still holding one can't expand it.
+    EvictOldest,
+}
+
+/// Counters describing an interner's memory use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct InternerStats {
+    /// States currently stored.
+    pub interned: usize,
+    /// Largest number of states stored at once.
+    pub peak_interned: usize,
+    /// States ever inserted, including evicted ones.
+    pub inserted: u64,
+    /// States forgotten by `EvictionPolicy::EvictOldest`.
+    pub evicted: u64,
+}
+
+/// Replays one domain transition `(state, action_id, sample) -> next_state`.
+pub type ReplayFn<'a, S> = &'a dyn Fn(&S, usize, f64) -> S;
+
+/// State-to-key storage behind `DomainSimulator`.
+/// Interners that do not keep full states rebuild them with `replay`, which re-runs
+/// `MdpDomain::step`; it is never called by interners that store states.
+pub trait KeyInterner<S> {
+    /// Store the start state and return its key. It is never evicted.
+    fn intern_start(&mut self, state: S) -> Result<StateKeyValue, MdpError>;
+
+    /// Return the key of `next`, reached from `parent` by `action_id` with `sample`.
+    fn intern_successor(
+        &mut self,
+        next: S,
+        parent: StateKeyValue,
+        action_id: usize,
+        sample: f64,
+        replay: ReplayFn<'_, S>,
+    ) -> Result<StateKeyValue, MdpError>;
+
+    /// Apply `f` to the state behind `key`; `None` for unknown or evicted keys.
+    fn with_state<T>(
+        &self,
+        key: StateKeyValue,
+        replay: ReplayFn<'_, S>,
+        f: impl FnOnce(&S) -> T,
+    ) -> Option<T>;
+
+    /// Return how many keys resolve.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return memory counters for this interner.
+    fn stats(&self) -> InternerStats;
+
+    /// Verify the interner's internal maps agree with each other, failing with
+    /// `MdpError::InconsistentInterner` if they do not. Interners without such
+    /// bookkeeping accept by default.
+    fn check_consistency(&self) -> Result<(), MdpError> {
+        Ok(())
+    }
+}
 
 /// Stable key interner for arbitrary states.
+/// Unbounded by default; `with_capacity_limit` caps how many states it keeps.
+///
+/// Keys are only as reliable as the state type's `Eq` and `Hash`. With `verifying()`, every
+/// insertion also checks that equality is reflexive and symmetric, that equal states hash
+/// alike, and that no stored state equals a new one under a different hash, failing with
+/// `MdpError::SuspiciousStateEquality` otherwise. The last check scans every stored state,
+/// so keep it to debugging runs.
 #[derive(Debug, Clone)]
 pub struct StateInterner<S>
 where
     S: Clone + Eq + Hash,
 {
-    states: Vec<S>,
-    state_to_key: HashMap<S, u64>,
+    states: HashMap<StateKeyValue, S>,
+    state_to_key: HashMap<S, StateKeyValue>,
+    /// Unpinned keys in insertion order, oldest first.
+    eviction_order: VecDeque<StateKeyValue>,
+    next_key: StateKeyValue,
+    limit: Option<(usize, EvictionPolicy)>,
+    verify: bool,
+    stats: InternerStats,
 }
 
 impl<S> Default for StateInterner<S>
@@ -17,8 +107,13 @@ where
 {
     fn default() -> Self {
         Self {
-            states: Vec::new(),
+            states: HashMap::new(),
             state_to_key: HashMap::new(),
+            eviction_order: VecDeque::new(),
+            next_key: 0,
+            limit: None,
+            verify: false,
+            stats: InternerStats::default(),
         }
     }
 }
@@ -31,23 +126,97 @@ where
         Self::default()
     }
 
-    /// Insert the state if needed and return a stable dense key.
-    pub fn intern(&mut self, state: S) -> u64 {
-        if let Some(key) = self.state_to_key.get(&state) {
-            return *key;
+    /// Create an interner that keeps at most `capacity` states and applies `policy` beyond that.
+    pub fn with_capacity_limit(capacity: usize, policy: EvictionPolicy) -> Self {
+        Self {
+            limit: Some((capacity, policy)),
+            ..Self::default()
+        }
+    }
+
+    /// Create an unbounded interner that checks the state type's `Eq` and `Hash` on every
+    /// insertion.
+    pub fn verifying() -> Self {
+        Self {
+            verify: true,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn configured(limit: Option<(usize, EvictionPolicy)>, verify: bool) -> Self {
+        Self {
+            limit,
+            verify,
+            ..Self::default()
+        }
+    }
+
+    /// Insert the state if needed and return a stable key.
+    /// Keys are dense while nothing has been evicted.
+    ///
+    /// # Panics
+    /// Panics if an `EvictionPolicy::Reject` limit is full; use `try_intern` to handle that case.
+    pub fn intern(&mut self, state: S) -> StateKeyValue {
+        self.try_intern(state)
+            .expect("state interner capacity exceeded")
+    }
+
+    /// Insert the state if needed and return a stable key, or `MdpError::InternerFull` if a
+    /// `Reject` limit is full.
+    pub fn try_intern(&mut self, state: S) -> Result<StateKeyValue, MdpError> {
+        self.insert(state, false)
+    }
+
+    /// Insert the state if needed and exempt it from eviction, e.g. for a start state.
+    /// A state that was already interned unpinned gets pinned too.
+    pub fn intern_pinned(&mut self, state: S) -> Result<StateKeyValue, MdpError> {
+        self.insert(state, true)
+    }
+
+    fn insert(&mut self, state: S, pinned: bool) -> Result<StateKeyValue, MdpError> {
+        if self.verify {
+            self.verify_equality(&state)?;
+        }
+        if let Some(&key) = self.state_to_key.get(&state) {
+            if pinned {
+                self.eviction_order.retain(|&queued| queued != key);
+            }
+            return Ok(key);
+        }
+
+        if let Some((capacity, policy)) = self.limit {
+            while self.states.len() >= capacity {
+                match (policy, self.eviction_order.pop_front()) {
+                    (EvictionPolicy::EvictOldest, Some(oldest)) => {
+                        if let Some(evicted) = self.states.remove(&oldest) {
+                            self.state_to_key.remove(&evicted);
+                            self.stats.evicted += 1;
+                        }
+                    }
+                    _ => return Err(MdpError::InternerFull { capacity }),
+                }
+            }
         }
 
-        let key = self.states.len() as u64;
-        self.states.push(state.clone());
+        let key = self.next_key;
+        self.next_key += 1;
+        self.states.insert(key, state.clone());
         self.state_to_key.insert(state, key);
-        key
+        if !pinned {
+            self.eviction_order.push_back(key);
+        }
+        self.stats.inserted += 1;
+        self.stats.interned = self.states.len();
+        self.stats.peak_interned = self.stats.peak_interned.max(self.states.len());
+        Ok(key)
     }
 
-    pub fn get(&self, key: u64) -> Option<&S> {
-        self.states.get(key as usize)
+    /// Resolve a key; `None` for unknown or evicted keys.
+    pub fn get(&self, key: StateKeyValue) -> Option<&S> {
+        self.states.get(&key)
     }
 
-    pub fn key_of(&self, state: &S) -> Option<u64> {
+    pub fn key_of(&self, state: &S) -> Option<StateKeyValue> {
         self.state_to_key.get(state).copied()
     }
 
@@ -58,4 +227,136 @@ where
     pub fn is_empty(&self) -> bool {
         self.states.is_empty()
     }
+
+    /// Return the configured capacity limit and policy, if any.
+    pub fn capacity_limit(&self) -> Option<(usize, EvictionPolicy)> {
+        self.limit
+    }
+
+    /// Return memory counters for this interner.
+    pub fn stats(&self) -> InternerStats {
+        self.stats
+    }
+
+    /// Return whether insertions check the state type's `Eq` and `Hash`.
+    pub fn is_verifying(&self) -> bool {
+        self.verify
+    }
+
+    /// Verify that keys and states map to each other one-to-one and that the eviction
+    /// queue and counters match the stored states.
+    pub fn check_consistency(&self) -> Result<(), MdpError> {
+        let inconsistent = |message: String| Err(MdpError::InconsistentInterner { message });
+        if self.states.len() != self.state_to_key.len() {
+            return inconsistent(format!(
+                "{} keys but {} distinct states",
+                self.states.len(),
+                self.state_to_key.len()
+            ));
+        }
+        for (&key, state) in &self.states {
+            if key >= self.next_key {
+                return inconsistent(format!("key {key} was never handed out"));
+            }
+            if self.state_to_key.get(state) != Some(&key) {
+                return inconsistent(format!("the state of key {key} does not map back to it"));
+            }
+        }
+        if let Some(key) = self
+            .eviction_order
+            .iter()
+            .find(|key| !self.states.contains_key(key))
+        {
+            return inconsistent(format!("eviction queue holds unknown key {key}"));
+        }
+        if self.stats.interned != self.states.len() {
+            return inconsistent(format!(
+                "stats count {} states but {} are stored",
+                self.stats.interned,
+                self.states.len()
+            ));
+        }
+        Ok(())
+    }
+
+    fn verify_equality(&self, state: &S) -> Result<(), MdpError> {
+        let suspicious = |message: String| Err(MdpError::SuspiciousStateEquality { message });
+        // Spelled as calls: these deliberately compare a value with itself or both ways.
+        if S::ne(state, state) {
+            return suspicious("a state is not equal to itself".to_string());
+        }
+        let copy = state.clone();
+        if copy != *state || hash_of(&copy) != hash_of(state) {
+            return suspicious("a state differs from its clone by `Eq` or `Hash`".to_string());
+        }
+        match self.state_to_key.get_key_value(state) {
+            Some((stored, key)) => {
+                if S::ne(stored, state) || S::ne(state, stored) {
+                    return suspicious(format!(
+                        "equality with the state of key {key} is not symmetric"
+                    ));
+                }
+                if hash_of(stored) != hash_of(state) {
+                    return suspicious(format!(
+                        "a state equal to the state of key {key} hashes differently"
+                    ));
+                }
+            }
+            None => {
+                if let Some((key, _)) = self.states.iter().find(|(_, stored)| *stored == state) {
+                    return suspicious(format!(
+                        "a state equal to the state of key {key} hashes differently"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn hash_of<S: Hash>(state: &S) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<S> KeyInterner<S> for StateInterner<S>
+where
+    S: Clone + Eq + Hash,
+{
+    fn intern_start(&mut self, state: S) -> Result<StateKeyValue, MdpError> {
+        self.intern_pinned(state)
+    }
+
+    fn intern_successor(
+        &mut self,
+        next: S,
+        _parent: StateKeyValue,
+        _action_id: usize,
+        _sample: f64,
+        _replay: ReplayFn<'_, S>,
+    ) -> Result<StateKeyValue, MdpError> {
+        self.try_intern(next)
+    }
+
+    fn with_state<T>(
+        &self,
+        key: StateKeyValue,
+        _replay: ReplayFn<'_, S>,
+        f: impl FnOnce(&S) -> T,
+    ) -> Option<T> {
+        self.get(key).map(f)
+    }
+
+    fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    fn stats(&self) -> InternerStats {
+        self.stats
+    }
+
+    fn check_consistency(&self) -> Result<(), MdpError> {
+        StateInterner::check_consistency(self)
+    }
 }
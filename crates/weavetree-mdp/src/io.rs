@@ -1,18 +1,31 @@
-use std::{fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
 
-use crate::{CompiledMdp, MdpError, MdpSpec};
+use crate::{CompileOptions, CompiledMdp, MdpError, MdpSpec};
 
-/// Load an MDP spec from YAML on disk.
+/// Load an MDP spec from YAML on disk, merging the states of the files it `include`s.
 pub fn load_yaml(path: impl AsRef<Path>) -> Result<MdpSpec, MdpError> {
-    let yaml = fs::read_to_string(path)?;
-    let spec: MdpSpec = serde_yaml::from_str(&yaml)?;
-    Ok(spec)
+    load_with_includes(
+        path.as_ref(),
+        &|text| Ok(serde_yaml::from_str(text)?),
+        &mut Vec::new(),
+    )
 }
 
 /// Load and compile an MDP from a YAML file.
 pub fn compile_yaml(path: impl AsRef<Path>) -> Result<CompiledMdp, MdpError> {
-    let spec = load_yaml(path)?;
-    spec.compile()
+    compile_yaml_with_options(path, &CompileOptions::default())
+}
+
+/// Load and compile an MDP from a YAML file with custom `CompileOptions`.
+pub fn compile_yaml_with_options(
+    path: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<CompiledMdp, MdpError> {
+    load_yaml(path)?.compile_with_options(options)
 }
 
 /// Serialize and write an MDP spec to YAML.
@@ -21,3 +34,181 @@ pub fn save_yaml(path: impl AsRef<Path>, spec: &MdpSpec) -> Result<(), MdpError>
     fs::write(path, yaml)?;
     Ok(())
 }
+
+/// Load an MDP spec from JSON on disk, merging the states of the files it `include`s.
+pub fn load_json(path: impl AsRef<Path>) -> Result<MdpSpec, MdpError> {
+    load_with_includes(
+        path.as_ref(),
+        &|text| Ok(serde_json::from_str(text)?),
+        &mut Vec::new(),
+    )
+}
+
+/// Load and compile an MDP from a JSON file.
+pub fn compile_json(path: impl AsRef<Path>) -> Result<CompiledMdp, MdpError> {
+    compile_json_with_options(path, &CompileOptions::default())
+}
+
+/// Load and compile an MDP from a JSON file with custom `CompileOptions`.
+pub fn compile_json_with_options(
+    path: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<CompiledMdp, MdpError> {
+    load_json(path)?.compile_with_options(options)
+}
+
+/// Parse and compile an MDP from a JSON string, e.g. one produced by another program.
+pub fn compile_json_str(json: &str) -> Result<CompiledMdp, MdpError> {
+    compile_json_str_with_options(json, &CompileOptions::default())
+}
+
+/// Parse and compile an MDP from a JSON string with custom `CompileOptions`.
+pub fn compile_json_str_with_options(
+    json: &str,
+    options: &CompileOptions,
+) -> Result<CompiledMdp, MdpError> {
+    let spec: MdpSpec = serde_json::from_str(json)?;
+    spec.compile_with_options(options)
+}
+
+/// Serialize and write an MDP spec to pretty-printed JSON.
+pub fn save_json(path: impl AsRef<Path>, spec: &MdpSpec) -> Result<(), MdpError> {
+    let json = serde_json::to_string_pretty(spec)?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// Load an MDP spec from TOML on disk, merging the states of the files it `include`s.
+#[cfg(feature = "toml")]
+pub fn load_toml(path: impl AsRef<Path>) -> Result<MdpSpec, MdpError> {
+    load_with_includes(
+        path.as_ref(),
+        &|text| Ok(toml::from_str(text)?),
+        &mut Vec::new(),
+    )
+}
+
+/// Load and compile an MDP from a TOML file.
+#[cfg(feature = "toml")]
+pub fn compile_toml(path: impl AsRef<Path>) -> Result<CompiledMdp, MdpError> {
+    compile_toml_with_options(path, &CompileOptions::default())
+}
+
+/// Load and compile an MDP from a TOML file with custom `CompileOptions`.
+#[cfg(feature = "toml")]
+pub fn compile_toml_with_options(
+    path: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<CompiledMdp, MdpError> {
+    load_toml(path)?.compile_with_options(options)
+}
+
+/// Parse and compile an MDP from a TOML string.
+#[cfg(feature = "toml")]
+pub fn compile_toml_str(text: &str) -> Result<CompiledMdp, MdpError> {
+    compile_toml_str_with_options(text, &CompileOptions::default())
+}
+
+/// Parse and compile an MDP from a TOML string with custom `CompileOptions`.
+#[cfg(feature = "toml")]
+pub fn compile_toml_str_with_options(
+    text: &str,
+    options: &CompileOptions,
+) -> Result<CompiledMdp, MdpError> {
+    let spec: MdpSpec = toml::from_str(text)?;
+    spec.compile_with_options(options)
+}
+
+/// Serialize and write an MDP spec to TOML.
+#[cfg(feature = "toml")]
+pub fn save_toml(path: impl AsRef<Path>, spec: &MdpSpec) -> Result<(), MdpError> {
+    let text = toml::to_string(spec)?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Load an MDP spec from a MessagePack file written by `save_binary`. Binary specs hold
+/// the states of their includes already, so `include` is not followed.
+#[cfg(feature = "binary")]
+pub fn load_binary(path: impl AsRef<Path>) -> Result<MdpSpec, MdpError> {
+    let bytes = fs::read(path)?;
+    Ok(rmp_serde::from_slice(&bytes)?)
+}
+
+/// Load and compile an MDP from a MessagePack file.
+#[cfg(feature = "binary")]
+pub fn compile_binary(path: impl AsRef<Path>) -> Result<CompiledMdp, MdpError> {
+    compile_binary_with_options(path, &CompileOptions::default())
+}
+
+/// Load and compile an MDP from a MessagePack file with custom `CompileOptions`.
+#[cfg(feature = "binary")]
+pub fn compile_binary_with_options(
+    path: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<CompiledMdp, MdpError> {
+    load_binary(path)?.compile_with_options(options)
+}
+
+/// Serialize and write an MDP spec as MessagePack, which loads faster than text formats for
+/// large generated models.
+#[cfg(feature = "binary")]
+pub fn save_binary(path: impl AsRef<Path>, spec: &MdpSpec) -> Result<(), MdpError> {
+    let bytes = rmp_serde::to_vec_named(spec)?;
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+type ParseFn<'a> = &'a dyn Fn(&str) -> Result<MdpSpec, MdpError>;
+
+/// Parse `path` and append the states of its includes, resolved relative to its directory
+/// and parsed in the same format. Included params and weights fill in names the including
+/// file lacks.
+/// `stack` holds the files currently being loaded, to reject include cycles.
+fn load_with_includes(
+    path: &Path,
+    parse: ParseFn<'_>,
+    stack: &mut Vec<PathBuf>,
+) -> Result<MdpSpec, MdpError> {
+    let canonical = fs::canonicalize(path)?;
+    if stack.contains(&canonical) {
+        return Err(MdpError::IncludeCycle {
+            path: path.display().to_string(),
+        });
+    }
+
+    let mut spec = parse(&fs::read_to_string(path)?)?;
+    let Some(includes) = spec.include.take() else {
+        return Ok(spec);
+    };
+
+    stack.push(canonical);
+    let base = path.parent().unwrap_or(Path::new(""));
+    let mut ids: HashSet<String> = spec.states.iter().map(|state| state.id.clone()).collect();
+    for include in includes {
+        let included_path = base.join(&include);
+        let included = load_with_includes(&included_path, parse, stack)?;
+        for state in included.states {
+            if !ids.insert(state.id.clone()) {
+                return Err(MdpError::DuplicateIncludedState {
+                    id: state.id,
+                    path: included_path.display().to_string(),
+                });
+            }
+            spec.states.push(state);
+        }
+        for (own, included) in [
+            (&mut spec.params, included.params),
+            (&mut spec.weights, included.weights),
+        ] {
+            if let Some(included) = included {
+                let own = own.get_or_insert_with(BTreeMap::new);
+                for (name, value) in included {
+                    own.entry(name).or_insert(value);
+                }
+            }
+        }
+    }
+    stack.pop();
+    Ok(spec)
+}
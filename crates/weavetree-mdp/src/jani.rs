@@ -0,0 +1,163 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde_json::{Value, json};
+
+use crate::{MdpError, MdpSpec};
+
+/// Name of the JANI variable holding the state index.
+const STATE_VARIABLE: &str = "s";
+/// Name of the transient JANI variable carrying transition rewards.
+const REWARD_VARIABLE: &str = "reward";
+
+impl MdpSpec {
+    /// Export this spec as a JANI model for checking it with tools such as Storm or Modest.
+    ///
+    /// The model has a single automaton with one integer variable `s`, the index of the
+    /// current state in `states`, and a transient real variable `reward` assigned on every
+    /// transition with the same reward `compile` computes: outcome reward plus the next
+    /// state's entry and terminal rewards, minus the action cost. Spec actions become JANI
+    /// actions of the same name, and terminal states loop on themselves with reward `0`.
+    ///
+    /// If the spec has terminal states, two properties are included: the maximal
+    /// probability of reaching one, and the maximal expected reward accumulated until then.
+    pub fn to_jani(&self) -> Result<Value, MdpError> {
+        self.validate()?;
+        let spec = self.resolve_params()?;
+        let empty = Default::default();
+        let weights = spec.weights.as_ref().unwrap_or(&empty);
+        let index: HashMap<&str, usize> = spec
+            .states
+            .iter()
+            .enumerate()
+            .map(|(i, state)| (state.id.as_str(), i))
+            .collect();
+
+        let is_state = |i: usize| json!({"op": "=", "left": STATE_VARIABLE, "right": i});
+        let assignments = |next: usize, reward: f64| {
+            json!([
+                {"ref": STATE_VARIABLE, "value": next},
+                {"ref": REWARD_VARIABLE, "value": reward},
+            ])
+        };
+
+        let mut action_names = BTreeSet::new();
+        let mut edges = Vec::new();
+        let mut terminals = Vec::new();
+        for (i, state) in spec.states.iter().enumerate() {
+            let actions = state.actions.as_deref().unwrap_or(&[]);
+            if state.terminal.unwrap_or(false) || actions.is_empty() {
+                if state.terminal.unwrap_or(false) {
+                    terminals.push(i);
+                }
+                edges.push(json!({
+                    "location": "l",
+                    "guard": {"exp": is_state(i)},
+                    "destinations": [{
+                        "location": "l",
+                        "probability": {"exp": 1.0},
+                        "assignments": assignments(i, 0.0),
+                    }],
+                }));
+                continue;
+            }
+
+            for action in actions {
+                action_names.insert(action.id.as_str());
+                let destinations: Vec<Value> = action
+                    .outcomes
+                    .iter()
+                    .zip(action.resolved_probabilities())
+                    .map(|(outcome, prob)| {
+                        let next = index[outcome.next.as_str()];
+                        let reward = outcome.reward.scalarized(weights)
+                            + spec.states[next].arrival_reward()
+                            - action.cost.unwrap_or(0.0);
+                        json!({
+                            "location": "l",
+                            "probability": {"exp": prob},
+                            "assignments": assignments(next, reward),
+                        })
+                    })
+                    .collect();
+                edges.push(json!({
+                    "location": "l",
+                    "action": action.id,
+                    "guard": {"exp": is_state(i)},
+                    "destinations": destinations,
+                }));
+            }
+        }
+
+        let properties = match terminals
+            .iter()
+            .map(|&i| is_state(i))
+            .reduce(|left, right| json!({"op": "∨", "left": left, "right": right}))
+        {
+            Some(terminal) => json!([
+                {
+                    "name": "max_reach_terminal",
+                    "expression": {
+                        "op": "filter",
+                        "fun": "max",
+                        "values": {
+                            "op": "Pmax",
+                            "exp": {"op": "U", "left": true, "right": terminal},
+                        },
+                        "states": {"op": "initial"},
+                    },
+                },
+                {
+                    "name": "max_reward_to_terminal",
+                    "expression": {
+                        "op": "filter",
+                        "fun": "max",
+                        "values": {
+                            "op": "Emax",
+                            "exp": REWARD_VARIABLE,
+                            "accumulate": ["steps"],
+                            "reach": terminal,
+                        },
+                        "states": {"op": "initial"},
+                    },
+                },
+            ]),
+            None => json!([]),
+        };
+
+        Ok(json!({
+            "jani-version": 1,
+            "name": "weavetree",
+            "type": "mdp",
+            "actions": action_names
+                .into_iter()
+                .map(|name| json!({"name": name}))
+                .collect::<Vec<_>>(),
+            "variables": [
+                {
+                    "name": STATE_VARIABLE,
+                    "type": {
+                        "kind": "bounded",
+                        "base": "int",
+                        "lower-bound": 0,
+                        "upper-bound": spec.states.len().saturating_sub(1),
+                    },
+                    "initial-value": index[spec.start.as_str()],
+                },
+                {
+                    "name": REWARD_VARIABLE,
+                    "type": "real",
+                    "initial-value": 0.0,
+                    "transient": true,
+                },
+            ],
+            "properties": properties,
+            "automata": [{
+                "name": "mdp",
+                "locations": [{"name": "l"}],
+                "initial-locations": ["l"],
+                "edges": edges,
+            }],
+            "system": {"elements": [{"automaton": "mdp"}]},
+        }))
+    }
+}
@@ -1,17 +1,72 @@
+mod belief;
+pub mod benchmarks;
 mod builder;
 mod compiled;
+mod diagnostics;
+mod diff;
 mod domain;
+mod enumerate;
+mod env;
+mod episode;
 mod error;
+mod expr;
+mod factored;
+mod fingerprint;
+pub mod generators;
+mod hashing;
 mod interner;
 mod io;
+mod jani;
+mod lint;
+mod matrix;
+mod report;
+mod rng;
 mod simulator;
 mod spec;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod turn_based;
 
-pub use builder::MdpBuilder;
-pub use compiled::{CompiledMdp, StateKey};
-pub use domain::MdpDomain;
+pub use belief::BeliefSimulator;
+pub use builder::{ActionHandle, MdpBuilder, StateHandle, TypedMdpBuilder};
+pub use compiled::{CompileOptions, CompiledMdp, PROB_TOLERANCE, PruneReport, StateKey};
+pub use diagnostics::{
+    ActionQComparison, NodeQComparison, OptimalValues, QComparison, compare_to_optimal,
+    value_iteration,
+};
+pub use diff::{ActionDiff, OutcomeDiff, OutcomeSummary, SpecDiff, StateDiff};
+pub use domain::{AfterstateDomain, AfterstatePhase, MdpDomain, TurnBasedDomain};
+pub use enumerate::enumerate_domain;
+pub use env::{Env, EnvStep, SimulatorEnv, Space};
+pub use episode::{
+    EpisodeSimulator, PolicyEvaluation, Trajectory, evaluate_policy, simulate_episode,
+};
 pub use error::MdpError;
-pub use interner::StateInterner;
-pub use io::{compile_yaml, load_yaml, save_yaml};
-pub use simulator::{DomainSimulator, MdpSimulator, SharedDomainSimulator};
-pub use spec::{ActionSpec, MdpSpec, OutcomeSpec, StateSpec};
+pub use factored::{FactoredActionSpec, FactoredOutcomeSpec, FactoredSpec, VariableSpec};
+pub use fingerprint::FingerprintInterner;
+pub use hashing::{hashed_state_key, stable_hash64, stable_hash128};
+pub use interner::{EvictionPolicy, InternerStats, KeyInterner, ReplayFn, StateInterner};
+#[cfg(feature = "binary")]
+pub use io::{compile_binary, compile_binary_with_options, load_binary, save_binary};
+pub use io::{
+    compile_json, compile_json_str, compile_json_str_with_options, compile_json_with_options,
+    compile_yaml, compile_yaml_with_options, load_json, load_yaml, save_json, save_yaml,
+};
+#[cfg(feature = "toml")]
+pub use io::{
+    compile_toml, compile_toml_str, compile_toml_str_with_options, compile_toml_with_options,
+    load_toml, save_toml,
+};
+pub use lint::{Lint, LintKind};
+pub use matrix::{DenseMatrices, SparseTriplets};
+pub use report::ModelReport;
+pub use rng::{RngState, StreamRng};
+pub use simulator::{
+    DefaultRng, DomainSimulator, MdpSimulator, SamplingMode, SharedDomainSimulator,
+    SyncSharedDomainSimulator,
+};
+pub use spec::{
+    ActionSpec, MdpSpec, OutcomeSpec, Probability, RestKeyword, Reward, RewardDistribution, Scalar,
+    StateSpec,
+};
+pub use turn_based::{SharedTurnBasedSimulator, TurnBasedSimulator};
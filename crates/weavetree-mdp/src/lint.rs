@@ -0,0 +1,113 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{MdpError, MdpSpec};
+
+/// Kind of a `Lint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintKind {
+    /// The start state cannot reach the state.
+    UnreachableState,
+    /// A non-terminal state without actions, where episodes end without a terminal reward.
+    DeadEnd,
+    /// A non-terminal state whose every outcome leads back to itself, so episodes entering it
+    /// never end.
+    Absorbing,
+    /// An outcome with an explicit probability of `0`, which is never sampled. A `rest`
+    /// outcome left with nothing is not reported.
+    ZeroProbabilityOutcome,
+}
+
+/// Likely modeling mistake in a spec that still compiles, from `MdpSpec::lint`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lint {
+    pub kind: LintKind,
+    pub state: String,
+    /// Action the lint is about, for `ZeroProbabilityOutcome`.
+    pub action: Option<String>,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = &self.state;
+        match (self.kind, &self.action) {
+            (LintKind::UnreachableState, _) => {
+                write!(f, "state '{state}' is unreachable from the start state")
+            }
+            (LintKind::DeadEnd, _) => write!(
+                f,
+                "state '{state}' is not terminal but has no actions, so episodes end there"
+            ),
+            (LintKind::Absorbing, _) => write!(
+                f,
+                "state '{state}' is not terminal but every outcome loops back to it"
+            ),
+            (LintKind::ZeroProbabilityOutcome, Some(action)) => write!(
+                f,
+                "action '{state}/{action}' has an outcome with probability 0"
+            ),
+            (LintKind::ZeroProbabilityOutcome, None) => {
+                write!(f, "state '{state}' has an outcome with probability 0")
+            }
+        }
+    }
+}
+
+impl MdpSpec {
+    /// Compile this spec and report likely modeling mistakes that are not errors: states the
+    /// start state cannot reach, non-terminal dead ends and self-absorbing states, and outcomes
+    /// that can never be sampled. Lints follow state order.
+    pub fn lint(&self) -> Result<Vec<Lint>, MdpError> {
+        let compiled = self.compile()?;
+        let spec = self.resolve_params()?;
+        let mut reachable = vec![false; spec.states.len()];
+        for key in compiled.reachable_states() {
+            reachable[key.index()] = true;
+        }
+
+        let mut lints = Vec::new();
+        let mut push = |kind, state: &str, action: Option<&str>| {
+            lints.push(Lint {
+                kind,
+                state: state.to_string(),
+                action: action.map(str::to_string),
+            });
+        };
+        for (state, reachable) in spec.states.iter().zip(reachable) {
+            let id = state.id.as_str();
+            let actions = state.actions.as_deref().unwrap_or(&[]);
+            let terminal = state.terminal.unwrap_or(false);
+            if !reachable {
+                push(LintKind::UnreachableState, id, None);
+            }
+            if !terminal && actions.is_empty() {
+                push(LintKind::DeadEnd, id, None);
+            }
+            if !terminal
+                && !actions.is_empty()
+                && actions.iter().all(|action| {
+                    action
+                        .outcomes
+                        .iter()
+                        .zip(action.resolved_probabilities())
+                        .all(|(outcome, prob)| outcome.next == state.id || prob == 0.0)
+                })
+            {
+                push(LintKind::Absorbing, id, None);
+            }
+            for action in actions {
+                let never_sampled = action
+                    .outcomes
+                    .iter()
+                    .zip(action.resolved_probabilities())
+                    .any(|(outcome, prob)| prob == 0.0 && !outcome.prob.is_rest());
+                if never_sampled {
+                    push(LintKind::ZeroProbabilityOutcome, id, Some(&action.id));
+                }
+            }
+        }
+        Ok(lints)
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{CompiledMdp, StateKey};
+
+/// Dense per-action transition and reward matrices, flattened row-major.
+///
+/// Action `a` means the `a`-th action of each state, so `num_actions` is the largest action
+/// count of any state. Terminal states and actions a state does not have get all-zero rows;
+/// `available` tells them apart from real actions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DenseMatrices {
+    pub num_states: usize,
+    pub num_actions: usize,
+    /// `P[a][s][s']` at `(a * num_states + s) * num_states + s'`, summing duplicate outcomes.
+    pub transitions: Vec<f64>,
+    /// Expected immediate reward `R[a][s]` at `a * num_states + s`.
+    pub rewards: Vec<f64>,
+    /// Whether state `s` has action `a`, at `a * num_states + s`.
+    pub available: Vec<bool>,
+    /// Whether state `s` is terminal, at `s`.
+    pub terminal: Vec<bool>,
+}
+
+impl DenseMatrices {
+    /// Return `P[action][state][next]`.
+    pub fn transition(&self, action: usize, state: usize, next: usize) -> f64 {
+        self.transitions[(action * self.num_states + state) * self.num_states + next]
+    }
+
+    /// Return the expected immediate reward `R[action][state]`.
+    pub fn reward(&self, action: usize, state: usize) -> f64 {
+        self.rewards[action * self.num_states + state]
+    }
+}
+
+/// Transitions in coordinate (COO) form, one entry per outcome, as parallel columns.
+///
+/// Terminal states have no entries. Outcomes of one action leading to the same state are kept
+/// as separate entries, since their rewards may differ; sum them to build a matrix.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SparseTriplets {
+    pub num_states: usize,
+    pub num_actions: usize,
+    pub action: Vec<usize>,
+    pub state: Vec<usize>,
+    pub next: Vec<usize>,
+    pub prob: Vec<f64>,
+    pub reward: Vec<f64>,
+}
+
+impl SparseTriplets {
+    /// Return the number of entries.
+    pub fn len(&self) -> usize {
+        self.prob.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prob.is_empty()
+    }
+}
+
+impl CompiledMdp {
+    /// Export dense transition and reward matrices for external linear-algebra tooling.
+    /// Memory grows with `num_actions * num_states^2`; prefer `to_sparse_triplets` for
+    /// large models.
+    pub fn to_dense_matrices(&self) -> DenseMatrices {
+        let num_states = self.state_count();
+        let num_actions = self.max_actions();
+        let mut matrices = DenseMatrices {
+            num_states,
+            num_actions,
+            transitions: vec![0.0; num_actions * num_states * num_states],
+            rewards: vec![0.0; num_actions * num_states],
+            available: vec![false; num_actions * num_states],
+            terminal: (0..num_states)
+                .map(|state| self.is_terminal(StateKey::from(state)) == Some(true))
+                .collect(),
+        };
+
+        for (action, state, next, prob, reward) in self.outcome_entries() {
+            let row = action * num_states + state;
+            matrices.transitions[row * num_states + next] += prob;
+            matrices.rewards[row] += prob * reward;
+            matrices.available[row] = true;
+        }
+        matrices
+    }
+
+    /// Export every outcome as an `(action, state, next, prob, reward)` entry.
+    pub fn to_sparse_triplets(&self) -> SparseTriplets {
+        let mut triplets = SparseTriplets {
+            num_states: self.state_count(),
+            num_actions: self.max_actions(),
+            action: Vec::new(),
+            state: Vec::new(),
+            next: Vec::new(),
+            prob: Vec::new(),
+            reward: Vec::new(),
+        };
+        for (action, state, next, prob, reward) in self.outcome_entries() {
+            triplets.action.push(action);
+            triplets.state.push(state);
+            triplets.next.push(next);
+            triplets.prob.push(prob);
+            triplets.reward.push(reward);
+        }
+        triplets
+    }
+
+    fn max_actions(&self) -> usize {
+        (0..self.state_count())
+            .filter_map(|state| self.num_actions(StateKey::from(state)))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Iterate `(action, state, next, prob, reward)` over the outcomes of non-terminal states.
+    fn outcome_entries(&self) -> impl Iterator<Item = (usize, usize, usize, f64, f64)> + '_ {
+        (0..self.state_count())
+            .map(StateKey::from)
+            .filter(|&state| self.is_terminal(state) == Some(false))
+            .flat_map(move |state| {
+                let num_actions = self.num_actions(state).unwrap_or(0);
+                (0..num_actions).flat_map(move |action| {
+                    self.transitions(state, action)
+                        .map(move |(next, prob, reward)| {
+                            (action, state.index(), next.index(), prob, reward)
+                        })
+                })
+            })
+    }
+}
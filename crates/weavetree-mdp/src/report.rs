@@ -0,0 +1,134 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CompiledMdp, StateKey};
+
+/// Size and shape statistics of a compiled MDP, from `CompiledMdp::report`.
+///
+/// Actions and outcomes are counted over non-terminal states only, since terminal states
+/// end episodes before any of their actions is taken.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelReport {
+    pub states: usize,
+    pub terminal_states: usize,
+    /// Share of states that are terminal.
+    pub terminal_ratio: f64,
+    /// States reachable from the start state; see `CompiledMdp::reachable_states`.
+    pub reachable_states: usize,
+    pub actions: usize,
+    pub outcomes: usize,
+    /// Most actions any state offers.
+    pub max_actions: usize,
+    /// Most outcomes any action has.
+    pub max_branching: usize,
+    /// Mean outcomes per action; `0.0` without actions.
+    pub mean_branching: f64,
+    /// Actions with a single outcome of positive probability.
+    pub deterministic_actions: usize,
+    /// Actions with several outcomes of positive probability.
+    pub stochastic_actions: usize,
+    /// Mean Shannon entropy of the outcome distributions in bits; `0.0` without actions.
+    pub mean_outcome_entropy: f64,
+    /// Smallest and largest expected outcome reward, including costs and entry rewards;
+    /// `None` without outcomes.
+    pub reward_range: Option<(f64, f64)>,
+}
+
+impl CompiledMdp {
+    /// Summarize the model: state, action, and outcome counts, branching, terminal ratio,
+    /// how stochastic its actions are, and the range of its rewards.
+    pub fn report(&self) -> ModelReport {
+        let mut report = ModelReport {
+            states: self.state_count(),
+            terminal_states: 0,
+            terminal_ratio: 0.0,
+            reachable_states: self.reachable_states().len(),
+            actions: 0,
+            outcomes: 0,
+            max_actions: 0,
+            max_branching: 0,
+            mean_branching: 0.0,
+            deterministic_actions: 0,
+            stochastic_actions: 0,
+            mean_outcome_entropy: 0.0,
+            reward_range: None,
+        };
+        let mut total_entropy = 0.0;
+
+        for state in (0..self.state_count()).map(StateKey::from) {
+            if self.is_terminal(state) == Some(true) {
+                report.terminal_states += 1;
+                continue;
+            }
+            let num_actions = self.num_actions(state).unwrap_or(0);
+            report.actions += num_actions;
+            report.max_actions = report.max_actions.max(num_actions);
+
+            for action in 0..num_actions {
+                let mut branching = 0;
+                let mut possible = 0;
+                for (_, prob, reward) in self.transitions(state, action) {
+                    branching += 1;
+                    if prob > 0.0 {
+                        possible += 1;
+                        total_entropy -= prob * prob.log2();
+                    }
+                    report.reward_range = Some(match report.reward_range {
+                        Some((low, high)) => (low.min(reward), high.max(reward)),
+                        None => (reward, reward),
+                    });
+                }
+                report.outcomes += branching;
+                report.max_branching = report.max_branching.max(branching);
+                match possible {
+                    0 => {}
+                    1 => report.deterministic_actions += 1,
+                    _ => report.stochastic_actions += 1,
+                }
+            }
+        }
+
+        if report.states > 0 {
+            report.terminal_ratio = report.terminal_states as f64 / report.states as f64;
+        }
+        if report.actions > 0 {
+            report.mean_branching = report.outcomes as f64 / report.actions as f64;
+            report.mean_outcome_entropy = total_entropy / report.actions as f64;
+        }
+        report
+    }
+}
+
+impl fmt::Display for ModelReport {
+    /// Multi-line summary for printing before experiments.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "states:     {} ({} reachable, {} terminal, {:.1}%)",
+            self.states,
+            self.reachable_states,
+            self.terminal_states,
+            100.0 * self.terminal_ratio
+        )?;
+        writeln!(
+            f,
+            "actions:    {} (at most {} per state)",
+            self.actions, self.max_actions
+        )?;
+        writeln!(
+            f,
+            "outcomes:   {} ({:.2} per action, at most {})",
+            self.outcomes, self.mean_branching, self.max_branching
+        )?;
+        writeln!(
+            f,
+            "stochastic: {} deterministic, {} stochastic actions, {:.3} bits mean entropy",
+            self.deterministic_actions, self.stochastic_actions, self.mean_outcome_entropy
+        )?;
+        match self.reward_range {
+            Some((low, high)) => writeln!(f, "rewards:    {low} to {high}"),
+            None => writeln!(f, "rewards:    none"),
+        }
+    }
+}
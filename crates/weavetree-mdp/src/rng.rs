@@ -0,0 +1,52 @@
+use rand::RngCore;
+use rand_chacha::{ChaCha8Rng, ChaCha12Rng, ChaCha20Rng};
+use serde::{Deserialize, Serialize};
+
+/// Position of a stream-addressable RNG, enough to resume it exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RngState {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    /// Offset into the stream, in 32-bit words.
+    pub word_pos: u128,
+}
+
+/// RNG with independent numbered streams per seed and an exportable position,
+/// implemented for the ChaCha generators.
+pub trait StreamRng: RngCore {
+    /// Switch to stream `stream` of the current seed, keeping the position within the stream.
+    fn set_stream(&mut self, stream: u64);
+
+    /// Capture the current position.
+    fn state(&self) -> RngState;
+
+    /// Rebuild a generator at a captured position.
+    fn from_state(state: &RngState) -> Self;
+}
+
+macro_rules! impl_stream_rng {
+    ($($rng:ty),*) => {$(
+        impl StreamRng for $rng {
+            fn set_stream(&mut self, stream: u64) {
+                <$rng>::set_stream(self, stream);
+            }
+
+            fn state(&self) -> RngState {
+                RngState {
+                    seed: self.get_seed(),
+                    stream: self.get_stream(),
+                    word_pos: self.get_word_pos(),
+                }
+            }
+
+            fn from_state(state: &RngState) -> Self {
+                let mut rng = <$rng as rand::SeedableRng>::from_seed(state.seed);
+                rng.set_stream(state.stream);
+                rng.set_word_pos(state.word_pos);
+                rng
+            }
+        }
+    )*};
+}
+
+impl_stream_rng!(ChaCha8Rng, ChaCha12Rng, ChaCha20Rng);
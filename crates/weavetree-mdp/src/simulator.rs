@@ -1,27 +1,120 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
 
 use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
-use weavetree_core::{ActionId, StateKey as CoreStateKey};
+use weavetree_core::{ActionId, StateKey as CoreStateKey, StateKeyValue};
 
-use crate::{CompiledMdp, MdpDomain, StateInterner, StateKey};
+use crate::{
+    CompiledMdp, EvictionPolicy, InternerStats, KeyInterner, MdpDomain, MdpError, RngState,
+    StateInterner, StateKey, StreamRng, Trajectory,
+};
+
+/// RNG used by the simulators unless another one is named.
+pub type DefaultRng = ChaCha8Rng;
+
+/// Draw a uniform sample in `[0, 1)` from the full 64 bits of the next RNG output.
+pub(crate) fn uniform_sample<R: RngCore>(rng: &mut R) -> f64 {
+    (rng.next_u64() as f64) / ((u64::MAX as f64) + 1.0)
+}
+
+/// How `MdpSimulator` picks an outcome among the possible transitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SamplingMode {
+    /// Draw outcomes according to their probabilities, and rewards from their distributions.
+    #[default]
+    Sample,
+    /// Always take the highest-probability outcome, ties going to the first listed, with
+    /// reward distributions yielding their mean. The RNG is not advanced.
+    MostLikely,
+}
 
 #[derive(Debug, Clone)]
 /// Seeded simulator over a compiled MDP.
-pub struct MdpSimulator {
+/// Generic over its RNG `R`, which defaults to `DefaultRng` (ChaCha8).
+pub struct MdpSimulator<R = DefaultRng> {
     mdp: CompiledMdp,
-    rng: ChaCha8Rng,
+    rng: R,
+    mode: SamplingMode,
 }
 
 impl MdpSimulator {
     /// Create a simulator with deterministic RNG seed.
     pub fn new(mdp: CompiledMdp, seed: u64) -> Self {
+        Self::with_seed(mdp, seed)
+    }
+}
+
+impl<R> MdpSimulator<R>
+where
+    R: RngCore + SeedableRng,
+{
+    /// Create a simulator whose RNG of type `R` is seeded from `seed`.
+    pub fn with_seed(mdp: CompiledMdp, seed: u64) -> Self {
+        Self::with_rng(mdp, R::seed_from_u64(seed))
+    }
+
+    /// Replace the RNG with a fresh one seeded from `seed`, as if the simulator had been
+    /// created with it.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = R::seed_from_u64(seed);
+    }
+}
+
+impl<R> MdpSimulator<R>
+where
+    R: StreamRng,
+{
+    /// Switch the RNG to stream `stream_id` of its current seed, e.g. one stream per episode.
+    pub fn set_stream(&mut self, stream_id: u64) {
+        self.rng.set_stream(stream_id);
+    }
+
+    /// Capture the RNG position, to replay the following steps with `restore_rng_state`.
+    pub fn rng_state(&self) -> RngState {
+        self.rng.state()
+    }
+
+    /// Resume the RNG at a position captured by `rng_state`.
+    pub fn restore_rng_state(&mut self, state: &RngState) {
+        self.rng = R::from_state(state);
+    }
+}
+
+impl<R> MdpSimulator<R>
+where
+    R: RngCore,
+{
+    /// Create a simulator drawing from an already constructed RNG,
+    /// e.g. a counter-based generator positioned on its own stream.
+    pub fn with_rng(mdp: CompiledMdp, rng: R) -> Self {
         Self {
             mdp,
-            rng: ChaCha8Rng::seed_from_u64(seed),
+            rng,
+            mode: SamplingMode::Sample,
         }
     }
 
+    /// Switch between sampled and most-likely outcomes, e.g. `SamplingMode::MostLikely` for
+    /// deterministic tests and baselines.
+    pub fn with_sampling_mode(mut self, mode: SamplingMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Return how this simulator picks outcomes.
+    pub fn sampling_mode(&self) -> SamplingMode {
+        self.mode
+    }
+
+    /// Borrow the RNG driving this simulator.
+    pub fn rng(&self) -> &R {
+        &self.rng
+    }
+
     /// Borrow the underlying compiled MDP.
     pub fn mdp(&self) -> &CompiledMdp {
         &self.mdp
@@ -35,22 +128,43 @@ impl MdpSimulator {
     /// Sample one `(next_state, reward, terminal)` transition.
     /// Invalid state/action inputs are treated as a no-op terminal transition.
     pub fn step(&mut self, state_key: StateKey, action_id: usize) -> (StateKey, f64, bool) {
-        let sample = (self.rng.next_u64() as f64) / ((u64::MAX as f64) + 1.0);
-        self.mdp
-            .sample_transition(state_key, action_id, sample)
-            .unwrap_or((state_key, 0.0, true))
+        let transition = match self.mode {
+            SamplingMode::Sample => {
+                let sample = uniform_sample(&mut self.rng);
+                let rng = &mut self.rng;
+                self.mdp
+                    .sample_transition(state_key, action_id, sample, || uniform_sample(rng))
+            }
+            SamplingMode::MostLikely => self.mdp.most_likely_transition(state_key, action_id),
+        };
+        transition.unwrap_or((state_key, 0.0, true))
+    }
+
+    /// Sample `n` independent `(next_state, reward, terminal)` transitions from one
+    /// state-action pair, drawing the same RNG values as `n` calls to `step`.
+    /// Invalid state/action inputs yield `n` no-op terminal transitions.
+    pub fn sample_batch(
+        &mut self,
+        state_key: StateKey,
+        action_id: usize,
+        n: usize,
+    ) -> Vec<(StateKey, f64, bool)> {
+        (0..n).map(|_| self.step(state_key, action_id)).collect()
     }
 }
 
 #[derive(Debug, Clone)]
 /// Seeded simulator over a user-defined MDP domain with arbitrary state encoding.
-pub struct DomainSimulator<D>
+/// Generic over its RNG `R`, which defaults to `DefaultRng` (ChaCha8), and over the
+/// `KeyInterner` `I` that maps states to keys, which defaults to `StateInterner`.
+pub struct DomainSimulator<D, R = DefaultRng, I = StateInterner<<D as MdpDomain>::State>>
 where
     D: MdpDomain,
 {
     domain: D,
-    state_interner: StateInterner<D::State>,
-    rng: ChaCha8Rng,
+    state_interner: I,
+    start_key: StateKeyValue,
+    rng: R,
 }
 
 impl<D> DomainSimulator<D>
@@ -59,18 +173,123 @@ where
 {
     /// Create a domain simulator with deterministic RNG seed.
     pub fn new(domain: D, seed: u64) -> Self {
-        let mut state_interner = StateInterner::new();
-        let _ = state_interner.intern(domain.start_state());
+        Self::with_seed(domain, seed)
+    }
+}
+
+impl<D, R> DomainSimulator<D, R>
+where
+    D: MdpDomain,
+    R: RngCore + SeedableRng,
+{
+    /// Create a domain simulator whose RNG of type `R` is seeded from `seed`.
+    pub fn with_seed(domain: D, seed: u64) -> Self {
+        Self::with_rng(domain, R::seed_from_u64(seed))
+    }
+}
+
+impl<D, R, I> DomainSimulator<D, R, I>
+where
+    D: MdpDomain,
+    R: RngCore + SeedableRng,
+    I: KeyInterner<D::State>,
+{
+    /// Replace the RNG with a fresh one seeded from `seed`. Interned keys stay valid.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = R::seed_from_u64(seed);
+    }
+}
+
+impl<D, R, I> DomainSimulator<D, R, I>
+where
+    D: MdpDomain,
+    R: StreamRng,
+    I: KeyInterner<D::State>,
+{
+    /// Switch the RNG to stream `stream_id` of its current seed, e.g. one stream per episode.
+    pub fn set_stream(&mut self, stream_id: u64) {
+        self.rng.set_stream(stream_id);
+    }
+
+    /// Capture the RNG position, to replay the following steps with `restore_rng_state`.
+    pub fn rng_state(&self) -> RngState {
+        self.rng.state()
+    }
+
+    /// Resume the RNG at a position captured by `rng_state`.
+    pub fn restore_rng_state(&mut self, state: &RngState) {
+        self.rng = R::from_state(state);
+    }
+}
+
+impl<D, R> DomainSimulator<D, R>
+where
+    D: MdpDomain,
+    R: RngCore,
+{
+    /// Create a domain simulator drawing from an already constructed RNG.
+    pub fn with_rng(domain: D, rng: R) -> Self {
+        Self::with_interner(domain, rng, StateInterner::new())
+    }
+
+    /// Bound the state interner to `capacity` states, applying `policy` once it is full.
+    /// The start state is pinned and never evicted. Keys of evicted states no longer resolve:
+    /// `try_step_by_key` and the other `try_` methods fail with `MdpError::UnknownStateKey`,
+    /// while `step_by_key` turns them into no-op terminal transitions that would skew a
+    /// search. Size the limit well above the states a single search touches.
+    pub fn with_state_capacity(self, capacity: usize, policy: EvictionPolicy) -> Self {
+        let verify = self.state_interner.is_verifying();
+        let interner = StateInterner::configured(Some((capacity.max(1), policy)), verify);
+        Self::with_interner(self.domain, self.rng, interner)
+    }
+
+    /// Check the state type's `Eq` and `Hash` whenever a state is interned, as
+    /// `StateInterner::verifying()` does. Suspicious behavior, such as equal states that
+    /// hash differently, makes `try_step_by_key` fail with `MdpError::SuspiciousStateEquality`
+    /// and `step_by_key` panic. Each check scans all stored states, so use it while
+    /// debugging a domain. Like `with_state_capacity`, this starts over from the start state.
+    pub fn with_state_checks(self) -> Self {
+        let limit = self.state_interner.capacity_limit();
+        let interner = StateInterner::configured(limit, true);
+        Self::with_interner(self.domain, self.rng, interner)
+    }
+
+    /// Resolve a key back into its decoded state.
+    pub fn state_for_key(&self, key: StateKeyValue) -> Option<&D::State> {
+        self.state_interner.get(key)
+    }
+}
+
+impl<D, R, I> DomainSimulator<D, R, I>
+where
+    D: MdpDomain,
+    R: RngCore,
+    I: KeyInterner<D::State>,
+{
+    /// Create a domain simulator over a specific interner, e.g. a `FingerprintInterner`.
+    ///
+    /// # Panics
+    /// Panics if the interner has no room for the start state.
+    pub fn with_interner(domain: D, rng: R, mut interner: I) -> Self {
+        let start_key = interner
+            .intern_start(domain.start_state())
+            .expect("interner must have room for the start state");
         Self {
             domain,
-            state_interner,
-            rng: ChaCha8Rng::seed_from_u64(seed),
+            state_interner: interner,
+            start_key,
+            rng,
         }
     }
 
+    /// Borrow the RNG driving this simulator.
+    pub fn rng(&self) -> &R {
+        &self.rng
+    }
+
     /// Return the key of the domain start state.
-    pub fn start_state_key(&self) -> u64 {
-        0
+    pub fn start_state_key(&self) -> StateKeyValue {
+        self.start_key
     }
 
     /// Borrow the underlying domain implementation.
@@ -78,60 +297,265 @@ where
         &self.domain
     }
 
-    /// Resolve a key back into its decoded state.
-    pub fn state_for_key(&self, key: u64) -> Option<&D::State> {
-        self.state_interner.get(key)
+    /// Borrow the state interner.
+    pub fn interner(&self) -> &I {
+        &self.state_interner
+    }
+
+    /// Return how many states the interner currently holds.
+    pub fn interned_states(&self) -> usize {
+        self.state_interner.len()
+    }
+
+    /// Return memory counters of the state interner.
+    pub fn interner_stats(&self) -> InternerStats {
+        self.state_interner.stats()
+    }
+
+    /// Verify the interner's bookkeeping, e.g. after a search, failing with
+    /// `MdpError::InconsistentInterner` if keys and states no longer map to each other.
+    pub fn check_interner(&self) -> Result<(), MdpError> {
+        self.state_interner.check_consistency()
+    }
+
+    /// Return an owned copy of the state behind a key, rebuilding it if the interner
+    /// does not store it.
+    pub fn decode_state(&self, key: StateKeyValue) -> Option<D::State> {
+        self.with_state(key, D::State::clone)
+    }
+
+    fn with_state<T>(&self, key: StateKeyValue, f: impl FnOnce(&D::State) -> T) -> Option<T> {
+        let domain = &self.domain;
+        let replay = |state: &D::State, action_id, sample| domain.step(state, action_id, sample).0;
+        self.state_interner.with_state(key, &replay, f)
     }
 
     /// Return whether an interned state key is terminal.
-    pub fn is_terminal_by_key(&self, state_key: u64) -> bool {
-        self.state_interner
-            .get(state_key)
-            .map(|state| self.domain.is_terminal(state))
+    pub fn is_terminal_by_key(&self, state_key: StateKeyValue) -> bool {
+        self.with_state(state_key, |state| self.domain.is_terminal(state))
             .unwrap_or(true)
     }
 
     /// Return how many actions are available for an interned state key.
-    pub fn num_actions_by_key(&self, state_key: u64) -> usize {
-        self.state_interner
-            .get(state_key)
-            .map(|state| self.domain.num_actions(state))
+    pub fn num_actions_by_key(&self, state_key: StateKeyValue) -> usize {
+        self.with_state(state_key, |state| self.domain.num_actions(state))
             .unwrap_or(0)
     }
 
+    /// Return the domain's label for an action of an interned state key.
+    pub fn action_label_by_key(
+        &self,
+        state_key: StateKeyValue,
+        action_id: usize,
+    ) -> Option<String> {
+        self.with_state(state_key, |state| {
+            self.domain.action_label(state, action_id)
+        })
+    }
+
     /// Sample one `(next_state_key, reward, terminal)` transition.
     /// Invalid state/action inputs are treated as a no-op terminal transition.
-    pub fn step_by_key(&mut self, state_key: u64, action_id: usize) -> (u64, f64, bool) {
-        let Some(state) = self.state_interner.get(state_key).cloned() else {
-            return (state_key, 0.0, true);
+    ///
+    /// # Panics
+    /// Panics if the interner rejects the next state; use `try_step_by_key` to handle that case.
+    pub fn step_by_key(
+        &mut self,
+        state_key: StateKeyValue,
+        action_id: usize,
+    ) -> (StateKeyValue, f64, bool) {
+        match self.try_step_by_key(state_key, action_id) {
+            Err(MdpError::UnknownStateKey { .. }) => (state_key, 0.0, true),
+            result => result.expect("state interner rejected the next state"),
+        }
+    }
+
+    /// Like `step_by_key`, but returns the interner's error, e.g. `MdpError::InternerFull`,
+    /// instead of panicking, and `MdpError::UnknownStateKey` for a key that does not resolve,
+    /// such as one evicted by `EvictionPolicy::EvictOldest`.
+    pub fn try_step_by_key(
+        &mut self,
+        state_key: StateKeyValue,
+        action_id: usize,
+    ) -> Result<(StateKeyValue, f64, bool), MdpError> {
+        let domain = &self.domain;
+        let rng = &mut self.rng;
+        let replay = |state: &D::State, action_id, sample| domain.step(state, action_id, sample).0;
+        let Some((next_state, reward, terminal, sample)) =
+            self.state_interner.with_state(state_key, &replay, |state| {
+                let sample = uniform_sample(rng);
+                let (next_state, reward, terminal) = domain.step(state, action_id, sample);
+                (next_state, reward, terminal, sample)
+            })
+        else {
+            return Err(MdpError::UnknownStateKey { key: state_key });
         };
 
-        let sample = (self.rng.next_u64() as f64) / ((u64::MAX as f64) + 1.0);
-        let (next_state, reward, terminal) = self.domain.step(&state, action_id, sample);
-        let next_key = self.state_interner.intern(next_state);
-        (next_key, reward, terminal)
+        let next_key = self
+            .state_interner
+            .intern_successor(next_state, state_key, action_id, sample, &replay)?;
+        Ok((next_key, reward, terminal))
+    }
+
+    /// Sample `n` independent `(next_state_key, reward, terminal)` transitions from one
+    /// state-action pair. The state is resolved once for the whole batch, which saves the
+    /// per-call lookup of `step_by_key` and, for replaying interners, repeated rebuilds.
+    /// An invalid state key yields `n` no-op terminal transitions.
+    ///
+    /// # Panics
+    /// Panics if the interner rejects a next state; use `try_sample_batch_by_key` to handle
+    /// that case.
+    pub fn sample_batch_by_key(
+        &mut self,
+        state_key: StateKeyValue,
+        action_id: usize,
+        n: usize,
+    ) -> Vec<(StateKeyValue, f64, bool)> {
+        match self.try_sample_batch_by_key(state_key, action_id, n) {
+            Err(MdpError::UnknownStateKey { .. }) => vec![(state_key, 0.0, true); n],
+            result => result.expect("state interner rejected the next state"),
+        }
+    }
+
+    /// Like `sample_batch_by_key`, but returns the interner's error instead of panicking,
+    /// and `MdpError::UnknownStateKey` for a key that does not resolve.
+    /// Transitions interned before the error keep their keys.
+    pub fn try_sample_batch_by_key(
+        &mut self,
+        state_key: StateKeyValue,
+        action_id: usize,
+        n: usize,
+    ) -> Result<Vec<(StateKeyValue, f64, bool)>, MdpError> {
+        let domain = &self.domain;
+        let rng = &mut self.rng;
+        let replay = |state: &D::State, action_id, sample| domain.step(state, action_id, sample).0;
+        let Some(outcomes) = self.state_interner.with_state(state_key, &replay, |state| {
+            (0..n)
+                .map(|_| {
+                    let sample = uniform_sample(rng);
+                    let (next_state, reward, terminal) = domain.step(state, action_id, sample);
+                    (next_state, reward, terminal, sample)
+                })
+                .collect::<Vec<_>>()
+        }) else {
+            return Err(MdpError::UnknownStateKey { key: state_key });
+        };
+
+        outcomes
+            .into_iter()
+            .map(|(next_state, reward, terminal, sample)| {
+                let next_key = self
+                    .state_interner
+                    .intern_successor(next_state, state_key, action_id, sample, &replay)?;
+                Ok((next_key, reward, terminal))
+            })
+            .collect()
+    }
+
+    /// Play from an interned state, asking `policy(state, num_actions)` for every action,
+    /// until a terminal state, a state without actions, or `max_steps` steps, and return the
+    /// undiscounted return with the trajectory. Useful for baseline policies and for testing
+    /// a domain without a tree. An unknown key yields an empty terminal trajectory.
+    ///
+    /// # Panics
+    /// Panics if the interner rejects a next state; use `try_rollout` to handle that case.
+    pub fn rollout<P>(
+        &mut self,
+        state_key: StateKeyValue,
+        policy: P,
+        max_steps: usize,
+    ) -> (f64, Trajectory)
+    where
+        P: FnMut(&D::State, usize) -> usize,
+    {
+        match self.try_rollout(state_key, policy, max_steps) {
+            Err(MdpError::UnknownStateKey { key }) if key == state_key => (
+                0.0,
+                Trajectory {
+                    states: vec![CoreStateKey::from_raw(state_key)],
+                    actions: Vec::new(),
+                    rewards: Vec::new(),
+                    terminal: true,
+                },
+            ),
+            result => result.expect("state interner rejected the next state"),
+        }
+    }
+
+    /// Like `rollout`, but returns the interner's error instead of panicking, and
+    /// `MdpError::UnknownStateKey` for a start key that does not resolve.
+    pub fn try_rollout<P>(
+        &mut self,
+        state_key: StateKeyValue,
+        mut policy: P,
+        max_steps: usize,
+    ) -> Result<(f64, Trajectory), MdpError>
+    where
+        P: FnMut(&D::State, usize) -> usize,
+    {
+        let terminal = self
+            .with_state(state_key, |state| self.domain.is_terminal(state))
+            .ok_or(MdpError::UnknownStateKey { key: state_key })?;
+        let mut state = state_key;
+        let mut trajectory = Trajectory {
+            states: vec![CoreStateKey::from_raw(state)],
+            actions: Vec::new(),
+            rewards: Vec::new(),
+            terminal,
+        };
+
+        while !trajectory.terminal && trajectory.len() < max_steps {
+            let Some(action) = self
+                .with_state(state, |decoded| {
+                    let num_actions = self.domain.num_actions(decoded);
+                    (num_actions > 0).then(|| policy(decoded, num_actions))
+                })
+                .flatten()
+            else {
+                break;
+            };
+            let (next, reward, terminal) = self.try_step_by_key(state, action)?;
+            trajectory.states.push(CoreStateKey::from_raw(next));
+            trajectory.actions.push(ActionId::from(action));
+            trajectory.rewards.push(reward);
+            trajectory.terminal = terminal;
+            state = next;
+        }
+
+        Ok((trajectory.total_return(), trajectory))
     }
 
     /// Wrap this simulator in shared interior mutability for MCTS callback wiring.
-    pub fn into_shared(self) -> SharedDomainSimulator<D> {
+    pub fn into_shared(self) -> SharedDomainSimulator<D, R, I> {
         SharedDomainSimulator::new(self)
     }
+
+    /// Wrap this simulator behind a mutex so its callbacks can be used from several threads.
+    pub fn into_sync_shared(self) -> SyncSharedDomainSimulator<D, R, I>
+    where
+        D: Send,
+        R: Send,
+        I: Send,
+    {
+        SyncSharedDomainSimulator::new(self)
+    }
 }
 
 /// Shared wrapper that offers direct callback adapters for `weavetree_core::Tree::run`.
 #[derive(Clone)]
-pub struct SharedDomainSimulator<D>
+pub struct SharedDomainSimulator<D, R = DefaultRng, I = StateInterner<<D as MdpDomain>::State>>
 where
     D: MdpDomain,
 {
-    inner: Rc<RefCell<DomainSimulator<D>>>,
+    inner: Rc<RefCell<DomainSimulator<D, R, I>>>,
 }
 
-impl<D> SharedDomainSimulator<D>
+impl<D, R, I> SharedDomainSimulator<D, R, I>
 where
     D: MdpDomain,
+    R: RngCore,
+    I: KeyInterner<D::State>,
 {
-    pub fn new(simulator: DomainSimulator<D>) -> Self {
+    pub fn new(simulator: DomainSimulator<D, R, I>) -> Self {
         Self {
             inner: Rc::new(RefCell::new(simulator)),
         }
@@ -140,7 +564,12 @@ where
     /// Return the encoded start state key for tree initialization.
     pub fn start_state_key(&self) -> CoreStateKey {
         let key = self.inner.borrow().start_state_key();
-        CoreStateKey::from(key)
+        CoreStateKey::from_raw(key)
+    }
+
+    /// Return memory counters of the wrapped simulator's state interner.
+    pub fn interner_stats(&self) -> InternerStats {
+        self.inner.borrow().interner_stats()
     }
 
     /// Return whether the root state is terminal.
@@ -152,17 +581,134 @@ where
     /// Build a callback compatible with `Tree::run` `num_actions`.
     pub fn num_actions_fn(&self) -> impl FnMut(CoreStateKey) -> usize + '_ {
         let inner = Rc::clone(&self.inner);
-        move |state| inner.borrow().num_actions_by_key(state.value())
+        move |state| inner.borrow().num_actions_by_key(state.raw())
+    }
+
+    /// Build a callback compatible with `TreeSnapshot::with_action_labels`.
+    pub fn action_label_fn(&self) -> impl FnMut(CoreStateKey, ActionId) -> Option<String> + '_ {
+        let inner = Rc::clone(&self.inner);
+        move |state, action| {
+            inner
+                .borrow()
+                .action_label_by_key(state.raw(), action.index())
+        }
     }
 
     /// Build a callback compatible with `Tree::run` `step`.
     pub fn step_fn(&self) -> impl FnMut(CoreStateKey, ActionId) -> (CoreStateKey, f64, bool) + '_ {
+        let inner = Rc::clone(&self.inner);
+        move |state, action| {
+            let (next, reward, terminal) =
+                inner.borrow_mut().step_by_key(state.raw(), action.index());
+            (CoreStateKey::from_raw(next), reward, terminal)
+        }
+    }
+
+    /// Build a callback compatible with `Tree::run_fallible` `step` that reports interner
+    /// errors such as `MdpError::InternerFull` instead of panicking.
+    pub fn try_step_fn(
+        &self,
+    ) -> impl FnMut(CoreStateKey, ActionId) -> Result<(CoreStateKey, f64, bool), MdpError> + '_
+    {
         let inner = Rc::clone(&self.inner);
         move |state, action| {
             let (next, reward, terminal) = inner
                 .borrow_mut()
-                .step_by_key(state.value(), action.index());
-            (CoreStateKey::from(next), reward, terminal)
+                .try_step_by_key(state.raw(), action.index())?;
+            Ok((CoreStateKey::from_raw(next), reward, terminal))
         }
     }
 }
+
+/// Thread-safe counterpart of `SharedDomainSimulator` backed by `Arc<Mutex<_>>`.
+/// Clones share one simulator, so every thread draws from the same RNG stream and interner;
+/// which thread gets which sample depends on scheduling, so multi-threaded runs are not
+/// reproducible even with a fixed seed. Callbacks lock the simulator once per call.
+pub struct SyncSharedDomainSimulator<D, R = DefaultRng, I = StateInterner<<D as MdpDomain>::State>>
+where
+    D: MdpDomain,
+{
+    inner: Arc<Mutex<DomainSimulator<D, R, I>>>,
+}
+
+impl<D, R, I> Clone for SyncSharedDomainSimulator<D, R, I>
+where
+    D: MdpDomain,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<D, R, I> SyncSharedDomainSimulator<D, R, I>
+where
+    D: MdpDomain + Send,
+    R: RngCore + Send,
+    I: KeyInterner<D::State> + Send,
+{
+    pub fn new(simulator: DomainSimulator<D, R, I>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(simulator)),
+        }
+    }
+
+    /// Lock the simulator. A panic in another thread's callback does not poison it for the
+    /// rest: the interner only changes after a domain step has returned.
+    fn lock(&self) -> MutexGuard<'_, DomainSimulator<D, R, I>> {
+        lock(&self.inner)
+    }
+
+    /// Return the encoded start state key for tree initialization.
+    pub fn start_state_key(&self) -> CoreStateKey {
+        CoreStateKey::from_raw(self.lock().start_state_key())
+    }
+
+    /// Return memory counters of the wrapped simulator's state interner.
+    pub fn interner_stats(&self) -> InternerStats {
+        self.lock().interner_stats()
+    }
+
+    /// Return whether the root state is terminal.
+    pub fn root_is_terminal(&self) -> bool {
+        let simulator = self.lock();
+        simulator.is_terminal_by_key(simulator.start_state_key())
+    }
+
+    /// Build a `Send` callback compatible with `Tree::run` `num_actions`.
+    pub fn num_actions_fn(&self) -> impl FnMut(CoreStateKey) -> usize + Send + use<D, R, I> {
+        let inner = Arc::clone(&self.inner);
+        move |state| lock(&inner).num_actions_by_key(state.raw())
+    }
+
+    /// Build a `Send` callback compatible with `Tree::run` `step`.
+    pub fn step_fn(
+        &self,
+    ) -> impl FnMut(CoreStateKey, ActionId) -> (CoreStateKey, f64, bool) + Send + use<D, R, I> {
+        let inner = Arc::clone(&self.inner);
+        move |state, action| {
+            let (next, reward, terminal) = lock(&inner).step_by_key(state.raw(), action.index());
+            (CoreStateKey::from_raw(next), reward, terminal)
+        }
+    }
+
+    /// Build a `Send` callback compatible with `Tree::run_fallible` `step` that reports
+    /// interner errors instead of panicking.
+    pub fn try_step_fn(
+        &self,
+    ) -> impl FnMut(CoreStateKey, ActionId) -> Result<(CoreStateKey, f64, bool), MdpError>
+    + Send
+    + use<D, R, I> {
+        let inner = Arc::clone(&self.inner);
+        move |state, action| {
+            let (next, reward, terminal) =
+                lock(&inner).try_step_by_key(state.raw(), action.index())?;
+            Ok((CoreStateKey::from_raw(next), reward, terminal))
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
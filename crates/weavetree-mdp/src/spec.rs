@@ -1,16 +1,31 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{CompiledMdp, MdpError, compiled::PROB_TOLERANCE};
+use crate::{CompileOptions, CompiledMdp, MdpError, PROB_TOLERANCE, PruneReport, expr};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-/// Serializable MDP schema used for YAML IO and validation.
+/// Serializable MDP schema used for YAML/JSON/TOML IO and validation.
 pub struct MdpSpec {
     /// Schema version for future compatibility checks.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<u32>,
-    /// String id of the start state.
+    /// Named values that outcome probabilities and rewards can reference as `${name}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<BTreeMap<String, f64>>,
+    /// Weight of every objective named in vector rewards; a vector reward compiles to the
+    /// weighted sum of its entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<BTreeMap<String, f64>>,
+    /// Files whose states are merged into this spec by the `load_*` functions, relative to
+    /// this file. Included files usually declare only `states`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
+    /// String id of the start state. May be omitted in included files.
+    #[serde(default)]
     pub start: String,
     /// All state declarations in the model.
     pub states: Vec<StateSpec>,
@@ -27,6 +42,25 @@ pub struct StateSpec {
     /// Available actions from this state.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub actions: Option<Vec<ActionSpec>>,
+    /// Reward added to every transition into this state, including self-loops.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_reward: Option<f64>,
+    /// Reward added to every transition into this terminal state, on top of `entry_reward`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal_reward: Option<f64>,
+    /// Free-form tags such as `goal` or `hazard`, kept by `CompiledMdp` for analysis.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+    /// Free-form key-value annotations, kept by `CompiledMdp`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+impl StateSpec {
+    /// Return the reward every transition into this state adds to its outcome reward.
+    pub fn arrival_reward(&self) -> f64 {
+        self.entry_reward.unwrap_or(0.0) + self.terminal_reward.unwrap_or(0.0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,14 +68,194 @@ pub struct StateSpec {
 pub struct ActionSpec {
     pub id: String,
     pub outcomes: Vec<OutcomeSpec>,
+    /// Cost subtracted from every outcome's reward, e.g. fuel or time per move.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    /// Free-form tags, kept by `CompiledMdp`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+    /// Free-form key-value annotations, kept by `CompiledMdp`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BTreeMap<String, String>>,
+    /// Condition under which the state offers this action, over the state's labels and
+    /// `${param}` references, e.g. `!blocked && ${doors} > 0`. A bare label is `1` if the
+    /// state carries it and `0` otherwise. The action is always available if omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_when: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// One probabilistic transition for an action.
 pub struct OutcomeSpec {
     pub next: String,
-    pub prob: f64,
-    pub reward: f64,
+    pub prob: Probability,
+    pub reward: Reward,
+    /// Probability of each observation this outcome emits, for partially observable models
+    /// run through `BeliefSimulator`. Outcomes without observations emit the empty observation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observations: Option<BTreeMap<String, f64>>,
+}
+
+/// Outcome probability: a number, `rest` for whatever the other outcomes leave over,
+/// or an expression over `params` such as `1 - ${p}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Probability {
+    Value(f64),
+    Rest(RestKeyword),
+    Expr(String),
+}
+
+impl Probability {
+    /// The `rest` shorthand; at most one outcome per action may use it.
+    pub const REST: Self = Self::Rest(RestKeyword::Rest);
+
+    /// Return the explicit value, or `None` for `rest` and unresolved expressions.
+    pub fn value(&self) -> Option<f64> {
+        match self {
+            Self::Value(value) => Some(*value),
+            Self::Rest(_) | Self::Expr(_) => None,
+        }
+    }
+
+    pub fn is_rest(&self) -> bool {
+        matches!(self, Self::Rest(_))
+    }
+}
+
+impl From<f64> for Probability {
+    fn from(value: f64) -> Self {
+        Self::Value(value)
+    }
+}
+
+/// A number, or an expression over `params` such as `${step_cost} * 2`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Scalar {
+    Value(f64),
+    Expr(String),
+}
+
+impl Scalar {
+    /// Return the value, or NaN for an unresolved expression; see `MdpSpec::resolve_params`.
+    pub fn value(&self) -> f64 {
+        match self {
+            Self::Value(value) => *value,
+            Self::Expr(_) => f64::NAN,
+        }
+    }
+}
+
+impl From<f64> for Scalar {
+    fn from(value: f64) -> Self {
+        Self::Value(value)
+    }
+}
+
+/// Outcome reward: a number or expression, a distribution sampled by `MdpSimulator`
+/// at every step, written e.g. `{normal: {mean: 1.0, std: 0.5}}`, or a vector of named
+/// objectives such as `{profit: 3.0, risk: -1.0}`, scalarized by `MdpSpec::weights`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Reward {
+    Fixed(Scalar),
+    // YAML would otherwise write the distribution as a `!normal` tag, which the untagged
+    // enum cannot read back.
+    Distribution(#[serde(with = "serde_yaml::with::singleton_map")] RewardDistribution),
+    Vector(BTreeMap<String, f64>),
+}
+
+impl Reward {
+    /// Return the expected reward, or NaN for an unresolved expression or a vector, which
+    /// needs weights; see `scalarized`.
+    pub fn value(&self) -> f64 {
+        match self {
+            Self::Fixed(scalar) => scalar.value(),
+            Self::Distribution(distribution) => distribution.mean(),
+            Self::Vector(_) => f64::NAN,
+        }
+    }
+
+    /// Return the expected reward with vector entries weighted by `weights`, or NaN if an
+    /// objective has no weight.
+    pub fn scalarized(&self, weights: &BTreeMap<String, f64>) -> f64 {
+        match self {
+            Self::Vector(values) => values
+                .iter()
+                .map(|(objective, value)| weights.get(objective).map_or(f64::NAN, |w| w * value))
+                .sum(),
+            other => other.value(),
+        }
+    }
+}
+
+impl From<f64> for Reward {
+    fn from(value: f64) -> Self {
+        Self::Fixed(Scalar::Value(value))
+    }
+}
+
+impl From<Scalar> for Reward {
+    fn from(value: Scalar) -> Self {
+        Self::Fixed(value)
+    }
+}
+
+/// Noise model of a `Reward::Distribution`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardDistribution {
+    Normal { mean: f64, std: f64 },
+    Uniform { low: f64, high: f64 },
+}
+
+impl RewardDistribution {
+    /// Return the expected value.
+    pub fn mean(&self) -> f64 {
+        match *self {
+            Self::Normal { mean, .. } => mean,
+            Self::Uniform { low, high } => (low + high) / 2.0,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        match *self {
+            Self::Normal { mean, std } => mean.is_finite() && std.is_finite() && std >= 0.0,
+            Self::Uniform { low, high } => low.is_finite() && high.is_finite() && low <= high,
+        }
+    }
+}
+
+/// The literal `rest` keyword of `Probability::Rest`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestKeyword {
+    Rest,
+}
+
+impl ActionSpec {
+    /// Return the outcome probabilities with `rest` resolved to one minus the explicit
+    /// probabilities, clamped at zero. Unresolved expressions yield NaN.
+    pub fn resolved_probabilities(&self) -> Vec<f64> {
+        let explicit: f64 = self.outcomes.iter().filter_map(|o| o.prob.value()).sum();
+        let rest = (1.0 - explicit).max(0.0);
+        self.outcomes
+            .iter()
+            .map(|outcome| match outcome.prob {
+                Probability::Value(value) => value,
+                Probability::Rest(_) => rest,
+                Probability::Expr(_) => f64::NAN,
+            })
+            .collect()
+    }
+
+    fn has_expressions(&self) -> bool {
+        self.outcomes.iter().any(|outcome| {
+            matches!(outcome.prob, Probability::Expr(_))
+                || matches!(outcome.reward, Reward::Fixed(Scalar::Expr(_)))
+        })
+    }
 }
 
 impl MdpSpec {
@@ -52,6 +266,102 @@ impl MdpSpec {
 
     /// Validate ids, transitions, and probability constraints.
     pub fn validate_with_tolerance(&self, tolerance: f64) -> Result<(), MdpError> {
+        let spec = self.resolve_params()?;
+        spec.validate_resolved(tolerance)
+    }
+
+    /// Set the weight of an objective, e.g. to sweep the trade-off between vector rewards.
+    pub fn set_weight(&mut self, objective: impl Into<String>, weight: f64) -> &mut Self {
+        self.weights
+            .get_or_insert_with(BTreeMap::new)
+            .insert(objective.into(), weight);
+        self
+    }
+
+    /// Set a parameter, e.g. to instantiate one member of a model family.
+    pub fn set_param(&mut self, name: impl Into<String>, value: f64) -> &mut Self {
+        self.params
+            .get_or_insert_with(BTreeMap::new)
+            .insert(name.into(), value);
+        self
+    }
+
+    /// Return the spec with every probability and reward expression evaluated against
+    /// `params` and every action whose `available_when` is false removed from its state,
+    /// borrowing `self` if there is nothing to resolve.
+    pub fn resolve_params(&self) -> Result<Cow<'_, MdpSpec>, MdpError> {
+        let has_expressions = self
+            .states
+            .iter()
+            .flat_map(|state| state.actions.iter().flatten())
+            .any(|action| action.has_expressions() || action.available_when.is_some());
+        if !has_expressions {
+            return Ok(Cow::Borrowed(self));
+        }
+
+        let empty = BTreeMap::new();
+        let params = self.params.as_ref().unwrap_or(&empty);
+        let evaluate = |expression: &str| {
+            expr::evaluate(expression, params).map_err(|message| MdpError::InvalidExpression {
+                expression: expression.to_string(),
+                message,
+            })
+        };
+
+        let mut spec = self.clone();
+        for state in &mut spec.states {
+            let Some(actions) = &mut state.actions else {
+                continue;
+            };
+            let labels = state.labels.as_deref().unwrap_or_default();
+            let has_label = |name: &str| {
+                Some(if labels.iter().any(|l| l == name) {
+                    1.0
+                } else {
+                    0.0
+                })
+            };
+            let mut kept = Vec::with_capacity(actions.len());
+            for mut action in actions.drain(..) {
+                let available = match action.available_when.take() {
+                    Some(condition) => {
+                        expr::evaluate_with(&condition, params, &has_label).map_err(|message| {
+                            MdpError::InvalidExpression {
+                                expression: condition.clone(),
+                                message,
+                            }
+                        })? != 0.0
+                    }
+                    None => true,
+                };
+                if available {
+                    kept.push(action);
+                }
+            }
+            *actions = kept;
+        }
+        for outcome in spec
+            .states
+            .iter_mut()
+            .flat_map(|state| state.actions.iter_mut().flatten())
+            .flat_map(|action| &mut action.outcomes)
+        {
+            if let Probability::Expr(expression) = &outcome.prob {
+                outcome.prob = Probability::Value(evaluate(expression)?);
+            }
+            if let Reward::Fixed(Scalar::Expr(expression)) = &outcome.reward {
+                outcome.reward = evaluate(expression)?.into();
+            }
+        }
+        Ok(Cow::Owned(spec))
+    }
+
+    fn validate_resolved(&self, tolerance: f64) -> Result<(), MdpError> {
+        // Includes are merged by the file loaders; a spec parsed from a string cannot resolve them.
+        if let Some(path) = self.include.iter().flatten().next() {
+            return Err(MdpError::UnresolvedInclude { path: path.clone() });
+        }
+
         // Start state id must be present and non-empty.
         if self.start.trim().is_empty() {
             return Err(MdpError::MissingStart);
@@ -74,6 +384,15 @@ impl MdpSpec {
             });
         }
 
+        let empty = BTreeMap::new();
+        let weights = self.weights.as_ref().unwrap_or(&empty);
+        if let Some((objective, weight)) = weights.iter().find(|(_, weight)| !weight.is_finite()) {
+            return Err(MdpError::InvalidObjectiveWeight {
+                objective: objective.clone(),
+                value: *weight,
+            });
+        }
+
         // Fast membership map for outcome target validation.
         let known_state_ids: HashMap<_, _> = self.states.iter().map(|s| (&s.id, true)).collect();
 
@@ -87,6 +406,24 @@ impl MdpSpec {
                 });
             }
 
+            if state.terminal_reward.is_some() && !terminal {
+                return Err(MdpError::TerminalRewardOnNonTerminal {
+                    state: state.id.clone(),
+                });
+            }
+
+            for value in [state.entry_reward, state.terminal_reward]
+                .into_iter()
+                .flatten()
+            {
+                if !value.is_finite() {
+                    return Err(MdpError::InvalidStateReward {
+                        state: state.id.clone(),
+                        value,
+                    });
+                }
+            }
+
             let mut action_ids = HashSet::with_capacity(actions.len());
             for action in actions {
                 if !action_ids.insert(action.id.clone()) {
@@ -96,6 +433,16 @@ impl MdpSpec {
                     });
                 }
 
+                if let Some(cost) = action.cost
+                    && !cost.is_finite()
+                {
+                    return Err(MdpError::InvalidActionCost {
+                        state: state.id.clone(),
+                        action: action.id.clone(),
+                        value: cost,
+                    });
+                }
+
                 if action.outcomes.is_empty() {
                     return Err(MdpError::EmptyOutcomes {
                         state: state.id.clone(),
@@ -103,23 +450,69 @@ impl MdpSpec {
                     });
                 }
 
+                if action.outcomes.iter().filter(|o| o.prob.is_rest()).count() > 1 {
+                    return Err(MdpError::MultipleRestOutcomes {
+                        state: state.id.clone(),
+                        action: action.id.clone(),
+                    });
+                }
+
                 let mut sum = 0.0_f64;
+                let mut has_rest = false;
                 for (i, outcome) in action.outcomes.iter().enumerate() {
-                    if outcome.prob.is_nan() || !outcome.prob.is_finite() || outcome.prob < 0.0 {
+                    if let Some(observations) = &outcome.observations {
+                        let valid = observations.values().all(|p| p.is_finite() && *p >= 0.0);
+                        let sum: f64 = observations.values().sum();
+                        if !valid || (sum - 1.0).abs() > tolerance {
+                            return Err(MdpError::InvalidObservations {
+                                state: state.id.clone(),
+                                action: action.id.clone(),
+                                outcome_index: i,
+                            });
+                        }
+                    }
+
+                    // A `rest` outcome skips the probability checks but not the others.
+                    let prob = outcome.prob.value();
+                    if let Some(prob) = prob
+                        && (prob.is_nan() || !prob.is_finite() || prob < 0.0)
+                    {
                         return Err(MdpError::InvalidProbability {
                             state: state.id.clone(),
                             action: action.id.clone(),
                             outcome_index: i,
-                            value: outcome.prob,
+                            value: prob,
+                        });
+                    }
+
+                    if let Reward::Distribution(distribution) = &outcome.reward
+                        && !distribution.is_valid()
+                    {
+                        return Err(MdpError::InvalidRewardDistribution {
+                            state: state.id.clone(),
+                            action: action.id.clone(),
+                            outcome_index: i,
+                            distribution: *distribution,
+                        });
+                    }
+
+                    if let Reward::Vector(values) = &outcome.reward
+                        && let Some(objective) = values
+                            .keys()
+                            .find(|objective| !weights.contains_key(*objective))
+                    {
+                        return Err(MdpError::UnknownObjective {
+                            objective: objective.clone(),
                         });
                     }
 
-                    if !outcome.reward.is_finite() {
+                    let reward = outcome.reward.scalarized(weights);
+                    if !reward.is_finite() {
                         return Err(MdpError::InvalidReward {
                             state: state.id.clone(),
                             action: action.id.clone(),
                             outcome_index: i,
-                            value: outcome.reward,
+                            value: reward,
                         });
                     }
 
@@ -131,11 +524,20 @@ impl MdpSpec {
                         });
                     }
 
-                    sum += outcome.prob;
+                    match prob {
+                        Some(prob) => sum += prob,
+                        None => has_rest = true,
+                    }
                 }
 
-                // Outcome probabilities for an action must sum to 1 within tolerance.
-                if (sum - 1.0).abs() > tolerance {
+                // Outcome probabilities for an action must sum to 1 within tolerance;
+                // a `rest` outcome absorbs any shortfall but not an excess.
+                let off = if has_rest {
+                    sum - 1.0
+                } else {
+                    (sum - 1.0).abs()
+                };
+                if off > tolerance {
                     return Err(MdpError::ProbabilitySum {
                         state: state.id.clone(),
                         action: action.id.clone(),
@@ -153,4 +555,63 @@ impl MdpSpec {
     pub fn compile(&self) -> Result<CompiledMdp, MdpError> {
         CompiledMdp::from_spec(self)
     }
+
+    /// Compile this spec with a custom probability tolerance, normalization, pruning, or
+    /// alias tables. Pruning through options discards the `PruneReport`; use
+    /// `compile_reachable` to keep it.
+    pub fn compile_with_options(&self, options: &CompileOptions) -> Result<CompiledMdp, MdpError> {
+        let compiled = if options.normalize {
+            let spec = self.resolve_params()?.normalized(options.tolerance);
+            CompiledMdp::from_spec(&spec)?
+        } else {
+            CompiledMdp::from_spec_with_tolerance(self, options.tolerance)?
+        };
+        let compiled = if options.prune_unreachable {
+            compiled.prune_unreachable().0
+        } else {
+            compiled
+        };
+        Ok(match options.alias_tables {
+            Some(min_outcomes) => compiled.with_alias_tables(min_outcomes),
+            None => compiled,
+        })
+    }
+
+    /// Compile this spec and drop the states unreachable from `start`; see
+    /// `CompiledMdp::prune_unreachable`.
+    pub fn compile_reachable(&self) -> Result<(CompiledMdp, PruneReport), MdpError> {
+        Ok(self.compile()?.prune_unreachable())
+    }
+
+    /// Return a copy with `rest` probabilities resolved and every action whose probabilities
+    /// sum to within `tolerance` of 1.0 rescaled to sum to exactly 1.0.
+    /// Actions further off, and actions with unresolved expressions, are left unchanged.
+    pub fn normalized(&self, tolerance: f64) -> MdpSpec {
+        let mut spec = self.clone();
+        for action in spec
+            .states
+            .iter_mut()
+            .flat_map(|state| state.actions.iter_mut().flatten())
+            .filter(|action| !action.has_expressions())
+        {
+            let probs = action.resolved_probabilities();
+            let sum: f64 = probs.iter().sum();
+            let scale = if sum > 0.0 && (sum - 1.0).abs() <= tolerance {
+                1.0 / sum
+            } else {
+                1.0
+            };
+            for (outcome, prob) in action.outcomes.iter_mut().zip(probs) {
+                outcome.prob = Probability::Value(prob * scale);
+            }
+        }
+        spec
+    }
+
+    /// Normalize probabilities that sum to within `tolerance` of 1.0, then compile with the
+    /// default strict tolerance. Opt-in leniency for hand-written models, e.g. three outcomes
+    /// of `0.3333`.
+    pub fn compile_normalized(&self, tolerance: f64) -> Result<CompiledMdp, MdpError> {
+        self.resolve_params()?.normalized(tolerance).compile()
+    }
 }
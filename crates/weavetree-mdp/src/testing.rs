@@ -0,0 +1,166 @@
+//! Proptest strategies for property-testing code that embeds weavetree models.
+//!
+//! Enabled by the `testing` feature, which also enables `weavetree_core::testing` for
+//! search configs and trees.
+
+use std::ops::RangeInclusive;
+
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+
+use crate::{
+    ActionSpec, CompiledMdp, MdpSpec, OutcomeSpec, Probability, Reward, RewardDistribution,
+    StateSpec,
+};
+
+/// Sizes of the specs `mdp_spec_with` generates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecShape {
+    pub states: RangeInclusive<usize>,
+    /// Actions of every non-terminal state.
+    pub actions: RangeInclusive<usize>,
+    pub outcomes: RangeInclusive<usize>,
+}
+
+impl Default for SpecShape {
+    /// One to six states, one to three actions, and one to three outcomes per action.
+    fn default() -> Self {
+        Self {
+            states: 1..=6,
+            actions: 1..=3,
+            outcomes: 1..=3,
+        }
+    }
+}
+
+/// Generate valid specs of the default `SpecShape`; see `mdp_spec_with`.
+pub fn mdp_spec() -> impl Strategy<Value = MdpSpec> {
+    mdp_spec_with(SpecShape::default())
+}
+
+/// Generate valid specs with states `s0..`, starting in `s0`, which is never terminal.
+///
+/// Other states are terminal with probability one quarter, with an optional terminal
+/// reward. Outcomes lead anywhere, including back to their own state, with probabilities
+/// from random weights, the last often written as `rest`. Rewards are fixed or normal or
+/// uniform distributions, all within `[-1, 1]` on average, and actions may carry a cost
+/// and states an entry reward. Every generated spec compiles.
+pub fn mdp_spec_with(shape: SpecShape) -> impl Strategy<Value = MdpSpec> {
+    let min_states = (*shape.states.start()).max(1);
+    let max_states = (*shape.states.end()).max(min_states);
+    (min_states..=max_states).prop_flat_map(move |n_states| {
+        vec(state_spec(n_states, shape.clone()), n_states).prop_map(|states| {
+            let states = states
+                .into_iter()
+                .enumerate()
+                .map(|(i, mut state)| {
+                    state.id = format!("s{i}");
+                    if i == 0 {
+                        state.terminal = None;
+                        state.terminal_reward = None;
+                    }
+                    if state.terminal == Some(true) {
+                        state.actions = None;
+                    }
+                    state
+                })
+                .collect();
+            MdpSpec {
+                version: Some(1),
+                params: None,
+                weights: None,
+                include: None,
+                start: "s0".to_string(),
+                states,
+            }
+        })
+    })
+}
+
+/// Generate compiled models of valid specs; see `mdp_spec`.
+pub fn compiled_mdp() -> impl Strategy<Value = CompiledMdp> {
+    mdp_spec().prop_map(|spec| spec.compile().expect("generated specs are valid"))
+}
+
+/// State with a placeholder id and actions even if terminal; `mdp_spec_with` renames it
+/// and drops the actions of states that stay terminal.
+fn state_spec(n_states: usize, shape: SpecShape) -> impl Strategy<Value = StateSpec> {
+    (
+        prop::bool::weighted(0.25),
+        vec(action_spec(n_states, shape.outcomes), shape.actions),
+        option::of(-1.0..=1.0f64),
+        option::of(-1.0..=1.0f64),
+    )
+        .prop_map(|(terminal, mut actions, entry_reward, terminal_reward)| {
+            for (i, action) in actions.iter_mut().enumerate() {
+                action.id = format!("a{i}");
+            }
+            StateSpec {
+                id: String::new(),
+                terminal: terminal.then_some(true),
+                actions: Some(actions),
+                entry_reward,
+                terminal_reward: terminal_reward.filter(|_| terminal),
+                labels: None,
+                metadata: None,
+            }
+        })
+}
+
+fn action_spec(
+    n_states: usize,
+    outcomes: RangeInclusive<usize>,
+) -> impl Strategy<Value = ActionSpec> {
+    let min_outcomes = (*outcomes.start()).max(1);
+    let max_outcomes = (*outcomes.end()).max(min_outcomes);
+    (
+        vec(
+            (0..n_states, 1..=100u32, reward()),
+            min_outcomes..=max_outcomes,
+        ),
+        any::<bool>(),
+        option::of(0.0..=1.0f64),
+    )
+        .prop_map(|(outcomes, use_rest, cost)| {
+            let total: u32 = outcomes.iter().map(|(_, weight, _)| weight).sum();
+            let last = outcomes.len() - 1;
+            let outcomes = outcomes
+                .into_iter()
+                .enumerate()
+                .map(|(i, (next, weight, reward))| OutcomeSpec {
+                    next: format!("s{next}"),
+                    prob: if use_rest && i == last {
+                        Probability::REST
+                    } else {
+                        Probability::Value(f64::from(weight) / f64::from(total))
+                    },
+                    reward,
+                    observations: None,
+                })
+                .collect();
+            ActionSpec {
+                id: String::new(),
+                outcomes,
+                cost,
+                labels: None,
+                metadata: None,
+                available_when: None,
+            }
+        })
+}
+
+fn reward() -> impl Strategy<Value = Reward> {
+    prop_oneof![
+        3 => (-1.0..=1.0f64).prop_map(Reward::from),
+        1 => (-1.0..=1.0f64, 0.0..=1.0f64).prop_map(|(mean, std)| {
+            Reward::Distribution(RewardDistribution::Normal { mean, std })
+        }),
+        1 => (-1.0..=1.0f64, 0.0..=1.0f64).prop_map(|(mean, width)| {
+            Reward::Distribution(RewardDistribution::Uniform {
+                low: mean - width,
+                high: mean + width,
+            })
+        }),
+    ]
+}
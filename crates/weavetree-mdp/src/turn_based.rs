@@ -0,0 +1,221 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rand::{RngCore, SeedableRng};
+use weavetree_core::{ActionId, StateKey as CoreStateKey, StateKeyValue};
+
+use crate::simulator::uniform_sample;
+use crate::{DefaultRng, KeyInterner, StateInterner, TurnBasedDomain};
+
+#[derive(Debug, Clone)]
+/// Seeded simulator over a `TurnBasedDomain`, interning states into keys like
+/// `DomainSimulator`. Generic over its RNG `R`, which defaults to `DefaultRng` (ChaCha8).
+///
+/// `step_by_key` returns every player's reward for `Tree::run_max_n`; `step_for_mover_by_key`
+/// returns only the reward of the player who moved, as two-player zero-sum searches with
+/// `PlayerMode::Negamax` expect.
+pub struct TurnBasedSimulator<D, R = DefaultRng>
+where
+    D: TurnBasedDomain,
+{
+    domain: D,
+    state_interner: StateInterner<D::State>,
+    start_key: StateKeyValue,
+    rng: R,
+}
+
+impl<D> TurnBasedSimulator<D>
+where
+    D: TurnBasedDomain,
+{
+    /// Create a turn-based simulator with deterministic RNG seed.
+    pub fn new(domain: D, seed: u64) -> Self {
+        Self::with_seed(domain, seed)
+    }
+}
+
+impl<D, R> TurnBasedSimulator<D, R>
+where
+    D: TurnBasedDomain,
+    R: RngCore + SeedableRng,
+{
+    /// Create a turn-based simulator whose RNG of type `R` is seeded from `seed`.
+    pub fn with_seed(domain: D, seed: u64) -> Self {
+        Self::with_rng(domain, R::seed_from_u64(seed))
+    }
+
+    /// Replace the RNG with a fresh one seeded from `seed`. Interned keys stay valid.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = R::seed_from_u64(seed);
+    }
+}
+
+impl<D, R> TurnBasedSimulator<D, R>
+where
+    D: TurnBasedDomain,
+    R: RngCore,
+{
+    /// Create a turn-based simulator drawing from an already constructed RNG.
+    pub fn with_rng(domain: D, rng: R) -> Self {
+        let mut state_interner = StateInterner::new();
+        let start_key = state_interner
+            .intern_start(domain.start_state())
+            .expect("an unbounded interner has room for the start state");
+        Self {
+            domain,
+            state_interner,
+            start_key,
+            rng,
+        }
+    }
+
+    /// Borrow the RNG driving this simulator.
+    pub fn rng(&self) -> &R {
+        &self.rng
+    }
+
+    /// Return the key of the domain start state.
+    pub fn start_state_key(&self) -> StateKeyValue {
+        self.start_key
+    }
+
+    /// Borrow the underlying domain implementation.
+    pub fn domain(&self) -> &D {
+        &self.domain
+    }
+
+    /// Resolve a key back into its decoded state.
+    pub fn state_for_key(&self, key: StateKeyValue) -> Option<&D::State> {
+        self.state_interner.get(key)
+    }
+
+    /// Return how many states the interner currently holds.
+    pub fn interned_states(&self) -> usize {
+        self.state_interner.len()
+    }
+
+    /// Return whether an interned state key is terminal.
+    pub fn is_terminal_by_key(&self, state_key: StateKeyValue) -> bool {
+        self.state_for_key(state_key)
+            .is_none_or(|state| self.domain.is_terminal(state))
+    }
+
+    /// Return how many actions are available for an interned state key.
+    pub fn num_actions_by_key(&self, state_key: StateKeyValue) -> usize {
+        self.state_for_key(state_key)
+            .map_or(0, |state| self.domain.num_actions(state))
+    }
+
+    /// Return the player to move in an interned state; `0` for unknown keys.
+    pub fn player_by_key(&self, state_key: StateKeyValue) -> usize {
+        self.state_for_key(state_key)
+            .map_or(0, |state| self.domain.current_player(state))
+    }
+
+    /// Sample one `(next_state_key, rewards, terminal)` transition with one reward per player.
+    /// Invalid state inputs are treated as a no-op terminal transition with zero rewards.
+    pub fn step_by_key(
+        &mut self,
+        state_key: StateKeyValue,
+        action_id: usize,
+    ) -> (StateKeyValue, Vec<f64>, bool) {
+        let Some(state) = self.state_interner.get(state_key) else {
+            return (state_key, vec![0.0; self.domain.num_players()], true);
+        };
+        let sample = uniform_sample(&mut self.rng);
+        let (next_state, rewards, terminal) = self.domain.step(state, action_id, sample);
+
+        let domain = &self.domain;
+        let replay = |state: &D::State, action_id, sample| domain.step(state, action_id, sample).0;
+        let next_key = self
+            .state_interner
+            .intern_successor(next_state, state_key, action_id, sample, &replay)
+            .expect("an unbounded interner accepts every state");
+        (next_key, rewards, terminal)
+    }
+
+    /// Sample one `(next_state_key, reward, terminal)` transition, where `reward` is the
+    /// reward of the player who moved.
+    pub fn step_for_mover_by_key(
+        &mut self,
+        state_key: StateKeyValue,
+        action_id: usize,
+    ) -> (StateKeyValue, f64, bool) {
+        let mover = self.player_by_key(state_key);
+        let (next_key, rewards, terminal) = self.step_by_key(state_key, action_id);
+        let reward = rewards.get(mover).copied().unwrap_or(0.0);
+        (next_key, reward, terminal)
+    }
+
+    /// Wrap this simulator in shared interior mutability for MCTS callback wiring.
+    pub fn into_shared(self) -> SharedTurnBasedSimulator<D, R> {
+        SharedTurnBasedSimulator {
+            inner: Rc::new(RefCell::new(self)),
+        }
+    }
+}
+
+/// Shared wrapper that offers callback adapters for `Tree::run_max_n` and for negamax
+/// searches through `Tree::run_with_extensions`.
+#[derive(Clone)]
+pub struct SharedTurnBasedSimulator<D, R = DefaultRng>
+where
+    D: TurnBasedDomain,
+{
+    inner: Rc<RefCell<TurnBasedSimulator<D, R>>>,
+}
+
+impl<D, R> SharedTurnBasedSimulator<D, R>
+where
+    D: TurnBasedDomain,
+    R: RngCore,
+{
+    /// Return the encoded start state key for tree initialization.
+    pub fn start_state_key(&self) -> CoreStateKey {
+        CoreStateKey::from_raw(self.inner.borrow().start_state_key())
+    }
+
+    /// Return whether the root state is terminal.
+    pub fn root_is_terminal(&self) -> bool {
+        let simulator = self.inner.borrow();
+        simulator.is_terminal_by_key(simulator.start_state_key())
+    }
+
+    /// Build a callback compatible with `run_max_n` `player_to_move` and
+    /// `SearchExtensions::with_player_to_move`.
+    pub fn player_to_move_fn(&self) -> impl FnMut(CoreStateKey) -> usize + use<D, R> {
+        let inner = Rc::clone(&self.inner);
+        move |state| inner.borrow().player_by_key(state.raw())
+    }
+
+    /// Build a callback compatible with `Tree::run` `num_actions`.
+    pub fn num_actions_fn(&self) -> impl FnMut(CoreStateKey) -> usize + use<D, R> {
+        let inner = Rc::clone(&self.inner);
+        move |state| inner.borrow().num_actions_by_key(state.raw())
+    }
+
+    /// Build a callback compatible with `run_max_n` `step`.
+    pub fn step_fn(
+        &self,
+    ) -> impl FnMut(CoreStateKey, ActionId) -> (CoreStateKey, Vec<f64>, bool) + use<D, R> {
+        let inner = Rc::clone(&self.inner);
+        move |state, action| {
+            let (next, rewards, terminal) =
+                inner.borrow_mut().step_by_key(state.raw(), action.index());
+            (CoreStateKey::from_raw(next), rewards, terminal)
+        }
+    }
+
+    /// Build a callback compatible with `Tree::run` `step` for `PlayerMode::Negamax`,
+    /// returning the reward of the player who moved.
+    pub fn negamax_step_fn(
+        &self,
+    ) -> impl FnMut(CoreStateKey, ActionId) -> (CoreStateKey, f64, bool) + use<D, R> {
+        let inner = Rc::clone(&self.inner);
+        move |state, action| {
+            let (next, reward, terminal) = inner
+                .borrow_mut()
+                .step_for_mover_by_key(state.raw(), action.index());
+            (CoreStateKey::from_raw(next), reward, terminal)
+        }
+    }
+}
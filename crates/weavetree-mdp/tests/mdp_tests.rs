@@ -1,7 +1,27 @@
 use std::cell::RefCell;
 
-use weavetree_core::{ActionId, ReturnType, SearchConfig, StateKey as CoreStateKey, Tree};
-use weavetree_mdp::{DomainSimulator, MdpDomain, MdpError, MdpSimulator, MdpSpec, StateKey};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::{ChaCha8Rng, ChaCha20Rng};
+use weavetree_core::{
+    ActionId, PlayerMode, ReturnType, RunError, SearchConfig, SearchExtensions,
+    StateKey as CoreStateKey, StateKeyValue, Tree,
+};
+use weavetree_mdp::benchmarks::{
+    FROZEN_LAKE_4X4, FROZEN_LAKE_8X8, TAXI_ACTIONS, chain, frozen_lake, inventory, river_swim, taxi,
+};
+use weavetree_mdp::generators::{
+    GRID_ACTIONS, GridWorldConfig, grid_cell_id, grid_world, random_mdp,
+};
+use weavetree_mdp::{
+    ActionSpec, AfterstateDomain, AfterstatePhase, BeliefSimulator, CompileOptions,
+    DomainSimulator, Env, EvictionPolicy, FactoredSpec, FingerprintInterner, LintKind, MdpBuilder,
+    MdpDomain, MdpError, MdpSimulator, MdpSpec, OutcomeSpec, PROB_TOLERANCE, Probability, Reward,
+    RewardDistribution, SamplingMode, Scalar, SimulatorEnv, Space, StateInterner, StateKey,
+    StateSpec, Trajectory, TurnBasedDomain, TurnBasedSimulator, TypedMdpBuilder,
+    compare_to_optimal, compile_json, compile_json_str, compile_json_str_with_options,
+    enumerate_domain, evaluate_policy, hashed_state_key, load_json, load_yaml, save_json,
+    simulate_episode, stable_hash64, stable_hash128, value_iteration,
+};
 
 const VALID_MDP_YAML: &str = r#"
 version: 1
@@ -81,6 +101,1503 @@ states:
     assert!(matches!(err, MdpError::UnknownNextState { .. }));
 }
 
+#[test]
+fn rest_probability_and_opt_in_normalization() {
+    let yaml = r#"
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: thirds
+        outcomes:
+          - next: s0
+            prob: 0.3333
+            reward: 0.0
+          - next: s1
+            prob: 0.3333
+            reward: 1.0
+          - next: s2
+            prob: 0.3333
+            reward: 2.0
+      - id: mostly_stay
+        outcomes:
+          - next: s1
+            prob: 0.25
+            reward: 1.0
+          - next: s0
+            prob: rest
+            reward: 0.0
+  - id: s1
+    terminal: true
+  - id: s2
+    terminal: true
+"#;
+    let spec: MdpSpec = serde_yaml::from_str(yaml).expect("valid syntax");
+    let rest_action = &spec.states[0].actions.as_ref().expect("actions")[1];
+    assert_eq!(rest_action.outcomes[1].prob, Probability::REST);
+    assert_eq!(rest_action.resolved_probabilities(), vec![0.25, 0.75]);
+    assert!(
+        serde_yaml::to_string(&spec)
+            .expect("spec should serialize")
+            .contains("prob: rest")
+    );
+
+    let err = spec
+        .compile()
+        .expect_err("thirds are off by more than the tolerance");
+    assert!(matches!(err, MdpError::ProbabilitySum { .. }));
+    assert!(matches!(
+        spec.compile_normalized(1e-9),
+        Err(MdpError::ProbabilitySum { .. })
+    ));
+
+    let normalized = spec.normalized(1e-3);
+    let thirds = &normalized.states[0].actions.as_ref().expect("actions")[0];
+    let sum: f64 = thirds.resolved_probabilities().iter().sum();
+    assert!((sum - 1.0).abs() < 1e-12);
+    let compiled = spec
+        .compile_normalized(1e-3)
+        .expect("normalized spec should compile");
+    let mut simulator = MdpSimulator::new(compiled, 1).with_sampling_mode(SamplingMode::MostLikely);
+    assert_eq!(
+        simulator.step(StateKey::from(0), 1),
+        (StateKey::from(0), 0.0, false)
+    );
+
+    let mut two_rests = spec.clone();
+    let actions = two_rests.states[0].actions.as_mut().expect("actions");
+    actions.remove(0);
+    actions[0].outcomes[0].prob = Probability::REST;
+    assert!(matches!(
+        two_rests.validate(),
+        Err(MdpError::MultipleRestOutcomes { .. })
+    ));
+
+    // Only the probability of a `rest` outcome is implicit; its target and reward are checked.
+    let mut rest_only = spec.clone();
+    rest_only.states[0]
+        .actions
+        .as_mut()
+        .expect("actions")
+        .remove(0);
+    rest_only.validate().expect("the rest action is valid");
+    let mut rest_to_nowhere = rest_only.clone();
+    rest_to_nowhere.states[0].actions.as_mut().expect("actions")[0].outcomes[1].next =
+        "nowhere".to_string();
+    assert!(matches!(
+        rest_to_nowhere.validate(),
+        Err(MdpError::UnknownNextState { .. })
+    ));
+    let mut rest_with_nan = rest_only;
+    rest_with_nan.states[0].actions.as_mut().expect("actions")[0].outcomes[1].reward =
+        f64::NAN.into();
+    assert!(matches!(
+        rest_with_nan.validate(),
+        Err(MdpError::InvalidReward {
+            outcome_index: 1,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn compile_options_relax_tighten_and_prune() {
+    let json = r#"{
+  "start": "s0",
+  "states": [
+    {"id": "s0", "actions": [
+      {"id": "thirds", "outcomes": [
+        {"next": "s0", "prob": 0.3333, "reward": 0.0},
+        {"next": "s1", "prob": 0.3333, "reward": 1.0},
+        {"next": "s1", "prob": 0.3333, "reward": 2.0}
+      ]},
+      {"id": "tenths", "outcomes": [
+        {"next": "s0", "prob": 0.1, "reward": 0.0},
+        {"next": "s1", "prob": 0.2, "reward": 0.0},
+        {"next": "s1", "prob": 0.7, "reward": 0.0}
+      ]}
+    ]},
+    {"id": "s1", "terminal": true},
+    {"id": "orphan", "terminal": true}
+  ]
+}"#;
+    let spec: MdpSpec = serde_json::from_str(json).expect("valid syntax");
+    assert_eq!(CompileOptions::default().tolerance, PROB_TOLERANCE);
+    assert!(matches!(
+        spec.compile_with_options(&CompileOptions::default()),
+        Err(MdpError::ProbabilitySum { .. })
+    ));
+
+    let relaxed = CompileOptions {
+        tolerance: 1e-3,
+        ..CompileOptions::default()
+    };
+    let compiled = compile_json_str_with_options(json, &relaxed).expect("thirds are close enough");
+    assert_eq!(compiled.state_count(), 3);
+
+    let strict = CompileOptions {
+        tolerance: 0.0,
+        normalize: false,
+        ..relaxed
+    };
+    assert!(matches!(
+        spec.compile_with_options(&strict),
+        Err(MdpError::ProbabilitySum { .. })
+    ));
+
+    let options: CompileOptions =
+        serde_yaml::from_str("tolerance: 0.001\nnormalize: true\nprune_unreachable: true\n")
+            .expect("options deserialize with defaults");
+    assert_eq!(options.alias_tables, None);
+    let compiled = spec
+        .compile_with_options(&options)
+        .expect("normalized thirds compile");
+    assert_eq!(compiled.state_count(), 2);
+    assert_eq!(compiled.state_key("orphan"), None);
+}
+
+#[test]
+fn spec_diff_ignores_order_and_reports_deltas() {
+    let old: MdpSpec = serde_yaml::from_str(
+        r#"
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: go
+        outcomes:
+          - {next: s1, prob: 0.5, reward: 1.0}
+          - {next: s1, prob: 0.25, reward: 3.0}
+          - {next: s0, prob: rest, reward: 0.0}
+      - id: wait
+        outcomes:
+          - {next: s0, prob: 1.0, reward: 0.0}
+  - id: s1
+    terminal: true
+  - id: old_sink
+    terminal: true
+"#,
+    )
+    .expect("valid syntax");
+    let reordered: MdpSpec = serde_yaml::from_str(
+        r#"
+start: s0
+states:
+  - id: old_sink
+    terminal: true
+  - id: s1
+    terminal: true
+  - id: s0
+    actions:
+      - id: wait
+        outcomes:
+          - {next: s0, prob: 1.0, reward: 0.0}
+      - id: go
+        outcomes:
+          - {next: s0, prob: 0.25, reward: 0.0}
+          - {next: s1, prob: 0.75, reward: "${bonus} + 0.6666666666666666"}
+params:
+  bonus: 1.0
+"#,
+    )
+    .expect("valid syntax");
+    assert!(old.diff(&reordered).expect("specs resolve").is_empty());
+
+    let mut new = reordered.clone();
+    new.states.retain(|state| state.id != "old_sink");
+    new.states[0].terminal_reward = Some(2.0);
+    let s0 = &mut new.states[1];
+    let actions = s0.actions.as_mut().expect("s0 has actions");
+    actions.retain(|action| action.id != "wait");
+    actions[0].cost = Some(0.5);
+    actions[0].outcomes[0].prob = Probability::Value(0.15);
+    actions[0].outcomes[1].prob = Probability::Value(0.8);
+    actions[0].outcomes.push(OutcomeSpec {
+        next: "s2".to_string(),
+        prob: Probability::Value(0.05),
+        reward: 10.0.into(),
+        observations: None,
+    });
+    actions.push(ActionSpec {
+        id: "jump".to_string(),
+        outcomes: vec![],
+        cost: None,
+        labels: None,
+        metadata: None,
+        available_when: None,
+    });
+    new.states.push(StateSpec {
+        id: "s2".to_string(),
+        terminal: Some(true),
+        actions: None,
+        entry_reward: None,
+        terminal_reward: None,
+        labels: None,
+        metadata: None,
+    });
+
+    let diff = old.diff(&new).expect("specs resolve");
+    assert_eq!(diff.start, None);
+    assert_eq!(diff.added_states, ["s2"]);
+    assert_eq!(diff.removed_states, ["old_sink"]);
+    assert_eq!(diff.changed_states.len(), 2);
+    let s0 = &diff.changed_states[0];
+    assert_eq!(s0.id, "s0");
+    assert_eq!(s0.added_actions, ["jump"]);
+    assert_eq!(s0.removed_actions, ["wait"]);
+    let go = &s0.changed_actions[0];
+    assert_eq!(go.cost, Some((0.0, 0.5)));
+    let nexts: Vec<_> = go.outcomes.iter().map(|o| o.next.as_str()).collect();
+    assert_eq!(nexts, ["s0", "s1", "s2"]);
+    assert!((go.outcomes[0].prob_delta() + 0.1).abs() < 1e-12);
+    assert!((go.outcomes[1].prob_delta() - 0.05).abs() < 1e-12);
+    assert!(go.outcomes[1].reward_delta().expect("kept").abs() < 1e-9);
+    assert_eq!(go.outcomes[2].before, None);
+    assert_eq!(go.outcomes[2].reward_delta(), None);
+    let s1 = &diff.changed_states[1];
+    assert_eq!(s1.terminal_reward, Some((0.0, 2.0)));
+
+    let text = diff.to_string();
+    assert!(text.contains("+ state s2\n"));
+    assert!(text.contains("- action s0/wait\n"));
+    assert!(text.contains("+ outcome s0/go -> s2: prob 0.05, reward 10\n"));
+}
+
+#[test]
+fn jani_export_encodes_states_actions_and_rewards() {
+    let spec: MdpSpec = serde_yaml::from_str(
+        r#"
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: go
+        cost: 0.5
+        outcomes:
+          - {next: goal, prob: 0.8, reward: 1.0}
+          - {next: s0, prob: rest, reward: 0.0}
+      - id: wait
+        outcomes:
+          - {next: s0, prob: 1.0, reward: {uniform: {low: 0.0, high: 1.0}}}
+  - id: goal
+    terminal: true
+    terminal_reward: 10.0
+"#,
+    )
+    .expect("valid syntax");
+    let jani = spec.to_jani().expect("valid spec exports");
+
+    assert_eq!(jani["type"], "mdp");
+    assert_eq!(
+        jani["actions"],
+        serde_json::json!([{"name": "go"}, {"name": "wait"}])
+    );
+    let state = &jani["variables"][0];
+    assert_eq!(state["type"]["upper-bound"], 1);
+    assert_eq!(state["initial-value"], 0);
+    assert_eq!(jani["variables"][1]["transient"], true);
+
+    let edges = jani["automata"][0]["edges"].as_array().expect("edges");
+    assert_eq!(edges.len(), 3);
+    let go = &edges[0];
+    assert_eq!(go["action"], "go");
+    assert_eq!(go["guard"]["exp"]["right"], 0);
+    let to_goal = &go["destinations"][0];
+    assert_eq!(to_goal["probability"]["exp"], 0.8);
+    assert_eq!(to_goal["assignments"][0]["value"], 1);
+    assert_eq!(to_goal["assignments"][1]["value"], 10.5);
+    let stay = &go["destinations"][1];
+    assert!((stay["probability"]["exp"].as_f64().unwrap() - 0.2).abs() < 1e-12);
+    assert_eq!(stay["assignments"][1]["value"], -0.5);
+    assert_eq!(edges[1]["destinations"][0]["assignments"][1]["value"], 0.5);
+
+    let goal_loop = &edges[2];
+    assert!(goal_loop.get("action").is_none());
+    assert_eq!(goal_loop["destinations"][0]["assignments"][0]["value"], 1);
+    let properties = jani["properties"].as_array().expect("properties");
+    assert_eq!(properties.len(), 2);
+    assert_eq!(properties[1]["expression"]["values"]["op"], "Emax");
+
+    let mut broken = spec.clone();
+    broken.start = "nowhere".to_string();
+    assert!(broken.to_jani().is_err());
+}
+
+#[test]
+fn model_report_summarizes_size_and_stochasticity() {
+    let report = chain(5).compile().expect("chain compiles").report();
+    assert_eq!(report.states, 5);
+    assert_eq!(report.reachable_states, 5);
+    assert_eq!((report.terminal_states, report.terminal_ratio), (0, 0.0));
+    assert_eq!((report.actions, report.max_actions), (10, 2));
+    assert_eq!(report.outcomes, 20);
+    assert_eq!((report.max_branching, report.mean_branching), (2, 2.0));
+    assert_eq!(
+        (report.deterministic_actions, report.stochastic_actions),
+        (0, 10)
+    );
+    let entropy = -(0.8_f64 * 0.8_f64.log2() + 0.2 * 0.2_f64.log2());
+    assert!((report.mean_outcome_entropy - entropy).abs() < 1e-12);
+    assert_eq!(report.reward_range, Some((0.0, 10.0)));
+
+    let lake = frozen_lake(&FROZEN_LAKE_4X4, false)
+        .compile()
+        .expect("lake compiles")
+        .report();
+    assert_eq!(lake.terminal_states, 5);
+    assert_eq!(lake.terminal_ratio, 5.0 / 16.0);
+    assert_eq!((lake.actions, lake.outcomes), (44, 44));
+    assert_eq!(lake.deterministic_actions, 44);
+    assert_eq!(lake.mean_outcome_entropy, 0.0);
+    assert!(
+        lake.to_string()
+            .contains("states:     16 (16 reachable, 5 terminal, 31.2%)")
+    );
+}
+
+#[test]
+fn lints_flag_modeling_mistakes_that_still_compile() {
+    let yaml = r#"
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: go
+        outcomes:
+          - next: goal
+            prob: 1.0
+            reward: 1.0
+          - next: stuck
+            prob: 0.0
+            reward: 0.0
+      - id: wait
+        outcomes:
+          - next: goal
+            prob: 0.5
+            reward: 0.0
+          - next: s0
+            prob: rest
+            reward: 0.0
+  - id: goal
+    terminal: true
+  - id: stuck
+    actions:
+      - id: spin
+        outcomes:
+          - next: stuck
+            prob: 1.0
+            reward: 0.0
+  - id: orphan
+"#;
+    let spec: MdpSpec = serde_yaml::from_str(yaml).expect("valid yaml");
+    let lints = spec.lint().expect("spec compiles");
+    let found: Vec<_> = lints
+        .iter()
+        .map(|lint| (lint.kind, lint.state.as_str(), lint.action.as_deref()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (LintKind::ZeroProbabilityOutcome, "s0", Some("go")),
+            (LintKind::Absorbing, "stuck", None),
+            (LintKind::UnreachableState, "orphan", None),
+            (LintKind::DeadEnd, "orphan", None),
+        ]
+    );
+    assert_eq!(
+        lints[0].to_string(),
+        "action 's0/go' has an outcome with probability 0"
+    );
+
+    let clean: MdpSpec = serde_yaml::from_str(VALID_MDP_YAML).expect("valid yaml");
+    let kinds: Vec<_> = clean
+        .lint()
+        .expect("spec compiles")
+        .iter()
+        .map(|l| l.kind)
+        .collect();
+    assert_eq!(kinds, vec![LintKind::DeadEnd]);
+}
+
+#[test]
+fn state_rewards_are_folded_into_incoming_transitions() {
+    let yaml = r#"
+start: s0
+states:
+  - id: s0
+    entry_reward: -1.0
+    actions:
+      - id: a0
+        outcomes:
+          - next: s0
+            prob: 0.5
+            reward: 0.5
+          - next: goal
+            prob: 0.5
+            reward: 0.5
+  - id: goal
+    terminal: true
+    entry_reward: 2.0
+    terminal_reward: 10.0
+"#;
+    let spec: MdpSpec = serde_yaml::from_str(yaml).expect("valid syntax");
+    let compiled = spec.compile().expect("compile should succeed");
+    let mut simulator = MdpSimulator::new(compiled, 3);
+    let mut rewards: Vec<_> = simulator
+        .sample_batch(StateKey::from(0), 0, 64)
+        .into_iter()
+        .map(|(next, reward, _)| (next.index(), reward))
+        .collect();
+    rewards.sort_by(|a, b| a.partial_cmp(b).expect("rewards are finite"));
+    rewards.dedup();
+    assert_eq!(rewards, vec![(0, -0.5), (1, 12.5)]);
+
+    let mut misplaced = spec.clone();
+    misplaced.states[0].terminal_reward = Some(1.0);
+    assert!(matches!(
+        misplaced.validate(),
+        Err(MdpError::TerminalRewardOnNonTerminal { .. })
+    ));
+}
+
+#[test]
+fn action_cost_is_subtracted_from_every_outcome() {
+    let yaml = r#"
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: drive
+        cost: 0.25
+        outcomes:
+          - next: s0
+            prob: 0.5
+            reward: 0.0
+          - next: s1
+            prob: rest
+            reward: 1.0
+  - id: s1
+    terminal: true
+"#;
+    let spec: MdpSpec = serde_yaml::from_str(yaml).expect("valid syntax");
+    let mut simulator = MdpSimulator::new(spec.compile().expect("compile should succeed"), 2);
+    for (next, reward, _) in simulator.sample_batch(StateKey::from(0), 0, 32) {
+        let expected = if next.index() == 0 { -0.25 } else { 0.75 };
+        assert_eq!(reward, expected);
+    }
+
+    let mut infinite = spec.clone();
+    infinite.states[0].actions.as_mut().expect("actions")[0].cost = Some(f64::INFINITY);
+    assert!(matches!(
+        infinite.validate(),
+        Err(MdpError::InvalidActionCost { .. })
+    ));
+}
+
+#[test]
+fn params_are_substituted_into_probabilities_and_rewards() {
+    let yaml = r#"
+params:
+  p: 0.8
+  step: 0.5
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: move
+        outcomes:
+          - next: s1
+            prob: ${p}
+            reward: ${step} * (1 + 1)
+          - next: s0
+            prob: 1 - ${p}
+            reward: -${step}
+  - id: s1
+    terminal: true
+"#;
+    let mut spec: MdpSpec = serde_yaml::from_str(yaml).expect("valid syntax");
+    let resolved = spec.resolve_params().expect("params should resolve");
+    let outcomes = &resolved.states[0].actions.as_ref().expect("actions")[0].outcomes;
+    assert_eq!(outcomes[0].prob, Probability::Value(0.8));
+    assert_eq!(outcomes[0].reward, Scalar::Value(1.0).into());
+    assert_eq!(outcomes[1].reward, Scalar::Value(-0.5).into());
+
+    spec.set_param("p", 1.0);
+    let compiled = spec.compile().expect("compile should succeed");
+    let mut simulator = MdpSimulator::new(compiled, 4);
+    assert!(
+        simulator
+            .sample_batch(StateKey::from(0), 0, 16)
+            .iter()
+            .all(|transition| *transition == (StateKey::from(1), 1.0, true))
+    );
+
+    spec.set_param("p", 1.5);
+    assert!(matches!(
+        spec.validate(),
+        Err(MdpError::InvalidProbability { .. })
+    ));
+
+    spec.params = None;
+    let err = spec.compile().expect_err("missing params should fail");
+    assert!(
+        matches!(&err, MdpError::InvalidExpression { message, .. } if message.contains("'p'")),
+        "{err}"
+    );
+}
+
+#[test]
+fn includes_merge_states_across_files() {
+    let dir = std::env::temp_dir().join(format!("weavetree-mdp-include-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("parts")).expect("temp dir should be creatable");
+    let write = |name: &str, text: &str| {
+        std::fs::write(dir.join(name), text).expect("temp file should be writable");
+    };
+    write(
+        "main.yaml",
+        r#"
+include: [parts/road.yaml]
+params:
+  p: 0.9
+start: home
+states:
+  - id: home
+    actions:
+      - id: leave
+        outcomes:
+          - next: road
+            prob: ${p}
+            reward: 0.0
+          - next: home
+            prob: rest
+            reward: 0.0
+"#,
+    );
+    write(
+        "parts/road.yaml",
+        r#"
+include: [goal.yaml]
+params:
+  p: 0.1
+  toll: 2.0
+states:
+  - id: road
+    actions:
+      - id: drive
+        outcomes:
+          - next: goal
+            prob: 1.0
+            reward: -${toll}
+"#,
+    );
+    write(
+        "parts/goal.yaml",
+        "states:\n  - id: goal\n    terminal: true\n",
+    );
+
+    let spec = load_yaml(dir.join("main.yaml")).expect("includes should resolve");
+    let ids: Vec<_> = spec.states.iter().map(|state| state.id.as_str()).collect();
+    assert_eq!(ids, ["home", "road", "goal"]);
+    let params = spec.params.as_ref().expect("params should merge");
+    assert_eq!((params["p"], params["toll"]), (0.9, 2.0));
+    assert_eq!(
+        spec.compile()
+            .expect("merged spec should compile")
+            .state_count(),
+        3
+    );
+
+    write(
+        "parts/goal.yaml",
+        "states:\n  - id: home\n    terminal: true\n",
+    );
+    assert!(matches!(
+        load_yaml(dir.join("main.yaml")),
+        Err(MdpError::DuplicateIncludedState { id, .. }) if id == "home"
+    ));
+
+    write("parts/goal.yaml", "include: [../main.yaml]\nstates: []\n");
+    assert!(matches!(
+        load_yaml(dir.join("main.yaml")),
+        Err(MdpError::IncludeCycle { .. })
+    ));
+
+    let unresolved: MdpSpec = serde_yaml::from_str(
+        &std::fs::read_to_string(dir.join("main.yaml")).expect("main file should exist"),
+    )
+    .expect("valid syntax");
+    assert!(matches!(
+        unresolved.validate(),
+        Err(MdpError::UnresolvedInclude { .. })
+    ));
+    std::fs::remove_dir_all(&dir).expect("temp dir should be removable");
+}
+
+#[test]
+fn factored_spec_grounds_reachable_states() {
+    let yaml = r#"
+params:
+  slip: 0.2
+variables:
+  - name: fuel
+    min: 0
+    max: 2
+  - name: pos
+    min: 0
+    max: 2
+start:
+  fuel: 2
+  pos: 0
+terminal: pos == 2
+actions:
+  - id: drive
+    when: fuel > 0
+    outcomes:
+      - prob: 1 - ${slip}
+        set:
+          fuel: fuel - 1
+          pos: pos + 1
+        reward: pos + 1
+      - prob: rest
+        set:
+          fuel: fuel - 1
+        reward: 0.0
+  - id: wait
+    when: fuel == 0 && !(pos == 1)
+    outcomes:
+      - prob: 1.0
+        reward: -1.0
+"#;
+    let factored: FactoredSpec = serde_yaml::from_str(yaml).expect("valid syntax");
+    let spec = factored.ground().expect("grounding should succeed");
+    let ids: Vec<_> = spec.states.iter().map(|state| state.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            "fuel=2,pos=0",
+            "fuel=1,pos=1",
+            "fuel=1,pos=0",
+            "fuel=0,pos=2",
+            "fuel=0,pos=1",
+            "fuel=0,pos=0",
+        ]
+    );
+    assert_eq!(spec.start, "fuel=2,pos=0");
+
+    let actions_of = |id: &str| {
+        let state = spec
+            .states
+            .iter()
+            .find(|state| state.id == id)
+            .expect("state exists");
+        state
+            .actions
+            .iter()
+            .flatten()
+            .map(|action| action.id.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(actions_of("fuel=1,pos=1"), ["drive"]);
+    assert_eq!(actions_of("fuel=0,pos=1"), Vec::<String>::new());
+    assert_eq!(actions_of("fuel=0,pos=0"), ["wait"]);
+    assert_eq!(spec.states[3].terminal, Some(true));
+
+    let drive = &spec.states[1].actions.as_ref().expect("actions")[0];
+    assert_eq!(drive.outcomes[0].next, "fuel=0,pos=2");
+    assert_eq!(drive.outcomes[0].reward, Scalar::Value(2.0).into());
+    assert_eq!(
+        factored
+            .compile()
+            .expect("compile should succeed")
+            .state_count(),
+        6
+    );
+
+    let mut overflowing = factored.clone();
+    overflowing.actions[0].outcomes[0]
+        .set
+        .insert("pos".into(), Scalar::Expr("pos + 3".into()));
+    assert!(matches!(
+        overflowing.ground(),
+        Err(MdpError::VariableOutOfRange { variable, value, .. }) if variable == "pos" && value == 3.0
+    ));
+
+    let mut misspelled = factored.clone();
+    misspelled.actions[0].when = Some("fuell > 0".into());
+    assert!(matches!(
+        misspelled.ground(),
+        Err(MdpError::InvalidExpression { message, .. }) if message.contains("fuell")
+    ));
+}
+
+#[test]
+fn grid_world_generator_builds_slippery_grid() {
+    let config = GridWorldConfig {
+        width: 3,
+        height: 2,
+        walls: vec![(1, 0)],
+        terminals: vec![((2, 0), 1.0), ((1, 1), -1.0)],
+        slip: 0.2,
+        step_reward: -0.1,
+        ..GridWorldConfig::default()
+    };
+    let spec = grid_world(&config).expect("grid should generate");
+    assert_eq!(spec.states.len(), 5);
+    assert_eq!(spec.start, grid_cell_id(0, 0));
+
+    let start = &spec.states[0];
+    let actions = start.actions.as_ref().expect("start has actions");
+    assert_eq!(
+        actions.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(),
+        GRID_ACTIONS
+    );
+    // Moving right from (0, 0) hits the wall; sliding up stays put, sliding down reaches (0, 1).
+    let right = &actions[3];
+    let outcomes: Vec<_> = right
+        .outcomes
+        .iter()
+        .map(|o| (o.next.as_str(), o.prob.value().expect("explicit")))
+        .collect();
+    assert_eq!(outcomes, [("0,0", 0.9), ("0,1", 0.1)]);
+
+    let compiled = spec.compile().expect("grid should compile");
+    let mut simulator =
+        MdpSimulator::new(compiled.clone(), 0).with_sampling_mode(SamplingMode::MostLikely);
+    let below = compiled.state_key("0,1").expect("cell exists");
+    assert_eq!(
+        simulator.step(below, 3),
+        (compiled.state_key("1,1").expect("cell exists"), -1.1, true)
+    );
+
+    let walled_start = GridWorldConfig {
+        walls: vec![(0, 0)],
+        ..GridWorldConfig::default()
+    };
+    assert!(matches!(
+        grid_world(&walled_start),
+        Err(MdpError::InvalidGeneratorConfig { .. })
+    ));
+}
+
+#[test]
+fn random_mdp_generator_is_seeded_and_valid() {
+    let spec = random_mdp(7, 20, 3, 4, -1.0..=2.0).expect("spec should generate");
+    let again = random_mdp(7, 20, 3, 4, -1.0..=2.0).expect("spec should generate");
+    let other = random_mdp(8, 20, 3, 4, -1.0..=2.0).expect("spec should generate");
+    let yaml = |spec: &MdpSpec| serde_yaml::to_string(spec).expect("spec should serialize");
+    assert_eq!(yaml(&spec), yaml(&again));
+    assert_ne!(yaml(&spec), yaml(&other));
+
+    assert_eq!(spec.states.len(), 20);
+    for action in spec
+        .states
+        .iter()
+        .flat_map(|state| state.actions.iter().flatten())
+    {
+        let mut targets: Vec<_> = action.outcomes.iter().map(|o| o.next.as_str()).collect();
+        targets.sort_unstable();
+        targets.dedup();
+        assert_eq!(targets.len(), 4);
+        assert!(
+            action
+                .outcomes
+                .iter()
+                .all(|o| (-1.0..=2.0).contains(&o.reward.value()))
+        );
+    }
+    assert_eq!(
+        spec.compile().expect("spec should compile").state_count(),
+        20
+    );
+
+    assert!(matches!(
+        random_mdp(7, 3, 1, 4, 0.0..=1.0),
+        Err(MdpError::InvalidGeneratorConfig { .. })
+    ));
+}
+
+#[test]
+fn benchmark_models_compile_and_behave() {
+    assert_eq!(chain(5).compile().expect("chain compiles").state_count(), 5);
+    assert_eq!(
+        river_swim(6)
+            .compile()
+            .expect("river swim compiles")
+            .state_count(),
+        6
+    );
+    assert_eq!(
+        inventory(4)
+            .compile()
+            .expect("inventory compiles")
+            .state_count(),
+        5
+    );
+    let lake = frozen_lake(&FROZEN_LAKE_4X4, true);
+    assert_eq!(lake.states.len(), 16);
+    assert_eq!(frozen_lake(&FROZEN_LAKE_8X8, false).states.len(), 64);
+    lake.compile().expect("frozen lake compiles");
+
+    let compiled = taxi().compile().expect("taxi compiles");
+    assert_eq!(compiled.state_count(), 500);
+    let mut simulator =
+        MdpSimulator::new(compiled.clone(), 0).with_sampling_mode(SamplingMode::MostLikely);
+    let route = [
+        "west", "west", "north", "north", "pickup", "south", "south", "east", "east", "east",
+        "south", "south", "dropoff",
+    ];
+    let mut state = compiled.start();
+    let mut total = 0.0;
+    let mut terminal = false;
+    for name in route {
+        let action = TAXI_ACTIONS
+            .iter()
+            .position(|a| *a == name)
+            .expect("taxi action");
+        let reward;
+        (state, reward, terminal) = simulator.step(state, action);
+        total += reward;
+    }
+    assert!(terminal);
+    assert_eq!(compiled.state_key("4,3,B,B"), Some(state));
+    assert_eq!(total, 8.0);
+}
+
+#[test]
+fn labels_and_metadata_survive_compilation() {
+    let yaml = r#"
+start: s0
+states:
+  - id: s0
+    labels: [start]
+    metadata:
+      color: blue
+    actions:
+      - id: jump
+        labels: [risky]
+        metadata:
+          note: may fall
+        outcomes:
+          - next: pit
+            prob: 0.5
+            reward: 0.0
+          - next: goal
+            prob: 0.5
+            reward: 1.0
+  - id: pit
+    terminal: true
+    labels: [hazard, terminal]
+  - id: goal
+    terminal: true
+    labels: [goal, terminal]
+"#;
+    let spec: MdpSpec = serde_yaml::from_str(yaml).expect("valid yaml");
+    let compiled = spec.compile().expect("compile should succeed");
+    let key = |id: &str| compiled.state_key(id).expect("state exists");
+
+    assert_eq!(compiled.states_with_label("goal"), [key("goal")]);
+    assert_eq!(
+        compiled.states_with_label("terminal"),
+        [key("pit"), key("goal")]
+    );
+    assert!(compiled.states_with_label("missing").is_empty());
+    assert_eq!(
+        compiled.state_labels(key("pit")),
+        Some(&["hazard".to_string(), "terminal".to_string()][..])
+    );
+    assert_eq!(compiled.state_metadata(key("s0"), "color"), Some("blue"));
+    assert_eq!(compiled.state_metadata(key("pit"), "color"), None);
+    assert_eq!(
+        compiled.action_labels(key("s0"), 0),
+        Some(&["risky".to_string()][..])
+    );
+    assert_eq!(
+        compiled.action_metadata(key("s0"), 0, "note"),
+        Some("may fall")
+    );
+    assert_eq!(compiled.action_labels(key("s0"), 1), None);
+
+    let saved = serde_yaml::to_string(&spec).expect("spec should serialize");
+    assert!(saved.contains("hazard") && saved.contains("may fall"));
+}
+
+#[test]
+fn available_when_filters_shared_action_templates() {
+    let yaml = r#"
+params:
+  doors: 1
+start: hall
+states:
+  - id: hall
+    actions: &moves
+      - id: wait
+        outcomes:
+          - next: hall
+            prob: 1.0
+            reward: 0.0
+      - id: open
+        available_when: "!locked && ${doors} > 0"
+        outcomes:
+          - next: exit
+            prob: 1.0
+            reward: 1.0
+  - id: cellar
+    labels: [locked]
+    actions: *moves
+  - id: exit
+    terminal: true
+"#;
+    let mut spec: MdpSpec = serde_yaml::from_str(yaml).expect("valid yaml");
+    let compiled = spec.compile().expect("compile should succeed");
+    let key = |compiled: &weavetree_mdp::CompiledMdp, id: &str| {
+        compiled.state_key(id).expect("state exists")
+    };
+    assert_eq!(compiled.num_actions(key(&compiled, "hall")), Some(2));
+    assert_eq!(compiled.num_actions(key(&compiled, "cellar")), Some(1));
+
+    let resolved = spec.resolve_params().expect("params resolve");
+    assert!(
+        resolved
+            .states
+            .iter()
+            .flat_map(|state| state.actions.iter().flatten())
+            .all(|action| action.available_when.is_none())
+    );
+
+    spec.set_param("doors", 0.0);
+    let compiled = spec.compile().expect("compile should succeed");
+    assert_eq!(compiled.num_actions(key(&compiled, "hall")), Some(1));
+
+    spec.states[0].actions.as_mut().expect("hall has actions")[1].available_when =
+        Some("${missing}".to_string());
+    assert!(matches!(
+        spec.compile(),
+        Err(MdpError::InvalidExpression { .. })
+    ));
+}
+
+#[test]
+fn reward_distributions_are_sampled_per_step() {
+    let yaml = r#"
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: noisy
+        outcomes:
+          - next: s0
+            prob: 1.0
+            reward: {normal: {mean: 2.0, std: 0.5}}
+      - id: bounded
+        cost: 1.0
+        outcomes:
+          - next: s0
+            prob: 1.0
+            reward: {uniform: {low: -1.0, high: 3.0}}
+"#;
+    let spec: MdpSpec = serde_yaml::from_str(yaml).expect("valid yaml");
+    assert_eq!(
+        spec.states[0].actions.as_ref().expect("actions")[0].outcomes[0].reward,
+        Reward::Distribution(RewardDistribution::Normal {
+            mean: 2.0,
+            std: 0.5
+        })
+    );
+    let compiled = spec.compile().expect("compile should succeed");
+    let start = compiled.start();
+
+    let mut simulator = MdpSimulator::new(compiled.clone(), 11);
+    let normal: Vec<f64> = (0..4_000).map(|_| simulator.step(start, 0).1).collect();
+    let mean = normal.iter().sum::<f64>() / normal.len() as f64;
+    let std = (normal.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / normal.len() as f64).sqrt();
+    assert!((mean - 2.0).abs() < 0.05, "mean {mean}");
+    assert!((std - 0.5).abs() < 0.05, "std {std}");
+
+    let uniform: Vec<f64> = (0..1_000).map(|_| simulator.step(start, 1).1).collect();
+    assert!(uniform.iter().all(|r| (-2.0..3.0).contains(r)));
+    assert!(uniform.iter().any(|r| *r < 0.0) && uniform.iter().any(|r| *r > 1.0));
+
+    let mut replay = MdpSimulator::new(compiled.clone(), 11);
+    assert_eq!(replay.step(start, 0).1, normal[0]);
+    let mut most_likely =
+        MdpSimulator::new(compiled.clone(), 11).with_sampling_mode(SamplingMode::MostLikely);
+    assert_eq!(most_likely.step(start, 1).1, 0.0);
+
+    let written = serde_yaml::to_string(&spec).expect("spec should serialize");
+    let read_back: MdpSpec = serde_yaml::from_str(&written).expect("written YAML reads back");
+    assert_eq!(
+        read_back.states[0].actions.as_ref().expect("actions")[0].outcomes[0].reward,
+        spec.states[0].actions.as_ref().expect("actions")[0].outcomes[0].reward
+    );
+
+    let invalid = yaml.replace("std: 0.5", "std: -0.5");
+    let spec: MdpSpec = serde_yaml::from_str(&invalid).expect("valid yaml");
+    assert!(matches!(
+        spec.compile(),
+        Err(MdpError::InvalidRewardDistribution {
+            outcome_index: 0,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn vector_rewards_are_scalarized_by_weights() {
+    let yaml = r#"
+weights:
+  profit: 1.0
+  risk: 0.5
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: gamble
+        cost: 0.5
+        outcomes:
+          - next: s0
+            prob: 1.0
+            reward: {profit: 3.0, risk: -1.0}
+      - id: hold
+        outcomes:
+          - next: s0
+            prob: 1.0
+            reward: 1.0
+"#;
+    let mut spec: MdpSpec = serde_yaml::from_str(yaml).expect("valid yaml");
+    let compiled = spec.compile().expect("compile should succeed");
+    assert_eq!(compiled.objectives(), ["profit", "risk"]);
+    assert_eq!(compiled.weights(), [1.0, 0.5]);
+    let start = compiled.start();
+    let reward = |compiled: &weavetree_mdp::CompiledMdp, action| {
+        compiled
+            .most_likely_transition(start, action)
+            .expect("valid action")
+            .1
+    };
+    assert_eq!(reward(&compiled, 0), 3.0 - 0.5 - 0.5);
+    assert_eq!(reward(&compiled, 1), 1.0);
+
+    let risk_averse = compiled
+        .clone()
+        .with_weights(&[("risk".to_string(), 4.0)].into())
+        .expect("known objective");
+    assert_eq!(risk_averse.weights(), [1.0, 4.0]);
+    assert_eq!(reward(&risk_averse, 0), 3.0 - 4.0 - 0.5);
+    assert_eq!(reward(&risk_averse, 1), 1.0);
+    assert!(matches!(
+        compiled.with_weights(&[("speed".to_string(), 1.0)].into()),
+        Err(MdpError::UnknownObjective { .. })
+    ));
+
+    spec.set_weight("risk", 0.0);
+    let compiled = spec.compile().expect("compile should succeed");
+    assert_eq!(reward(&compiled, 0), 2.5);
+
+    spec.weights.as_mut().expect("weights").remove("risk");
+    assert!(matches!(
+        spec.compile(),
+        Err(MdpError::UnknownObjective { objective }) if objective == "risk"
+    ));
+}
+
+const TIGER_YAML: &str = r#"
+start: left
+states:
+  - id: left
+    actions:
+      - id: listen
+        outcomes:
+          - next: left
+            prob: 1.0
+            reward: -1.0
+            observations: { hear-left: 0.85, hear-right: 0.15 }
+      - id: open-left
+        outcomes:
+          - { next: done, prob: 1.0, reward: -100.0 }
+  - id: right
+    actions:
+      - id: listen
+        outcomes:
+          - next: right
+            prob: 1.0
+            reward: -1.0
+            observations: { hear-left: 0.15, hear-right: 0.85 }
+      - id: open-left
+        outcomes:
+          - { next: done, prob: 1.0, reward: 10.0 }
+  - id: done
+    terminal: true
+"#;
+
+#[test]
+fn belief_simulator_tracks_observations() {
+    let spec: MdpSpec = serde_yaml::from_str(TIGER_YAML).expect("valid syntax");
+    let compiled = spec.compile().expect("compile should succeed");
+    assert_eq!(compiled.observations(), ["", "hear-left", "hear-right"]);
+    assert_eq!(compiled.observation_key("hear-right"), Some(2));
+
+    let left = compiled.state_key("left").expect("state exists");
+    let right = compiled.state_key("right").expect("state exists");
+    let done = compiled.state_key("done").expect("state exists");
+    let mut simulator = BeliefSimulator::new(compiled, 5)
+        .with_initial_belief(&[(left, 1.0), (right, 1.0)])
+        .expect("valid belief");
+    let start = simulator.start_state_key();
+    assert_eq!(
+        simulator.belief(start),
+        Some(&[(left, 0.5), (right, 0.5)][..])
+    );
+    assert_eq!(simulator.num_actions_by_key(start), 2);
+
+    let heard_left = simulator
+        .update_belief(start, 0, "hear-left")
+        .expect("possible observation");
+    let belief = simulator.belief(heard_left).expect("interned belief");
+    assert!((belief[0].1 - 0.85).abs() < 1e-12);
+    let twice = simulator
+        .update_belief(heard_left, 0, "hear-left")
+        .expect("possible observation");
+    let belief = simulator.belief(twice).expect("interned belief");
+    assert!((belief[0].1 - 0.85 * 0.85 / (0.85 * 0.85 + 0.15 * 0.15)).abs() < 1e-12);
+    assert_eq!(
+        simulator.update_belief(heard_left, 0, "hear-right"),
+        Some(start)
+    );
+    assert_eq!(simulator.update_belief(start, 0, "roar"), None);
+    assert_eq!(simulator.update_belief(start, 1, "hear-left"), None);
+
+    let opened = simulator
+        .update_belief(start, 1, "")
+        .expect("opening emits the empty observation");
+    assert_eq!(simulator.belief(opened), Some(&[(done, 1.0)][..]));
+    assert!(simulator.is_terminal_by_key(opened));
+    assert!(!simulator.is_terminal_by_key(start));
+
+    let interned = simulator.belief_count();
+    for _ in 0..50 {
+        let (next, reward, terminal) = simulator.step_by_key(start, 0);
+        assert!(next == heard_left || simulator.belief(next).unwrap()[0].1 < 0.5);
+        assert_eq!(reward, -1.0);
+        assert!(!terminal);
+    }
+    assert_eq!(simulator.belief_count(), interned + 1);
+
+    let trajectory = simulate_episode(&mut simulator, |_state, _n| ActionId::from(1), 10);
+    assert!(trajectory.terminal);
+    assert_eq!(trajectory.len(), 1);
+    assert!([-100.0, 10.0].contains(&trajectory.rewards[0]));
+
+    let mut invalid = spec.clone();
+    invalid.states[0].actions.as_mut().unwrap()[0].outcomes[0].observations =
+        Some([("hear-left".to_string(), 0.5)].into());
+    assert!(matches!(
+        invalid.compile(),
+        Err(MdpError::InvalidObservations {
+            outcome_index: 0,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn transition_matrices_export_dense_and_sparse() {
+    let spec: MdpSpec = serde_yaml::from_str(VALID_MDP_YAML).expect("valid yaml");
+    let compiled = spec.compile().expect("compile should succeed");
+
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+    let dense = compiled.to_dense_matrices();
+    assert_eq!((dense.num_states, dense.num_actions), (3, 2));
+    assert_eq!(dense.transitions.len(), 2 * 3 * 3);
+    assert!(close(dense.transition(0, 0, 1), 0.7));
+    assert!(close(dense.transition(0, 0, 0), 0.3));
+    assert!(close(dense.transition(1, 0, 2), 1.0));
+    assert!(close(dense.reward(0, 0), 0.7));
+    assert!(close(dense.reward(1, 0), -0.2));
+    assert_eq!(dense.available, [true, false, false, true, false, false]);
+    assert_eq!(dense.terminal, [false, true, false]);
+
+    let sparse = compiled.to_sparse_triplets();
+    assert_eq!(sparse.len(), 3);
+    assert_eq!(sparse.action, [0, 0, 1]);
+    assert_eq!(sparse.state, [0, 0, 0]);
+    assert_eq!(sparse.next, [1, 0, 2]);
+    assert_eq!(sparse.reward, [1.0, 0.0, -0.2]);
+    for i in 0..sparse.len() {
+        let (action, state, next) = (sparse.action[i], sparse.state[i], sparse.next[i]);
+        assert!(close(sparse.prob[i], dense.transition(action, state, next)));
+    }
+}
+
+#[test]
+fn unreachable_states_are_pruned_with_remapping() {
+    let mut builder = MdpBuilder::new();
+    builder
+        .set_start("s1")
+        .add_state("orphan", false)
+        .add_state("s1", false)
+        .add_state("goal", true)
+        .add_state("behind_goal", true);
+    builder.add_action("s1", "go").expect("state exists");
+    builder
+        .add_outcome("s1", "go", "goal", 1.0, 5.0)
+        .expect("action exists");
+    builder.add_action("orphan", "go").expect("state exists");
+    builder
+        .add_outcome("orphan", "go", "s1", 1.0, 0.0)
+        .expect("action exists");
+    let spec = builder.build_spec().expect("spec should build");
+
+    let compiled = spec.compile().expect("compile should succeed");
+    let key = |id: &str| compiled.state_key(id).expect("state exists");
+    assert_eq!(compiled.reachable_states(), [key("s1"), key("goal")]);
+
+    let (pruned, report) = compiled.clone().prune_unreachable();
+    assert_eq!(pruned.state_count(), 2);
+    assert_eq!(report.removed, ["orphan", "behind_goal"]);
+    assert_eq!(report.new_key(key("orphan")), None);
+    let new_start = report.new_key(key("s1")).expect("start is kept");
+    assert_eq!(pruned.start(), new_start);
+    assert_eq!(pruned.state_id(new_start), Some("s1"));
+
+    let mut simulator = MdpSimulator::new(pruned.clone(), 0);
+    let (next, reward, terminal) = simulator.step(new_start, 0);
+    assert_eq!(pruned.state_id(next), Some("goal"));
+    assert_eq!((reward, terminal), (5.0, true));
+
+    let (from_spec, _) = spec.compile_reachable().expect("compile should succeed");
+    assert_eq!(from_spec.state_count(), 2);
+}
+
+#[test]
+fn typed_builder_references_states_by_handle() {
+    let mut builder = TypedMdpBuilder::new();
+    let s0 = builder.add_state("s0", false);
+    let goal = builder.add_state("goal", true);
+    let go = builder.add_action(s0, "go");
+    let wait = builder.add_action(s0, "wait");
+    assert_eq!(go.state(), s0);
+    builder
+        .set_start(s0)
+        .add_outcome(go, goal, 0.75, 5.0)
+        .add_outcome(go, s0, 0.25, 0.0)
+        .add_outcome(wait, s0, 1.0, -1.0);
+    assert_eq!(builder.state_id(goal), "goal");
+    let spec = builder.build_spec().expect("spec should build");
+
+    let mut expected = MdpBuilder::new();
+    expected
+        .set_start("s0")
+        .add_state("s0", false)
+        .add_state("goal", true);
+    expected.add_action("s0", "go").expect("state exists");
+    expected.add_action("s0", "wait").expect("state exists");
+    for (action, next, prob, reward) in [
+        ("go", "goal", 0.75, 5.0),
+        ("go", "s0", 0.25, 0.0),
+        ("wait", "s0", 1.0, -1.0),
+    ] {
+        expected
+            .add_outcome("s0", action, next, prob, reward)
+            .expect("action exists");
+    }
+    let expected = expected.build_spec().expect("spec should build");
+    assert_eq!(
+        serde_json::to_value(&spec).unwrap(),
+        serde_json::to_value(&expected).unwrap()
+    );
+
+    let mut missing_start = TypedMdpBuilder::new();
+    missing_start.add_state("s0", true);
+    assert!(matches!(
+        missing_start.build_spec(),
+        Err(MdpError::MissingStart)
+    ));
+}
+
+#[test]
+fn bulk_builder_calls_build_a_chain() {
+    let n = 1_000;
+    let mut builder = MdpBuilder::new();
+    builder
+        .set_start("s0")
+        .add_states((0..n).map(|i| (format!("s{i}"), i + 1 == n)));
+    for i in 0..n - 1 {
+        builder
+            .add_deterministic_transition(format!("s{i}"), "forward", format!("s{}", i + 1), 1.0)
+            .expect("state exists")
+            .add_actions(format!("s{i}"), ["stay", "gamble"])
+            .expect("state exists")
+            .add_outcome(format!("s{i}"), "stay", format!("s{i}"), 1.0, 0.0)
+            .expect("action exists")
+            .add_outcomes(
+                format!("s{i}"),
+                "gamble",
+                [("s0", 0.5, 0.0), (format!("s{}", n - 1).as_str(), 0.5, 2.0)],
+            )
+            .expect("action exists");
+    }
+    assert!(matches!(
+        builder.add_deterministic_transition("missing", "go", "s0", 0.0),
+        Err(MdpError::BuilderUnknownState { .. })
+    ));
+    assert!(matches!(
+        builder.add_outcomes("s0", "missing", [("s0", 1.0, 0.0)]),
+        Err(MdpError::BuilderUnknownAction { .. })
+    ));
+
+    let spec = builder.build_spec().expect("spec should build");
+    let gamble = &spec.states[0].actions.as_ref().expect("s0 has actions")[2];
+    assert_eq!(gamble.id, "gamble");
+    let last = format!("s{}", n - 1);
+    let outcomes: Vec<_> = gamble
+        .outcomes
+        .iter()
+        .map(|outcome| {
+            (
+                outcome.next.as_str(),
+                outcome.prob.value(),
+                outcome.reward.value(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        outcomes,
+        [("s0", Some(0.5), 0.0), (last.as_str(), Some(0.5), 2.0)]
+    );
+
+    let compiled = spec.compile().expect("compile should succeed");
+    assert_eq!(compiled.state_count(), n);
+    let s0 = compiled.state_key("s0").expect("state exists");
+    assert_eq!(compiled.num_actions(s0), Some(3));
+    let last = compiled.state_key(&last).expect("state exists");
+    assert_eq!(compiled.is_terminal(last), Some(true));
+}
+
+#[test]
+fn builder_lookups_scale_to_large_models() {
+    let n = 100_000;
+    let id = |i: usize| format!("s{}", i % n);
+    let mut builder = MdpBuilder::new();
+    builder
+        .set_start("s0")
+        .add_states((0..n).map(|i| (id(i), false)));
+    for i in 0..n {
+        builder
+            .add_actions(id(i), ["left", "right"])
+            .expect("state exists")
+            .add_outcomes(id(i), "left", [(id(i + n - 1), 1.0, 0.0)])
+            .expect("action exists")
+            .add_outcomes(id(i), "right", [(id(i + 1), 0.5, 1.0), (id(i), 0.5, 0.0)])
+            .expect("action exists");
+    }
+    let compiled = builder.compile().expect("compile should succeed");
+    assert_eq!(compiled.state_count(), n);
+    assert_eq!(compiled.reachable_states().len(), n);
+
+    let mut duplicated = MdpBuilder::new();
+    duplicated
+        .set_start("s0")
+        .add_states([("s0", false), ("s0", true)]);
+    duplicated
+        .add_deterministic_transition("s0", "stay", "s0", 0.0)
+        .expect("first s0 exists");
+    assert!(matches!(
+        duplicated.build_spec(),
+        Err(MdpError::DuplicateStateId { .. })
+    ));
+}
+
+#[test]
+fn json_specs_round_trip_and_compile() {
+    let spec: MdpSpec = serde_yaml::from_str(VALID_MDP_YAML).expect("valid yaml");
+    let path = std::env::temp_dir().join(format!("weavetree-mdp-{}.json", std::process::id()));
+    save_json(&path, &spec).expect("json should be written");
+    let loaded = load_json(&path).expect("json should load");
+    let compiled = compile_json(&path).expect("json should compile");
+    std::fs::remove_file(&path).expect("temp file should be removable");
+
+    assert_eq!(
+        serde_yaml::to_string(&loaded).expect("spec should serialize"),
+        serde_yaml::to_string(&spec).expect("spec should serialize")
+    );
+    assert_eq!(compiled.state_count(), 3);
+
+    let inline = compile_json_str(r#"{"start": "s0", "states": [{"id": "s0", "terminal": true}]}"#)
+        .expect("inline json should compile");
+    assert_eq!(inline.state_key("s0"), Some(inline.start()));
+
+    let err = compile_json_str(r#"{"start": "s0""#).expect_err("truncated json should fail");
+    assert!(matches!(err, MdpError::Json(_)));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml_specs_round_trip_and_compile() {
+    use weavetree_mdp::{compile_toml, compile_toml_str, load_toml, save_toml};
+
+    let spec: MdpSpec = serde_yaml::from_str(VALID_MDP_YAML).expect("valid yaml");
+    let path = std::env::temp_dir().join(format!("weavetree-mdp-{}.toml", std::process::id()));
+    save_toml(&path, &spec).expect("toml should be written");
+    let loaded = load_toml(&path).expect("toml should load");
+    let compiled = compile_toml(&path).expect("toml should compile");
+    std::fs::remove_file(&path).expect("temp file should be removable");
+
+    assert_eq!(
+        serde_yaml::to_string(&loaded).expect("spec should serialize"),
+        serde_yaml::to_string(&spec).expect("spec should serialize")
+    );
+    assert_eq!(compiled.state_count(), 3);
+
+    let inline = compile_toml_str(
+        r#"
+start = "s0"
+
+[[states]]
+id = "s0"
+
+[[states.actions]]
+id = "a0"
+
+[[states.actions.outcomes]]
+next = "s1"
+prob = 1.0
+reward = 1.0
+
+[[states]]
+id = "s1"
+terminal = true
+"#,
+    )
+    .expect("inline toml should compile");
+    assert_eq!(inline.num_actions(inline.start()), Some(1));
+
+    let err = compile_toml_str("start = ").expect_err("truncated toml should fail");
+    assert!(matches!(err, MdpError::TomlParse(_)));
+}
+
+#[cfg(feature = "binary")]
+#[test]
+fn binary_specs_round_trip_and_compile() {
+    use weavetree_mdp::{compile_binary, load_binary, save_binary};
+
+    let spec: MdpSpec = serde_yaml::from_str(VALID_MDP_YAML).expect("valid yaml");
+    let path = std::env::temp_dir().join(format!("weavetree-mdp-{}.bin", std::process::id()));
+    save_binary(&path, &spec).expect("binary should be written");
+    let loaded = load_binary(&path).expect("binary should load");
+    let compiled = compile_binary(&path).expect("binary should compile");
+
+    std::fs::write(&path, b"\xc1").expect("temp file should be writable");
+    let err = load_binary(&path).expect_err("invalid MessagePack should fail");
+    std::fs::remove_file(&path).expect("temp file should be removable");
+
+    assert_eq!(
+        serde_yaml::to_string(&loaded).expect("spec should serialize"),
+        serde_yaml::to_string(&spec).expect("spec should serialize")
+    );
+    assert_eq!(compiled.state_count(), 3);
+    assert!(matches!(err, MdpError::BinaryParse(_)));
+}
+
 #[test]
 fn sampling_is_deterministic_for_fixed_seed() {
     let yaml = r#"
@@ -114,7 +1631,214 @@ states:
         trace_b.push(sim_b.step(StateKey::from(0), 0));
     }
 
-    assert_eq!(trace_a, trace_b);
+    assert_eq!(trace_a, trace_b);
+}
+
+#[test]
+fn sample_batch_matches_repeated_steps() {
+    let spec: MdpSpec = serde_yaml::from_str(
+        r#"
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: a0
+        outcomes:
+          - next: s0
+            prob: 0.6
+            reward: 0.0
+          - next: s1
+            prob: 0.4
+            reward: 1.0
+  - id: s1
+    terminal: true
+"#,
+    )
+    .expect("valid syntax");
+    let compiled = spec.compile().expect("compile should succeed");
+
+    let mut batched = MdpSimulator::new(compiled.clone(), 42);
+    let mut stepped = MdpSimulator::new(compiled, 42);
+    let expected: Vec<_> = (0..32)
+        .map(|_| stepped.step(StateKey::from(0), 0))
+        .collect();
+    assert_eq!(batched.sample_batch(StateKey::from(0), 0, 32), expected);
+    assert_eq!(batched.sample_batch(StateKey::from(0), 0, 0), Vec::new());
+
+    let mut domain_batched = DomainSimulator::new(WalkDomain, 3);
+    let mut domain_stepped = DomainSimulator::new(WalkDomain, 3);
+    let start = domain_batched.start_state_key();
+    let expected: Vec<_> = (0..4)
+        .map(|_| domain_stepped.step_by_key(start, 0))
+        .collect();
+    assert_eq!(domain_batched.sample_batch_by_key(start, 0, 4), expected);
+    assert_eq!(
+        domain_batched.sample_batch_by_key(99, 0, 2),
+        vec![(99, 0.0, true); 2]
+    );
+}
+
+#[test]
+fn simulator_rng_can_be_reseeded_split_and_restored() {
+    let compiled = serde_yaml::from_str::<MdpSpec>(VALID_MDP_YAML)
+        .expect("valid syntax")
+        .compile()
+        .expect("compile should succeed");
+    let mut simulator = MdpSimulator::new(compiled.clone(), 11);
+    let fresh = sample_trace(&mut simulator);
+
+    simulator.reseed(11);
+    assert_eq!(sample_trace(&mut simulator), fresh);
+
+    let snapshot = simulator.rng_state();
+    let yaml = serde_yaml::to_string(&snapshot).expect("rng state should serialize");
+    let continued = sample_trace(&mut simulator);
+    simulator.restore_rng_state(&serde_yaml::from_str(&yaml).expect("rng state should parse"));
+    assert_eq!(sample_trace(&mut simulator), continued);
+
+    simulator.reseed(11);
+    simulator.set_stream(1);
+    let stream_one = sample_trace(&mut simulator);
+    assert_ne!(stream_one, fresh);
+    let mut other = MdpSimulator::new(compiled, 11);
+    other.set_stream(1);
+    assert_eq!(sample_trace(&mut other), stream_one);
+
+    let mut domain = DomainSimulator::new(
+        PayloadWalk {
+            stamp_payload: false,
+        },
+        4,
+    );
+    let start = domain.start_state_key();
+    let snapshot = domain.rng_state();
+    let first = domain.sample_batch_by_key(start, 0, 16);
+    domain.restore_rng_state(&snapshot);
+    assert_eq!(domain.sample_batch_by_key(start, 0, 16), first);
+}
+
+#[test]
+fn most_likely_mode_always_takes_highest_probability_outcome() {
+    let spec: MdpSpec = serde_yaml::from_str(
+        r#"
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: risky
+        outcomes:
+          - next: s1
+            prob: 0.3
+            reward: 5.0
+          - next: s0
+            prob: 0.7
+            reward: -1.0
+      - id: tied
+        outcomes:
+          - next: s1
+            prob: 0.5
+            reward: 2.0
+          - next: s0
+            prob: 0.5
+            reward: 0.0
+  - id: s1
+    terminal: true
+"#,
+    )
+    .expect("valid syntax");
+    let compiled = spec.compile().expect("compile should succeed");
+    let s0 = StateKey::from(0);
+    let s1 = StateKey::from(1);
+
+    assert_eq!(
+        compiled.most_likely_transition(s0, 0),
+        Some((s0, -1.0, false))
+    );
+    assert_eq!(
+        compiled.most_likely_transition(s0, 1),
+        Some((s1, 2.0, true))
+    );
+    assert_eq!(
+        compiled.most_likely_transition(s1, 0),
+        Some((s1, 0.0, true))
+    );
+    assert_eq!(compiled.most_likely_transition(s0, 2), None);
+
+    let mut simulator = MdpSimulator::new(compiled, 3).with_sampling_mode(SamplingMode::MostLikely);
+    let rng_before = simulator.rng().clone();
+    assert!(
+        simulator
+            .sample_batch(s0, 0, 50)
+            .iter()
+            .all(|transition| *transition == (s0, -1.0, false))
+    );
+    assert_eq!(simulator.rng(), &rng_before);
+}
+
+#[test]
+fn alias_tables_sample_the_same_distribution() {
+    let probs = [0.5, 0.25, 0.0, 0.125, 0.125];
+    let mut builder = MdpBuilder::new();
+    builder.set_start("s0").add_state("s0", false);
+    builder.add_action("s0", "a0").expect("state exists");
+    for (idx, prob) in probs.iter().enumerate() {
+        builder.add_state(format!("t{idx}"), true);
+        builder
+            .add_outcome("s0", "a0", format!("t{idx}"), *prob, idx as f64)
+            .expect("action exists");
+    }
+    let compiled = builder.compile().expect("compile should succeed");
+
+    let draws = 40_000;
+    let frequencies = |mut simulator: MdpSimulator| {
+        let mut counts = [0usize; 5];
+        for (_, reward, _) in simulator.sample_batch(StateKey::from(0), 0, draws) {
+            counts[reward as usize] += 1;
+        }
+        counts.map(|count| count as f64 / draws as f64)
+    };
+    let with_cdf = frequencies(MdpSimulator::new(compiled.clone(), 8));
+    let with_alias = frequencies(MdpSimulator::new(compiled.with_alias_tables(4), 8));
+
+    assert_eq!(with_alias[2], 0.0);
+    for ((cdf, alias), prob) in with_cdf.iter().zip(&with_alias).zip(probs) {
+        assert!((cdf - prob).abs() < 0.01, "cdf {cdf} vs {prob}");
+        assert!((alias - prob).abs() < 0.01, "alias {alias} vs {prob}");
+    }
+}
+
+fn sample_trace<R: RngCore>(simulator: &mut MdpSimulator<R>) -> Vec<(StateKey, f64, bool)> {
+    (0..20)
+        .map(|_| simulator.step(StateKey::from(0), 0))
+        .collect()
+}
+
+#[test]
+fn simulators_accept_user_supplied_rngs() {
+    let spec: MdpSpec = serde_yaml::from_str(VALID_MDP_YAML).expect("valid yaml");
+    let compiled = spec.compile().expect("compile should succeed");
+
+    let default_trace = sample_trace(&mut MdpSimulator::new(compiled.clone(), 9));
+    let explicit_trace = sample_trace(&mut MdpSimulator::with_rng(
+        compiled.clone(),
+        ChaCha8Rng::seed_from_u64(9),
+    ));
+    assert_eq!(default_trace, explicit_trace);
+
+    // Counter-based streams: the same seed on another stream gives an independent sequence.
+    let mut stream_rng = ChaCha8Rng::seed_from_u64(9);
+    stream_rng.set_stream(1);
+    let mut stream = MdpSimulator::with_rng(compiled.clone(), stream_rng);
+    assert_ne!(sample_trace(&mut stream), default_trace);
+    assert_eq!(stream.rng().get_stream(), 1);
+
+    let mut chacha20 = MdpSimulator::<ChaCha20Rng>::with_seed(compiled, 9);
+    assert_eq!(sample_trace(&mut chacha20).len(), 20);
+
+    let mut domain = DomainSimulator::<_, ChaCha20Rng>::with_seed(CounterDomain, 3);
+    let (next, reward, terminal) = domain.step_by_key(domain.start_state_key(), 1);
+    assert_eq!((reward, terminal), (3.0, true));
+    assert!(domain.state_for_key(next).is_some());
 }
 
 #[test]
@@ -154,6 +1878,7 @@ states:
         max_steps: 2,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 2,
+        ..SearchConfig::default()
     };
 
     let run = tree
@@ -184,6 +1909,60 @@ states:
     assert_eq!(best.index(), 1);
 }
 
+#[test]
+fn tree_estimates_compare_against_exact_q_values() {
+    let spec: MdpSpec = serde_yaml::from_str(VALID_MDP_YAML).expect("valid yaml");
+    let compiled = spec.compile().expect("compile should succeed");
+    let start = compiled.start();
+
+    let optimal = value_iteration(&compiled, 1.0, 1e-12, 1_000);
+    assert!(optimal.residual <= 1e-12);
+    assert!((optimal.value(start) - 1.0).abs() < 1e-9);
+    assert_eq!(optimal.q(start, 1), Some(-0.2));
+    assert_eq!(optimal.best_action(start), Some(0));
+    assert!(optimal.q[compiled.state_key("s1").expect("exists").index()].is_empty());
+
+    let search_sim = RefCell::new(MdpSimulator::new(compiled.clone(), 3));
+    let mut tree = Tree::new(CoreStateKey::from(start.index() as u64), false);
+    let config = SearchConfig {
+        iterations: 100,
+        max_steps: 20,
+        ..SearchConfig::default()
+    };
+    tree.run(
+        &config,
+        |state| {
+            search_sim
+                .borrow()
+                .num_actions(StateKey::from(state.value() as usize))
+        },
+        |state, action| {
+            let (next, reward, terminal) = search_sim
+                .borrow_mut()
+                .step(StateKey::from(state.value() as usize), action.index());
+            (CoreStateKey::from(next.index() as u64), reward, terminal)
+        },
+        |_state, _n| ActionId::from(0),
+    )
+    .expect("run should succeed");
+
+    let root_only = compare_to_optimal(&tree, &compiled, &optimal, false);
+    assert_eq!(root_only.nodes.len(), 1);
+    let root = root_only.root().expect("root is compared");
+    assert_eq!(root.state_id, "s0");
+    assert_eq!(root.recommended_regret, Some(0.0));
+    assert!((root.actions[1].regret - 1.2).abs() < 1e-9);
+    let a0 = &root.actions[0];
+    assert!(a0.visits > 0);
+    assert_eq!(a0.error, a0.estimate.map(|estimate| estimate - a0.optimal));
+    assert!(root_only.max_abs_error >= a0.error.expect("visited").abs());
+
+    let full = compare_to_optimal(&tree, &compiled, &optimal, true);
+    assert!(full.nodes.len() > 1);
+    assert!(full.nodes.iter().all(|node| node.state_id == "s0"));
+    assert!(full.max_abs_error >= root_only.max_abs_error);
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum CounterPhase {
     Running,
@@ -254,6 +2033,7 @@ fn mcts_runs_with_custom_typed_state_domain() {
         max_steps: 2,
         return_type: ReturnType::Discounted,
         fixed_horizon_steps: 2,
+        ..SearchConfig::default()
     };
 
     let run = tree
@@ -274,3 +2054,868 @@ fn mcts_runs_with_custom_typed_state_domain() {
 
     assert_eq!(best.index(), 1);
 }
+
+/// Nim with one pile: players alternately take one or two stones, and taking the last
+/// stone wins.
+struct NimDomain;
+
+impl TurnBasedDomain for NimDomain {
+    type State = (u32, usize);
+
+    fn num_players(&self) -> usize {
+        2
+    }
+
+    fn start_state(&self) -> Self::State {
+        (4, 0)
+    }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        state.0 == 0
+    }
+
+    fn current_player(&self, state: &Self::State) -> usize {
+        state.1
+    }
+
+    fn num_actions(&self, state: &Self::State) -> usize {
+        state.0.min(2) as usize
+    }
+
+    fn step(
+        &self,
+        state: &Self::State,
+        action_id: usize,
+        _sample: f64,
+    ) -> (Self::State, Vec<f64>, bool) {
+        let pile = state.0.saturating_sub(action_id as u32 + 1);
+        let mut rewards = vec![0.0; 2];
+        if pile == 0 {
+            rewards[state.1] = 1.0;
+            rewards[1 - state.1] = -1.0;
+        }
+        ((pile, 1 - state.1), rewards, pile == 0)
+    }
+}
+
+#[test]
+fn turn_based_simulator_drives_max_n_and_negamax_searches() {
+    let mut simulator = TurnBasedSimulator::new(NimDomain, 3);
+    let start = simulator.start_state_key();
+    assert_eq!(simulator.player_by_key(start), 0);
+    assert_eq!(simulator.num_actions_by_key(start), 2);
+    let (next, rewards, terminal) = simulator.step_by_key(start, 1);
+    assert_eq!(simulator.state_for_key(next), Some(&(2, 1)));
+    assert_eq!(rewards, vec![0.0, 0.0]);
+    assert!(!terminal);
+    let (_, reward, terminal) = simulator.step_for_mover_by_key(next, 1);
+    assert_eq!(reward, 1.0);
+    assert!(terminal);
+
+    let config = SearchConfig {
+        iterations: 400,
+        c: 1.0,
+        max_steps: 10,
+        ..SearchConfig::default()
+    };
+
+    // Taking one stone leaves the opponent a losing pile of three.
+    let shared = TurnBasedSimulator::new(NimDomain, 5).into_shared();
+    let mut tree = Tree::new(shared.start_state_key(), shared.root_is_terminal());
+    tree.run_max_n(
+        &config,
+        shared.player_to_move_fn(),
+        shared.num_actions_fn(),
+        shared.step_fn(),
+        |_state, _n| ActionId::from(0),
+    )
+    .expect("max-n run should succeed");
+    assert_eq!(
+        tree.best_root_action_by_visits().expect("root exists"),
+        Some(ActionId::from(0))
+    );
+
+    let shared = TurnBasedSimulator::new(NimDomain, 5).into_shared();
+    let mut tree = Tree::new(shared.start_state_key(), shared.root_is_terminal());
+    let negamax = SearchConfig {
+        player_mode: PlayerMode::Negamax,
+        ..config
+    };
+    tree.run_with_extensions(
+        &negamax,
+        shared.num_actions_fn(),
+        shared.negamax_step_fn(),
+        |_state, _n| ActionId::from(0),
+        SearchExtensions::new().with_player_to_move(shared.player_to_move_fn()),
+    )
+    .expect("negamax run should succeed");
+    assert_eq!(
+        tree.best_root_action_by_visits().expect("root exists"),
+        Some(ActionId::from(0))
+    );
+}
+
+/// Players add one or two to a running total, after which a coin adds one more; the game
+/// ends at five or above.
+struct CoinAdderDomain;
+
+impl MdpDomain for CoinAdderDomain {
+    type State = u32;
+
+    fn start_state(&self) -> u32 {
+        0
+    }
+
+    fn is_terminal(&self, state: &u32) -> bool {
+        *state >= 5
+    }
+
+    fn num_actions(&self, _state: &u32) -> usize {
+        2
+    }
+
+    fn step(&self, state: &u32, action_id: usize, sample: f64) -> (u32, f64, bool) {
+        let (afterstate, reward) = self.afterstate(state, action_id).expect("always split");
+        let (next, chance_reward, terminal) = self.resolve_chance(&afterstate, sample);
+        (next, reward + chance_reward, terminal)
+    }
+
+    fn action_label(&self, _state: &u32, action_id: usize) -> String {
+        format!("add {}", action_id + 1)
+    }
+
+    fn afterstate(&self, state: &u32, action_id: usize) -> Option<(u32, f64)> {
+        Some((state + action_id as u32 + 1, 1.0))
+    }
+
+    fn resolve_chance(&self, afterstate: &u32, sample: f64) -> (u32, f64, bool) {
+        let next = afterstate + u32::from(sample >= 0.5);
+        (next, 0.0, next >= 5)
+    }
+}
+
+#[test]
+fn afterstate_domain_splits_actions_from_chance() {
+    let domain = AfterstateDomain::new(CoinAdderDomain);
+    assert_eq!(domain.start_state(), AfterstatePhase::Decision(0));
+    assert_eq!(
+        domain.step(&AfterstatePhase::Decision(0), 1, 0.9),
+        (AfterstatePhase::Afterstate(2), 1.0, false)
+    );
+    assert_eq!(domain.num_actions(&AfterstatePhase::Afterstate(2)), 1);
+    assert!(!domain.is_terminal(&AfterstatePhase::Afterstate(7)));
+    assert_eq!(
+        domain.step(&AfterstatePhase::Afterstate(4), 0, 0.9),
+        (AfterstatePhase::Decision(5), 0.0, true)
+    );
+
+    // Domains without afterstates pass straight through.
+    let plain = AfterstateDomain::new(CounterDomain);
+    let start = plain.start_state();
+    let AfterstatePhase::Decision(inner_start) = &start else {
+        panic!("episodes start in a decision state");
+    };
+    assert_eq!(plain.step(&start, 1, 0.3), {
+        let (next, reward, terminal) = CounterDomain.step(inner_start, 1, 0.3);
+        (AfterstatePhase::Decision(next), reward, terminal)
+    });
+
+    // Different decisions reaching the same afterstate share its key.
+    let mut simulator = DomainSimulator::new(AfterstateDomain::new(CoinAdderDomain), 3);
+    let root = simulator.start_state_key();
+    let (two_directly, _, _) = simulator.step_by_key(root, 1);
+    let (one, _, _) = simulator.step_by_key(root, 0);
+    let one = loop {
+        let (next, _, _) = simulator.step_by_key(one, 0);
+        if simulator.state_for_key(next) == Some(&AfterstatePhase::Decision(1)) {
+            break next;
+        }
+    };
+    let (two_via_one, _, _) = simulator.step_by_key(one, 0);
+    assert_eq!(two_directly, two_via_one);
+}
+
+#[test]
+fn action_labels_reach_simulators_and_snapshots() {
+    let counter = DomainSimulator::new(CounterDomain, 1);
+    assert_eq!(
+        counter.action_label_by_key(counter.start_state_key(), 1),
+        Some("1".to_string())
+    );
+
+    let mut split = DomainSimulator::new(AfterstateDomain::new(CoinAdderDomain), 1);
+    let root = split.start_state_key();
+    assert_eq!(
+        split.action_label_by_key(root, 0),
+        Some("add 1".to_string())
+    );
+    let (afterstate, _, _) = split.step_by_key(root, 0);
+    assert_eq!(
+        split.action_label_by_key(afterstate, 0),
+        Some("chance".to_string())
+    );
+    assert_eq!(split.action_label_by_key(StateKeyValue::MAX, 0), None);
+
+    let shared = DomainSimulator::new(CoinAdderDomain, 2).into_shared();
+    let mut tree = Tree::new(shared.start_state_key(), shared.root_is_terminal());
+    let config = SearchConfig {
+        iterations: 10,
+        ..SearchConfig::default()
+    };
+    tree.run(
+        &config,
+        shared.num_actions_fn(),
+        shared.step_fn(),
+        |_state, _n| ActionId::from(0),
+    )
+    .expect("run should succeed");
+
+    let snapshot = tree.snapshot().with_action_labels(shared.action_label_fn());
+    let labels: Vec<_> = snapshot.nodes[0]
+        .edges
+        .iter()
+        .map(|edge| edge.label.as_deref())
+        .collect();
+    assert_eq!(labels, [Some("add 1"), Some("add 2")]);
+    let json = serde_json::to_string(&snapshot).expect("snapshot serializes");
+    assert!(json.contains("\"label\":\"add 2\""));
+    assert!(
+        !serde_json::to_string(&tree.snapshot())
+            .unwrap()
+            .contains("label")
+    );
+}
+
+#[test]
+fn domain_rollout_plays_a_policy_to_the_end() {
+    let mut simulator = DomainSimulator::new(CoinAdderDomain, 4);
+    let mut seen = Vec::new();
+    let (total, trajectory) = simulator.rollout(
+        simulator.start_state_key(),
+        |state, num_actions| {
+            assert_eq!(num_actions, 2);
+            seen.push(*state);
+            0
+        },
+        100,
+    );
+    assert!(trajectory.terminal);
+    assert_eq!(total, trajectory.len() as f64);
+    assert_eq!(trajectory.total_return(), total);
+    assert_eq!(seen.len(), trajectory.len());
+    for (state, key) in seen.iter().zip(&trajectory.states) {
+        assert_eq!(simulator.state_for_key(key.raw()), Some(state));
+    }
+    assert!(
+        *simulator
+            .state_for_key(trajectory.final_state().raw())
+            .unwrap()
+            >= 5
+    );
+
+    let (total, trajectory) = simulator.rollout(0, |_, _| 1, 1);
+    assert_eq!((total, trajectory.len()), (1.0, 1));
+    assert!(!trajectory.terminal);
+
+    let (total, trajectory) = simulator.rollout(999, |_, _| 0, 10);
+    assert_eq!(total, 0.0);
+    assert!(trajectory.is_empty() && trajectory.terminal);
+}
+
+#[test]
+fn simulator_envs_follow_gymnasium_semantics() {
+    let lake = frozen_lake(&FROZEN_LAKE_4X4, false)
+        .compile()
+        .expect("lake compiles");
+    let goal = lake.state_key(&grid_cell_id(3, 3)).expect("goal exists");
+    let mut env = SimulatorEnv::new(MdpSimulator::new(lake, 0));
+    assert_eq!(env.observation_space(), Space::Discrete { n: 16 });
+    assert_eq!(env.reset(Some(3)), 0);
+    assert_eq!(env.action_space(), Space::Discrete { n: 4 });
+
+    let mut total = 0.0;
+    let mut last = None;
+    for action in [1, 1, 3, 3, 1, 3] {
+        let step = env.step(action);
+        assert!(!step.truncated);
+        total += step.reward;
+        last = Some(step);
+    }
+    let last = last.expect("steps were taken");
+    assert!(last.terminated);
+    assert_eq!(last.observation, goal.index());
+    assert_eq!(total, 1.0);
+    assert_eq!(env.action_space(), Space::Discrete { n: 0 });
+    let after = env.step(0);
+    assert_eq!(
+        (after.observation, after.reward, after.terminated),
+        (goal.index(), 0.0, true)
+    );
+
+    let mut env = SimulatorEnv::new(DomainSimulator::new(CoinAdderDomain, 1)).with_max_steps(2);
+    assert_eq!(env.observation_space(), Space::Unbounded);
+    let start = env.reset(None);
+    assert_eq!(env.simulator().state_for_key(start), Some(&0));
+    let first = env.step(0);
+    assert!(!first.terminated && !first.truncated);
+    let second = env.step(0);
+    assert!(second.truncated && !second.terminated);
+    assert_eq!(env.steps(), 2);
+    assert_eq!(env.reset(Some(1)), start);
+    assert_eq!(env.steps(), 0);
+    assert_eq!(env.step(0), first, "reseeding replays the episode");
+}
+
+#[test]
+fn enumerate_domain_builds_a_solvable_spec() {
+    let coin_outcomes = |state: &u32, action_id: usize| {
+        let afterstate = state + action_id as u32 + 1;
+        vec![(afterstate, 0.5, 1.0), (afterstate + 1, 0.5, 1.0)]
+    };
+    let spec = enumerate_domain(&CoinAdderDomain, 100, coin_outcomes).expect("finite domain");
+    assert_eq!(spec.start, "0");
+    assert_eq!(spec.states.len(), 8);
+    assert_eq!(spec.states[1].id, "1");
+    assert_eq!(
+        spec.states
+            .iter()
+            .filter(|state| state.terminal == Some(true))
+            .count(),
+        3
+    );
+
+    let compiled = spec.compile().expect("enumerated spec compiles");
+    let optimal = value_iteration(&compiled, 1.0, 1e-12, 100);
+    // Adding one each move stretches the game, and every move pays one.
+    assert_eq!(optimal.best_action(compiled.start()), Some(0));
+
+    assert!(matches!(
+        enumerate_domain(&CoinAdderDomain, 4, coin_outcomes),
+        Err(MdpError::TooManyStates { limit: 4 })
+    ));
+}
+
+#[test]
+fn stable_hashes_depend_only_on_state_values() {
+    #[derive(serde::Serialize)]
+    struct Board {
+        cells: Vec<u8>,
+        to_move: Option<char>,
+        score: f64,
+    }
+    #[derive(serde::Serialize)]
+    struct WideBoard {
+        cells: Vec<u64>,
+        to_move: Option<char>,
+        score: f32,
+    }
+
+    let board = Board {
+        cells: vec![0, 1, 2],
+        to_move: Some('x'),
+        score: 0.0,
+    };
+    let same = WideBoard {
+        cells: vec![0, 1, 2],
+        to_move: Some('x'),
+        score: -0.0,
+    };
+    let moved = Board {
+        cells: vec![0, 2, 1],
+        ..board
+    };
+
+    let hash = stable_hash64(&board).expect("board should hash");
+    // Pinned: keys must stay valid across processes and releases.
+    assert_eq!(hash, 0x32a6_0915_07bb_6b6f);
+    assert_eq!(stable_hash64(&same).expect("board should hash"), hash);
+    assert_ne!(stable_hash64(&moved).expect("board should hash"), hash);
+    assert_eq!(
+        stable_hash128(&board).expect("board should hash"),
+        stable_hash128(&same).expect("board should hash")
+    );
+    assert_eq!(
+        hashed_state_key(&board).expect("board should hash"),
+        hashed_state_key(&same).expect("board should hash")
+    );
+    assert_ne!(
+        hashed_state_key(&board).expect("board should hash"),
+        hashed_state_key(&moved).expect("board should hash")
+    );
+}
+
+/// Never-ending walk that reaches a new state on every step.
+struct WalkDomain;
+
+impl MdpDomain for WalkDomain {
+    type State = u64;
+
+    fn start_state(&self) -> Self::State {
+        0
+    }
+
+    fn is_terminal(&self, _state: &Self::State) -> bool {
+        false
+    }
+
+    fn num_actions(&self, _state: &Self::State) -> usize {
+        1
+    }
+
+    fn step(
+        &self,
+        state: &Self::State,
+        _action_id: usize,
+        _sample: f64,
+    ) -> (Self::State, f64, bool) {
+        (state + 1, 0.0, false)
+    }
+}
+
+/// Keys states by `hashed_state_key`, as a domain without a dense interner would.
+#[cfg(feature = "wide-state-keys")]
+#[derive(Default)]
+struct HashedInterner {
+    states: std::collections::HashMap<StateKeyValue, u64>,
+}
+
+#[cfg(feature = "wide-state-keys")]
+impl weavetree_mdp::KeyInterner<u64> for HashedInterner {
+    fn intern_start(&mut self, state: u64) -> Result<StateKeyValue, MdpError> {
+        let key = hashed_state_key(&state)?.raw();
+        self.states.insert(key, state);
+        Ok(key)
+    }
+
+    fn intern_successor(
+        &mut self,
+        next: u64,
+        _parent: StateKeyValue,
+        _action_id: usize,
+        _sample: f64,
+        _replay: weavetree_mdp::ReplayFn<'_, u64>,
+    ) -> Result<StateKeyValue, MdpError> {
+        self.intern_start(next)
+    }
+
+    fn with_state<T>(
+        &self,
+        key: StateKeyValue,
+        _replay: weavetree_mdp::ReplayFn<'_, u64>,
+        f: impl FnOnce(&u64) -> T,
+    ) -> Option<T> {
+        self.states.get(&key).map(f)
+    }
+
+    fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    fn stats(&self) -> weavetree_mdp::InternerStats {
+        weavetree_mdp::InternerStats {
+            interned: self.states.len(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "wide-state-keys")]
+#[test]
+fn domain_simulator_keeps_128_bit_interner_keys() {
+    let shared = DomainSimulator::with_interner(
+        WalkDomain,
+        ChaCha8Rng::seed_from_u64(3),
+        HashedInterner::default(),
+    )
+    .into_shared();
+    let start = shared.start_state_key();
+    assert_eq!(start, hashed_state_key(&0_u64).expect("state should hash"));
+    assert!(start.raw() > StateKeyValue::from(u64::MAX));
+
+    let mut tree = Tree::new(start, shared.root_is_terminal());
+    let config = SearchConfig {
+        iterations: 8,
+        max_steps: 4,
+        ..SearchConfig::default()
+    };
+    tree.run(
+        &config,
+        shared.num_actions_fn(),
+        shared.step_fn(),
+        |_state: CoreStateKey, _n: usize| ActionId::from(0),
+    )
+    .expect("run should succeed");
+
+    let expected = hashed_state_key(&1_u64).expect("state should hash").raw();
+    assert!(
+        tree.snapshot()
+            .nodes
+            .iter()
+            .any(|node| node.state_key == expected)
+    );
+}
+
+#[test]
+fn intern_pinned_pins_a_state_interned_earlier() {
+    let mut interner = StateInterner::with_capacity_limit(2, EvictionPolicy::EvictOldest);
+    let start = interner.intern("start");
+    assert_eq!(interner.intern_pinned("start").expect("pin"), start);
+
+    interner.intern("a");
+    interner.intern("b");
+    interner.intern("c");
+    assert_eq!(interner.get(start), Some(&"start"));
+    assert_eq!(interner.key_of(&"a"), None);
+    assert_eq!(interner.len(), 2);
+    interner.check_consistency().expect("consistent");
+}
+
+#[test]
+fn domain_simulator_bounds_interned_states() {
+    let mut evicting =
+        DomainSimulator::new(WalkDomain, 1).with_state_capacity(4, EvictionPolicy::EvictOldest);
+    let mut key = evicting.start_state_key();
+    for _ in 0..10 {
+        key = evicting.step_by_key(key, 0).0;
+    }
+    let stats = evicting.interner_stats();
+    assert_eq!(evicting.interned_states(), 4);
+    assert_eq!(stats.peak_interned, 4);
+    assert_eq!(stats.inserted, 11);
+    assert_eq!(stats.evicted, 7);
+    assert_eq!(evicting.state_for_key(key), Some(&10));
+    assert_eq!(evicting.state_for_key(evicting.start_state_key()), Some(&0));
+    assert_eq!(evicting.state_for_key(1), None);
+    assert!(matches!(
+        evicting.try_step_by_key(1, 0),
+        Err(MdpError::UnknownStateKey { key: 1 })
+    ));
+    assert!(matches!(
+        evicting.try_sample_batch_by_key(1, 0, 3),
+        Err(MdpError::UnknownStateKey { key: 1 })
+    ));
+    assert!(matches!(
+        evicting.try_rollout(1, |_state, _n| 0, 5),
+        Err(MdpError::UnknownStateKey { key: 1 })
+    ));
+    assert_eq!(evicting.step_by_key(1, 0), (1, 0.0, true));
+
+    let shared = DomainSimulator::new(WalkDomain, 1)
+        .with_state_capacity(3, EvictionPolicy::Reject)
+        .into_shared();
+    let config = SearchConfig {
+        iterations: 10,
+        max_steps: 10,
+        ..SearchConfig::default()
+    };
+    let mut num_actions = shared.num_actions_fn();
+    let err = Tree::new(shared.start_state_key(), false)
+        .run_fallible(
+            &config,
+            |state| Ok(num_actions(state)),
+            shared.try_step_fn(),
+            |_state, _n| Ok(ActionId::from(0)),
+        )
+        .expect_err("capacity should be exceeded");
+    assert!(matches!(
+        err,
+        RunError::Callback(MdpError::InternerFull { capacity: 3 })
+    ));
+    assert_eq!(shared.interner_stats().interned, 3);
+}
+
+/// Random walk on a line whose state carries a payload that `Hash` ignores,
+/// so states that differ only in `payload` share a fingerprint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PayloadState {
+    position: i64,
+    payload: Vec<u8>,
+}
+
+impl std::hash::Hash for PayloadState {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.position.hash(state);
+    }
+}
+
+struct PayloadWalk {
+    stamp_payload: bool,
+}
+
+impl MdpDomain for PayloadWalk {
+    type State = PayloadState;
+
+    fn start_state(&self) -> Self::State {
+        PayloadState {
+            position: 0,
+            payload: vec![0; 1024],
+        }
+    }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        state.position.abs() >= 5
+    }
+
+    fn num_actions(&self, state: &Self::State) -> usize {
+        if self.is_terminal(state) { 0 } else { 2 }
+    }
+
+    fn step(&self, state: &Self::State, action_id: usize, sample: f64) -> (Self::State, f64, bool) {
+        let direction = if action_id == 0 { 1 } else { -1 };
+        let slip = if sample < 0.2 { -direction } else { direction };
+        let mut next = state.clone();
+        next.position += slip;
+        if self.stamp_payload {
+            next.payload[0] = next.payload[0].wrapping_add(1);
+        }
+        let terminal = self.is_terminal(&next);
+        (next, if terminal { 1.0 } else { 0.0 }, terminal)
+    }
+}
+
+#[test]
+fn fingerprint_interner_matches_full_interner_without_storing_states() {
+    let mut full = DomainSimulator::new(
+        PayloadWalk {
+            stamp_payload: false,
+        },
+        5,
+    );
+    let mut fingerprinted = DomainSimulator::with_interner(
+        PayloadWalk {
+            stamp_payload: false,
+        },
+        ChaCha8Rng::seed_from_u64(5),
+        FingerprintInterner::verifying(),
+    );
+
+    for episode in 0..20 {
+        let mut full_key = full.start_state_key();
+        let mut fingerprinted_key = fingerprinted.start_state_key();
+        for step in 0..12 {
+            let action = (episode + step) % 2;
+            let a = full.step_by_key(full_key, action);
+            let b = fingerprinted.step_by_key(fingerprinted_key, action);
+            assert_eq!(a, b);
+            (full_key, fingerprinted_key) = (a.0, b.0);
+        }
+    }
+    assert_eq!(full.interned_states(), fingerprinted.interned_states());
+    for key in 0..full.interned_states() as StateKeyValue {
+        assert_eq!(
+            full.state_for_key(key).cloned(),
+            fingerprinted.decode_state(key)
+        );
+    }
+    assert!(
+        fingerprinted
+            .interner()
+            .key_of(&full.domain().start_state())
+            == Some(0)
+    );
+
+    let mut colliding = DomainSimulator::with_interner(
+        PayloadWalk {
+            stamp_payload: true,
+        },
+        ChaCha8Rng::seed_from_u64(5),
+        FingerprintInterner::verifying(),
+    );
+    let first = colliding
+        .try_step_by_key(0, 0)
+        .expect("first visit should intern");
+    let back = colliding
+        .try_step_by_key(first.0, 1)
+        .and_then(|(key, ..)| colliding.try_step_by_key(key, 0));
+    assert!(matches!(back, Err(MdpError::FingerprintCollision { .. })));
+}
+
+/// Position whose `Eq` ignores the visit counter that its `Hash` includes.
+#[derive(Debug, Clone)]
+struct LooseState {
+    pos: u8,
+    visits: u8,
+}
+
+impl PartialEq for LooseState {
+    fn eq(&self, other: &Self) -> bool {
+        self.pos == other.pos
+    }
+}
+
+impl Eq for LooseState {}
+
+impl std::hash::Hash for LooseState {
+    fn hash<H: std::hash::Hasher>(&self, hasher: &mut H) {
+        (self.pos, self.visits).hash(hasher);
+    }
+}
+
+struct ToggleDomain;
+
+impl MdpDomain for ToggleDomain {
+    type State = LooseState;
+
+    fn start_state(&self) -> Self::State {
+        LooseState { pos: 0, visits: 0 }
+    }
+
+    fn is_terminal(&self, _state: &Self::State) -> bool {
+        false
+    }
+
+    fn num_actions(&self, _state: &Self::State) -> usize {
+        1
+    }
+
+    fn step(
+        &self,
+        state: &Self::State,
+        _action_id: usize,
+        _sample: f64,
+    ) -> (Self::State, f64, bool) {
+        let next = LooseState {
+            pos: 1 - state.pos,
+            visits: state.visits + 1,
+        };
+        (next, 0.0, false)
+    }
+}
+
+#[test]
+fn state_checks_report_eq_and_hash_disagreements() {
+    let mut unchecked = DomainSimulator::new(ToggleDomain, 1);
+    let (first, ..) = unchecked.step_by_key(0, 0);
+    unchecked.step_by_key(first, 0);
+    assert!(unchecked.check_interner().is_ok());
+
+    let mut checked = DomainSimulator::new(ToggleDomain, 1).with_state_checks();
+    assert!(checked.interner().is_verifying());
+    let (first, ..) = checked
+        .try_step_by_key(0, 0)
+        .expect("a new position interns");
+    let back = checked.try_step_by_key(first, 0);
+    assert!(matches!(
+        back,
+        Err(MdpError::SuspiciousStateEquality { ref message }) if message.contains("key 0")
+    ));
+    assert!(checked.check_interner().is_ok());
+
+    let bounded = DomainSimulator::new(ToggleDomain, 1)
+        .with_state_checks()
+        .with_state_capacity(4, EvictionPolicy::Reject);
+    assert!(bounded.interner().is_verifying());
+}
+
+#[test]
+fn sync_shared_domain_simulator_serves_parallel_searches() {
+    let shared = DomainSimulator::new(
+        PayloadWalk {
+            stamp_payload: false,
+        },
+        3,
+    )
+    .into_sync_shared();
+    let config = SearchConfig {
+        iterations: 50,
+        max_steps: 20,
+        ..SearchConfig::default()
+    };
+
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let root = shared.start_state_key();
+            let root_is_terminal = shared.root_is_terminal();
+            let num_actions = shared.num_actions_fn();
+            let step = shared.step_fn();
+            let config = config.clone();
+            std::thread::spawn(move || {
+                let mut tree = Tree::new(root, root_is_terminal);
+                tree.run(&config, num_actions, step, |_state, _n| ActionId::from(0))
+                    .expect("run should succeed")
+                    .iterations_completed
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        assert_eq!(worker.join().expect("worker should not panic"), 50);
+    }
+    // Positions -5..=5 are the only states the walk can reach.
+    let stats = shared.interner_stats();
+    assert!(stats.interned > 1 && stats.interned <= 11);
+}
+
+#[test]
+fn simulate_episode_records_trajectory_until_stop() {
+    let spec: MdpSpec = serde_yaml::from_str(VALID_MDP_YAML).expect("valid yaml");
+    let mut simulator = MdpSimulator::new(spec.compile().expect("compile should succeed"), 9);
+
+    let dead_end = simulate_episode(&mut simulator, |_state, _n| ActionId::from(1), 10);
+    assert_eq!(
+        dead_end.states,
+        vec![CoreStateKey::from(0), CoreStateKey::from(2)]
+    );
+    assert_eq!(dead_end.actions, vec![ActionId::from(1)]);
+    assert_eq!(dead_end.rewards, vec![-0.2]);
+    assert!(!dead_end.terminal);
+
+    let goal = simulate_episode(&mut simulator, |_state, _n| ActionId::from(0), 100);
+    assert!(goal.terminal);
+    assert_eq!(goal.final_state(), CoreStateKey::from(1));
+    assert_eq!(goal.states.len(), goal.len() + 1);
+    assert_eq!(goal.total_return(), 1.0);
+
+    let capped = simulate_episode(&mut simulator, |_state, _n| ActionId::from(0), 0);
+    assert!(capped.is_empty() && !capped.terminal);
+
+    let yaml = serde_yaml::to_string(&goal).expect("trajectory should serialize");
+    let parsed: Trajectory = serde_yaml::from_str(&yaml).expect("trajectory should parse");
+    assert_eq!(parsed, goal);
+}
+
+#[test]
+fn greedy_policy_from_search_evaluates_on_simulator() {
+    let spec: MdpSpec = serde_yaml::from_str(VALID_MDP_YAML).expect("valid yaml");
+    let compiled = spec.compile().expect("compile should succeed");
+    let start = compiled.start();
+    let search_sim = RefCell::new(MdpSimulator::new(compiled.clone(), 21));
+
+    let mut tree = Tree::new(CoreStateKey::from(start.index() as u64), false);
+    let config = SearchConfig {
+        iterations: 200,
+        max_steps: 20,
+        ..SearchConfig::default()
+    };
+    tree.run(
+        &config,
+        |state| {
+            search_sim
+                .borrow()
+                .num_actions(StateKey::from(state.value() as usize))
+        },
+        |state, action| {
+            let (next, reward, terminal) = search_sim
+                .borrow_mut()
+                .step(StateKey::from(state.value() as usize), action.index());
+            (CoreStateKey::from(next.index() as u64), reward, terminal)
+        },
+        |_state, _n| ActionId::from(0),
+    )
+    .expect("run should succeed");
+
+    let policy = tree.greedy_policy();
+    assert_eq!(
+        policy.action(CoreStateKey::from(start.index() as u64)),
+        Some(ActionId::from(0))
+    );
+
+    let mut eval_sim = MdpSimulator::new(compiled, 5);
+    let evaluation = evaluate_policy(&mut eval_sim, &policy, 30, 50);
+    assert_eq!(evaluation.episodes, 30);
+    assert_eq!(evaluation.mean_return, 1.0);
+    assert_eq!(evaluation.return_std_dev, 0.0);
+    assert_eq!(evaluation.terminal_rate, 1.0);
+    assert!(evaluation.mean_length >= 1.0);
+    assert_eq!(evaluation.off_policy_steps, 0);
+}
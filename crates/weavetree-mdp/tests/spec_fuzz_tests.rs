@@ -0,0 +1,474 @@
+//! Property-based fuzzing of the spec parser, validator, and compiler.
+//!
+//! Specs come from three sources: valid random models with a few adversarial mutations,
+//! free-form YAML assembled from schema fragments, and arbitrary parameter expressions.
+//! Whatever the input, parsing, validation, and compilation must not panic, `validate`
+//! and `compile` must fail with the same error, and a spec that compiles must yield a
+//! well-formed model.
+
+use std::collections::{BTreeMap, HashMap};
+
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+use weavetree_mdp::generators::random_mdp;
+use weavetree_mdp::{
+    ActionSpec, CompileOptions, CompiledMdp, MdpSimulator, MdpSpec, OutcomeSpec, PROB_TOLERANCE,
+    Probability, Reward, RewardDistribution, Scalar, StateKey, compile_json_str,
+};
+
+/// Ids outcomes and starts may point at; `zz` is never declared and `""` is never valid.
+const IDS: [&str; 6] = ["s0", "s1", "s2", "s3", "zz", ""];
+
+/// Tokens of `${param}` expressions and `available_when` conditions. `rest` is left out
+/// because it would parse back as `Probability::Rest`.
+const EXPRESSION_TOKENS: [&str; 22] = [
+    "${p}",
+    "${q}",
+    "${missing}",
+    "${",
+    "}",
+    "1",
+    "0.5",
+    "1e308",
+    "0",
+    "-",
+    "+",
+    "*",
+    "/",
+    "(",
+    ")",
+    "!",
+    "&&",
+    "||",
+    "<",
+    ">=",
+    "==",
+    "goal",
+];
+
+/// Lines of model YAML at various nesting levels, for free-form documents.
+const YAML_FRAGMENTS: [&str; 30] = [
+    "version: 1",
+    "start: s0",
+    "start: zz",
+    "params: {p: 0.5}",
+    "weights: {profit: 1.0}",
+    "include: [other.yaml]",
+    "states:",
+    "states: []",
+    "  - id: s0",
+    "  - id: s1",
+    "    terminal: true",
+    "    terminal_reward: .nan",
+    "    entry_reward: 1e309",
+    "    labels: [goal]",
+    "    actions:",
+    "    actions: []",
+    "      - id: a",
+    "        cost: -.inf",
+    "        available_when: goal && ${p} > 0",
+    "        outcomes:",
+    "        outcomes: []",
+    "          - next: s0",
+    "          - next: s1",
+    "            prob: rest",
+    "            prob: 0.5",
+    "            prob: 1 - ${p}",
+    "            reward: 1",
+    "            reward: {normal: {mean: 0, std: -1}}",
+    "            reward: {profit: 2, risk: 1}",
+    "            observations: {o: 0.5, p: 0.5}",
+];
+
+/// Adversarial edit of a spec. Indices wrap around the spec's collections.
+#[derive(Debug, Clone)]
+enum Mutation {
+    SetStart(String),
+    DuplicateState(usize),
+    MarkTerminal(usize),
+    MakeTerminal(usize),
+    SetEntryReward(usize, f64),
+    SetTerminalReward(usize, f64),
+    DuplicateAction(usize, usize),
+    ClearOutcomes(usize, usize),
+    SetCost(usize, usize, f64),
+    SetAvailableWhen(usize, usize, String),
+    SetNext(usize, usize, usize, String),
+    SetProb(usize, usize, usize, Probability),
+    SetReward(usize, usize, usize, Reward),
+    SetObservations(usize, usize, usize, BTreeMap<String, f64>),
+    SetParam(String, f64),
+    SetWeight(String, f64),
+    AddInclude,
+}
+
+fn id() -> impl Strategy<Value = String> {
+    proptest::sample::select(&IDS[..]).prop_map(str::to_string)
+}
+
+/// Mostly tame numbers, with non-finite and negative values mixed in.
+fn number() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        4 => -2.0..2.0_f64,
+        1 => Just(0.0),
+        1 => Just(1.0),
+        1 => any::<f64>(),
+        1 => Just(f64::NAN),
+        1 => Just(f64::INFINITY),
+        1 => Just(f64::NEG_INFINITY),
+    ]
+}
+
+fn expression() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => vec(proptest::sample::select(&EXPRESSION_TOKENS[..]), 0..10)
+            .prop_map(|tokens| tokens.join(" ")),
+        1 => "\\PC{0,16}",
+    ]
+    .prop_filter("`rest` parses as `Probability::Rest`", |text| {
+        text != "rest"
+    })
+}
+
+fn probability() -> impl Strategy<Value = Probability> {
+    prop_oneof![
+        number().prop_map(Probability::Value),
+        Just(Probability::REST),
+        expression().prop_map(Probability::Expr),
+    ]
+}
+
+fn objective() -> impl Strategy<Value = String> {
+    proptest::sample::select(&["profit", "risk"][..]).prop_map(str::to_string)
+}
+
+fn reward() -> impl Strategy<Value = Reward> {
+    prop_oneof![
+        number().prop_map(Reward::from),
+        expression().prop_map(|text| Reward::Fixed(Scalar::Expr(text))),
+        (number(), number())
+            .prop_map(|(mean, std)| Reward::Distribution(RewardDistribution::Normal { mean, std })),
+        (number(), number()).prop_map(|(low, high)| Reward::Distribution(
+            RewardDistribution::Uniform { low, high }
+        )),
+        btree_map(objective(), number(), 0..3).prop_map(Reward::Vector),
+    ]
+}
+
+fn mutation() -> impl Strategy<Value = Mutation> {
+    let index = || 0..8usize;
+    prop_oneof![
+        id().prop_map(Mutation::SetStart),
+        index().prop_map(Mutation::DuplicateState),
+        index().prop_map(Mutation::MarkTerminal),
+        index().prop_map(Mutation::MakeTerminal),
+        (index(), number()).prop_map(|(s, value)| Mutation::SetEntryReward(s, value)),
+        (index(), number()).prop_map(|(s, value)| Mutation::SetTerminalReward(s, value)),
+        (index(), index()).prop_map(|(s, a)| Mutation::DuplicateAction(s, a)),
+        (index(), index()).prop_map(|(s, a)| Mutation::ClearOutcomes(s, a)),
+        (index(), index(), number()).prop_map(|(s, a, cost)| Mutation::SetCost(s, a, cost)),
+        (index(), index(), expression())
+            .prop_map(|(s, a, condition)| Mutation::SetAvailableWhen(s, a, condition)),
+        (index(), index(), index(), id())
+            .prop_map(|(s, a, o, next)| Mutation::SetNext(s, a, o, next)),
+        (index(), index(), index(), probability())
+            .prop_map(|(s, a, o, prob)| Mutation::SetProb(s, a, o, prob)),
+        (index(), index(), index(), reward())
+            .prop_map(|(s, a, o, reward)| Mutation::SetReward(s, a, o, reward)),
+        (index(), index(), index(), btree_map("[op]", number(), 0..3))
+            .prop_map(|(s, a, o, observations)| Mutation::SetObservations(s, a, o, observations)),
+        ("[pq]", number()).prop_map(|(name, value)| Mutation::SetParam(name, value)),
+        (objective(), number()).prop_map(|(name, value)| Mutation::SetWeight(name, value)),
+        Just(Mutation::AddInclude),
+    ]
+}
+
+fn state_index(spec: &MdpSpec, s: usize) -> Option<usize> {
+    (!spec.states.is_empty()).then(|| s % spec.states.len())
+}
+
+fn action_mut(spec: &mut MdpSpec, s: usize, a: usize) -> Option<&mut ActionSpec> {
+    let s = state_index(spec, s)?;
+    let actions = spec.states[s].actions.as_mut()?;
+    let len = actions.len();
+    (len > 0).then(|| &mut actions[a % len])
+}
+
+fn outcome_mut(spec: &mut MdpSpec, s: usize, a: usize, o: usize) -> Option<&mut OutcomeSpec> {
+    let outcomes = &mut action_mut(spec, s, a)?.outcomes;
+    let len = outcomes.len();
+    (len > 0).then(|| &mut outcomes[o % len])
+}
+
+fn apply(spec: &mut MdpSpec, mutation: Mutation) {
+    match mutation {
+        Mutation::SetStart(id) => spec.start = id,
+        Mutation::DuplicateState(s) => {
+            if let Some(s) = state_index(spec, s) {
+                spec.states.push(spec.states[s].clone());
+            }
+        }
+        Mutation::MarkTerminal(s) => {
+            if let Some(s) = state_index(spec, s) {
+                spec.states[s].terminal = Some(true);
+            }
+        }
+        Mutation::MakeTerminal(s) => {
+            if let Some(s) = state_index(spec, s) {
+                spec.states[s].terminal = Some(true);
+                spec.states[s].actions = None;
+            }
+        }
+        Mutation::SetEntryReward(s, value) => {
+            if let Some(s) = state_index(spec, s) {
+                spec.states[s].entry_reward = Some(value);
+            }
+        }
+        Mutation::SetTerminalReward(s, value) => {
+            if let Some(s) = state_index(spec, s) {
+                spec.states[s].terminal_reward = Some(value);
+            }
+        }
+        Mutation::DuplicateAction(s, a) => {
+            if let Some(s) = state_index(spec, s)
+                && let Some(actions) = spec.states[s].actions.as_mut()
+                && !actions.is_empty()
+            {
+                let copy = actions[a % actions.len()].clone();
+                actions.push(copy);
+            }
+        }
+        Mutation::ClearOutcomes(s, a) => {
+            if let Some(action) = action_mut(spec, s, a) {
+                action.outcomes.clear();
+            }
+        }
+        Mutation::SetCost(s, a, cost) => {
+            if let Some(action) = action_mut(spec, s, a) {
+                action.cost = Some(cost);
+            }
+        }
+        Mutation::SetAvailableWhen(s, a, condition) => {
+            if let Some(action) = action_mut(spec, s, a) {
+                action.available_when = Some(condition);
+            }
+        }
+        Mutation::SetNext(s, a, o, next) => {
+            if let Some(outcome) = outcome_mut(spec, s, a, o) {
+                outcome.next = next;
+            }
+        }
+        Mutation::SetProb(s, a, o, prob) => {
+            if let Some(outcome) = outcome_mut(spec, s, a, o) {
+                outcome.prob = prob;
+            }
+        }
+        Mutation::SetReward(s, a, o, reward) => {
+            if let Some(outcome) = outcome_mut(spec, s, a, o) {
+                outcome.reward = reward;
+            }
+        }
+        Mutation::SetObservations(s, a, o, observations) => {
+            if let Some(outcome) = outcome_mut(spec, s, a, o) {
+                outcome.observations = Some(observations);
+            }
+        }
+        Mutation::SetParam(name, value) => {
+            spec.set_param(name, value);
+        }
+        Mutation::SetWeight(name, value) => {
+            spec.set_weight(name, value);
+        }
+        Mutation::AddInclude => spec.include = Some(vec!["other.yaml".to_string()]),
+    }
+}
+
+/// Random valid model over `s0..s3`, then up to three mutations.
+fn mutated_spec() -> impl Strategy<Value = MdpSpec> {
+    (any::<u64>(), 1..=4usize, 1..=3usize)
+        .prop_flat_map(|(seed, n_states, n_actions)| {
+            (
+                Just(seed),
+                Just(n_states),
+                Just(n_actions),
+                1..=n_states,
+                vec(mutation(), 0..4),
+            )
+        })
+        .prop_map(|(seed, n_states, n_actions, branching, mutations)| {
+            let mut spec = random_mdp(seed, n_states, n_actions, branching, -1.0..=1.0)
+                .expect("generator arguments are in range");
+            for mutation in mutations {
+                apply(&mut spec, mutation);
+            }
+            spec
+        })
+}
+
+fn yaml_document() -> impl Strategy<Value = String> {
+    vec(proptest::sample::select(&YAML_FRAGMENTS[..]), 0..24).prop_map(|lines| lines.join("\n"))
+}
+
+/// Check that validation, compilation, and linting agree on `spec`, and that whatever
+/// compiles is a well-formed model.
+fn check_spec(spec: &MdpSpec) -> Result<(), TestCaseError> {
+    let validated = spec.validate().map_err(|error| error.to_string());
+    let compiled = spec.compile();
+    prop_assert_eq!(
+        &validated,
+        &compiled
+            .as_ref()
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    );
+    prop_assert_eq!(validated.is_ok(), spec.lint().is_ok());
+    let _ = spec.compile_with_options(&CompileOptions {
+        normalize: true,
+        prune_unreachable: true,
+        ..CompileOptions::default()
+    });
+    if let Ok(compiled) = compiled {
+        check_compiled(spec, &compiled)?;
+    }
+    Ok(())
+}
+
+fn check_compiled(spec: &MdpSpec, compiled: &CompiledMdp) -> Result<(), TestCaseError> {
+    prop_assert_eq!(compiled.state_count(), spec.states.len());
+    prop_assert!(compiled.start().index() < compiled.state_count());
+
+    let triplets = compiled.to_sparse_triplets();
+    let mut sums: HashMap<(usize, usize), f64> = HashMap::new();
+    for i in 0..triplets.len() {
+        let prob = triplets.prob[i];
+        prop_assert!(prob.is_finite() && prob >= 0.0, "probability {}", prob);
+        prop_assert!(
+            triplets.reward[i].is_finite(),
+            "reward {}",
+            triplets.reward[i]
+        );
+        prop_assert!(triplets.next[i] < compiled.state_count());
+        *sums
+            .entry((triplets.state[i], triplets.action[i]))
+            .or_default() += prob;
+    }
+    for ((state, action), sum) in sums {
+        prop_assert!(
+            (sum - 1.0).abs() <= PROB_TOLERANCE + 1e-12,
+            "state {} action {} sums to {}",
+            state,
+            action,
+            sum
+        );
+    }
+
+    let report = compiled.report();
+    if let Some((low, high)) = report.reward_range {
+        prop_assert!(low.is_finite() && high.is_finite() && low <= high);
+    }
+
+    let mut simulator = MdpSimulator::new(compiled.clone(), 0);
+    let mut state = compiled.start();
+    for step in 0..16 {
+        let num_actions = simulator.num_actions(state);
+        if compiled.is_terminal(state) == Some(true) || num_actions == 0 {
+            break;
+        }
+        let (next, reward, terminal) = simulator.step(state, step % num_actions);
+        prop_assert!(next.index() < compiled.state_count());
+        prop_assert!(reward.is_finite());
+        prop_assert_eq!(compiled.is_terminal(next), Some(terminal));
+        state = StateKey::from(next.index());
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn mutated_specs_validate_and_compile_consistently(spec in mutated_spec()) {
+        check_spec(&spec)?;
+    }
+
+    #[test]
+    fn yaml_round_trip_preserves_validation(spec in mutated_spec()) {
+        let yaml = serde_yaml::to_string(&spec).expect("specs serialize to YAML");
+        let parsed: MdpSpec = serde_yaml::from_str(&yaml).expect("serialized specs parse back");
+        prop_assert_eq!(
+            parsed.validate().map_err(|error| error.to_string()),
+            spec.validate().map_err(|error| error.to_string())
+        );
+        check_spec(&parsed)?;
+    }
+
+    #[test]
+    fn free_form_yaml_never_panics(text in yaml_document()) {
+        if let Ok(spec) = serde_yaml::from_str::<MdpSpec>(&text) {
+            check_spec(&spec)?;
+        }
+    }
+
+    #[test]
+    fn arbitrary_json_never_panics(text in "\\PC{0,64}") {
+        let _ = compile_json_str(&text);
+    }
+
+    #[test]
+    fn expressions_never_panic(prob in expression(), condition in expression(), p in number()) {
+        let mut spec = random_mdp(0, 2, 1, 2, 0.0..=1.0).expect("generator arguments are in range");
+        spec.set_param("p", p);
+        let action = &mut spec.states[0].actions.as_mut().expect("random states have actions")[0];
+        action.outcomes[0].prob = Probability::Expr(prob);
+        action.available_when = Some(condition);
+        check_spec(&spec)?;
+    }
+
+    #[test]
+    fn deeply_nested_expressions_fail_without_overflowing(
+        prefix in vec(proptest::sample::select(&["(", "-", "!"][..]), 0..4096),
+    ) {
+        let depth = prefix.len();
+        let closing = ")".repeat(prefix.iter().filter(|token| **token == "(").count());
+        let mut spec = random_mdp(0, 1, 1, 1, 0.0..=1.0).expect("generator arguments are in range");
+        spec.states[0].actions.as_mut().expect("random states have actions")[0].outcomes[0]
+            .reward = Reward::Fixed(Scalar::Expr(format!("{}1{closing}", prefix.concat())));
+        let validated = spec.validate();
+        if depth > 256 {
+            prop_assert!(validated.is_err());
+        }
+        check_spec(&spec)?;
+    }
+}
+
+#[cfg(feature = "testing")]
+proptest! {
+    #[test]
+    fn testing_specs_compile_and_search_cleanly(
+        spec in weavetree_mdp::testing::mdp_spec_with(weavetree_mdp::testing::SpecShape {
+            states: 2..=8,
+            ..Default::default()
+        }),
+        config in weavetree_core::testing::search_config(),
+    ) {
+        use weavetree_mdp::EpisodeSimulator;
+
+        prop_assert!((2..=8).contains(&spec.states.len()));
+        spec.validate().expect("generated specs are valid");
+        check_spec(&spec)?;
+
+        let compiled = spec.compile().expect("generated specs compile");
+        let simulator = std::cell::RefCell::new(MdpSimulator::new(compiled, 0));
+        let start = simulator.borrow().start_key();
+        let mut tree = weavetree_core::Tree::new(start, false);
+        let metrics = tree
+            .run_with_extensions(
+                &config,
+                |state| simulator.borrow().num_actions_key(state),
+                |state, action| simulator.borrow_mut().step_key(state, action),
+                |_state, _num_actions| weavetree_core::ActionId::from(0),
+                weavetree_core::SearchExtensions::new().with_value_bootstrap(|_| 0.0),
+            )
+            .expect("search should succeed");
+        prop_assert_eq!(metrics.iterations_completed, config.iterations);
+    }
+}
@@ -2,19 +2,20 @@
 #![allow(clippy::useless_conversion)]
 
 use std::{
-    cell::RefCell,
+    cell::{Ref, RefCell, RefMut},
     collections::HashMap,
     fs::File,
     io::{BufWriter, Write},
 };
 
 use ::weavetree_core::{
-    ActionId, ReturnType, RunError, RunLogEvent, RunMetrics, SearchConfig,
-    StateKey as CoreStateKey, Tree, TreeError,
+    ActionId, PlayerMode, ReturnType, RunError, RunLogEvent, RunMetrics, SearchConfig,
+    StateKey as CoreStateKey, StateKeyValue, Tree, TreeError, ValueNormalization,
 };
 use ::weavetree_mdp::{CompiledMdp, MdpError, MdpSimulator, MdpSpec, StateKey, compile_yaml};
-use pyo3::exceptions::{PyKeyError, PyTypeError, PyValueError};
+use pyo3::exceptions::{PyKeyError, PyRuntimeError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 
@@ -41,6 +42,26 @@ fn parse_return_type(value: &str) -> PyResult<ReturnType> {
     }
 }
 
+fn parse_value_normalization(value: &str) -> PyResult<ValueNormalization> {
+    match value {
+        "none" => Ok(ValueNormalization::None),
+        "min_max" => Ok(ValueNormalization::MinMax),
+        _ => Err(PyValueError::new_err(
+            "invalid value_normalization; expected one of: none, min_max",
+        )),
+    }
+}
+
+fn parse_player_mode(value: &str) -> PyResult<PlayerMode> {
+    match value {
+        "single_agent" => Ok(PlayerMode::SingleAgent),
+        "negamax" => Ok(PlayerMode::Negamax),
+        _ => Err(PyValueError::new_err(
+            "invalid player_mode; expected one of: single_agent, negamax",
+        )),
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum PyLogFormat {
     Text,
@@ -200,6 +221,22 @@ pub struct PyMdpSimulator {
     inner: RefCell<MdpSimulator>,
 }
 
+impl PyMdpSimulator {
+    /// `Tree.run` holds the simulator for the whole search, with the GIL released, so
+    /// other threads using it meanwhile get an error instead of a panic.
+    fn borrow_inner(&self) -> PyResult<Ref<'_, MdpSimulator>> {
+        self.inner.try_borrow().map_err(|_| simulator_in_use())
+    }
+
+    fn borrow_inner_mut(&self) -> PyResult<RefMut<'_, MdpSimulator>> {
+        self.inner.try_borrow_mut().map_err(|_| simulator_in_use())
+    }
+}
+
+fn simulator_in_use() -> PyErr {
+    PyRuntimeError::new_err("simulator is in use by a running search")
+}
+
 #[pymethods]
 impl PyMdpSimulator {
     #[new]
@@ -217,8 +254,8 @@ impl PyMdpSimulator {
     ///
     /// Invalid keys return `0`.
     #[pyo3(text_signature = "($self, state_key, /)")]
-    fn num_actions(&self, state_key: usize) -> usize {
-        self.inner.borrow().num_actions(parse_state_key(state_key))
+    fn num_actions(&self, state_key: usize) -> PyResult<usize> {
+        Ok(self.borrow_inner()?.num_actions(parse_state_key(state_key)))
     }
 
     /// step($self, state_key, action_id, /)
@@ -231,12 +268,11 @@ impl PyMdpSimulator {
     ///
     /// Invalid inputs are treated as a terminal no-op transition.
     #[pyo3(text_signature = "($self, state_key, action_id, /)")]
-    fn step(&self, state_key: usize, action_id: usize) -> (usize, f64, bool) {
+    fn step(&self, state_key: usize, action_id: usize) -> PyResult<(usize, f64, bool)> {
         let (next, reward, terminal) = self
-            .inner
-            .borrow_mut()
+            .borrow_inner_mut()?
             .step(parse_state_key(state_key), action_id);
-        (next.index(), reward, terminal)
+        Ok((next.index(), reward, terminal))
     }
 }
 
@@ -259,7 +295,7 @@ impl PyMdpSimulator {
 pub struct PyTypedSimulator {
     domain: Py<PyAny>,
     states: RefCell<Vec<Py<PyAny>>>,
-    token_to_key: RefCell<HashMap<Vec<u8>, u64>>,
+    token_to_key: RefCell<HashMap<Vec<u8>, StateKeyValue>>,
     rng: RefCell<ChaCha8Rng>,
     check_token_collisions: bool,
 }
@@ -285,7 +321,7 @@ impl PyTypedSimulator {
         ))
     }
 
-    fn intern_state(&self, py: Python<'_>, state: Py<PyAny>) -> PyResult<u64> {
+    fn intern_state(&self, py: Python<'_>, state: Py<PyAny>) -> PyResult<StateKeyValue> {
         let frozen_state = Self::deep_copy(py, state.bind(py))?;
         let token_obj = self
             .domain
@@ -313,20 +349,21 @@ impl PyTypedSimulator {
             return Ok(*existing);
         }
 
-        let key = self.states.borrow().len() as u64;
+        let key = self.states.borrow().len() as StateKeyValue;
         self.states.borrow_mut().push(frozen_state);
         self.token_to_key.borrow_mut().insert(token, key);
         Ok(key)
     }
 
-    fn state_by_key(&self, py: Python<'_>, key: u64) -> Option<Py<PyAny>> {
+    fn state_by_key(&self, py: Python<'_>, key: StateKeyValue) -> Option<Py<PyAny>> {
+        let index = usize::try_from(key).ok()?;
         self.states
             .borrow()
-            .get(key as usize)
+            .get(index)
             .map(|state| state.clone_ref(py))
     }
 
-    fn num_actions_by_key_impl(&self, state_key: u64) -> PyResult<usize> {
+    fn num_actions_by_key_impl(&self, state_key: StateKeyValue) -> PyResult<usize> {
         Python::with_gil(|py| {
             let Some(state) = self.state_by_key(py, state_key) else {
                 return Ok(0);
@@ -338,7 +375,11 @@ impl PyTypedSimulator {
         })
     }
 
-    fn step_by_key_impl(&self, state_key: u64, action_id: usize) -> PyResult<(u64, f64, bool)> {
+    fn step_by_key_impl(
+        &self,
+        state_key: StateKeyValue,
+        action_id: usize,
+    ) -> PyResult<(StateKeyValue, f64, bool)> {
         Python::with_gil(|py| {
             let Some(state) = self.state_by_key(py, state_key) else {
                 return Ok((state_key, 0.0, true));
@@ -395,7 +436,7 @@ impl PyTypedSimulator {
     ///
     /// Return the interned key of the domain start state.
     #[pyo3(text_signature = "($self, /)")]
-    fn start_state_key(&self) -> u64 {
+    fn start_state_key(&self) -> StateKeyValue {
         0
     }
 
@@ -407,7 +448,7 @@ impl PyTypedSimulator {
     /// Raises:
     ///     KeyError: If `state_key` does not exist.
     #[pyo3(text_signature = "($self, state_key, /)")]
-    fn state_for_key(&self, state_key: u64) -> PyResult<Py<PyAny>> {
+    fn state_for_key(&self, state_key: StateKeyValue) -> PyResult<Py<PyAny>> {
         Python::with_gil(|py| {
             let state = self
                 .state_by_key(py, state_key)
@@ -423,7 +464,7 @@ impl PyTypedSimulator {
     ///
     /// Invalid keys are treated as terminal.
     #[pyo3(text_signature = "($self, state_key, /)")]
-    fn is_terminal_by_key(&self, state_key: u64) -> PyResult<bool> {
+    fn is_terminal_by_key(&self, state_key: StateKeyValue) -> PyResult<bool> {
         Python::with_gil(|py| {
             let Some(state) = self.state_by_key(py, state_key) else {
                 return Ok(true);
@@ -442,7 +483,7 @@ impl PyTypedSimulator {
     ///
     /// Invalid keys return `0`.
     #[pyo3(text_signature = "($self, state_key, /)")]
-    fn num_actions_by_key(&self, state_key: u64) -> PyResult<usize> {
+    fn num_actions_by_key(&self, state_key: StateKeyValue) -> PyResult<usize> {
         self.num_actions_by_key_impl(state_key)
     }
 
@@ -453,7 +494,11 @@ impl PyTypedSimulator {
     ///
     /// Invalid state/action inputs are treated as terminal no-op transitions.
     #[pyo3(text_signature = "($self, state_key, action_id, /)")]
-    fn step_by_key(&self, state_key: u64, action_id: usize) -> PyResult<(u64, f64, bool)> {
+    fn step_by_key(
+        &self,
+        state_key: StateKeyValue,
+        action_id: usize,
+    ) -> PyResult<(StateKeyValue, f64, bool)> {
         self.step_by_key_impl(state_key, action_id)
     }
 }
@@ -490,7 +535,7 @@ fn compile_yaml_str(yaml: &str) -> PyResult<PyCompiledMdp> {
 
 #[pyclass(name = "SearchConfig", module = "weavetree.mcts")]
 #[derive(Clone)]
-/// SearchConfig(iterations=256, c=1.4, gamma=1.0, max_steps=128, return_type='discounted', fixed_horizon_steps=32, /)
+/// SearchConfig(iterations=256, c=1.4, gamma=1.0, max_steps=128, return_type='discounted', fixed_horizon_steps=32, value_normalization='none', player_mode='single_agent', solver=False, /)
 /// --
 ///
 /// MCTS search configuration.
@@ -501,10 +546,11 @@ pub struct PySearchConfig {
 #[pymethods]
 impl PySearchConfig {
     #[new]
-    #[pyo3(signature = (iterations=256, c=1.4, gamma=1.0, max_steps=128, return_type="discounted", fixed_horizon_steps=32))]
+    #[pyo3(signature = (iterations=256, c=1.4, gamma=1.0, max_steps=128, return_type="discounted", fixed_horizon_steps=32, value_normalization="none", player_mode="single_agent", solver=false))]
     #[pyo3(
-        text_signature = "(iterations=256, c=1.4, gamma=1.0, max_steps=128, return_type='discounted', fixed_horizon_steps=32, /)"
+        text_signature = "(iterations=256, c=1.4, gamma=1.0, max_steps=128, return_type='discounted', fixed_horizon_steps=32, value_normalization='none', player_mode='single_agent', solver=False, /)"
     )]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         iterations: usize,
         c: f64,
@@ -512,8 +558,13 @@ impl PySearchConfig {
         max_steps: usize,
         return_type: &str,
         fixed_horizon_steps: usize,
+        value_normalization: &str,
+        player_mode: &str,
+        solver: bool,
     ) -> PyResult<Self> {
         let rt = parse_return_type(return_type)?;
+        let value_normalization = parse_value_normalization(value_normalization)?;
+        let player_mode = parse_player_mode(player_mode)?;
 
         if iterations == 0 {
             return Err(PyValueError::new_err("iterations must be greater than 0"));
@@ -541,6 +592,10 @@ impl PySearchConfig {
                 max_steps,
                 return_type: rt,
                 fixed_horizon_steps,
+                value_normalization,
+                player_mode,
+                solver,
+                ..SearchConfig::default()
             },
         })
     }
@@ -634,6 +689,40 @@ impl PyTree {
             .map_err(tree_err_to_py)
     }
 
+    /// root_action_stats($self, /)
+    /// --
+    ///
+    /// Return one dict per legal root action, in action order, with keys `action_id`,
+    /// `visits`, `q` (mean value), and `outcomes`: a list of
+    /// `{next_state_key, child_node_id, count}` dicts for the sampled next states.
+    #[pyo3(text_signature = "($self, /)")]
+    fn root_action_stats<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let stats = self.inner.root_action_stats(0.0).map_err(tree_err_to_py)?;
+        stats
+            .into_iter()
+            .filter(|stat| stat.legal)
+            .map(|stat| {
+                let outcomes = stat
+                    .outcomes
+                    .iter()
+                    .map(|outcome| {
+                        let dict = PyDict::new_bound(py);
+                        dict.set_item("next_state_key", outcome.next_state_key)?;
+                        dict.set_item("child_node_id", outcome.child_node_id)?;
+                        dict.set_item("count", outcome.count)?;
+                        Ok(dict)
+                    })
+                    .collect::<PyResult<Vec<_>>>()?;
+                let dict = PyDict::new_bound(py);
+                dict.set_item("action_id", stat.action_id.index())?;
+                dict.set_item("visits", stat.visits)?;
+                dict.set_item("q", stat.q)?;
+                dict.set_item("outcomes", outcomes)?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
     /// run($self, simulator, config, rollout_action=0, rollout_policy=None, *, detailed_logging=False, log_format='text', log_path=None, export_tree_path=None)
     /// --
     ///
@@ -644,6 +733,9 @@ impl PyTree {
     /// Otherwise `rollout_action` is used and clamped to valid range.
     /// Callback failures are propagated immediately.
     ///
+    /// With an `MdpSimulator` and no `rollout_policy` the search releases the GIL;
+    /// otherwise it calls into Python and holds the GIL throughout.
+    ///
     /// If `detailed_logging=True`, per-iteration diagnostics are printed.
     /// If `log_path` is provided, diagnostics are also written to disk.
     /// `log_format` accepts `"text"` or `"jsonl"`.
@@ -676,7 +768,8 @@ impl PyTree {
         let logging_enabled = detailed_logging || log_writer.is_some();
 
         if let Ok(simulator) = simulator.extract::<PyRef<'_, PyMdpSimulator>>() {
-            let sim_cell = &simulator.inner;
+            let mut sim = simulator.borrow_inner_mut()?;
+            let sim: &mut MdpSimulator = &mut sim;
             let mut logging_error: Option<PyErr> = None;
             let mut iteration_index: usize = 0;
 
@@ -689,32 +782,30 @@ impl PyTree {
                 )?;
             }
 
-            let metrics = self
-                .inner
-                .run_with_hook_fallible(
-                    &config.inner,
+            // Without a `rollout_policy` the search never calls into Python, so it runs
+            // with the GIL released. A Python policy is called on every rollout step, so
+            // that path keeps the GIL rather than taking it back each time.
+            let tree = &mut self.inner;
+            let search_config = &config.inner;
+            let search = || {
+                let sim_cell = RefCell::new(sim);
+                tree.run_with_hook_fallible(
+                    search_config,
                     |state| {
-                        Ok::<usize, PyErr>(
-                            sim_cell
-                                .borrow()
-                                .num_actions(StateKey::from(state.value() as usize)),
-                        )
+                        Ok::<usize, PyErr>(sim_cell.borrow().num_actions(StateKey::from(state)))
                     },
                     |state, action| {
                         Ok::<(CoreStateKey, f64, bool), PyErr>({
                             let mut sim = sim_cell.borrow_mut();
                             let (next, reward, terminal) =
-                                sim.step(StateKey::from(state.value() as usize), action.index());
+                                sim.step(StateKey::from(state), action.index());
                             (CoreStateKey::from(next.index() as u64), reward, terminal)
                         })
                     },
                     |state, num_actions| {
                         if let Some(policy) = &rollout_policy {
                             let action_id = Python::with_gil(|py| -> PyResult<usize> {
-                                policy
-                                    .bind(py)
-                                    .call1((state.value(), num_actions))?
-                                    .extract()
+                                policy.bind(py).call1((state.raw(), num_actions))?.extract()
                             })?;
                             Ok(ActionId::from(action_id))
                         } else {
@@ -743,10 +834,16 @@ impl PyTree {
                         }
                     },
                 )
-                .map_err(|err| match err {
-                    RunError::Tree(tree_err) => tree_err_to_py(tree_err),
-                    RunError::Callback(py_err) => py_err,
-                })?;
+            };
+            let metrics = if rollout_policy.is_some() {
+                search()
+            } else {
+                simulator.py().allow_threads(search)
+            }
+            .map_err(|err| match err {
+                RunError::Tree(tree_err) => tree_err_to_py(tree_err),
+                RunError::Callback(py_err) => py_err,
+            })?;
 
             if let Some(err) = logging_error {
                 return Err(err);
@@ -790,19 +887,18 @@ impl PyTree {
                 .inner
                 .run_with_hook_fallible(
                     &config.inner,
-                    |state| sim.num_actions_by_key_impl(state.value()),
+                    |state| sim.num_actions_by_key_impl(state.raw()),
                     |state, action| {
-                        sim.step_by_key_impl(state.value(), action.index()).map(
-                            |(next, reward, terminal)| (CoreStateKey::from(next), reward, terminal),
+                        sim.step_by_key_impl(state.raw(), action.index()).map(
+                            |(next, reward, terminal)| {
+                                (CoreStateKey::from_raw(next), reward, terminal)
+                            },
                         )
                     },
                     |state, num_actions| {
                         if let Some(policy) = &rollout_policy {
                             let action_id = Python::with_gil(|py| -> PyResult<usize> {
-                                policy
-                                    .bind(py)
-                                    .call1((state.value(), num_actions))?
-                                    .extract()
+                                policy.bind(py).call1((state.raw(), num_actions))?.extract()
                             })?;
                             Ok(ActionId::from(action_id))
                         } else {
@@ -1,4 +1,5 @@
 import json
+import threading
 
 import pytest
 
@@ -336,3 +337,88 @@ def test_tree_run_exports_tree_snapshot_json(tmp_path):
     assert payload["root_node_id"] == 0
     assert payload["node_count"] >= 1
     assert len(payload["nodes"]) == payload["node_count"]
+
+
+
+def test_tree_root_action_stats_lists_visits_values_and_outcomes():
+    compiled = compile_yaml_str(VALID_MDP_YAML)
+    sim = MdpSimulator(compiled, 7)
+    t = tree(compiled.start_state_key(), compiled.is_terminal(compiled.start_state_key()))
+    assert t.root_action_stats() == []
+
+    config = SearchConfig(iterations=20, c=1.0, gamma=1.0, max_steps=2)
+    t.run(sim, config)
+
+    stats = t.root_action_stats()
+    assert [stat["action_id"] for stat in stats] == [0, 1]
+    assert sum(stat["visits"] for stat in stats) == 20
+    for stat in stats:
+        assert set(stat) == {"action_id", "visits", "q", "outcomes"}
+        assert sum(outcome["count"] for outcome in stat["outcomes"]) == stat["visits"]
+
+    a1_outcomes = stats[1]["outcomes"]
+    assert [outcome["next_state_key"] for outcome in a1_outcomes] == [
+        compiled.state_key("s2")
+    ]
+    assert stats[1]["q"] == pytest.approx(-0.2)
+
+
+def test_tree_run_with_mdp_simulator_releases_the_gil():
+    compiled = compile_yaml_str(VALID_MDP_YAML)
+    config = SearchConfig(iterations=50_000, c=1.0, gamma=1.0, max_steps=8)
+    t = tree(compiled.start_state_key(), False)
+    saw_running = threading.Event()
+    stop = threading.Event()
+
+    def watch():
+        # The running search borrows the tree; seeing that needs the GIL released.
+        while not stop.is_set():
+            try:
+                t.node_count()
+            except RuntimeError:
+                saw_running.set()
+                return
+
+    watcher = threading.Thread(target=watch)
+    watcher.start()
+    runs = 0
+    while not saw_running.is_set() and runs < 20:
+        t.run(MdpSimulator(compiled, 7), config)
+        runs += 1
+    stop.set()
+    watcher.join()
+
+    assert saw_running.is_set()
+    expected = tree(compiled.start_state_key(), False)
+    for _ in range(runs):
+        expected.run(MdpSimulator(compiled, 7), config)
+    assert t.root_action_stats() == expected.root_action_stats()
+
+
+def test_mdp_simulator_is_unavailable_while_a_search_holds_it():
+    compiled = compile_yaml_str(VALID_MDP_YAML)
+    sim = MdpSimulator(compiled, 7)
+    config = SearchConfig(iterations=5, c=1.0, gamma=1.0, max_steps=8)
+    t = tree(compiled.start_state_key(), False)
+    errors = []
+
+    def rollout_policy(_state_key: int, _num_actions: int) -> int:
+        # Called from inside the search, which holds the simulator and the tree.
+        for call in (lambda: sim.num_actions(compiled.start_state_key()), t.node_count):
+            with pytest.raises(RuntimeError) as err:
+                call()
+            errors.append(str(err.value))
+        return 0
+
+    t.run(sim, config, rollout_policy=rollout_policy)
+
+    assert errors
+    assert "simulator is in use by a running search" in errors
+    assert sim.num_actions(compiled.start_state_key()) == 2
+
+
+def test_search_config_rejects_unknown_value_normalization():
+    SearchConfig(value_normalization="min_max")
+
+    with pytest.raises(ValueError):
+        SearchConfig(value_normalization="z_score")
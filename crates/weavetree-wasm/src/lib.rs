@@ -0,0 +1,304 @@
+//! WebAssembly bindings for `weavetree-core` and `weavetree-mdp`.
+//!
+//! Build with `wasm-pack build crates/weavetree-wasm --target web` and import the generated
+//! package from JavaScript. The exported names follow JavaScript conventions (`compileYaml`,
+//! `MdpSimulator.step`, `Tree.run`, ...); state keys and action ids are plain numbers.
+
+use std::cell::RefCell;
+
+use ::weavetree_core::{
+    ActionId, RunError, RunMetrics as CoreRunMetrics, SearchConfig as CoreSearchConfig,
+    StateKey as CoreStateKey, Tree as CoreTree,
+};
+use ::weavetree_mdp::{
+    CompiledMdp as CoreCompiledMdp, MdpError, MdpSimulator as CoreMdpSimulator, MdpSpec, StateKey,
+};
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+
+fn to_js_error(err: impl std::fmt::Display) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+/// Compile an MDP from YAML text.
+///
+/// Throws if YAML parsing or MDP validation fails.
+#[wasm_bindgen(js_name = compileYaml)]
+pub fn compile_yaml(yaml: &str) -> Result<CompiledMdp, JsError> {
+    let spec: MdpSpec =
+        serde_yaml::from_str(yaml).map_err(|err| to_js_error(MdpError::Yaml(err)))?;
+    let inner = spec.compile().map_err(to_js_error)?;
+    Ok(CompiledMdp { inner })
+}
+
+/// Immutable compiled MDP model.
+///
+/// Lookups of unknown state keys or ids return `undefined`.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct CompiledMdp {
+    inner: CoreCompiledMdp,
+}
+
+#[wasm_bindgen]
+impl CompiledMdp {
+    #[wasm_bindgen(js_name = startStateKey)]
+    pub fn start_state_key(&self) -> usize {
+        self.inner.start().index()
+    }
+
+    #[wasm_bindgen(js_name = stateCount)]
+    pub fn state_count(&self) -> usize {
+        self.inner.state_count()
+    }
+
+    #[wasm_bindgen(js_name = isTerminal)]
+    pub fn is_terminal(&self, state_key: usize) -> Option<bool> {
+        self.inner.is_terminal(StateKey::from(state_key))
+    }
+
+    #[wasm_bindgen(js_name = numActions)]
+    pub fn num_actions(&self, state_key: usize) -> Option<usize> {
+        self.inner.num_actions(StateKey::from(state_key))
+    }
+
+    /// Return the string id of a state key.
+    #[wasm_bindgen(js_name = stateId)]
+    pub fn state_id(&self, state_key: usize) -> Option<String> {
+        self.inner
+            .state_id(StateKey::from(state_key))
+            .map(str::to_owned)
+    }
+
+    /// Return the state key of a string id.
+    #[wasm_bindgen(js_name = stateKey)]
+    pub fn state_key(&self, state_id: &str) -> Option<usize> {
+        self.inner.state_key(state_id).map(|key| key.index())
+    }
+}
+
+/// One sampled transition.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepResult {
+    #[wasm_bindgen(js_name = nextStateKey)]
+    pub next_state_key: usize,
+    pub reward: f64,
+    pub terminal: bool,
+}
+
+/// Seeded simulator over a compiled MDP.
+///
+/// Transitions are sampled deterministically from the seed.
+#[wasm_bindgen]
+pub struct MdpSimulator {
+    inner: RefCell<CoreMdpSimulator>,
+}
+
+#[wasm_bindgen]
+impl MdpSimulator {
+    #[wasm_bindgen(constructor)]
+    pub fn new(compiled: &CompiledMdp, seed: u32) -> Self {
+        Self {
+            inner: RefCell::new(CoreMdpSimulator::new(
+                compiled.inner.clone(),
+                u64::from(seed),
+            )),
+        }
+    }
+
+    /// Return the number of actions available from `state_key`, `0` for unknown keys.
+    #[wasm_bindgen(js_name = numActions)]
+    pub fn num_actions(&self, state_key: usize) -> usize {
+        self.inner.borrow().num_actions(StateKey::from(state_key))
+    }
+
+    /// Sample one transition from `(state_key, action_id)`.
+    ///
+    /// Invalid inputs are treated as a terminal no-op transition.
+    pub fn step(&self, state_key: usize, action_id: usize) -> StepResult {
+        let (next, reward, terminal) = self
+            .inner
+            .borrow_mut()
+            .step(StateKey::from(state_key), action_id);
+        StepResult {
+            next_state_key: next.index(),
+            reward,
+            terminal,
+        }
+    }
+}
+
+/// MCTS search configuration.
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct SearchConfig {
+    inner: CoreSearchConfig,
+}
+
+#[wasm_bindgen]
+impl SearchConfig {
+    /// Create the default configuration.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse and validate a configuration from YAML (or JSON) text, with the same fields
+    /// as the CLI's `--config` files. Missing fields take their defaults.
+    #[wasm_bindgen(js_name = fromYaml)]
+    pub fn from_yaml(yaml: &str) -> Result<SearchConfig, JsError> {
+        let inner = CoreSearchConfig::from_yaml_str(yaml).map_err(to_js_error)?;
+        Ok(Self { inner })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn iterations(&self) -> usize {
+        self.inner.iterations
+    }
+}
+
+/// Aggregate metrics for a full MCTS run.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunMetrics {
+    #[wasm_bindgen(js_name = iterationsRequested)]
+    pub iterations_requested: usize,
+    #[wasm_bindgen(js_name = iterationsCompleted)]
+    pub iterations_completed: usize,
+    #[wasm_bindgen(js_name = totalReturnSum)]
+    pub total_return_sum: f64,
+    #[wasm_bindgen(js_name = averageTotalReturn)]
+    pub average_total_return: f64,
+}
+
+impl From<CoreRunMetrics> for RunMetrics {
+    fn from(value: CoreRunMetrics) -> Self {
+        Self {
+            iterations_requested: value.iterations_requested,
+            iterations_completed: value.iterations_completed,
+            total_return_sum: value.total_return_sum,
+            average_total_return: value.average_total_return,
+        }
+    }
+}
+
+/// Search tree for MCTS.
+#[wasm_bindgen]
+pub struct Tree {
+    inner: CoreTree,
+}
+
+#[wasm_bindgen]
+impl Tree {
+    #[wasm_bindgen(constructor)]
+    pub fn new(root_state_key: usize, root_is_terminal: bool) -> Self {
+        Self {
+            inner: CoreTree::new(CoreStateKey::from(root_state_key as u64), root_is_terminal),
+        }
+    }
+
+    #[wasm_bindgen(js_name = nodeCount)]
+    pub fn node_count(&self) -> usize {
+        self.inner.node_count()
+    }
+
+    /// Return the best root action by mean value, or `undefined` before any expansion.
+    #[wasm_bindgen(js_name = bestRootActionByValue)]
+    pub fn best_root_action_by_value(&self) -> Result<Option<usize>, JsError> {
+        self.inner
+            .best_root_action_by_value()
+            .map(|opt| opt.map(|a| a.index()))
+            .map_err(to_js_error)
+    }
+
+    /// Return the best root action by visit count, or `undefined` before any expansion.
+    #[wasm_bindgen(js_name = bestRootActionByVisits)]
+    pub fn best_root_action_by_visits(&self) -> Result<Option<usize>, JsError> {
+        self.inner
+            .best_root_action_by_visits()
+            .map(|opt| opt.map(|a| a.index()))
+            .map_err(to_js_error)
+    }
+
+    /// Run MCTS against `simulator`.
+    ///
+    /// `rollout` is an optional `(stateKey, numActions) => actionId` callback. Without it,
+    /// rollouts take the first action, unless `config` names a built-in rollout policy.
+    /// Exceptions thrown by the callback are rethrown, and a result that is not an integer
+    /// action id is an error.
+    pub fn run(
+        &mut self,
+        simulator: &MdpSimulator,
+        config: &SearchConfig,
+        rollout: Option<Function>,
+    ) -> Result<RunMetrics, JsValue> {
+        let metrics = self
+            .run_with_policy(
+                simulator,
+                &config.inner,
+                |state_key, num_actions| match &rollout {
+                    Some(rollout) => call_rollout(rollout, state_key, num_actions),
+                    None => Ok(0),
+                },
+            )
+            .map_err(|err| match err {
+                RunError::Tree(err) => to_js_error(err).into(),
+                RunError::Callback(err) => err,
+            })?;
+        Ok(metrics.into())
+    }
+
+    /// Return the tree as pretty-printed `TreeSnapshot` JSON, as Weavetree Studio's
+    /// `Visualize Tree` view expects.
+    #[wasm_bindgen(js_name = snapshotJson)]
+    pub fn snapshot_json(&self) -> Result<String, JsError> {
+        self.inner.snapshot_json_pretty().map_err(to_js_error)
+    }
+}
+
+impl Tree {
+    fn run_with_policy<E>(
+        &mut self,
+        simulator: &MdpSimulator,
+        config: &CoreSearchConfig,
+        mut rollout: impl FnMut(usize, usize) -> Result<usize, E>,
+    ) -> Result<CoreRunMetrics, RunError<E>> {
+        let sim_cell = &simulator.inner;
+        self.inner.run_fallible(
+            config,
+            |state| Ok(sim_cell.borrow().num_actions(mdp_state_key(state))),
+            |state, action| {
+                let (next, reward, terminal) = sim_cell
+                    .borrow_mut()
+                    .step(mdp_state_key(state), action.index());
+                Ok((CoreStateKey::from(next.index() as u64), reward, terminal))
+            },
+            |state, num_actions| {
+                rollout(mdp_state_key(state).index(), num_actions).map(ActionId::from)
+            },
+        )
+    }
+}
+
+fn mdp_state_key(state: CoreStateKey) -> StateKey {
+    StateKey::from(state)
+}
+
+fn call_rollout(
+    rollout: &Function,
+    state_key: usize,
+    num_actions: usize,
+) -> Result<usize, JsValue> {
+    let action = rollout.call2(
+        &JsValue::NULL,
+        &JsValue::from(state_key),
+        &JsValue::from(num_actions),
+    )?;
+    match action.as_f64() {
+        Some(id) if id >= 0.0 && id.fract() == 0.0 && id <= usize::MAX as f64 => Ok(id as usize),
+        _ => Err(
+            JsError::new("rollout callback must return a non-negative integer action id").into(),
+        ),
+    }
+}
@@ -0,0 +1,93 @@
+//! Native tests of the binding logic. Error paths build `JsError`s, which need a JavaScript
+//! host, so only successful calls are covered here.
+
+use weavetree_wasm::{MdpSimulator, SearchConfig, Tree, compile_yaml};
+
+const YAML: &str = r#"
+version: 1
+start: s0
+states:
+  - id: s0
+    actions:
+      - id: a0
+        outcomes:
+          - next: s1
+            prob: 0.7
+            reward: 1.0
+          - next: s0
+            prob: 0.3
+            reward: 0.0
+      - id: a1
+        outcomes:
+          - next: s2
+            prob: 1.0
+            reward: -0.2
+  - id: s1
+    terminal: true
+  - id: s2
+    terminal: true
+"#;
+
+#[test]
+fn compiled_mdp_exposes_layout() {
+    let compiled = compile_yaml(YAML).expect("spec compiles");
+    let start = compiled.start_state_key();
+
+    assert_eq!(compiled.state_count(), 3);
+    assert_eq!(compiled.state_id(start).as_deref(), Some("s0"));
+    assert_eq!(compiled.state_key("s1"), Some(1));
+    assert_eq!(compiled.num_actions(start), Some(2));
+    assert_eq!(compiled.is_terminal(1), Some(true));
+    assert_eq!(compiled.is_terminal(99), None);
+    assert_eq!(compiled.state_key("missing"), None);
+}
+
+#[test]
+fn simulator_steps_are_deterministic_per_seed() {
+    let compiled = compile_yaml(YAML).expect("spec compiles");
+    let a = MdpSimulator::new(&compiled, 7);
+    let b = MdpSimulator::new(&compiled, 7);
+
+    for _ in 0..32 {
+        assert_eq!(a.step(0, 0), b.step(0, 0));
+    }
+    let result = a.step(0, 1);
+    assert_eq!(result.next_state_key, 2);
+    assert_eq!(result.reward, -0.2);
+    assert!(result.terminal);
+    assert_eq!(a.num_actions(2), 0);
+}
+
+#[test]
+fn tree_run_without_callback_searches_the_simulator() {
+    let compiled = compile_yaml(YAML).expect("spec compiles");
+    let simulator = MdpSimulator::new(&compiled, 3);
+    let config =
+        SearchConfig::from_yaml("iterations: 200\nmax_steps: 16\n").expect("config is valid");
+    let mut tree = Tree::new(compiled.start_state_key(), false);
+
+    let metrics = tree
+        .run(&simulator, &config, None)
+        .expect("search succeeds");
+
+    assert_eq!(config.iterations(), 200);
+    assert_eq!(metrics.iterations_completed, 200);
+    assert!(tree.node_count() > 1);
+    assert_eq!(
+        tree.best_root_action_by_visits().expect("root exists"),
+        Some(0)
+    );
+    assert!(
+        tree.snapshot_json()
+            .expect("snapshot serializes")
+            .contains("\"nodes\"")
+    );
+}
+
+#[test]
+fn default_search_config_matches_core() {
+    assert_eq!(
+        SearchConfig::new().iterations(),
+        weavetree_core::SearchConfig::default().iterations
+    );
+}